@@ -0,0 +1,278 @@
+use std::fmt;
+
+use crate::{GlobalConfig, Market, Shard};
+
+/// One difference between two configs
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    MarketAdded(Market),
+    MarketRemoved(Market),
+    MarketChanged {
+        symbol: String,
+        field: &'static str,
+        old: String,
+        new: String,
+    },
+    ShardAdded(Shard),
+    ShardRemoved(Shard),
+    ShardChanged {
+        id: u32,
+        field: &'static str,
+        old: String,
+        new: String,
+    },
+    FlagChanged {
+        flag: &'static str,
+        old: String,
+        new: String,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::MarketAdded(m) => write!(f, "+ market {} (shard {})", m.symbol, m.shard_id),
+            Change::MarketRemoved(m) => write!(f, "- market {} (shard {})", m.symbol, m.shard_id),
+            Change::MarketChanged { symbol, field, old, new } => {
+                write!(f, "~ market {} {}: {} → {}", symbol, field, old, new)
+            }
+            Change::ShardAdded(s) => write!(f, "+ shard {} ({})", s.id, s.endpoint),
+            Change::ShardRemoved(s) => write!(f, "- shard {} ({})", s.id, s.endpoint),
+            Change::ShardChanged { id, field, old, new } => {
+                write!(f, "~ shard {} {}: {} → {}", id, field, old, new)
+            }
+            Change::FlagChanged { flag, old, new } => write!(f, "~ flag {}: {} → {}", flag, old, new),
+        }
+    }
+}
+
+fn show<T: fmt::Debug>(value: &Option<T>) -> String {
+    match value {
+        Some(v) => format!("{:?}", v),
+        None => "unset".to_string(),
+    }
+}
+
+fn market_fields(m: &Market) -> [(&'static str, String); 5] {
+    [
+        ("shard_id", m.shard_id.to_string()),
+        ("base_asset", m.base_asset.clone()),
+        ("quote_asset", m.quote_asset.clone()),
+        ("tick_size", show(&m.tick_size)),
+        ("min_order_size", show(&m.min_order_size)),
+    ]
+}
+
+fn shard_fields(s: &Shard) -> [(&'static str, String); 2] {
+    [("endpoint", s.endpoint.clone()), ("region", show(&s.region))]
+}
+
+/// Changes that turn `old` into `new`: markets keyed by symbol, shards by id
+pub fn diff(old: &GlobalConfig, new: &GlobalConfig) -> Vec<Change> {
+    let mut changes = Vec::new();
+
+    for market in &old.markets {
+        match new.market(&market.symbol) {
+            None => changes.push(Change::MarketRemoved(market.clone())),
+            Some(updated) => {
+                for ((field, before), (_, after)) in market_fields(market).into_iter().zip(market_fields(updated)) {
+                    if before != after {
+                        changes.push(Change::MarketChanged {
+                            symbol: market.symbol.clone(),
+                            field,
+                            old: before,
+                            new: after,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    for market in &new.markets {
+        if old.market(&market.symbol).is_none() {
+            changes.push(Change::MarketAdded(market.clone()));
+        }
+    }
+
+    for shard in &old.shards {
+        match new.shard(shard.id) {
+            None => changes.push(Change::ShardRemoved(shard.clone())),
+            Some(updated) => {
+                for ((field, before), (_, after)) in shard_fields(shard).into_iter().zip(shard_fields(updated)) {
+                    if before != after {
+                        changes.push(Change::ShardChanged {
+                            id: shard.id,
+                            field,
+                            old: before,
+                            new: after,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    for shard in &new.shards {
+        if old.shard(shard.id).is_none() {
+            changes.push(Change::ShardAdded(shard.clone()));
+        }
+    }
+
+    let (a, b) = (&old.feature_flags, &new.feature_flags);
+    let flags = [
+        ("maintenance_mode", a.maintenance_mode.to_string(), b.maintenance_mode.to_string()),
+        (
+            "enable_new_matching_algo",
+            a.enable_new_matching_algo.to_string(),
+            b.enable_new_matching_algo.to_string(),
+        ),
+        (
+            "max_leverage_multiplier",
+            a.max_leverage_multiplier.to_string(),
+            b.max_leverage_multiplier.to_string(),
+        ),
+    ];
+    for (flag, old, new) in flags {
+        if old != new {
+            changes.push(Change::FlagChanged { flag, old, new });
+        }
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff, Change};
+    use crate::{FeatureFlags, GlobalConfig, Market, Shard};
+
+    fn market(symbol: &str, shard_id: u32) -> Market {
+        let base = symbol.trim_end_matches("-USDT");
+        Market {
+            symbol: symbol.to_string(),
+            shard_id,
+            base_asset: base.to_string(),
+            quote_asset: "USDT".to_string(),
+            tick_size: Some(0.1),
+            min_order_size: Some(10.0),
+        }
+    }
+
+    fn shard(id: u32, endpoint: &str) -> Shard {
+        Shard {
+            id,
+            endpoint: endpoint.to_string(),
+            region: None,
+        }
+    }
+
+    fn config() -> GlobalConfig {
+        GlobalConfig {
+            markets: vec![market("BTC-USDT", 1), market("ETH-USDT", 1)],
+            shards: vec![shard(1, "http://127.0.0.1:3003"), shard(2, "http://127.0.0.1:3004")],
+            feature_flags: FeatureFlags::default(),
+        }
+    }
+
+    #[test]
+    fn identical_configs_have_no_changes() {
+        assert!(diff(&config(), &config()).is_empty());
+    }
+
+    #[test]
+    fn lists_market_changes_field_by_field() {
+        let old = config();
+        let mut new = config();
+        new.markets[0].tick_size = Some(0.5);
+        new.markets[0].min_order_size = None;
+        new.markets.remove(1);
+        new.markets.push(market("SOL-USDT", 2));
+
+        let changes = diff(&old, &new);
+
+        assert_eq!(
+            changes,
+            vec![
+                Change::MarketChanged {
+                    symbol: "BTC-USDT".to_string(),
+                    field: "tick_size",
+                    old: "0.1".to_string(),
+                    new: "0.5".to_string(),
+                },
+                Change::MarketChanged {
+                    symbol: "BTC-USDT".to_string(),
+                    field: "min_order_size",
+                    old: "10.0".to_string(),
+                    new: "unset".to_string(),
+                },
+                Change::MarketRemoved(market("ETH-USDT", 1)),
+                Change::MarketAdded(market("SOL-USDT", 2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn lists_shard_and_flag_changes() {
+        let old = config();
+        let mut new = config();
+        new.shards[0].endpoint = "http://10.0.0.5:3003".to_string();
+        new.shards[0].region = Some("eu-west".to_string());
+        new.shards.remove(1);
+        new.shards.push(shard(3, "http://127.0.0.1:3005"));
+        new.feature_flags.maintenance_mode = true;
+        new.feature_flags.max_leverage_multiplier = 100;
+
+        let changes = diff(&old, &new);
+
+        assert_eq!(
+            changes,
+            vec![
+                Change::ShardChanged {
+                    id: 1,
+                    field: "endpoint",
+                    old: "http://127.0.0.1:3003".to_string(),
+                    new: "http://10.0.0.5:3003".to_string(),
+                },
+                Change::ShardChanged {
+                    id: 1,
+                    field: "region",
+                    old: "unset".to_string(),
+                    new: "\"eu-west\"".to_string(),
+                },
+                Change::ShardRemoved(shard(2, "http://127.0.0.1:3004")),
+                Change::ShardAdded(shard(3, "http://127.0.0.1:3005")),
+                Change::FlagChanged {
+                    flag: "maintenance_mode",
+                    old: "false".to_string(),
+                    new: "true".to_string(),
+                },
+                Change::FlagChanged {
+                    flag: "max_leverage_multiplier",
+                    old: "1000".to_string(),
+                    new: "100".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn changes_display_as_a_readable_list() {
+        let old = config();
+        let mut new = config();
+        new.markets[1].shard_id = 2;
+        new.markets.push(market("SOL-USDT", 2));
+        new.shards.remove(1);
+        new.feature_flags.enable_new_matching_algo = true;
+
+        let lines: Vec<String> = diff(&old, &new).iter().map(ToString::to_string).collect();
+
+        assert_eq!(
+            lines,
+            vec![
+                "~ market ETH-USDT shard_id: 1 → 2",
+                "+ market SOL-USDT (shard 2)",
+                "- shard 2 (http://127.0.0.1:3004)",
+                "~ flag enable_new_matching_algo: false → true",
+            ]
+        );
+    }
+}
@@ -0,0 +1,151 @@
+//! GoDark global configuration (`config/global-config.schema.json`)
+//!
+//! One file describes the markets, the matching engine shards that own them
+//! (Zone A) and the feature flags every service reads. [`load`] parses and
+//! validates it in one step; a file that breaks several rules reports all of
+//! them at once in [`ConfigError::Invalid`].
+//!
+//! ```no_run
+//! let config = gdx_config::load("config/global-config.json")?;
+//! let shard = config.shard_for("BTC-USDT");
+//! # Ok::<(), gdx_config::ConfigError>(())
+//! ```
+
+pub mod diff;
+pub mod reload;
+
+mod raw;
+mod validate;
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+pub use diff::{diff, Change};
+pub use reload::{ConfigHandle, ConfigWatcher, ReloadEvent, WatcherThread};
+pub use validate::Violation;
+
+/// Quote asset every market settles in
+pub const QUOTE_ASSET: &str = "USDT";
+/// Highest leverage the margin tiers support
+pub const MAX_LEVERAGE: u32 = 1000;
+/// On-chain price precision; tick sizes must be a multiple of `10^-PRICE_DECIMALS`
+pub const PRICE_DECIMALS: u32 = 6;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GlobalConfig {
+    pub markets: Vec<Market>,
+    pub shards: Vec<Shard>,
+    pub feature_flags: FeatureFlags,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Market {
+    /// e.g. `BTC-USDT`
+    pub symbol: String,
+    /// Zone A shard that matches this market
+    pub shard_id: u32,
+    pub base_asset: String,
+    pub quote_asset: String,
+    /// Minimum price increment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tick_size: Option<f64>,
+    /// Minimum order notional
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_order_size: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Shard {
+    pub id: u32,
+    pub endpoint: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureFlags {
+    pub maintenance_mode: bool,
+    pub enable_new_matching_algo: bool,
+    pub max_leverage_multiplier: u32,
+}
+
+impl Default for FeatureFlags {
+    fn default() -> Self {
+        Self {
+            maintenance_mode: false,
+            enable_new_matching_algo: false,
+            max_leverage_multiplier: MAX_LEVERAGE,
+        }
+    }
+}
+
+impl GlobalConfig {
+    pub fn market(&self, symbol: &str) -> Option<&Market> {
+        self.markets.iter().find(|m| m.symbol == symbol)
+    }
+
+    pub fn shard(&self, id: u32) -> Option<&Shard> {
+        self.shards.iter().find(|s| s.id == id)
+    }
+
+    /// Shard an order for `symbol` must be routed to
+    pub fn shard_for(&self, symbol: &str) -> Option<&Shard> {
+        self.market(symbol).and_then(|m| self.shard(m.shard_id))
+    }
+
+    pub fn markets_on(&self, shard_id: u32) -> impl Iterator<Item = &Market> {
+        self.markets.iter().filter(move |m| m.shard_id == shard_id)
+    }
+
+    pub fn symbols(&self) -> Vec<&str> {
+        self.markets.iter().map(|m| m.symbol.as_str()).collect()
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    /// Not JSON, or a field has the wrong type
+    Parse(serde_json::Error),
+    /// Well-formed but breaks one or more rules
+    Invalid(Vec<Violation>),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "cannot read config: {}", e),
+            ConfigError::Parse(e) => write!(f, "malformed config: {}", e),
+            ConfigError::Invalid(violations) => {
+                write!(f, "{} violation(s):", violations.len())?;
+                for violation in violations {
+                    write!(f, "\n  - {}", violation)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io(e) => Some(e),
+            ConfigError::Parse(e) => Some(e),
+            ConfigError::Invalid(_) => None,
+        }
+    }
+}
+
+/// Parse and validate a config document
+pub fn parse(json: &str) -> Result<GlobalConfig, ConfigError> {
+    let raw: raw::RawConfig = serde_json::from_str(json).map_err(ConfigError::Parse)?;
+    validate::validate(raw).map_err(ConfigError::Invalid)
+}
+
+/// Read, parse and validate a config file
+pub fn load(path: impl AsRef<Path>) -> Result<GlobalConfig, ConfigError> {
+    let json = std::fs::read_to_string(path).map_err(ConfigError::Io)?;
+    parse(&json)
+}
@@ -0,0 +1,38 @@
+//! The config as written, before validation
+//!
+//! Every field is optional here so that missing fields become violations
+//! listed alongside all the others, instead of aborting deserialization at
+//! the first one.
+
+use serde::Deserialize;
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct RawConfig {
+    pub markets: Option<Vec<RawMarket>>,
+    pub shards: Option<Vec<RawShard>>,
+    pub feature_flags: Option<RawFeatureFlags>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct RawMarket {
+    pub symbol: Option<String>,
+    pub shard_id: Option<i64>,
+    pub base_asset: Option<String>,
+    pub quote_asset: Option<String>,
+    pub tick_size: Option<f64>,
+    pub min_order_size: Option<f64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct RawShard {
+    pub id: Option<i64>,
+    pub endpoint: Option<String>,
+    pub region: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct RawFeatureFlags {
+    pub maintenance_mode: Option<bool>,
+    pub enable_new_matching_algo: Option<bool>,
+    pub max_leverage_multiplier: Option<i64>,
+}
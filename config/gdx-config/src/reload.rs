@@ -0,0 +1,173 @@
+//! Hot reload
+//!
+//! [`ConfigWatcher`] polls the file and swaps in a new config only when it
+//! validates; a broken edit is reported and the last good config stays
+//! live. Services hold a cheap [`ConfigHandle`] and call
+//! [`ConfigHandle::current`] wherever they need settings. [`ConfigWatcher::spawn`]
+//! polls on a background thread until [`WatcherThread::stop`] is called.
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, RwLock};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+use crate::diff::{diff, Change};
+use crate::{load, ConfigError, GlobalConfig};
+
+/// Shared, always-valid view of the current config
+#[derive(Debug, Clone)]
+pub struct ConfigHandle {
+    inner: Arc<RwLock<Arc<GlobalConfig>>>,
+}
+
+impl ConfigHandle {
+    pub fn new(config: GlobalConfig) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(Arc::new(config))),
+        }
+    }
+
+    /// Snapshot of the config; stays consistent even if a reload happens
+    pub fn current(&self) -> Arc<GlobalConfig> {
+        self.inner.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn replace(&self, config: GlobalConfig) {
+        *self.inner.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(config);
+    }
+}
+
+/// Outcome of a reload attempt
+#[derive(Debug)]
+pub enum ReloadEvent {
+    /// New config is live
+    Reloaded(Vec<Change>),
+    /// File changed but is invalid; the previous config stays live
+    Rejected(ConfigError),
+}
+
+pub struct ConfigWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    handle: ConfigHandle,
+}
+
+impl ConfigWatcher {
+    /// Load `path`; fails if the initial config is invalid
+    pub fn new(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref().to_path_buf();
+        let modified = modified(&path);
+        let config = load(&path)?;
+        Ok(Self {
+            path,
+            modified,
+            handle: ConfigHandle::new(config),
+        })
+    }
+
+    pub fn handle(&self) -> ConfigHandle {
+        self.handle.clone()
+    }
+
+    /// Reload if the file changed since the last check
+    ///
+    /// Returns `None` when nothing changed on disk, and an empty
+    /// `Reloaded` when the file was rewritten with equivalent content.
+    pub fn poll(&mut self) -> Option<ReloadEvent> {
+        let modified = modified(&self.path);
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+
+        match load(&self.path) {
+            Ok(config) => {
+                let changes = diff(&self.handle.current(), &config);
+                if !changes.is_empty() {
+                    self.handle.replace(config);
+                }
+                Some(ReloadEvent::Reloaded(changes))
+            }
+            Err(e) => Some(ReloadEvent::Rejected(e)),
+        }
+    }
+
+    /// Poll every `interval` on a background thread, calling `on_event`
+    /// after each reload attempt. The thread runs until the returned
+    /// [`WatcherThread`] is stopped or dropped.
+    ///
+    /// # Panics
+    ///
+    /// If `interval` is zero, which would poll the file in a busy loop.
+    pub fn spawn(
+        mut self,
+        interval: Duration,
+        mut on_event: impl FnMut(&ReloadEvent) + Send + 'static,
+    ) -> (ConfigHandle, WatcherThread) {
+        assert!(!interval.is_zero(), "config poll interval must be positive");
+        let handle = self.handle();
+        let (stop, stopped) = mpsc::channel();
+        let thread = thread::spawn(move || {
+            // Waiting on the channel rather than sleeping lets a stop
+            // request end the thread without waiting out the interval
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                if let Some(event) = self.poll() {
+                    on_event(&event);
+                }
+            }
+        });
+        (handle, WatcherThread { stop, thread })
+    }
+}
+
+/// The polling thread started by [`ConfigWatcher::spawn`]
+#[derive(Debug)]
+pub struct WatcherThread {
+    stop: Sender<()>,
+    thread: JoinHandle<()>,
+}
+
+impl WatcherThread {
+    /// Stop polling and wait for the thread to finish; a reload already in
+    /// progress completes first
+    pub fn stop(self) {
+        // A send error means the thread is already gone
+        let _ = self.stop.send(());
+        let _ = self.thread.join();
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::time::{Duration, Instant};
+
+    use super::{ConfigWatcher, ReloadEvent};
+
+    #[test]
+    fn stopping_ends_the_polling_thread_promptly() {
+        let path = std::env::temp_dir().join(format!("gdx-config-reload-{}.json", std::process::id()));
+        std::fs::write(&path, include_str!("../../global-config.example.json")).unwrap();
+        let watcher = ConfigWatcher::new(&path).unwrap();
+
+        let (events, received) = mpsc::channel();
+        let (handle, thread) = watcher.spawn(Duration::from_millis(20), move |event| {
+            let _ = events.send(matches!(event, ReloadEvent::Rejected(_)));
+        });
+        // An invalid edit is rejected and the last good config stays live
+        std::thread::sleep(Duration::from_millis(50));
+        std::fs::write(&path, "{}").unwrap();
+        assert!(received.recv_timeout(Duration::from_secs(5)).unwrap());
+        assert_eq!(handle.current().markets.len(), 3);
+
+        let start = Instant::now();
+        thread.stop();
+        assert!(start.elapsed() < Duration::from_secs(1));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,544 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::raw::{RawConfig, RawFeatureFlags, RawMarket, RawShard};
+use crate::{FeatureFlags, GlobalConfig, Market, Shard, MAX_LEVERAGE, PRICE_DECIMALS, QUOTE_ASSET};
+
+/// One broken rule; `path` points at the offending field, e.g. `markets[2].shard_id`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    Missing { path: String },
+    /// Symbol does not match `^[A-Z]+-USDT$`
+    BadSymbol { path: String, symbol: String },
+    DuplicateSymbol { path: String, symbol: String, first: String },
+    /// Symbol disagrees with `base_asset` / `quote_asset`
+    AssetMismatch { path: String, symbol: String, expected: String },
+    /// Market points at a shard that is not defined
+    UnknownShard { path: String, symbol: String, shard_id: i64 },
+    DuplicateShard { path: String, id: i64, first: String },
+    OutOfRange { path: String, value: String, expected: String },
+    BadEndpoint { path: String, endpoint: String },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::Missing { path } => write!(f, "{}: required field is missing", path),
+            Violation::BadSymbol { path, symbol } => {
+                write!(f, "{}: {:?} does not match ^[A-Z]+-USDT$", path, symbol)
+            }
+            Violation::DuplicateSymbol { path, symbol, first } => {
+                write!(f, "{}: {} is already defined at {}", path, symbol, first)
+            }
+            Violation::AssetMismatch { path, symbol, expected } => {
+                write!(f, "{}: symbol {} does not match its assets ({})", path, symbol, expected)
+            }
+            Violation::UnknownShard { path, symbol, shard_id } => {
+                write!(f, "{}: {} is assigned to shard {}, which is not defined", path, symbol, shard_id)
+            }
+            Violation::DuplicateShard { path, id, first } => {
+                write!(f, "{}: shard {} is already defined at {}", path, id, first)
+            }
+            Violation::OutOfRange { path, value, expected } => {
+                write!(f, "{}: {} is out of range, expected {}", path, value, expected)
+            }
+            Violation::BadEndpoint { path, endpoint } => {
+                write!(f, "{}: {:?} is not an absolute URI", path, endpoint)
+            }
+        }
+    }
+}
+
+fn valid_symbol(symbol: &str) -> bool {
+    symbol
+        .strip_suffix("-USDT")
+        .is_some_and(|base| !base.is_empty() && base.bytes().all(|b| b.is_ascii_uppercase()))
+}
+
+/// `scheme://host...` with a non-empty scheme and host
+fn valid_endpoint(endpoint: &str) -> bool {
+    match endpoint.split_once("://") {
+        Some((scheme, rest)) => {
+            !scheme.is_empty()
+                && scheme.bytes().all(|b| b.is_ascii_alphanumeric() || b"+-.".contains(&b))
+                && !rest.is_empty()
+                && !rest.starts_with('/')
+                && !rest.contains(char::is_whitespace)
+        }
+        None => false,
+    }
+}
+
+/// Whether `value` is a positive multiple of the on-chain price unit
+fn on_price_grid(value: f64) -> bool {
+    let units = value * 10f64.powi(PRICE_DECIMALS as i32);
+    units >= 1.0 - 1e-9 && (units - units.round()).abs() < 1e-6
+}
+
+fn shard_id(value: i64, path: &str, violations: &mut Vec<Violation>) -> Option<u32> {
+    match u32::try_from(value) {
+        Ok(id) => Some(id),
+        Err(_) => {
+            violations.push(Violation::OutOfRange {
+                path: path.to_string(),
+                value: value.to_string(),
+                expected: format!("0..={}", u32::MAX),
+            });
+            None
+        }
+    }
+}
+
+fn required<T>(value: Option<T>, path: String, violations: &mut Vec<Violation>) -> Option<T> {
+    if value.is_none() {
+        violations.push(Violation::Missing { path });
+    }
+    value
+}
+
+fn validate_shards(shards: Vec<RawShard>, violations: &mut Vec<Violation>) -> Vec<Shard> {
+    let mut seen: HashMap<i64, String> = HashMap::new();
+    let mut valid = Vec::new();
+
+    for (i, shard) in shards.into_iter().enumerate() {
+        let path = format!("shards[{}]", i);
+        let id = required(shard.id, format!("{}.id", path), violations);
+        let endpoint = required(shard.endpoint, format!("{}.endpoint", path), violations);
+
+        if let Some(id) = id {
+            if let Some(first) = seen.get(&id) {
+                violations.push(Violation::DuplicateShard {
+                    path: format!("{}.id", path),
+                    id,
+                    first: first.clone(),
+                });
+            } else {
+                seen.insert(id, format!("{}.id", path));
+            }
+        }
+        if let Some(endpoint) = &endpoint {
+            if !valid_endpoint(endpoint) {
+                violations.push(Violation::BadEndpoint {
+                    path: format!("{}.endpoint", path),
+                    endpoint: endpoint.clone(),
+                });
+            }
+        }
+
+        let id = id.and_then(|id| shard_id(id, &format!("{}.id", path), violations));
+        if let (Some(id), Some(endpoint)) = (id, endpoint) {
+            valid.push(Shard {
+                id,
+                endpoint,
+                region: shard.region,
+            });
+        }
+    }
+    valid
+}
+
+fn validate_market(
+    path: &str,
+    market: RawMarket,
+    shard_ids: &[i64],
+    seen: &mut HashMap<String, String>,
+    violations: &mut Vec<Violation>,
+) -> Option<Market> {
+    let symbol = required(market.symbol, format!("{}.symbol", path), violations);
+    let shard = required(market.shard_id, format!("{}.shard_id", path), violations);
+    let base_asset = required(market.base_asset, format!("{}.base_asset", path), violations);
+    let quote_asset = required(market.quote_asset, format!("{}.quote_asset", path), violations);
+
+    if let Some(symbol) = &symbol {
+        if !valid_symbol(symbol) {
+            violations.push(Violation::BadSymbol {
+                path: format!("{}.symbol", path),
+                symbol: symbol.clone(),
+            });
+        }
+        if let Some(first) = seen.get(symbol) {
+            violations.push(Violation::DuplicateSymbol {
+                path: format!("{}.symbol", path),
+                symbol: symbol.clone(),
+                first: first.clone(),
+            });
+        } else {
+            seen.insert(symbol.clone(), format!("{}.symbol", path));
+        }
+
+        if let (Some(base), Some(quote)) = (&base_asset, &quote_asset) {
+            let expected = format!("{}-{}", base, quote);
+            if *symbol != expected {
+                violations.push(Violation::AssetMismatch {
+                    path: path.to_string(),
+                    symbol: symbol.clone(),
+                    expected,
+                });
+            }
+        }
+        if let Some(shard) = shard {
+            if !shard_ids.contains(&shard) {
+                violations.push(Violation::UnknownShard {
+                    path: format!("{}.shard_id", path),
+                    symbol: symbol.clone(),
+                    shard_id: shard,
+                });
+            }
+        }
+    }
+
+    if let Some(quote) = &quote_asset {
+        if quote != QUOTE_ASSET {
+            violations.push(Violation::OutOfRange {
+                path: format!("{}.quote_asset", path),
+                value: quote.clone(),
+                expected: QUOTE_ASSET.to_string(),
+            });
+        }
+    }
+    if let Some(tick) = market.tick_size {
+        if !tick.is_finite() || !on_price_grid(tick) {
+            violations.push(Violation::OutOfRange {
+                path: format!("{}.tick_size", path),
+                value: tick.to_string(),
+                expected: format!("a positive multiple of 1e-{}", PRICE_DECIMALS),
+            });
+        }
+    }
+    if let Some(min) = market.min_order_size {
+        if !min.is_finite() || min <= 0.0 {
+            violations.push(Violation::OutOfRange {
+                path: format!("{}.min_order_size", path),
+                value: min.to_string(),
+                expected: "a positive number".to_string(),
+            });
+        }
+    }
+
+    let shard_id = shard.and_then(|id| shard_id(id, &format!("{}.shard_id", path), violations));
+    Some(Market {
+        symbol: symbol?,
+        shard_id: shard_id?,
+        base_asset: base_asset?,
+        quote_asset: quote_asset?,
+        tick_size: market.tick_size,
+        min_order_size: market.min_order_size,
+    })
+}
+
+fn validate_flags(flags: RawFeatureFlags, violations: &mut Vec<Violation>) -> FeatureFlags {
+    let defaults = FeatureFlags::default();
+    let leverage = match flags.max_leverage_multiplier {
+        None => defaults.max_leverage_multiplier,
+        Some(value) if (1..=MAX_LEVERAGE as i64).contains(&value) => value as u32,
+        Some(value) => {
+            violations.push(Violation::OutOfRange {
+                path: "feature_flags.max_leverage_multiplier".to_string(),
+                value: value.to_string(),
+                expected: format!("1..={}", MAX_LEVERAGE),
+            });
+            defaults.max_leverage_multiplier
+        }
+    };
+    FeatureFlags {
+        maintenance_mode: flags.maintenance_mode.unwrap_or(defaults.maintenance_mode),
+        enable_new_matching_algo: flags
+            .enable_new_matching_algo
+            .unwrap_or(defaults.enable_new_matching_algo),
+        max_leverage_multiplier: leverage,
+    }
+}
+
+/// Check every rule and collect all violations before giving up
+pub(crate) fn validate(raw: RawConfig) -> Result<GlobalConfig, Vec<Violation>> {
+    let mut violations = Vec::new();
+
+    let shards = required(raw.shards, "shards".to_string(), &mut violations).unwrap_or_default();
+    let shard_ids: Vec<i64> = shards.iter().filter_map(|s| s.id).collect();
+    let shards = validate_shards(shards, &mut violations);
+
+    let raw_markets = required(raw.markets, "markets".to_string(), &mut violations).unwrap_or_default();
+    let mut seen = HashMap::new();
+    let markets: Vec<Market> = raw_markets
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, market)| {
+            validate_market(&format!("markets[{}]", i), market, &shard_ids, &mut seen, &mut violations)
+        })
+        .collect();
+
+    let flags = required(raw.feature_flags, "feature_flags".to_string(), &mut violations).unwrap_or_default();
+    let feature_flags = validate_flags(flags, &mut violations);
+
+    if violations.is_empty() {
+        Ok(GlobalConfig {
+            markets,
+            shards,
+            feature_flags,
+        })
+    } else {
+        Err(violations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::Violation;
+    use crate::{parse, ConfigError};
+
+    fn valid() -> Value {
+        json!({
+            "markets": [
+                { "symbol": "BTC-USDT", "shard_id": 1, "base_asset": "BTC", "quote_asset": "USDT",
+                  "tick_size": 0.1, "min_order_size": 10 },
+                { "symbol": "SOL-USDT", "shard_id": 2, "base_asset": "SOL", "quote_asset": "USDT" }
+            ],
+            "shards": [
+                { "id": 1, "endpoint": "http://127.0.0.1:3003", "region": "local" },
+                { "id": 2, "endpoint": "wss://zone-a-2.example.com/ws" }
+            ],
+            "feature_flags": {
+                "maintenance_mode": false,
+                "enable_new_matching_algo": true,
+                "max_leverage_multiplier": 500
+            }
+        })
+    }
+
+    fn violations(config: Value) -> Vec<Violation> {
+        match parse(&config.to_string()) {
+            Err(ConfigError::Invalid(violations)) => violations,
+            other => panic!("expected violations, got {:?}", other),
+        }
+    }
+
+    fn only(config: Value) -> Violation {
+        let mut found = violations(config);
+        assert_eq!(found.len(), 1, "{:?}", found);
+        found.remove(0)
+    }
+
+    fn out_of_range(path: &str) -> impl Fn(&Violation) -> bool + '_ {
+        move |v| matches!(v, Violation::OutOfRange { path: p, .. } if p == path)
+    }
+
+    #[test]
+    fn accepts_a_valid_config() {
+        let config = parse(&valid().to_string()).unwrap();
+        assert_eq!(config.symbols(), vec!["BTC-USDT", "SOL-USDT"]);
+        assert_eq!(config.shard_for("SOL-USDT").unwrap().id, 2);
+        assert_eq!(config.markets[1].tick_size, None);
+        assert_eq!(config.feature_flags.max_leverage_multiplier, 500);
+    }
+
+    #[test]
+    fn accepts_the_example_config() {
+        parse(include_str!("../../global-config.example.json")).unwrap();
+    }
+
+    #[test]
+    fn missing_fields_are_violations() {
+        let mut config = valid();
+        config["markets"][0].as_object_mut().unwrap().remove("base_asset");
+        assert_eq!(
+            only(config),
+            Violation::Missing {
+                path: "markets[0].base_asset".to_string()
+            }
+        );
+
+        let mut config = valid();
+        config.as_object_mut().unwrap().remove("feature_flags");
+        assert_eq!(
+            only(config),
+            Violation::Missing {
+                path: "feature_flags".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn flags_default_when_absent() {
+        let mut config = valid();
+        config["feature_flags"] = json!({});
+        let flags = parse(&config.to_string()).unwrap().feature_flags;
+        assert_eq!(flags, crate::FeatureFlags::default());
+    }
+
+    #[test]
+    fn symbols_must_be_uppercase_usdt_pairs() {
+        for symbol in ["btc-USDT", "BTC-USD", "-USDT", "BTC1-USDT"] {
+            let mut config = valid();
+            config["markets"][0]["symbol"] = json!(symbol);
+            let found = violations(config);
+            assert!(
+                found.iter().any(|v| matches!(v, Violation::BadSymbol { symbol: s, .. } if s == symbol)),
+                "{}: {:?}",
+                symbol,
+                found
+            );
+        }
+    }
+
+    #[test]
+    fn symbols_must_be_unique() {
+        let mut config = valid();
+        config["markets"][1] = config["markets"][0].clone();
+        assert_eq!(
+            only(config),
+            Violation::DuplicateSymbol {
+                path: "markets[1].symbol".to_string(),
+                symbol: "BTC-USDT".to_string(),
+                first: "markets[0].symbol".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn symbols_must_match_their_assets() {
+        let mut config = valid();
+        config["markets"][0]["base_asset"] = json!("ETH");
+        assert_eq!(
+            only(config),
+            Violation::AssetMismatch {
+                path: "markets[0]".to_string(),
+                symbol: "BTC-USDT".to_string(),
+                expected: "ETH-USDT".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn markets_must_use_a_defined_shard() {
+        let mut config = valid();
+        config["markets"][1]["shard_id"] = json!(7);
+        assert_eq!(
+            only(config),
+            Violation::UnknownShard {
+                path: "markets[1].shard_id".to_string(),
+                symbol: "SOL-USDT".to_string(),
+                shard_id: 7,
+            }
+        );
+    }
+
+    #[test]
+    fn shard_ids_must_be_unique_and_in_range() {
+        let mut config = valid();
+        config["shards"][1]["id"] = json!(1);
+        config["markets"][1]["shard_id"] = json!(1);
+        assert_eq!(
+            only(config),
+            Violation::DuplicateShard {
+                path: "shards[1].id".to_string(),
+                id: 1,
+                first: "shards[0].id".to_string(),
+            }
+        );
+
+        let mut config = valid();
+        config["shards"][1]["id"] = json!(-2);
+        config["markets"][1]["shard_id"] = json!(-2);
+        let found = violations(config);
+        assert!(found.iter().any(out_of_range("shards[1].id")), "{:?}", found);
+        assert!(found.iter().any(out_of_range("markets[1].shard_id")), "{:?}", found);
+    }
+
+    #[test]
+    fn endpoints_must_be_absolute_uris() {
+        for endpoint in ["127.0.0.1:3003", "http://", "http:///path", "://host", "http://a b"] {
+            let mut config = valid();
+            config["shards"][0]["endpoint"] = json!(endpoint);
+            assert_eq!(
+                only(config),
+                Violation::BadEndpoint {
+                    path: "shards[0].endpoint".to_string(),
+                    endpoint: endpoint.to_string(),
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn quote_asset_must_be_usdt() {
+        let mut config = valid();
+        config["markets"][0]["quote_asset"] = json!("USDC");
+        config["markets"][0]["symbol"] = json!("BTC-USDC");
+        let found = violations(config);
+        assert!(found.iter().any(out_of_range("markets[0].quote_asset")), "{:?}", found);
+    }
+
+    #[test]
+    fn tick_sizes_must_sit_on_the_price_grid() {
+        for tick in [0.0, -0.1, 0.0000001, 0.10000005] {
+            let mut config = valid();
+            config["markets"][0]["tick_size"] = json!(tick);
+            assert!(out_of_range("markets[0].tick_size")(&only(config)), "{}", tick);
+        }
+        for tick in [0.000001, 0.01, 0.5, 25.0] {
+            let mut config = valid();
+            config["markets"][0]["tick_size"] = json!(tick);
+            assert!(parse(&config.to_string()).is_ok(), "{}", tick);
+        }
+    }
+
+    #[test]
+    fn min_order_size_must_be_positive() {
+        for min in [0.0, -5.0] {
+            let mut config = valid();
+            config["markets"][0]["min_order_size"] = json!(min);
+            assert!(out_of_range("markets[0].min_order_size")(&only(config)), "{}", min);
+        }
+    }
+
+    #[test]
+    fn leverage_multiplier_is_bounded() {
+        for leverage in [0, 1001, -1] {
+            let mut config = valid();
+            config["feature_flags"]["max_leverage_multiplier"] = json!(leverage);
+            assert!(
+                out_of_range("feature_flags.max_leverage_multiplier")(&only(config)),
+                "{}",
+                leverage
+            );
+        }
+    }
+
+    #[test]
+    fn reports_every_violation_at_once() {
+        let mut config = valid();
+        config["markets"][0]["symbol"] = json!("btc-usdt");
+        config["markets"][1]["shard_id"] = json!(9);
+        config["markets"][1]["min_order_size"] = json!(0);
+        config["shards"][1]["endpoint"] = json!("zone-a-2");
+        config["feature_flags"]["max_leverage_multiplier"] = json!(2000);
+
+        let error = parse(&config.to_string()).unwrap_err();
+        let ConfigError::Invalid(found) = &error else {
+            panic!("expected violations, got {:?}", error);
+        };
+        assert_eq!(found.len(), 6, "{:?}", found);
+
+        let message = error.to_string();
+        assert!(message.starts_with("6 violation(s):"), "{}", message);
+        for path in [
+            "markets[0].symbol",
+            "markets[0]:",
+            "markets[1].shard_id",
+            "markets[1].min_order_size",
+            "shards[1].endpoint",
+            "feature_flags.max_leverage_multiplier",
+        ] {
+            assert!(message.contains(&format!("\n  - {}", path)), "{} not in {}", path, message);
+        }
+    }
+
+    #[test]
+    fn type_errors_are_parse_errors() {
+        let mut config = valid();
+        config["markets"][0]["shard_id"] = json!("one");
+        assert!(matches!(parse(&config.to_string()), Err(ConfigError::Parse(_))));
+    }
+}
@@ -0,0 +1,76 @@
+//! Mock oracle program for localnet
+//!
+//! Localnet has no Pyth, so this program owns Pyth-layout price accounts and
+//! lets a single authority overwrite their bytes. Readers that decode the
+//! Pyth v2 layout see an ordinary price feed.
+//!
+//! Account layout: the first `PRICE_ACCOUNT_SIZE` bytes are the Pyth price
+//! account, followed by the 32-byte authority pubkey.
+//!
+//! Instruction data: `[offset: u32 LE][bytes...]`
+//! Accounts: `[writable] price account`, `[signer] authority`
+//!
+//! The first write to an account with an all-zero authority slot claims it
+//! for the signer; later writes must be signed by that authority.
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    declare_id,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+declare_id!("GdxMockPyth11111111111111111111111111111111");
+
+/// Size of a Pyth v2 price account
+pub const PRICE_ACCOUNT_SIZE: usize = 3312;
+/// Total account size: Pyth price account + authority
+pub const ACCOUNT_SIZE: usize = PRICE_ACCOUNT_SIZE + 32;
+
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let price_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+
+    if price_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if instruction_data.len() < 4 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let offset = u32::from_le_bytes(instruction_data[..4].try_into().unwrap()) as usize;
+    let bytes = &instruction_data[4..];
+    if offset + bytes.len() > PRICE_ACCOUNT_SIZE {
+        msg!("Write of {} bytes at {} exceeds price account", bytes.len(), offset);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut data = price_account.try_borrow_mut_data()?;
+    if data.len() != ACCOUNT_SIZE {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let stored = &mut data[PRICE_ACCOUNT_SIZE..];
+    if stored.iter().all(|b| *b == 0) {
+        stored.copy_from_slice(authority.key.as_ref());
+    } else if stored != authority.key.as_ref() {
+        msg!("Signer is not the price account authority");
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    data[offset..offset + bytes.len()].copy_from_slice(bytes);
+    Ok(())
+}
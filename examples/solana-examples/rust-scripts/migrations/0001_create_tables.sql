@@ -0,0 +1,138 @@
+-- GDX indexer schema
+--
+-- Every event table has the event's place in the chain, typed columns the
+-- indexer fills from the decoded event, and the event itself in `data` for
+-- fields without a column. A field that cannot be read as its column's type
+-- is left NULL (and logged by the indexer) rather than failing the block.
+-- Amounts are raw fixed-point integers. Rows carry their slot: rolling back
+-- an abandoned fork is `DELETE ... WHERE slot > $1` on every table.
+
+CREATE TABLE indexer_checkpoint (
+    id         INT PRIMARY KEY DEFAULT 1 CHECK (id = 1),
+    slot       BIGINT NOT NULL,
+    blockhash  TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Blocks indexed since the last finalized slot, for fork detection
+CREATE TABLE indexed_blocks (
+    slot      BIGINT PRIMARY KEY,
+    blockhash TEXT NOT NULL
+);
+
+CREATE TABLE vault_transactions (
+    id              BIGSERIAL PRIMARY KEY,
+    slot            BIGINT NOT NULL,
+    signature       TEXT NOT NULL,
+    event_index     INT NOT NULL,
+    program_id      TEXT NOT NULL,
+    event           TEXT NOT NULL,
+    block_time      TIMESTAMPTZ,
+    vault           TEXT,
+    owner           TEXT,
+    -- Receiving vault of a transfer
+    counterparty    TEXT,
+    amount          NUMERIC,
+    -- Total balance after a deposit or withdrawal
+    balance_after   NUMERIC,
+    -- Available and locked balances after a lock or unlock
+    available_after NUMERIC,
+    locked_after    NUMERIC,
+    data            JSONB NOT NULL,
+    UNIQUE (signature, event_index)
+);
+CREATE INDEX vault_transactions_owner ON vault_transactions (owner, slot);
+CREATE INDEX vault_transactions_vault ON vault_transactions (vault, slot);
+CREATE INDEX vault_transactions_slot ON vault_transactions (slot);
+
+-- Position history: one row per open / modify / close event
+CREATE TABLE positions (
+    id          BIGSERIAL PRIMARY KEY,
+    slot        BIGINT NOT NULL,
+    signature   TEXT NOT NULL,
+    event_index INT NOT NULL,
+    program_id  TEXT NOT NULL,
+    event       TEXT NOT NULL,
+    block_time  TIMESTAMPTZ,
+    position    TEXT,
+    owner       TEXT,
+    symbol      TEXT,
+    side        TEXT,
+    size        NUMERIC,
+    entry_price NUMERIC,
+    margin      NUMERIC,
+    leverage    INT,
+    data        JSONB NOT NULL,
+    UNIQUE (signature, event_index)
+);
+CREATE INDEX positions_position ON positions (position, slot);
+CREATE INDEX positions_owner ON positions (owner, slot);
+CREATE INDEX positions_slot ON positions (slot);
+
+-- Latest state of each position
+CREATE VIEW current_positions AS
+SELECT DISTINCT ON (position) *
+FROM positions
+WHERE position IS NOT NULL
+ORDER BY position, slot DESC, event_index DESC;
+
+CREATE TABLE liquidations (
+    id          BIGSERIAL PRIMARY KEY,
+    slot        BIGINT NOT NULL,
+    signature   TEXT NOT NULL,
+    event_index INT NOT NULL,
+    program_id  TEXT NOT NULL,
+    event       TEXT NOT NULL,
+    block_time  TIMESTAMPTZ,
+    position    TEXT,
+    owner       TEXT,
+    liquidator  TEXT,
+    symbol      TEXT,
+    size        NUMERIC,
+    price       NUMERIC,
+    reward      NUMERIC,
+    bad_debt    NUMERIC,
+    data        JSONB NOT NULL,
+    UNIQUE (signature, event_index)
+);
+CREATE INDEX liquidations_owner ON liquidations (owner, slot);
+CREATE INDEX liquidations_liquidator ON liquidations (liquidator, slot);
+CREATE INDEX liquidations_slot ON liquidations (slot);
+
+CREATE TABLE funding_payments (
+    id          BIGSERIAL PRIMARY KEY,
+    slot        BIGINT NOT NULL,
+    signature   TEXT NOT NULL,
+    event_index INT NOT NULL,
+    program_id  TEXT NOT NULL,
+    event       TEXT NOT NULL,
+    block_time  TIMESTAMPTZ,
+    position    TEXT,
+    owner       TEXT,
+    symbol      TEXT,
+    -- Signed, as emitted (basis points in the funding rate spec)
+    rate        NUMERIC,
+    amount      NUMERIC,
+    data        JSONB NOT NULL,
+    UNIQUE (signature, event_index)
+);
+CREATE INDEX funding_payments_position ON funding_payments (position, slot);
+CREATE INDEX funding_payments_symbol ON funding_payments (symbol, slot);
+CREATE INDEX funding_payments_slot ON funding_payments (slot);
+
+CREATE TABLE settlements (
+    id          BIGSERIAL PRIMARY KEY,
+    slot        BIGINT NOT NULL,
+    signature   TEXT NOT NULL,
+    event_index INT NOT NULL,
+    program_id  TEXT NOT NULL,
+    event       TEXT NOT NULL,
+    block_time  TIMESTAMPTZ,
+    batch_id    TEXT,
+    merkle_root TEXT,
+    trade_count INT,
+    data        JSONB NOT NULL,
+    UNIQUE (signature, event_index)
+);
+CREATE INDEX settlements_batch ON settlements (batch_id);
+CREATE INDEX settlements_slot ON settlements (slot);
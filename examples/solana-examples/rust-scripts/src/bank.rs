@@ -0,0 +1,199 @@
+//! In-process ledger for tests: no validator, no network
+//!
+//! [`Bank`] wraps a `solana-program-test` bank, so transactions run through
+//! the real runtime: the builtin programs, and SPL Token and the associated
+//! token account (ATA) program as BPF. A test that needs its own program
+//! adds it to a [`ProgramTest`] with [`processor!`] and passes that to
+//! [`Bank::start`]; the program then runs natively, with CPIs and PDA
+//! signing working as on chain.
+//!
+//! The slot only moves with [`Bank::warp_to_slot`], which also moves the
+//! clock forward [`MS_PER_SLOT`] per slot; [`Bank::set_unix_timestamp`]
+//! sets the clock directly. As in program-test, a new blockhash is
+//! registered every 400ms of wall time and on every warp, while the block
+//! height only grows with warps. `Bank` implements [`crate::client::LedgerClient`], so
+//! flows written against the trait run unchanged in `cargo test`.
+
+use anyhow::{anyhow, Context, Result};
+use solana_program_test::{BanksClient, ProgramTestBanksClientExt, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    clock::{Clock, Slot, UnixTimestamp},
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    system_instruction,
+    transaction::{Transaction, TransactionError},
+};
+use std::collections::BTreeSet;
+use std::sync::{Mutex, MutexGuard};
+
+pub use solana_program_test::{processor, ProgramTest};
+
+/// Fee per signature, as on the clusters
+pub const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+/// Clock when the bank starts (2024-01-01T00:00:00Z)
+pub const GENESIS_UNIX_TIMESTAMP: UnixTimestamp = 1_704_067_200;
+/// Wall-clock time per slot used by [`Bank::warp_to_slot`]
+pub const MS_PER_SLOT: i64 = 400;
+
+/// Outcome of [`Bank::process_transaction`]
+#[derive(Debug, Clone)]
+pub struct Processed {
+    /// `false` if the bank refused the transaction before running it (bad
+    /// signature, unknown blockhash, no fee, already processed); nothing
+    /// was charged and no status was recorded
+    pub executed: bool,
+    pub result: Result<(), TransactionError>,
+    pub logs: Vec<String>,
+}
+
+pub struct Bank {
+    context: Mutex<ProgramTestContext>,
+    /// Funds airdrops
+    faucet: Keypair,
+    /// Every address a transaction or [`Bank::set_account`] touched; the
+    /// bank has no index by owner, so program accounts are found among these
+    touched: Mutex<BTreeSet<Pubkey>>,
+}
+
+impl Bank {
+    /// A bank with the default programs only
+    pub async fn new() -> Self {
+        Self::start(ProgramTest::default()).await
+    }
+
+    /// Start `program_test` with the clock at [`GENESIS_UNIX_TIMESTAMP`]
+    pub async fn start(program_test: ProgramTest) -> Self {
+        let context = program_test.start_with_context().await;
+        let faucet = context.payer.insecure_clone();
+        let bank = Self {
+            context: Mutex::new(context),
+            faucet,
+            touched: Mutex::new(BTreeSet::new()),
+        };
+        bank.set_unix_timestamp(GENESIS_UNIX_TIMESTAMP)
+            .await
+            .expect("program-test bank has a clock");
+        bank
+    }
+
+    fn context(&self) -> MutexGuard<'_, ProgramTestContext> {
+        self.context.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// A client for the bank; cheap, each call gets its own
+    pub fn banks_client(&self) -> BanksClient {
+        self.context().banks_client.clone()
+    }
+
+    fn touch(&self, addresses: impl IntoIterator<Item = Pubkey>) {
+        self.touched.lock().unwrap_or_else(|e| e.into_inner()).extend(addresses);
+    }
+
+    /// Overwrite an account directly, e.g. to seed a test fixture
+    pub fn set_account(&self, address: Pubkey, account: Account) {
+        self.context().set_account(&address, &account.into());
+        self.touch([address]);
+    }
+
+    /// Every account owned by `program_id`, ordered by address
+    pub async fn program_accounts(&self, program_id: &Pubkey) -> Result<Vec<(Pubkey, Account)>> {
+        let addresses: Vec<Pubkey> = self
+            .touched
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .copied()
+            .collect();
+        let mut client = self.banks_client();
+        let mut accounts = Vec::new();
+        for address in addresses {
+            match client.get_account(address).await? {
+                Some(account) if account.owner == *program_id => accounts.push((address, account)),
+                _ => {}
+            }
+        }
+        Ok(accounts)
+    }
+
+    pub async fn clock(&self) -> Result<Clock> {
+        Ok(self.banks_client().get_sysvar::<Clock>().await?)
+    }
+
+    /// Jump forward to `slot`; the clock advances [`MS_PER_SLOT`] per slot.
+    /// Slots at or before the current one are ignored.
+    pub async fn warp_to_slot(&self, slot: Slot) -> Result<()> {
+        let before = self.clock().await?;
+        if slot <= before.slot {
+            return Ok(());
+        }
+        self.context()
+            .warp_to_slot(slot)
+            .map_err(|e| anyhow!("Failed to warp to slot {}: {}", slot, e))?;
+        let elapsed = (slot - before.slot) as i64 * MS_PER_SLOT / 1000;
+        let clock = Clock {
+            unix_timestamp: before.unix_timestamp + elapsed,
+            ..self.clock().await?
+        };
+        self.context().set_sysvar(&clock);
+        Ok(())
+    }
+
+    /// Set the clock's unix timestamp without changing the slot
+    pub async fn set_unix_timestamp(&self, timestamp: UnixTimestamp) -> Result<()> {
+        let clock = Clock {
+            unix_timestamp: timestamp,
+            epoch_start_timestamp: timestamp,
+            ..self.clock().await?
+        };
+        self.context().set_sysvar(&clock);
+        Ok(())
+    }
+
+    /// Credit lamports from the bank's faucet
+    pub async fn airdrop(&self, to: &Pubkey, lamports: u64) -> Result<Signature> {
+        let mut client = self.banks_client();
+        let mut blockhash = client.get_latest_blockhash().await?;
+        loop {
+            let tx = Transaction::new_signed_with_payer(
+                &[system_instruction::transfer(&self.faucet.pubkey(), to, lamports)],
+                Some(&self.faucet.pubkey()),
+                &[&self.faucet],
+                blockhash,
+            );
+            let processed = self.process_transaction(&tx).await?;
+            match processed.result {
+                Ok(()) => return Ok(tx.signatures[0]),
+                // The same airdrop under the same blockhash; wait for the next one
+                Err(TransactionError::AlreadyProcessed) => {
+                    blockhash = client.get_new_latest_blockhash(&blockhash).await?;
+                }
+                Err(e) => return Err(e).with_context(|| format!("Airdrop to {} failed", to)),
+            }
+        }
+    }
+
+    /// Execute a signed transaction and commit it
+    ///
+    /// The fee is charged even when an instruction fails, as on chain.
+    pub async fn process_transaction(&self, tx: &Transaction) -> Result<Processed> {
+        // The bank executes without checking signatures; a node checks them first
+        if let Err(e) = tx.verify() {
+            return Ok(Processed {
+                executed: false,
+                result: Err(e),
+                logs: Vec::new(),
+            });
+        }
+        self.touch(tx.message.account_keys.iter().copied());
+        let outcome = self
+            .banks_client()
+            .process_transaction_with_metadata(tx.clone())
+            .await?;
+        Ok(Processed {
+            executed: outcome.metadata.is_some(),
+            result: outcome.result,
+            logs: outcome.metadata.map(|m| m.log_messages).unwrap_or_default(),
+        })
+    }
+}
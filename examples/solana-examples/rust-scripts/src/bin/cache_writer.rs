@@ -0,0 +1,309 @@
+use anyhow::{Context, Result};
+use clap::Parser;
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_examples_scripts::client::resilient::{ResilientClient, RetryPolicy};
+use solana_examples_scripts::client::{get_accounts, LedgerClient};
+use solana_examples_scripts::cache::{CacheWriter, PositionUpdate, PriceUpdate, VaultUpdate, DEFAULT_REDIS_URL};
+use solana_examples_scripts::gdx::accounts::{CollateralVault, Position};
+use solana_examples_scripts::gdx::AnchorAccount;
+use solana_examples_scripts::liquidation::scanner::PriceInputs;
+use solana_examples_scripts::oracle::pyth::PythPriceAccount;
+use solana_examples_scripts::pubsub::{Notification, Subscriber, Subscription, DEFAULT_WS_URL};
+use solana_sdk::{clock::Slot, commitment_config::CommitmentConfig, pubkey::Pubkey};
+use std::collections::HashMap;
+use std::process;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// How often the RPC endpoints are health-checked
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Keep oracle prices, collateral vault balances and open positions in
+/// Redis, updated from account subscriptions
+///
+/// A full snapshot is loaded on start, after every reconnect and every
+/// --resync-secs; in between, only pushed account changes are applied.
+/// Closed position accounts leave their program, so they stop producing
+/// notifications; the periodic resync is what removes them.
+#[derive(Parser)]
+#[command(name = "cache-writer")]
+struct Cli {
+    /// Oracle price account per market, as SYMBOL=ADDRESS (repeatable)
+    #[arg(long = "price")]
+    prices: Vec<String>,
+    /// Collateral vault program id
+    #[arg(long)]
+    vault_program: Option<String>,
+    /// Position management program id
+    #[arg(long)]
+    position_program: Option<String>,
+    /// Seconds between full resyncs
+    #[arg(long, default_value = "60", value_parser = clap::value_parser!(u64).range(1..))]
+    resync_secs: u64,
+    /// Redis connection URL
+    #[arg(long, default_value = DEFAULT_REDIS_URL)]
+    redis_url: String,
+    /// RPC endpoint for snapshots; repeat to add fallbacks, tried in order
+    #[arg(long = "url", default_value = "http://127.0.0.1:8899")]
+    urls: Vec<String>,
+    /// Requests per second allowed to each RPC endpoint
+    #[arg(long)]
+    rps: Option<u32>,
+    /// Validator PubSub endpoint
+    #[arg(long, default_value = DEFAULT_WS_URL)]
+    ws_url: String,
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    if let Err(e) = run(cli).await {
+        eprintln!("Error: {:#}", e);
+        process::exit(1);
+    }
+}
+
+/// What the writer watches
+struct Sources {
+    feeds: HashMap<Pubkey, String>,
+    vault_program: Option<Pubkey>,
+    position_program: Option<Pubkey>,
+}
+
+/// Everything a snapshot loads
+struct Snapshot {
+    slot: Slot,
+    prices: Vec<PriceUpdate>,
+    vaults: Vec<VaultUpdate>,
+    positions: Vec<PositionUpdate>,
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    let inputs = PriceInputs::parse(&cli.prices, &[])?;
+    let parse = |id: &Option<String>, name: &str| {
+        id.as_deref()
+            .map(Pubkey::from_str)
+            .transpose()
+            .with_context(|| format!("Invalid {} program id", name))
+    };
+    let sources = Sources {
+        feeds: inputs.feeds.into_iter().map(|(symbol, feed)| (feed, symbol)).collect(),
+        vault_program: parse(&cli.vault_program, "vault")?,
+        position_program: parse(&cli.position_program, "position")?,
+    };
+    if sources.feeds.is_empty() && sources.vault_program.is_none() && sources.position_program.is_none() {
+        anyhow::bail!("Nothing to cache: pass --price, --vault-program or --position-program");
+    }
+
+    let mut cache = CacheWriter::connect(&cli.redis_url).await?;
+    println!("🧊 Caching into {}", cli.redis_url);
+
+    let client = ResilientClient::new(&cli.urls, CommitmentConfig::confirmed(), cli.rps, RetryPolicy::default())?;
+    let _health_checks = client.spawn_health_checks(HEALTH_CHECK_INTERVAL);
+
+    let mut subscriber = Subscriber::new(cli.ws_url.clone());
+    for feed in sources.feeds.keys() {
+        subscriber = subscriber.subscribe(Subscription::Account(*feed));
+    }
+    if let Some(program_id) = sources.vault_program {
+        subscriber = subscriber.subscribe(anchor_accounts::<CollateralVault>(program_id));
+    }
+    if let Some(program_id) = sources.position_program {
+        subscriber = subscriber.subscribe(anchor_accounts::<Position>(program_id));
+    }
+    let mut notifications = subscriber.spawn();
+
+    // Open positions by account address, to publish closes found on resync
+    let mut open: HashMap<Pubkey, PositionUpdate> = HashMap::new();
+    let mut resync = tokio::time::interval(Duration::from_secs(cli.resync_secs));
+    resync.tick().await;
+
+    loop {
+        let notification = tokio::select! {
+            notification = notifications.recv() => match notification {
+                Some(notification) => notification,
+                None => return Ok(()),
+            },
+            _ = resync.tick() => {
+                if let Err(e) = sync(&client, &sources, &mut cache, &mut open).await {
+                    eprintln!("❌ Resync failed: {:#}", e);
+                }
+                continue;
+            }
+            _ = tokio::signal::ctrl_c() => {
+                println!("\n🛑 Stopped");
+                return Ok(());
+            }
+        };
+
+        // Whether anything was written; stale updates are dropped silently
+        let result = match notification {
+            Notification::Connected { .. } => {
+                println!("✅ Subscribed, loading snapshot");
+                sync(&client, &sources, &mut cache, &mut open).await.map(|_| true)
+            }
+            Notification::Disconnected { error, retry_in } => {
+                eprintln!("⚠️  Disconnected ({}), retrying in {:?}", error, retry_in);
+                Ok(false)
+            }
+            Notification::Account { address, slot, account } => match sources.feeds.get(&address) {
+                Some(symbol) => match PythPriceAccount::decode(&account.data).and_then(|p| p.to_price_data()) {
+                    Ok(price) => cache.write_price(&PriceUpdate::new(symbol, &price, slot)).await,
+                    Err(e) => {
+                        eprintln!("⚠️  {}: {:#}", symbol, e);
+                        Ok(false)
+                    }
+                },
+                None => Ok(false),
+            },
+            Notification::Program {
+                program_id,
+                address,
+                slot,
+                account,
+            } => {
+                if Some(program_id) == sources.vault_program {
+                    match CollateralVault::decode(&account.data) {
+                        Ok(vault) => cache.write_vault(&VaultUpdate::new(&address, &vault, slot)).await,
+                        Err(_) => Ok(false),
+                    }
+                } else {
+                    match Position::decode(&account.data) {
+                        Ok(position) => {
+                            let update = PositionUpdate::new(&address, &position, slot);
+                            if update.closed {
+                                open.remove(&address);
+                            } else {
+                                open.insert(address, update.clone());
+                            }
+                            cache.write_position(&update).await
+                        }
+                        Err(_) => Ok(false),
+                    }
+                }
+            }
+            _ => Ok(false),
+        };
+        if let Err(e) = result {
+            eprintln!("❌ {:#}", e);
+        }
+    }
+}
+
+/// `programSubscribe` to accounts of one Anchor type
+fn anchor_accounts<T: AnchorAccount>(program_id: Pubkey) -> Subscription {
+    Subscription::Program {
+        program_id,
+        filters: vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, T::discriminator().to_vec()))],
+    }
+}
+
+/// Load a full snapshot and write it, publishing closes for positions that
+/// are gone
+async fn sync(
+    client: &ResilientClient,
+    sources: &Sources,
+    cache: &mut CacheWriter,
+    open: &mut HashMap<Pubkey, PositionUpdate>,
+) -> Result<()> {
+    let snapshot = load_snapshot(client, sources).await?;
+
+    // Anything pushed after the snapshot slot is newer; the cache keeps it
+    let mut stale = 0;
+    for price in &snapshot.prices {
+        if !cache.write_price(price).await? {
+            stale += 1;
+        }
+    }
+    for vault in &snapshot.vaults {
+        if !cache.write_vault(vault).await? {
+            stale += 1;
+        }
+    }
+    if sources.position_program.is_some() {
+        let mut current: HashMap<Pubkey, PositionUpdate> = snapshot
+            .positions
+            .iter()
+            .filter(|p| !p.closed)
+            .filter_map(|p| Pubkey::from_str(&p.position).ok().map(|address| (address, p.clone())))
+            .collect();
+        cache.replace_positions(snapshot.slot, &snapshot.positions).await?;
+        for (address, mut update) in open.drain() {
+            if current.contains_key(&address) {
+                continue;
+            }
+            if update.slot > snapshot.slot {
+                // Opened after the snapshot was taken
+                current.insert(address, update);
+            } else {
+                update.closed = true;
+                update.slot = snapshot.slot;
+                cache.write_position(&update).await?;
+            }
+        }
+        *open = current;
+    }
+    if stale > 0 {
+        println!("   Kept {} newer cached update(s) over the snapshot", stale);
+    }
+    println!(
+        "🔄 Snapshot at slot {}: {} price(s), {} vault(s), {} open position(s)",
+        snapshot.slot,
+        snapshot.prices.len(),
+        snapshot.vaults.len(),
+        open.len()
+    );
+    Ok(())
+}
+
+async fn load_snapshot(client: &impl LedgerClient, sources: &Sources) -> Result<Snapshot> {
+    let slot = client.get_slot().await.context("Failed to read the current slot")?;
+
+    let feeds: Vec<(&Pubkey, &String)> = sources.feeds.iter().collect();
+    let addresses: Vec<Pubkey> = feeds.iter().map(|(feed, _)| **feed).collect();
+    let (accounts, vaults, positions) = tokio::try_join!(
+        get_accounts(client, &addresses),
+        async {
+            match sources.vault_program {
+                Some(program_id) => CollateralVault::fetch_all(client, &program_id).await,
+                None => Ok((Vec::new(), Vec::new())),
+            }
+        },
+        async {
+            match sources.position_program {
+                Some(program_id) => Position::fetch_all(client, &program_id).await,
+                None => Ok((Vec::new(), Vec::new())),
+            }
+        },
+    )?;
+    let (vaults, bad_vaults) = vaults;
+    let (positions, bad_positions) = positions;
+    for (address, e) in bad_vaults.iter().chain(&bad_positions) {
+        eprintln!("⚠️  Skipping {}: {:#}", address, e);
+    }
+
+    let mut prices = Vec::new();
+    for ((feed, symbol), account) in feeds.into_iter().zip(accounts) {
+        let price = account
+            .with_context(|| format!("Pyth account {} not found", feed))
+            .and_then(|account| PythPriceAccount::decode(&account.data)?.to_price_data());
+        match price {
+            Ok(price) => prices.push(PriceUpdate::new(symbol, &price, slot)),
+            Err(e) => eprintln!("⚠️  {}: {:#}", symbol, e),
+        }
+    }
+    let vaults = vaults
+        .iter()
+        .map(|(address, vault)| VaultUpdate::new(address, vault, slot))
+        .collect();
+    let positions = positions
+        .iter()
+        .map(|(address, position)| PositionUpdate::new(address, position, slot))
+        .collect();
+    Ok(Snapshot {
+        slot,
+        prices,
+        vaults,
+        positions,
+    })
+}
@@ -0,0 +1,168 @@
+use anyhow::{Context, Result};
+use clap::Parser;
+use solana_examples_scripts::client::resilient::{ResilientClient, RetryPolicy};
+use solana_examples_scripts::client::until_ctrl_c;
+use solana_examples_scripts::gdx::{ui_amount, QUOTE_DECIMALS};
+use solana_examples_scripts::keypair::{load_keypair, DEFAULT_KEYPAIR_PATH};
+use solana_examples_scripts::liquidation::executor::{self, LiquidationPrograms};
+use solana_examples_scripts::liquidation::scanner::{self, PriceInputs};
+use solana_examples_scripts::liquidation::Candidate;
+use solana_examples_scripts::oracle::OracleConfig;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+use std::process;
+use std::str::FromStr;
+
+/// Scan every open GDX position and queue the liquidatable ones
+///
+/// Dry-run by default: prints the queue without sending anything.
+#[derive(Parser)]
+#[command(name = "liquidation-scanner")]
+struct Cli {
+    /// Position management program id
+    #[arg(long)]
+    position_program: String,
+    /// Collateral vault program id (loads vaults for display and --cross-margin)
+    #[arg(long)]
+    vault_program: Option<String>,
+    /// Liquidation engine program id (required with --execute)
+    #[arg(long)]
+    liquidation_program: Option<String>,
+    /// Oracle price account per market, as SYMBOL=ADDRESS (repeatable)
+    #[arg(long = "price")]
+    prices: Vec<String>,
+    /// Fixed mark price per market, as SYMBOL=PRICE (repeatable, overrides --price)
+    #[arg(long = "mark")]
+    marks: Vec<String>,
+    /// Count the owner's free vault balance as margin
+    #[arg(long)]
+    cross_margin: bool,
+    /// Maximum oracle price age in seconds
+    #[arg(long, default_value = "30")]
+    max_staleness: i64,
+    /// Maximum oracle confidence interval in basis points
+    #[arg(long, default_value = "100")]
+    max_confidence: u64,
+    /// Submit liquidate_partial / liquidate_full transactions
+    #[arg(long)]
+    execute: bool,
+    /// Liquidator keypair (signs and receives rewards)
+    #[arg(long, default_value = DEFAULT_KEYPAIR_PATH)]
+    keypair: String,
+    /// RPC endpoint; repeat to add fallbacks, tried in order
+    #[arg(long = "url", default_value = "http://127.0.0.1:8899")]
+    urls: Vec<String>,
+    /// Requests per second allowed to each RPC endpoint
+    #[arg(long)]
+    rps: Option<u32>,
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    if let Err(e) = run(cli).await {
+        eprintln!("Error: {:#}", e);
+        process::exit(1);
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    println!("🔎 Scanning positions for liquidation...");
+    let client = ResilientClient::new(&cli.urls, CommitmentConfig::confirmed(), cli.rps, RetryPolicy::default())?;
+    let position_program = Pubkey::from_str(&cli.position_program).context("Invalid position program id")?;
+    let vault_program = cli
+        .vault_program
+        .as_deref()
+        .map(Pubkey::from_str)
+        .transpose()
+        .context("Invalid vault program id")?;
+
+    let inputs = PriceInputs::parse(&cli.prices, &cli.marks)?;
+    let config = OracleConfig {
+        max_staleness: cli.max_staleness,
+        max_confidence: cli.max_confidence,
+        ..OracleConfig::default()
+    };
+    let result = until_ctrl_c(scanner::scan(
+        &client,
+        &position_program,
+        vault_program.as_ref(),
+        &inputs,
+        &config,
+        cli.cross_margin,
+    ))
+    .await?;
+
+    println!("   RPC URL: {}", cli.urls.join(", "));
+    println!("   Open positions marked: {}", result.positions.len());
+    for (address, e) in &result.undecodable {
+        eprintln!("   ⚠️  Skipping {}: {:#}", address, e);
+    }
+    for symbol in &result.unpriced {
+        println!("   ⚠️  No valid mark price for {} — its positions were skipped", symbol);
+    }
+
+    let queue = result.queue.sorted();
+    if queue.is_empty() {
+        println!("✅ No liquidatable positions");
+        return Ok(());
+    }
+
+    println!("\n🚨 Liquidation queue ({} positions):", queue.len());
+    println!(
+        "   {:<4} {:<44} {:<10} {:<5} {:>14} {:>10} {:>8}  Action",
+        "#", "Position", "Market", "Side", "Notional", "Ratio", "Maint"
+    );
+    for (i, candidate) in queue.iter().enumerate() {
+        print_candidate(i + 1, candidate);
+    }
+
+    if !cli.execute {
+        println!("\n💡 Dry run. Re-run with --execute to submit these liquidations.");
+        return Ok(());
+    }
+
+    let liquidation = cli
+        .liquidation_program
+        .as_deref()
+        .context("--liquidation-program is required with --execute")?;
+    let programs = LiquidationPrograms {
+        liquidation: Pubkey::from_str(liquidation).context("Invalid liquidation program id")?,
+        vault: vault_program.context("--vault-program is required with --execute")?,
+    };
+    let liquidator = load_keypair(&cli.keypair)?;
+
+    println!("\n⚡ Executing liquidations...");
+    let mut succeeded = 0;
+    for candidate in &queue {
+        let symbol = &candidate.marked.position.symbol;
+        let Some(feed) = inputs.feeds.get(symbol) else {
+            println!("   ⏭️  {}: no --price feed for {}, skipped", candidate.marked.address, symbol);
+            continue;
+        };
+        match executor::execute(&client, &programs, candidate, feed, &liquidator).await {
+            Ok(signature) => {
+                succeeded += 1;
+                println!("   ✅ {} {} → {}", candidate.marked.address, candidate.recommendation, signature);
+            }
+            Err(e) => println!("   ❌ {:#}", e),
+        }
+    }
+
+    println!("\n✅ {}/{} liquidations confirmed", succeeded, queue.len());
+    Ok(())
+}
+
+fn print_candidate(rank: usize, candidate: &Candidate) {
+    let marked = &candidate.marked;
+    println!(
+        "   {:<4} {:<44} {:<10} {:<5} {:>14.2} {:>9.2}% {:>7.2}%  {}",
+        rank,
+        marked.address.to_string(),
+        marked.position.symbol,
+        marked.position.side.to_string(),
+        ui_amount(marked.notional, QUOTE_DECIMALS),
+        marked.margin_ratio_bps as f64 / 100.0,
+        marked.maintenance_bps as f64 / 100.0,
+        candidate.recommendation
+    );
+}
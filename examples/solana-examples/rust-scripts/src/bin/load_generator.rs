@@ -0,0 +1,379 @@
+use anyhow::{bail, Result};
+use clap::{Parser, ValueEnum};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use solana_examples_scripts::engine::stream::{EngineEvent, OrderStream};
+use solana_examples_scripts::engine::{EngineClient, OrderStatus, DEFAULT_ENGINE_URL};
+use solana_examples_scripts::loadgen::report::{
+    LatencyHistogram, MATCHING_TARGET, ORDERS_PER_SECOND_TARGET, SETTLEMENT_TARGET,
+};
+use solana_examples_scripts::loadgen::{load_markets, FlowConfig, OrderFlow, SizeDistribution};
+use solana_examples_scripts::settlement::relayer::RelayerClient;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::time::Instant;
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SizeDist {
+    Fixed,
+    Uniform,
+    Lognormal,
+}
+
+/// Drive the mock matching engine with synthetic order flow
+///
+/// Arrivals are open-loop: submit-to-ack latency is measured from the time
+/// an order was scheduled, so a slow engine shows up as latency instead of
+/// silently lowering the offered rate.
+#[derive(Parser)]
+#[command(name = "load-generator")]
+struct Cli {
+    /// Mean order arrival rate per second (Poisson)
+    #[arg(long, default_value = "100")]
+    rate: f64,
+    /// Test length in seconds
+    #[arg(long, default_value = "30")]
+    duration: u64,
+    /// Global config file to take the market list from
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Market to trade (repeatable; overrides --config)
+    #[arg(long = "market")]
+    markets: Vec<String>,
+    /// Number of synthetic users (random wallet addresses)
+    #[arg(long, default_value = "100")]
+    users: usize,
+    /// Share of maker (pegged) orders; the rest are market orders
+    #[arg(long, default_value = "0.3")]
+    maker_ratio: f64,
+    /// Share of dark orders (the mock engine does not act on visibility yet)
+    #[arg(long, default_value = "0.5")]
+    dark_ratio: f64,
+    /// Order size distribution
+    #[arg(long, value_enum, default_value = "lognormal")]
+    size_dist: SizeDist,
+    /// Fixed size, uniform lower bound, or log-normal mean
+    #[arg(long, default_value = "0.1")]
+    size: f64,
+    /// Uniform upper bound
+    #[arg(long)]
+    size_max: Option<f64>,
+    /// Log-normal shape
+    #[arg(long, default_value = "1.0")]
+    size_sigma: f64,
+    #[arg(long, default_value = "1")]
+    leverage: u16,
+    /// Maximum orders in flight
+    #[arg(long, default_value = "256", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    concurrency: usize,
+    /// RNG seed, for repeatable flow
+    #[arg(long, default_value = "42")]
+    seed: u64,
+    /// Settlement relayer URL; enables fill-to-settlement latency
+    #[arg(long)]
+    relayer_url: Option<String>,
+    /// Seconds to keep waiting for fills and settlements after the last order
+    #[arg(long, default_value = "10")]
+    drain: u64,
+    /// Write one .hgrm percentile file per histogram into this directory
+    #[arg(long)]
+    report_dir: Option<PathBuf>,
+    /// Engine base URL
+    #[arg(long, default_value = DEFAULT_ENGINE_URL)]
+    url: String,
+}
+
+/// State shared between the generator, submit tasks and the feed
+#[derive(Default)]
+struct Shared {
+    acks: Mutex<HashMap<String, Instant>>,
+    fills: Mutex<HashMap<String, Instant>>,
+    terminal: Mutex<HashMap<String, OrderStatus>>,
+    submit_ack: Mutex<Option<LatencyHistogram>>,
+    fill_settlement: Mutex<Option<LatencyHistogram>>,
+    submitted: AtomicU64,
+    errors: AtomicU64,
+    unsettled: AtomicU64,
+    settling: AtomicUsize,
+}
+
+fn record(histogram: &Mutex<Option<LatencyHistogram>>, latency: Duration) {
+    if let Some(h) = histogram.lock().unwrap().as_mut() {
+        h.record(latency);
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    if let Err(e) = run(cli).await {
+        eprintln!("Error: {:#}", e);
+        process::exit(1);
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    let markets = match (&cli.config, cli.markets.is_empty()) {
+        (_, false) => cli.markets.clone(),
+        (Some(path), true) => load_markets(path)?,
+        (None, true) => bail!("Pass --market or --config"),
+    };
+    let size = match cli.size_dist {
+        SizeDist::Fixed => SizeDistribution::Fixed(cli.size),
+        SizeDist::Uniform => SizeDistribution::Uniform {
+            min: cli.size,
+            max: cli.size_max.unwrap_or(cli.size * 2.0),
+        },
+        SizeDist::Lognormal => SizeDistribution::LogNormal {
+            mean: cli.size,
+            sigma: cli.size_sigma,
+        },
+    };
+    let mut rng = StdRng::seed_from_u64(cli.seed);
+    let users: Vec<String> = (0..cli.users)
+        .map(|_| Pubkey::new_from_array(rng.gen()).to_string())
+        .collect();
+    let flow = OrderFlow::new(FlowConfig {
+        rate: cli.rate,
+        markets,
+        users: users.clone(),
+        maker_ratio: cli.maker_ratio,
+        dark_ratio: cli.dark_ratio,
+        size,
+        leverage: cli.leverage,
+    })?;
+
+    println!("🚦 Load test against {}", cli.url);
+    println!("   Rate: {} orders/s for {}s", cli.rate, cli.duration);
+    println!("   Markets: {}", flow.config().markets.join(", "));
+    println!(
+        "   Users: {}  Makers: {:.0}%  Dark: {:.0}%",
+        cli.users,
+        cli.maker_ratio * 100.0,
+        cli.dark_ratio * 100.0
+    );
+
+    let client = EngineClient::new(cli.url.clone());
+    client.health().await?;
+    let relayer = cli.relayer_url.clone().map(RelayerClient::new);
+    if let Some(relayer) = &relayer {
+        if !relayer.health().await {
+            println!("   ⚠️  Settlement relayer is not healthy; settlement latency may be empty");
+        }
+    }
+
+    let shared = Arc::new(Shared {
+        submit_ack: Mutex::new(Some(LatencyHistogram::new("submit-to-ack"))),
+        fill_settlement: Mutex::new(Some(LatencyHistogram::new("fill-to-settlement"))),
+        ..Shared::default()
+    });
+
+    let stream = OrderStream::connect(&client.ws_url()).await?;
+    let feed = tokio::spawn(follow_feed(
+        stream,
+        shared.clone(),
+        relayer,
+        users.into_iter().collect(),
+        Duration::from_secs(cli.drain.max(5)),
+    ));
+
+    // Open-loop arrivals
+    let permits = Arc::new(Semaphore::new(cli.concurrency));
+    let started = Instant::now();
+    let end = started + Duration::from_secs(cli.duration);
+    let mut next = started;
+    println!("\n▶️  Running (Ctrl-C to stop early)...");
+    loop {
+        next += flow.next_gap(&mut rng);
+        if next >= end {
+            break;
+        }
+        tokio::select! {
+            _ = tokio::time::sleep_until(next) => {}
+            _ = tokio::signal::ctrl_c() => {
+                println!("\n🛑 Stopped early");
+                break;
+            }
+        }
+
+        let order = flow.next_order(&mut rng);
+        let permit = permits.clone().acquire_owned().await?;
+        let client = client.clone();
+        let shared = shared.clone();
+        let scheduled = next;
+        shared.submitted.fetch_add(1, Ordering::Relaxed);
+        tokio::spawn(async move {
+            match client.submit_order(&order).await {
+                Ok(ack) => {
+                    let now = Instant::now();
+                    record(&shared.submit_ack, now - scheduled);
+                    shared.acks.lock().unwrap().insert(ack.order_id, now);
+                }
+                Err(e) => {
+                    if shared.errors.fetch_add(1, Ordering::Relaxed) == 0 {
+                        eprintln!("   ❌ {:#}", e);
+                    }
+                }
+            }
+            drop(permit);
+        });
+    }
+    let elapsed = started.elapsed().min(Duration::from_secs(cli.duration));
+    let _ = permits.acquire_many(cli.concurrency as u32).await?;
+
+    // Let fills and settlements catch up
+    let drain_end = Instant::now() + Duration::from_secs(cli.drain);
+    while Instant::now() < drain_end {
+        let filled = shared.fills.lock().unwrap().len() + shared.terminal.lock().unwrap().len();
+        let acked = shared.acks.lock().unwrap().len();
+        if filled >= acked && shared.settling.load(Ordering::Relaxed) == 0 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    feed.abort();
+
+    report(&shared, elapsed, cli.relayer_url.is_some(), cli.report_dir.as_deref())
+}
+
+/// Record fill times from the engine feed and poll the relayer for settlement
+async fn follow_feed(
+    mut stream: OrderStream,
+    shared: Arc<Shared>,
+    relayer: Option<RelayerClient>,
+    users: HashSet<String>,
+    settle_timeout: Duration,
+) {
+    while let Some(event) = stream.next().await {
+        let Ok(EngineEvent::OrderUpdate(order)) = event else {
+            continue;
+        };
+        if !users.contains(&order.user_id) {
+            continue;
+        }
+        match order.status {
+            OrderStatus::Matched => {
+                let filled_at = Instant::now();
+                {
+                    let mut fills = shared.fills.lock().unwrap();
+                    if fills.contains_key(&order.id) {
+                        continue;
+                    }
+                    fills.insert(order.id.clone(), filled_at);
+                }
+                let Some(relayer) = &relayer else {
+                    continue;
+                };
+                for trade in order.fills {
+                    let relayer = relayer.clone();
+                    let shared = shared.clone();
+                    shared.settling.fetch_add(1, Ordering::Relaxed);
+                    tokio::spawn(async move {
+                        let deadline = filled_at + settle_timeout;
+                        loop {
+                            if let Ok(Some(batch)) = relayer.batch_by_trade(&trade.id).await {
+                                if batch.is_on_chain() {
+                                    record(&shared.fill_settlement, filled_at.elapsed());
+                                    break;
+                                }
+                            }
+                            if Instant::now() >= deadline {
+                                shared.unsettled.fetch_add(1, Ordering::Relaxed);
+                                break;
+                            }
+                            tokio::time::sleep(Duration::from_millis(50)).await;
+                        }
+                        shared.settling.fetch_sub(1, Ordering::Relaxed);
+                    });
+                }
+            }
+            OrderStatus::Failed | OrderStatus::Cancelled => {
+                shared.terminal.lock().unwrap().insert(order.id, order.status);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn report(shared: &Shared, elapsed: Duration, settlement: bool, report_dir: Option<&std::path::Path>) -> Result<()> {
+    let submit_ack = shared.submit_ack.lock().unwrap().take().expect("histogram");
+    let fill_settlement = shared.fill_settlement.lock().unwrap().take().expect("histogram");
+
+    let acks = shared.acks.lock().unwrap();
+    let fills = shared.fills.lock().unwrap();
+    let mut ack_fill = LatencyHistogram::new("ack-to-fill");
+    for (order_id, filled_at) in fills.iter() {
+        if let Some(acked_at) = acks.get(order_id) {
+            ack_fill.record(filled_at.saturating_duration_since(*acked_at));
+        }
+    }
+
+    let submitted = shared.submitted.load(Ordering::Relaxed);
+    let failed = shared
+        .terminal
+        .lock()
+        .unwrap()
+        .values()
+        .filter(|s| **s == OrderStatus::Failed)
+        .count();
+    let throughput = acks.len() as f64 / elapsed.as_secs_f64();
+
+    println!("\n📊 Orders:");
+    println!("   Submitted: {}", submitted);
+    println!("   Acknowledged: {} ({:.0}/s)", acks.len(), throughput);
+    println!("   Filled: {}", fills.len());
+    println!("   Failed: {}", failed);
+    println!("   Errors: {}", shared.errors.load(Ordering::Relaxed));
+    if settlement {
+        println!("   Unsettled (timed out): {}", shared.unsettled.load(Ordering::Relaxed));
+    }
+
+    println!("\n⏱️  Latency:");
+    let mut histograms = vec![&submit_ack, &ack_fill];
+    if settlement {
+        histograms.push(&fill_settlement);
+    }
+    for histogram in &histograms {
+        println!("   {}", histogram.summary());
+    }
+
+    println!("\n🎯 Targets (section 8):");
+    if throughput >= ORDERS_PER_SECOND_TARGET {
+        println!("   ✅ Throughput {:.0} orders/s", throughput);
+    } else {
+        println!(
+            "   ❌ Throughput {:.0} orders/s < {} sustained (raise --rate to test it)",
+            throughput, ORDERS_PER_SECOND_TARGET
+        );
+    }
+    let mut checks = vec![("Matching", &ack_fill, MATCHING_TARGET)];
+    if settlement {
+        checks.push(("Settlement", &fill_settlement, SETTLEMENT_TARGET));
+    }
+    for (name, histogram, target) in checks {
+        if histogram.is_empty() {
+            println!("   ⚠️  {}: no samples", name);
+            continue;
+        }
+        let misses = histogram.misses(&target);
+        if misses.is_empty() {
+            println!("   ✅ {} latency within target", name);
+        } else {
+            println!("   ❌ {} latency: {}", name, misses.join(", "));
+        }
+    }
+
+    if let Some(dir) = report_dir {
+        for histogram in &histograms {
+            histogram.save(dir)?;
+        }
+        println!("\n💾 HDR percentile files written to {}", dir.display());
+    }
+
+    Ok(())
+}
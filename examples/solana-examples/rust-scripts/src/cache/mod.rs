@@ -0,0 +1,314 @@
+//! Hot GDX state in Redis
+//!
+//! The cache writer keeps one hash per entity and publishes every change, so
+//! readers (web3 UI, liquidation scanner) get current state from Redis
+//! instead of polling the RPC node:
+//!
+//! | Key                       | Fields                                  |
+//! |---------------------------|-----------------------------------------|
+//! | `gdx:price:<SYMBOL>`      | [`PriceUpdate`] fields                  |
+//! | `gdx:vault:<OWNER>`       | [`VaultUpdate`] fields                  |
+//! | `gdx:positions:<OWNER>`   | symbol → [`PositionUpdate`] JSON        |
+//!
+//! Each write also publishes the update as JSON on [`PRICE_CHANNEL`],
+//! [`VAULT_CHANNEL`] or [`POSITION_CHANNEL`], in the same script as the
+//! hash write. Amounts are raw fixed-point integers (see `gdx::*_DECIMALS`).
+//!
+//! Every update carries the slot it was read at, and a write older than
+//! what is cached is dropped, so a resync snapshot taken before a pushed
+//! update cannot roll it back. Position slots live in
+//! `gdx:position-slots:<OWNER>` and outlive a close, so a stale snapshot
+//! cannot reopen a closed position either.
+
+use anyhow::{Context, Result};
+use redis::aio::ConnectionManager;
+use serde::Serialize;
+use solana_sdk::{clock::Slot, pubkey::Pubkey};
+use std::fmt;
+
+use crate::gdx::accounts::{CollateralVault, Position};
+use crate::oracle::PriceData;
+
+/// Redis from docker-compose
+pub const DEFAULT_REDIS_URL: &str = "redis://127.0.0.1:6379";
+
+pub const PRICE_CHANNEL: &str = "gdx:prices";
+pub const VAULT_CHANNEL: &str = "gdx:vaults";
+pub const POSITION_CHANNEL: &str = "gdx:positions";
+
+/// Set of owners that have a `gdx:positions:<OWNER>` hash
+const POSITION_OWNERS: &str = "gdx:position-owners";
+const POSITION_SLOTS_PREFIX: &str = "gdx:position-slots:";
+
+/// `HSET` the fields and publish, unless the hash holds a newer `slot`.
+/// KEYS: hash. ARGV: slot, channel, message, field/value pairs.
+const WRITE_HASH: &str = r"
+local cached = tonumber(redis.call('HGET', KEYS[1], 'slot'))
+if cached and cached > tonumber(ARGV[1]) then return 0 end
+redis.call('HSET', KEYS[1], unpack(ARGV, 4))
+redis.call('PUBLISH', ARGV[2], ARGV[3])
+return 1
+";
+
+/// Store or drop one position and publish, unless a newer slot is recorded
+/// for it. KEYS: positions hash, slots hash, owners set. ARGV: symbol, slot,
+/// JSON, closed (`1`/`0`), owner, channel.
+const WRITE_POSITION: &str = r"
+local cached = tonumber(redis.call('HGET', KEYS[2], ARGV[1]))
+if cached and cached > tonumber(ARGV[2]) then return 0 end
+redis.call('HSET', KEYS[2], ARGV[1], ARGV[2])
+if ARGV[4] == '1' then
+  redis.call('HDEL', KEYS[1], ARGV[1])
+else
+  redis.call('HSET', KEYS[1], ARGV[1], ARGV[3])
+end
+redis.call('SADD', KEYS[3], ARGV[5])
+redis.call('PUBLISH', ARGV[6], ARGV[3])
+return 1
+";
+
+/// Make the cached positions match a snapshot, keeping every entry (open or
+/// closed) recorded at a newer slot than the snapshot's. KEYS: owners set.
+/// ARGV: snapshot slot, positions prefix, slots prefix, then owner, symbol,
+/// JSON triples.
+const REPLACE_POSITIONS: &str = r"
+local slot = tonumber(ARGV[1])
+local snapshot = {}
+for i = 4, #ARGV, 3 do
+  snapshot[ARGV[i]] = snapshot[ARGV[i]] or {}
+  snapshot[ARGV[i]][ARGV[i + 1]] = ARGV[i + 2]
+end
+for _, owner in ipairs(redis.call('SMEMBERS', KEYS[1])) do
+  snapshot[owner] = snapshot[owner] or {}
+end
+for owner, fresh in pairs(snapshot) do
+  local positions = ARGV[2] .. owner
+  local slots = ARGV[3] .. owner
+  local cached = redis.call('HGETALL', slots)
+  local newer = {}
+  for i = 1, #cached, 2 do
+    if tonumber(cached[i + 1]) > slot then
+      newer[cached[i]] = true
+    else
+      redis.call('HDEL', slots, cached[i])
+    end
+  end
+  for _, symbol in ipairs(redis.call('HKEYS', positions)) do
+    if not newer[symbol] then redis.call('HDEL', positions, symbol) end
+  end
+  for symbol, json in pairs(fresh) do
+    if not newer[symbol] then
+      redis.call('HSET', positions, symbol, json)
+      redis.call('HSET', slots, symbol, ARGV[1])
+    end
+  end
+  if redis.call('EXISTS', slots) == 1 then
+    redis.call('SADD', KEYS[1], owner)
+  else
+    redis.call('SREM', KEYS[1], owner)
+  end
+end
+return 1
+";
+
+pub fn price_key(symbol: &str) -> String {
+    format!("gdx:price:{}", symbol)
+}
+
+pub fn vault_key(owner: impl fmt::Display) -> String {
+    format!("gdx:vault:{}", owner)
+}
+
+pub fn positions_key(owner: impl fmt::Display) -> String {
+    format!("gdx:positions:{}", owner)
+}
+
+fn position_slots_key(owner: impl fmt::Display) -> String {
+    format!("{}{}", POSITION_SLOTS_PREFIX, owner)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PriceUpdate {
+    pub symbol: String,
+    /// `price * 10^expo`
+    pub price: i64,
+    pub confidence: u64,
+    pub expo: i32,
+    /// Unix publish time; readers apply their own staleness limit
+    pub timestamp: i64,
+    pub source: String,
+    pub slot: Slot,
+}
+
+impl PriceUpdate {
+    pub fn new(symbol: &str, price: &PriceData, slot: Slot) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            price: price.price,
+            confidence: price.confidence,
+            expo: price.expo,
+            timestamp: price.timestamp,
+            source: price.source.to_string(),
+            slot,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultUpdate {
+    pub owner: String,
+    pub vault: String,
+    pub total_balance: u64,
+    pub available_balance: u64,
+    pub locked_balance: u64,
+    pub slot: Slot,
+}
+
+impl VaultUpdate {
+    pub fn new(address: &Pubkey, vault: &CollateralVault, slot: Slot) -> Self {
+        Self {
+            owner: vault.owner.to_string(),
+            vault: address.to_string(),
+            total_balance: vault.total_balance,
+            available_balance: vault.available_balance,
+            locked_balance: vault.locked_balance,
+            slot,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionUpdate {
+    pub owner: String,
+    pub symbol: String,
+    pub position: String,
+    pub side: String,
+    pub size: u64,
+    pub entry_price: u64,
+    pub margin: u64,
+    pub leverage: u8,
+    pub liquidation_price: u64,
+    pub slot: Slot,
+    /// Set on the message published when a position closes
+    pub closed: bool,
+}
+
+impl PositionUpdate {
+    pub fn new(address: &Pubkey, position: &Position, slot: Slot) -> Self {
+        Self {
+            owner: position.owner.to_string(),
+            symbol: position.symbol.clone(),
+            position: address.to_string(),
+            side: position.side.to_string(),
+            size: position.size,
+            entry_price: position.entry_price,
+            margin: position.margin,
+            leverage: position.leverage,
+            liquidation_price: position.liquidation_price,
+            slot,
+            closed: position.size == 0,
+        }
+    }
+}
+
+/// Flatten a struct into `(field, value)` pairs for `HSET`
+fn hash_fields(value: &impl Serialize) -> Result<Vec<(String, String)>> {
+    let serde_json::Value::Object(map) = serde_json::to_value(value)? else {
+        anyhow::bail!("Expected a struct");
+    };
+    Ok(map
+        .into_iter()
+        .map(|(field, value)| match value {
+            serde_json::Value::String(s) => (field, s),
+            other => (field, other.to_string()),
+        })
+        .collect())
+}
+
+pub struct CacheWriter {
+    conn: ConnectionManager,
+}
+
+impl CacheWriter {
+    /// Connect; the connection reconnects on its own after Redis restarts
+    pub async fn connect(url: &str) -> Result<Self> {
+        let client = redis::Client::open(url).with_context(|| format!("Invalid Redis URL {}", url))?;
+        let conn = ConnectionManager::new(client)
+            .await
+            .with_context(|| format!("Failed to connect to {}", url))?;
+        Ok(Self { conn })
+    }
+
+    /// Write a price unless a newer one is cached; returns whether it was written
+    pub async fn write_price(&mut self, update: &PriceUpdate) -> Result<bool> {
+        self.write_hash(&price_key(&update.symbol), PRICE_CHANNEL, update.slot, update)
+            .await
+            .context("Failed to write price")
+    }
+
+    /// Write a vault balance unless a newer one is cached; returns whether it
+    /// was written
+    pub async fn write_vault(&mut self, update: &VaultUpdate) -> Result<bool> {
+        self.write_hash(&vault_key(&update.owner), VAULT_CHANNEL, update.slot, update)
+            .await
+            .context("Failed to write vault")
+    }
+
+    async fn write_hash(&mut self, key: &str, channel: &str, slot: Slot, update: &impl Serialize) -> Result<bool> {
+        let mut eval = redis::cmd("EVAL");
+        eval.arg(WRITE_HASH)
+            .arg(1)
+            .arg(key)
+            .arg(slot)
+            .arg(channel)
+            .arg(serde_json::to_string(update)?);
+        for (field, value) in hash_fields(update)? {
+            eval.arg(field).arg(value);
+        }
+        let written: i64 = eval.query_async(&mut self.conn).await?;
+        Ok(written == 1)
+    }
+
+    /// Store an open position, or drop it if `update.closed`, unless a newer
+    /// update for it is cached; returns whether it was written
+    pub async fn write_position(&mut self, update: &PositionUpdate) -> Result<bool> {
+        let written: i64 = redis::cmd("EVAL")
+            .arg(WRITE_POSITION)
+            .arg(3)
+            .arg(positions_key(&update.owner))
+            .arg(position_slots_key(&update.owner))
+            .arg(POSITION_OWNERS)
+            .arg(&update.symbol)
+            .arg(update.slot)
+            .arg(serde_json::to_string(update)?)
+            .arg(if update.closed { "1" } else { "0" })
+            .arg(&update.owner)
+            .arg(POSITION_CHANNEL)
+            .query_async(&mut self.conn)
+            .await
+            .context("Failed to write position")?;
+        Ok(written == 1)
+    }
+
+    /// Replace the cached positions with a snapshot taken at `slot`, so
+    /// positions closed while nobody was watching disappear. Positions
+    /// updated after `slot` keep their newer state.
+    pub async fn replace_positions(&mut self, slot: Slot, positions: &[PositionUpdate]) -> Result<()> {
+        let mut eval = redis::cmd("EVAL");
+        eval.arg(REPLACE_POSITIONS)
+            .arg(1)
+            .arg(POSITION_OWNERS)
+            .arg(slot)
+            .arg(positions_key(""))
+            .arg(POSITION_SLOTS_PREFIX);
+        for update in positions.iter().filter(|p| !p.closed) {
+            eval.arg(&update.owner)
+                .arg(&update.symbol)
+                .arg(serde_json::to_string(update)?);
+        }
+        eval.query_async::<_, i64>(&mut self.conn)
+            .await
+            .context("Failed to replace positions")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+//! [`LedgerClient`] over the in-process [`Bank`]
+//!
+//! Transactions are processed and committed as they are sent, so sends and
+//! airdrops are confirmed when they return.
+
+use anyhow::{bail, Context, Result};
+use solana_client::rpc_filter::RpcFilterType;
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    clock::Slot,
+    commitment_config::CommitmentLevel,
+    hash::Hash,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::Signature,
+    transaction::{Transaction, TransactionError},
+};
+
+use super::LedgerClient;
+use crate::bank::Bank;
+
+impl LedgerClient for Bank {
+    async fn get_balance(&self, address: &Pubkey) -> Result<u64> {
+        Ok(self.banks_client().get_balance(*address).await?)
+    }
+
+    async fn get_account(&self, address: &Pubkey) -> Result<Option<Account>> {
+        Ok(self.banks_client().get_account(*address).await?)
+    }
+
+    async fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        let mut client = self.banks_client();
+        let mut accounts = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            accounts.push(client.get_account(*address).await?);
+        }
+        Ok(accounts)
+    }
+
+    async fn get_program_accounts(&self, program_id: &Pubkey, filters: &[RpcFilterType]) -> Result<Vec<(Pubkey, Account)>> {
+        Ok(self
+            .program_accounts(program_id)
+            .await?
+            .into_iter()
+            .filter(|(_, account)| {
+                let shared = AccountSharedData::from(account.clone());
+                filters.iter().all(|filter| filter.allows(&shared))
+            })
+            .collect())
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64> {
+        let rent: Rent = self.banks_client().get_rent().await?;
+        Ok(rent.minimum_balance(data_len))
+    }
+
+    async fn get_latest_blockhash(&self) -> Result<Hash> {
+        Ok(self.banks_client().get_latest_blockhash().await?)
+    }
+
+    async fn get_latest_blockhash_with_expiry(&self) -> Result<(Hash, u64)> {
+        self.banks_client()
+            .get_latest_blockhash_with_commitment(CommitmentLevel::Processed)
+            .await?
+            .context("Bank has no blockhash")
+    }
+
+    /// The slot transactions are processing in
+    async fn get_slot(&self) -> Result<Slot> {
+        Ok(self.clock().await?.slot)
+    }
+
+    /// Height of the last rooted block; it trails the working bank by a
+    /// block after a warp, so expiry is seen a little late, never early
+    async fn get_block_height(&self) -> Result<u64> {
+        Ok(self.banks_client().get_root_block_height().await?)
+    }
+
+    async fn request_airdrop(&self, to: &Pubkey, lamports: u64) -> Result<Signature> {
+        self.airdrop(to, lamports).await
+    }
+
+    /// Like a send with `skip_preflight`: a transaction that executed and
+    /// failed is accepted, its status carries the error. Transactions that
+    /// never execute (bad signature, unknown blockhash, no fee) are rejected.
+    async fn send_transaction(&self, tx: &Transaction) -> Result<Signature> {
+        let processed = self.process_transaction(tx).await?;
+        match processed.result {
+            _ if processed.executed => Ok(tx.signatures[0]),
+            Ok(()) | Err(TransactionError::AlreadyProcessed) => Ok(tx.signatures[0]),
+            Err(e) => Err(e).context("Transaction rejected"),
+        }
+    }
+
+    async fn resend_transaction(&self, tx: &Transaction) -> Result<Signature> {
+        self.send_transaction(tx).await
+    }
+
+    async fn get_signature_status(&self, signature: &Signature) -> Result<Option<Result<(), TransactionError>>> {
+        let status = self.banks_client().get_transaction_status(*signature).await?;
+        Ok(status.map(|status| status.err.map_or(Ok(()), Err)))
+    }
+
+    async fn send_and_confirm_transaction(&self, tx: &Transaction) -> Result<Signature> {
+        let processed = self.process_transaction(tx).await?;
+        match processed.result {
+            Ok(()) => Ok(tx.signatures[0]),
+            Err(e) if processed.logs.is_empty() => bail!("Transaction failed: {}", e),
+            Err(e) => bail!("Transaction failed: {}\n  {}", e, processed.logs.join("\n  ")),
+        }
+    }
+}
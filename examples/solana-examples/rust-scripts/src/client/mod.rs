@@ -0,0 +1,149 @@
+//! One interface over the ledgers our flows talk to
+//!
+//! Commands and libraries take `&impl LedgerClient` rather than a concrete
+//! RPC client, so the same code runs against a validator over JSON-RPC
+//! (blocking or nonblocking `RpcClient`), against the in-process
+//! [`crate::bank::Bank`] in `cargo test`, or against a
+//! [`recording::Replayer`] serving a recorded session.
+//! [`resilient::ResilientClient`] spreads calls over several RPC endpoints
+//! with retries, failover and rate limiting. The trait covers the
+//! account and transaction calls our tools make; block and transaction
+//! history queries (indexer, event listener) stay RPC-specific.
+//!
+//! Methods are async. The blocking `RpcClient` implementation answers
+//! synchronously inside the future.
+
+pub mod bank;
+pub mod recording;
+pub mod resilient;
+pub mod sender;
+pub mod rpc;
+
+use anyhow::{bail, Context, Result};
+use futures_util::{stream, StreamExt, TryStreamExt};
+use solana_client::rpc_filter::RpcFilterType;
+use solana_sdk::{
+    account::Account,
+    clock::Slot,
+    hash::Hash,
+    instruction::Instruction,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use std::future::Future;
+
+pub use solana_client::rpc_request::MAX_MULTIPLE_ACCOUNTS;
+
+/// `getMultipleAccounts` calls [`get_accounts`] keeps in flight
+pub const FAN_OUT: usize = 8;
+
+pub trait LedgerClient: Send + Sync {
+    /// Lamports held by `address`; 0 when the account does not exist
+    fn get_balance(&self, address: &Pubkey) -> impl Future<Output = Result<u64>> + Send;
+
+    /// The account at `address`, or `None` when it does not exist
+    fn get_account(&self, address: &Pubkey) -> impl Future<Output = Result<Option<Account>>> + Send;
+
+    /// Accounts at `addresses`, in order; `None` for the missing ones
+    fn get_multiple_accounts(&self, addresses: &[Pubkey])
+        -> impl Future<Output = Result<Vec<Option<Account>>>> + Send;
+
+    /// Every account owned by `program_id` that passes all `filters`
+    fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        filters: &[RpcFilterType],
+    ) -> impl Future<Output = Result<Vec<(Pubkey, Account)>>> + Send;
+
+    fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> impl Future<Output = Result<u64>> + Send;
+
+    fn get_latest_blockhash(&self) -> impl Future<Output = Result<Hash>> + Send;
+
+    /// The latest blockhash and the last block height at which a transaction
+    /// using it can still land
+    fn get_latest_blockhash_with_expiry(&self) -> impl Future<Output = Result<(Hash, u64)>> + Send;
+
+    fn get_slot(&self) -> impl Future<Output = Result<Slot>> + Send;
+
+    fn get_block_height(&self) -> impl Future<Output = Result<u64>> + Send;
+
+    /// Airdrop lamports and wait until the airdrop is confirmed
+    fn request_airdrop(&self, to: &Pubkey, lamports: u64) -> impl Future<Output = Result<Signature>> + Send;
+
+    /// Submit a signed transaction without waiting for it
+    fn send_transaction(&self, tx: &Transaction) -> impl Future<Output = Result<Signature>> + Send;
+
+    /// Submit a signed transaction without preflight checks, to rebroadcast
+    /// one that may already be in flight
+    fn resend_transaction(&self, tx: &Transaction) -> impl Future<Output = Result<Signature>> + Send;
+
+    /// `None` while the transaction is unknown or not yet confirmed,
+    /// otherwise whether it succeeded. Searches the whole transaction
+    /// history, not just recent slots.
+    fn get_signature_status(
+        &self,
+        signature: &Signature,
+    ) -> impl Future<Output = Result<Option<Result<(), TransactionError>>>> + Send;
+
+    /// Submit a signed transaction and wait until it is confirmed
+    fn send_and_confirm_transaction(&self, tx: &Transaction) -> impl Future<Output = Result<Signature>> + Send;
+}
+
+/// Sign `instructions` with `payer` (fee payer) and `signers`, send them in
+/// one transaction and wait for confirmation. The payer may also be one of
+/// `signers`.
+pub async fn send(
+    client: &impl LedgerClient,
+    instructions: &[Instruction],
+    payer: &Keypair,
+    signers: &[&Keypair],
+) -> Result<Signature> {
+    let blockhash = client.get_latest_blockhash().await?;
+    let signers = signers_with_payer(payer, signers);
+    let tx = Transaction::new_signed_with_payer(instructions, Some(&payer.pubkey()), &signers, blockhash);
+    client.send_and_confirm_transaction(&tx).await
+}
+
+/// `payer` first, then `signers` other than the payer, without duplicates
+fn signers_with_payer<'a>(payer: &'a Keypair, signers: &[&'a Keypair]) -> Vec<&'a Keypair> {
+    let mut all = vec![payer];
+    for signer in signers {
+        if !all.iter().any(|s| s.pubkey() == signer.pubkey()) {
+            all.push(signer);
+        }
+    }
+    all
+}
+
+/// Amount held by an SPL token account, in base units
+pub async fn token_balance(client: &impl LedgerClient, token_account: &Pubkey) -> Result<u64> {
+    let Some(account) = client.get_account(token_account).await? else {
+        bail!("Token account {} not found", token_account);
+    };
+    Ok(spl_token::state::Account::unpack(&account.data)
+        .with_context(|| format!("{} is not a token account", token_account))?
+        .amount)
+}
+
+/// Fetch any number of accounts, [`MAX_MULTIPLE_ACCOUNTS`] per call with up
+/// to [`FAN_OUT`] calls in flight; results are in the order of `addresses`
+pub async fn get_accounts(client: &impl LedgerClient, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+    let chunks: Vec<Vec<Pubkey>> = addresses.chunks(MAX_MULTIPLE_ACCOUNTS).map(<[_]>::to_vec).collect();
+    let batches: Vec<_> = stream::iter(chunks)
+        .map(|chunk| async move { client.get_multiple_accounts(&chunk).await })
+        .buffered(FAN_OUT)
+        .try_collect()
+        .await?;
+    Ok(batches.into_iter().flatten().collect())
+}
+
+/// Run `work` unless Ctrl-C comes first, in which case it is dropped
+/// mid-flight and an error is returned
+pub async fn until_ctrl_c<T>(work: impl Future<Output = Result<T>>) -> Result<T> {
+    tokio::select! {
+        result = work => result,
+        _ = tokio::signal::ctrl_c() => bail!("Interrupted"),
+    }
+}
@@ -0,0 +1,250 @@
+//! Record a session against a real ledger, replay it without one
+//!
+//! [`Recorder`] wraps any [`LedgerClient`] and notes every call with its
+//! arguments and outcome; [`Recorder::save`] writes the tape as JSON.
+//! [`Replayer`] loads a tape and answers the same calls, in the same order,
+//! with the recorded outcomes. A call that does not match the next entry on
+//! the tape fails loudly, so a replayed test also checks that the code under
+//! test still makes the calls it made when recorded. Record with fixed
+//! keypairs: transactions are compared byte for byte.
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use solana_client::rpc_filter::RpcFilterType;
+use solana_sdk::{
+    account::Account,
+    clock::Slot,
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::{Transaction, TransactionError},
+};
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::Mutex;
+
+use super::LedgerClient;
+
+/// One call on the tape
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Call {
+    pub method: String,
+    pub args: Value,
+    /// The result, or the error message
+    pub outcome: Result<Value, String>,
+}
+
+pub struct Recorder<C> {
+    inner: C,
+    tape: Mutex<Vec<Call>>,
+}
+
+impl<C: LedgerClient> Recorder<C> {
+    pub fn new(inner: C) -> Self {
+        Self {
+            inner,
+            tape: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// The calls recorded so far
+    pub fn calls(&self) -> Vec<Call> {
+        self.tape.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.calls())?;
+        std::fs::write(path, json).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    fn note<T: Serialize>(&self, method: &str, args: Value, result: Result<T>) -> Result<T> {
+        let outcome = match &result {
+            Ok(value) => Ok(serde_json::to_value(value)?),
+            Err(e) => Err(format!("{:#}", e)),
+        };
+        self.tape.lock().unwrap_or_else(|e| e.into_inner()).push(Call {
+            method: method.to_string(),
+            args,
+            outcome,
+        });
+        result
+    }
+}
+
+impl<C: LedgerClient> LedgerClient for Recorder<C> {
+    async fn get_balance(&self, address: &Pubkey) -> Result<u64> {
+        let result = self.inner.get_balance(address).await;
+        self.note("get_balance", json!([address]), result)
+    }
+
+    async fn get_account(&self, address: &Pubkey) -> Result<Option<Account>> {
+        let result = self.inner.get_account(address).await;
+        self.note("get_account", json!([address]), result)
+    }
+
+    async fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        let result = self.inner.get_multiple_accounts(addresses).await;
+        self.note("get_multiple_accounts", json!([addresses]), result)
+    }
+
+    async fn get_program_accounts(&self, program_id: &Pubkey, filters: &[RpcFilterType]) -> Result<Vec<(Pubkey, Account)>> {
+        let result = self.inner.get_program_accounts(program_id, filters).await;
+        self.note("get_program_accounts", json!([program_id, filters]), result)
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64> {
+        let result = self.inner.get_minimum_balance_for_rent_exemption(data_len).await;
+        self.note("get_minimum_balance_for_rent_exemption", json!([data_len]), result)
+    }
+
+    async fn get_latest_blockhash(&self) -> Result<Hash> {
+        let result = self.inner.get_latest_blockhash().await;
+        self.note("get_latest_blockhash", json!([]), result)
+    }
+
+    async fn get_latest_blockhash_with_expiry(&self) -> Result<(Hash, u64)> {
+        let result = self.inner.get_latest_blockhash_with_expiry().await;
+        self.note("get_latest_blockhash_with_expiry", json!([]), result)
+    }
+
+    async fn get_slot(&self) -> Result<Slot> {
+        let result = self.inner.get_slot().await;
+        self.note("get_slot", json!([]), result)
+    }
+
+    async fn get_block_height(&self) -> Result<u64> {
+        let result = self.inner.get_block_height().await;
+        self.note("get_block_height", json!([]), result)
+    }
+
+    async fn request_airdrop(&self, to: &Pubkey, lamports: u64) -> Result<Signature> {
+        let result = self.inner.request_airdrop(to, lamports).await;
+        self.note("request_airdrop", json!([to, lamports]), result)
+    }
+
+    async fn send_transaction(&self, tx: &Transaction) -> Result<Signature> {
+        let result = self.inner.send_transaction(tx).await;
+        self.note("send_transaction", json!([tx]), result)
+    }
+
+    async fn resend_transaction(&self, tx: &Transaction) -> Result<Signature> {
+        let result = self.inner.resend_transaction(tx).await;
+        self.note("resend_transaction", json!([tx]), result)
+    }
+
+    async fn get_signature_status(&self, signature: &Signature) -> Result<Option<Result<(), TransactionError>>> {
+        let result = self.inner.get_signature_status(signature).await;
+        self.note("get_signature_status", json!([signature]), result)
+    }
+
+    async fn send_and_confirm_transaction(&self, tx: &Transaction) -> Result<Signature> {
+        let result = self.inner.send_and_confirm_transaction(tx).await;
+        self.note("send_and_confirm_transaction", json!([tx]), result)
+    }
+}
+
+pub struct Replayer {
+    tape: Mutex<VecDeque<Call>>,
+}
+
+impl Replayer {
+    pub fn new(calls: Vec<Call>) -> Self {
+        Self {
+            tape: Mutex::new(calls.into()),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let calls = serde_json::from_str(&json).with_context(|| format!("Invalid tape {}", path.display()))?;
+        Ok(Self::new(calls))
+    }
+
+    /// Calls on the tape not replayed yet
+    pub fn remaining(&self) -> usize {
+        self.tape.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    fn play<T: DeserializeOwned>(&self, method: &str, args: Value) -> Result<T> {
+        let mut tape = self.tape.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(call) = tape.pop_front() else {
+            bail!("Replay: unexpected {}({}), the tape has ended", method, args);
+        };
+        if call.method != method || call.args != args {
+            bail!(
+                "Replay: expected {}({}), got {}({})",
+                call.method,
+                call.args,
+                method,
+                args
+            );
+        }
+        match call.outcome {
+            Ok(value) => serde_json::from_value(value).with_context(|| format!("Replay: bad {} result", method)),
+            Err(message) => Err(anyhow!(message)),
+        }
+    }
+}
+
+impl LedgerClient for Replayer {
+    async fn get_balance(&self, address: &Pubkey) -> Result<u64> {
+        self.play("get_balance", json!([address]))
+    }
+
+    async fn get_account(&self, address: &Pubkey) -> Result<Option<Account>> {
+        self.play("get_account", json!([address]))
+    }
+
+    async fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        self.play("get_multiple_accounts", json!([addresses]))
+    }
+
+    async fn get_program_accounts(&self, program_id: &Pubkey, filters: &[RpcFilterType]) -> Result<Vec<(Pubkey, Account)>> {
+        self.play("get_program_accounts", json!([program_id, filters]))
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64> {
+        self.play("get_minimum_balance_for_rent_exemption", json!([data_len]))
+    }
+
+    async fn get_latest_blockhash(&self) -> Result<Hash> {
+        self.play("get_latest_blockhash", json!([]))
+    }
+
+    async fn get_latest_blockhash_with_expiry(&self) -> Result<(Hash, u64)> {
+        self.play("get_latest_blockhash_with_expiry", json!([]))
+    }
+
+    async fn get_slot(&self) -> Result<Slot> {
+        self.play("get_slot", json!([]))
+    }
+
+    async fn get_block_height(&self) -> Result<u64> {
+        self.play("get_block_height", json!([]))
+    }
+
+    async fn request_airdrop(&self, to: &Pubkey, lamports: u64) -> Result<Signature> {
+        self.play("request_airdrop", json!([to, lamports]))
+    }
+
+    async fn send_transaction(&self, tx: &Transaction) -> Result<Signature> {
+        self.play("send_transaction", json!([tx]))
+    }
+
+    async fn resend_transaction(&self, tx: &Transaction) -> Result<Signature> {
+        self.play("resend_transaction", json!([tx]))
+    }
+
+    async fn get_signature_status(&self, signature: &Signature) -> Result<Option<Result<(), TransactionError>>> {
+        self.play("get_signature_status", json!([signature]))
+    }
+
+    async fn send_and_confirm_transaction(&self, tx: &Transaction) -> Result<Signature> {
+        self.play("send_and_confirm_transaction", json!([tx]))
+    }
+}
@@ -0,0 +1,409 @@
+//! [`LedgerClient`] over several RPC endpoints, with retries and failover
+//!
+//! Every call goes to the first healthy endpoint, in the order given. Errors
+//! are classified ([`classify`]):
+//! - **Retry**: timeouts and dropped connections. The call is retried after
+//!   an exponential backoff with full jitter. An endpoint is marked down
+//!   after [`RetryPolicy::max_failures`] of these in a row.
+//! - **Failover**: the endpoint cannot serve right now, e.g. HTTP 429, 5xx,
+//!   or a node that is behind or missing the data. It is marked down for
+//!   [`RetryPolicy::cooldown`] and the call moves to the next endpoint.
+//! - **Fatal**: retrying cannot help, e.g. invalid params, unknown method,
+//!   or a transaction the runtime rejected. The call fails at once.
+//!
+//! The RPC client already waits out a few 429s (honouring `Retry-After`)
+//! before reporting one. It also asks each node for its version before the
+//! first call; if that fails, the endpoint is failed over whatever the cause.
+//!
+//! A down endpoint comes back when its cooldown ends or when a health check
+//! ([`ResilientClient::check_health`]) finds it healthy. If every endpoint is
+//! down, the one that comes back first is used. Each endpoint can be rate
+//! limited to a number of requests per second.
+
+use anyhow::{bail, Result};
+use futures_util::future::join_all;
+use rand::Rng;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    nonblocking::rpc_client::RpcClient,
+    rpc_custom_error::{
+        JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE, JSON_RPC_SERVER_ERROR_BLOCK_STATUS_NOT_AVAILABLE_YET,
+        JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED, JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+        JSON_RPC_SERVER_ERROR_NO_SNAPSHOT,
+    },
+    rpc_filter::RpcFilterType,
+    rpc_request::RpcError,
+};
+use solana_sdk::{
+    account::Account,
+    clock::Slot,
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::{Transaction, TransactionError},
+};
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+use super::LedgerClient;
+
+/// Per-request HTTP timeout
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// JSON-RPC internal error; some providers also use it for overload
+const JSON_RPC_INTERNAL_ERROR: i64 = -32603;
+
+/// What to do about a failed call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// Try the same endpoint again after a backoff
+    Retry,
+    /// Mark the endpoint down and try the next one
+    Failover,
+    /// Give up
+    Fatal,
+}
+
+impl fmt::Display for ErrorClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Retry => write!(f, "retry"),
+            Self::Failover => write!(f, "failover"),
+            Self::Fatal => write!(f, "fatal"),
+        }
+    }
+}
+
+/// Classify an error returned by an RPC-backed [`LedgerClient`]. Errors that
+/// did not come from the RPC transport are fatal.
+pub fn classify(error: &anyhow::Error) -> ErrorClass {
+    match error.chain().find_map(|e| e.downcast_ref::<ClientError>()) {
+        Some(error) => classify_kind(error.kind()),
+        None => ErrorClass::Fatal,
+    }
+}
+
+fn classify_kind(kind: &ClientErrorKind) -> ErrorClass {
+    match kind {
+        ClientErrorKind::Io(_) => ErrorClass::Retry,
+        ClientErrorKind::Reqwest(e) => match e.status() {
+            Some(status) if status.as_u16() == 429 || status.is_server_error() => ErrorClass::Failover,
+            Some(_) => ErrorClass::Fatal,
+            None => ErrorClass::Retry,
+        },
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => match *code {
+            JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY
+            | JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED
+            | JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE
+            | JSON_RPC_SERVER_ERROR_BLOCK_STATUS_NOT_AVAILABLE_YET
+            | JSON_RPC_SERVER_ERROR_NO_SNAPSHOT
+            | JSON_RPC_INTERNAL_ERROR => ErrorClass::Failover,
+            _ => ErrorClass::Fatal,
+        },
+        // A response we could not read; another node may answer properly
+        ClientErrorKind::RpcError(RpcError::RpcRequestError(_) | RpcError::ParseError(_))
+        | ClientErrorKind::SerdeJson(_) => ErrorClass::Failover,
+        ClientErrorKind::RpcError(RpcError::ForUser(_))
+        | ClientErrorKind::SigningError(_)
+        | ClientErrorKind::TransactionError(_)
+        | ClientErrorKind::Custom(_) => ErrorClass::Fatal,
+    }
+}
+
+/// How hard [`ResilientClient`] tries before giving up on a call
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Attempts per call, over all endpoints
+    pub max_attempts: u32,
+    /// Backoff cap for the first retry; doubles with each retry
+    pub min_backoff: Duration,
+    /// Upper bound on the backoff cap
+    pub max_backoff: Duration,
+    /// How long a failed-over endpoint is skipped
+    pub cooldown: Duration,
+    /// Consecutive retryable failures before an endpoint is marked down
+    pub max_failures: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 6,
+            min_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(8),
+            cooldown: Duration::from_secs(30),
+            max_failures: 3,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Backoff before retry number `retry` (0-based): uniform in
+    /// `0..=min(max_backoff, min_backoff * 2^retry)`
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let cap = self
+            .min_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff);
+        let millis = rand::thread_rng().gen_range(0..=cap.as_millis() as u64);
+        Duration::from_millis(millis)
+    }
+}
+
+/// Spaces requests at least `1 / requests_per_second` apart
+struct RateLimiter {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    fn new(requests_per_second: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(1) / requests_per_second.max(1),
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Wait for this request's slot
+    async fn acquire(&self) {
+        let slot = {
+            let mut next = self.next.lock().unwrap();
+            let slot = (*next).max(Instant::now());
+            *next = slot + self.interval;
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+}
+
+#[derive(Default)]
+struct Health {
+    down_until: Option<Instant>,
+    failures: u32,
+    last_error: Option<String>,
+}
+
+struct Endpoint {
+    client: RpcClient,
+    limiter: Option<RateLimiter>,
+    health: Mutex<Health>,
+}
+
+impl Endpoint {
+    fn down_until(&self) -> Option<Instant> {
+        self.health
+            .lock()
+            .unwrap()
+            .down_until
+            .filter(|until| *until > Instant::now())
+    }
+
+    fn succeeded(&self) {
+        *self.health.lock().unwrap() = Health::default();
+    }
+
+    fn failed(&self, error: &anyhow::Error, class: ErrorClass, policy: &RetryPolicy) {
+        let mut health = self.health.lock().unwrap();
+        health.failures += 1;
+        health.last_error = Some(format!("{:#}", error));
+        if class == ErrorClass::Failover || health.failures >= policy.max_failures {
+            health.down_until = Some(Instant::now() + policy.cooldown);
+        }
+    }
+}
+
+/// State of one endpoint, as reported by [`ResilientClient::status`]
+#[derive(Debug, Clone)]
+pub struct EndpointStatus {
+    pub url: String,
+    /// Time left until the endpoint is used again; `None` when it is up
+    pub down_for: Option<Duration>,
+    /// Failures since the last success
+    pub failures: u32,
+    pub last_error: Option<String>,
+}
+
+/// A [`LedgerClient`] that spreads calls over several RPC endpoints
+pub struct ResilientClient {
+    endpoints: Arc<[Endpoint]>,
+    policy: RetryPolicy,
+}
+
+impl ResilientClient {
+    /// Endpoints in order of preference; `requests_per_second` limits each
+    /// one separately
+    pub fn new(
+        urls: &[String],
+        commitment: CommitmentConfig,
+        requests_per_second: Option<u32>,
+        policy: RetryPolicy,
+    ) -> Result<Self> {
+        if urls.is_empty() {
+            bail!("At least one RPC endpoint is required");
+        }
+        let endpoints = urls
+            .iter()
+            .map(|url| Endpoint {
+                client: RpcClient::new_with_timeout_and_commitment(url.clone(), REQUEST_TIMEOUT, commitment),
+                limiter: requests_per_second.map(RateLimiter::new),
+                health: Mutex::new(Health::default()),
+            })
+            .collect();
+        Ok(Self { endpoints, policy })
+    }
+
+    pub fn status(&self) -> Vec<EndpointStatus> {
+        self.endpoints
+            .iter()
+            .map(|endpoint| {
+                let health = endpoint.health.lock().unwrap();
+                EndpointStatus {
+                    url: endpoint.client.url(),
+                    down_for: health
+                        .down_until
+                        .and_then(|until| until.checked_duration_since(Instant::now())),
+                    failures: health.failures,
+                    last_error: health.last_error.clone(),
+                }
+            })
+            .collect()
+    }
+
+    /// Call `getHealth` on every endpoint: healthy ones are marked up, the
+    /// others down for a cooldown
+    pub async fn check_health(&self) {
+        check_health(&self.endpoints, &self.policy).await;
+    }
+
+    /// Run [`Self::check_health`] every `interval` until the handle is
+    /// aborted
+    pub fn spawn_health_checks(&self, interval: Duration) -> JoinHandle<()> {
+        let endpoints = self.endpoints.clone();
+        let policy = self.policy.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                check_health(&endpoints, &policy).await;
+            }
+        })
+    }
+
+    /// The first endpoint that is up or, when all are down, the one that
+    /// comes back first
+    fn pick(&self) -> &Endpoint {
+        self.endpoints
+            .iter()
+            .min_by_key(|endpoint| endpoint.down_until())
+            .expect("at least one endpoint")
+    }
+
+    async fn call<'c, T, F, Fut>(&'c self, f: F) -> Result<T>
+    where
+        F: Fn(&'c RpcClient) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut retry = 0;
+        loop {
+            let endpoint = self.pick();
+            if let Some(limiter) = &endpoint.limiter {
+                limiter.acquire().await;
+            }
+            let error = match f(&endpoint.client).await {
+                Ok(value) => {
+                    endpoint.succeeded();
+                    return Ok(value);
+                }
+                Err(error) => error,
+            };
+            let class = classify(&error);
+            if class == ErrorClass::Fatal {
+                return Err(error);
+            }
+            endpoint.failed(&error, class, &self.policy);
+            retry += 1;
+            if retry >= self.policy.max_attempts {
+                return Err(error.context(format!("Giving up after {} attempts", retry)));
+            }
+            tokio::time::sleep(self.policy.backoff(retry - 1)).await;
+        }
+    }
+}
+
+async fn check_health(endpoints: &[Endpoint], policy: &RetryPolicy) {
+    let results = join_all(endpoints.iter().map(|endpoint| endpoint.client.get_health())).await;
+    for (endpoint, result) in endpoints.iter().zip(results) {
+        match result {
+            Ok(()) => endpoint.succeeded(),
+            Err(e) => endpoint.failed(&e.into(), ErrorClass::Failover, policy),
+        }
+    }
+}
+
+impl LedgerClient for ResilientClient {
+    async fn get_balance(&self, address: &Pubkey) -> Result<u64> {
+        self.call(|client| LedgerClient::get_balance(client, address)).await
+    }
+
+    async fn get_account(&self, address: &Pubkey) -> Result<Option<Account>> {
+        self.call(|client| LedgerClient::get_account(client, address)).await
+    }
+
+    async fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        self.call(|client| LedgerClient::get_multiple_accounts(client, addresses)).await
+    }
+
+    async fn get_program_accounts(&self, program_id: &Pubkey, filters: &[RpcFilterType]) -> Result<Vec<(Pubkey, Account)>> {
+        self.call(|client| LedgerClient::get_program_accounts(client, program_id, filters))
+            .await
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64> {
+        self.call(|client| LedgerClient::get_minimum_balance_for_rent_exemption(client, data_len))
+            .await
+    }
+
+    async fn get_latest_blockhash(&self) -> Result<Hash> {
+        self.call(LedgerClient::get_latest_blockhash).await
+    }
+
+    async fn get_latest_blockhash_with_expiry(&self) -> Result<(Hash, u64)> {
+        self.call(LedgerClient::get_latest_blockhash_with_expiry).await
+    }
+
+    async fn get_slot(&self) -> Result<Slot> {
+        self.call(LedgerClient::get_slot).await
+    }
+
+    async fn get_block_height(&self) -> Result<u64> {
+        self.call(LedgerClient::get_block_height).await
+    }
+
+    async fn request_airdrop(&self, to: &Pubkey, lamports: u64) -> Result<Signature> {
+        self.call(|client| LedgerClient::request_airdrop(client, to, lamports)).await
+    }
+
+    /// Resending is safe: the signature makes a transaction land at most once
+    async fn send_transaction(&self, tx: &Transaction) -> Result<Signature> {
+        self.call(|client| LedgerClient::send_transaction(client, tx)).await
+    }
+
+    async fn resend_transaction(&self, tx: &Transaction) -> Result<Signature> {
+        self.call(|client| LedgerClient::resend_transaction(client, tx)).await
+    }
+
+    async fn get_signature_status(&self, signature: &Signature) -> Result<Option<Result<(), TransactionError>>> {
+        self.call(|client| LedgerClient::get_signature_status(client, signature))
+            .await
+    }
+
+    async fn send_and_confirm_transaction(&self, tx: &Transaction) -> Result<Signature> {
+        self.call(|client| LedgerClient::send_and_confirm_transaction(client, tx))
+            .await
+    }
+}
@@ -0,0 +1,193 @@
+//! [`LedgerClient`] over JSON-RPC, for the blocking and nonblocking clients
+//!
+//! Both read at the commitment the client was created with.
+
+use anyhow::{Context, Result};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    nonblocking, rpc_client,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSendTransactionConfig},
+    rpc_filter::RpcFilterType,
+};
+use solana_sdk::{
+    account::Account,
+    clock::Slot,
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::{Transaction, TransactionError},
+};
+
+use super::LedgerClient;
+
+fn program_accounts_config(filters: &[RpcFilterType], commitment: CommitmentConfig) -> RpcProgramAccountsConfig {
+    RpcProgramAccountsConfig {
+        filters: (!filters.is_empty()).then(|| filters.to_vec()),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(commitment),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    }
+}
+
+fn skip_preflight() -> RpcSendTransactionConfig {
+    RpcSendTransactionConfig {
+        skip_preflight: true,
+        ..RpcSendTransactionConfig::default()
+    }
+}
+
+impl LedgerClient for rpc_client::RpcClient {
+    async fn get_balance(&self, address: &Pubkey) -> Result<u64> {
+        Self::get_balance(self, address).with_context(|| format!("Failed to get balance of {}", address))
+    }
+
+    async fn get_account(&self, address: &Pubkey) -> Result<Option<Account>> {
+        Ok(self
+            .get_account_with_commitment(address, self.commitment())
+            .with_context(|| format!("Failed to fetch {}", address))?
+            .value)
+    }
+
+    async fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        Self::get_multiple_accounts(self, addresses).context("Failed to fetch accounts")
+    }
+
+    async fn get_program_accounts(&self, program_id: &Pubkey, filters: &[RpcFilterType]) -> Result<Vec<(Pubkey, Account)>> {
+        self.get_program_accounts_with_config(program_id, program_accounts_config(filters, self.commitment()))
+            .with_context(|| format!("Failed to load accounts of {}", program_id))
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64> {
+        Self::get_minimum_balance_for_rent_exemption(self, data_len).context("Failed to get rent exemption")
+    }
+
+    async fn get_latest_blockhash(&self) -> Result<Hash> {
+        Self::get_latest_blockhash(self).context("Failed to get recent blockhash")
+    }
+
+    async fn get_latest_blockhash_with_expiry(&self) -> Result<(Hash, u64)> {
+        self.get_latest_blockhash_with_commitment(self.commitment())
+            .context("Failed to get recent blockhash")
+    }
+
+    async fn get_slot(&self) -> Result<Slot> {
+        Self::get_slot(self).context("Failed to get slot")
+    }
+
+    async fn get_block_height(&self) -> Result<u64> {
+        Self::get_block_height(self).context("Failed to get block height")
+    }
+
+    async fn request_airdrop(&self, to: &Pubkey, lamports: u64) -> Result<Signature> {
+        let signature = Self::request_airdrop(self, to, lamports)
+            .context("Failed to request airdrop. Make sure localnet is running.")?;
+        self.poll_for_signature_with_commitment(&signature, self.commitment())
+            .context("Failed to confirm airdrop transaction")?;
+        Ok(signature)
+    }
+
+    async fn send_transaction(&self, tx: &Transaction) -> Result<Signature> {
+        Ok(Self::send_transaction(self, tx)?)
+    }
+
+    async fn resend_transaction(&self, tx: &Transaction) -> Result<Signature> {
+        Ok(self.send_transaction_with_config(tx, skip_preflight())?)
+    }
+
+    async fn get_signature_status(&self, signature: &Signature) -> Result<Option<Result<(), TransactionError>>> {
+        self.get_signature_status_with_commitment_and_history(signature, self.commitment(), true)
+            .with_context(|| format!("Failed to get status of {}", signature))
+    }
+
+    async fn send_and_confirm_transaction(&self, tx: &Transaction) -> Result<Signature> {
+        Ok(Self::send_and_confirm_transaction(self, tx)?)
+    }
+}
+
+impl LedgerClient for nonblocking::rpc_client::RpcClient {
+    async fn get_balance(&self, address: &Pubkey) -> Result<u64> {
+        Self::get_balance(self, address)
+            .await
+            .with_context(|| format!("Failed to get balance of {}", address))
+    }
+
+    async fn get_account(&self, address: &Pubkey) -> Result<Option<Account>> {
+        Ok(self
+            .get_account_with_commitment(address, self.commitment())
+            .await
+            .with_context(|| format!("Failed to fetch {}", address))?
+            .value)
+    }
+
+    async fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        Self::get_multiple_accounts(self, addresses)
+            .await
+            .context("Failed to fetch accounts")
+    }
+
+    async fn get_program_accounts(&self, program_id: &Pubkey, filters: &[RpcFilterType]) -> Result<Vec<(Pubkey, Account)>> {
+        self.get_program_accounts_with_config(program_id, program_accounts_config(filters, self.commitment()))
+            .await
+            .with_context(|| format!("Failed to load accounts of {}", program_id))
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64> {
+        Self::get_minimum_balance_for_rent_exemption(self, data_len)
+            .await
+            .context("Failed to get rent exemption")
+    }
+
+    async fn get_latest_blockhash(&self) -> Result<Hash> {
+        Self::get_latest_blockhash(self)
+            .await
+            .context("Failed to get recent blockhash")
+    }
+
+    async fn get_latest_blockhash_with_expiry(&self) -> Result<(Hash, u64)> {
+        self.get_latest_blockhash_with_commitment(self.commitment())
+            .await
+            .context("Failed to get recent blockhash")
+    }
+
+    async fn get_slot(&self) -> Result<Slot> {
+        Self::get_slot(self).await.context("Failed to get slot")
+    }
+
+    async fn get_block_height(&self) -> Result<u64> {
+        Self::get_block_height(self)
+            .await
+            .context("Failed to get block height")
+    }
+
+    async fn request_airdrop(&self, to: &Pubkey, lamports: u64) -> Result<Signature> {
+        let signature = Self::request_airdrop(self, to, lamports)
+            .await
+            .context("Failed to request airdrop. Make sure localnet is running.")?;
+        self.poll_for_signature_with_commitment(&signature, self.commitment())
+            .await
+            .context("Failed to confirm airdrop transaction")?;
+        Ok(signature)
+    }
+
+    async fn send_transaction(&self, tx: &Transaction) -> Result<Signature> {
+        Ok(Self::send_transaction(self, tx).await?)
+    }
+
+    async fn resend_transaction(&self, tx: &Transaction) -> Result<Signature> {
+        Ok(self.send_transaction_with_config(tx, skip_preflight()).await?)
+    }
+
+    async fn get_signature_status(&self, signature: &Signature) -> Result<Option<Result<(), TransactionError>>> {
+        self.get_signature_status_with_commitment_and_history(signature, self.commitment(), true)
+            .await
+            .with_context(|| format!("Failed to get status of {}", signature))
+    }
+
+    async fn send_and_confirm_transaction(&self, tx: &Transaction) -> Result<Signature> {
+        Ok(Self::send_and_confirm_transaction(self, tx).await?)
+    }
+}
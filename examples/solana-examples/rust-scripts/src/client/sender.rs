@@ -0,0 +1,285 @@
+//! Send a transaction until it lands, re-signing it when its blockhash expires
+//!
+//! [`send_with_retry`] signs the instructions with the latest blockhash and
+//! sends them once with preflight checks, so obvious failures come back at
+//! once. It then rebroadcasts the same transaction without preflight every
+//! [`SendConfig::rebroadcast_interval`] until it is confirmed, fails, or its
+//! blockhash expires. A leader that dropped it gets another copy, and the
+//! signature keeps it from landing twice. Only a node that refused the
+//! transaction itself, e.g. in preflight simulation, ends the attempt at the
+//! first send; after a transport error the transaction may have been
+//! delivered, so it is watched like any other.
+//!
+//! On expiry the instructions are signed again with a fresh blockhash, up to
+//! [`SendConfig::max_blockhashes`] blockhashes. This is only safe once the
+//! old transaction can no longer land. So the sender waits until the block
+//! height, read at the client's commitment, has passed the old blockhash's
+//! last valid height, then checks the old signature one last time. Use a
+//! client at `confirmed` or `finalized` commitment, because at `processed` a
+//! transaction seen on a fork that is later dropped would look unknown.
+//!
+//! Every attempt is recorded in the returned [`SendReport`], successful or
+//! not.
+
+use anyhow::{anyhow, Result};
+use solana_client::client_error::ClientError;
+use solana_sdk::{
+    hash::Hash,
+    instruction::Instruction,
+    signature::{Keypair, Signature, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use std::fmt;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use super::{signers_with_payer, LedgerClient};
+
+/// RPC errors tolerated per attempt before giving up on it
+pub const MAX_ERRORS: usize = 20;
+
+#[derive(Debug, Clone)]
+pub struct SendConfig {
+    /// Time between status checks, each followed by a rebroadcast
+    pub rebroadcast_interval: Duration,
+    /// Blockhashes to try before giving up; 1 never re-signs
+    pub max_blockhashes: u32,
+}
+
+impl Default for SendConfig {
+    fn default() -> Self {
+        Self {
+            rebroadcast_interval: Duration::from_secs(2),
+            max_blockhashes: 3,
+        }
+    }
+}
+
+/// How one signed transaction ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttemptEnd {
+    /// Landed and succeeded
+    Confirmed,
+    /// Landed and failed; the instructions ran, so it is not retried
+    Failed(TransactionError),
+    /// Its blockhash expired before it landed
+    Expired,
+    /// The first send was refused with a transaction error, e.g. preflight
+    /// simulation failed, so it cannot land
+    Rejected(String),
+    /// The sender could not get a blockhash to sign with
+    NoBlockhash(String),
+    /// Too many RPC errors to tell whether it landed; not re-signed
+    Unknown,
+}
+
+impl fmt::Display for AttemptEnd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Confirmed => write!(f, "confirmed"),
+            Self::Failed(e) => write!(f, "failed: {}", e),
+            Self::Expired => write!(f, "blockhash expired"),
+            Self::Rejected(e) => write!(f, "rejected: {}", e),
+            Self::NoBlockhash(e) => write!(f, "no blockhash: {}", e),
+            Self::Unknown => write!(f, "outcome unknown after {} RPC errors", MAX_ERRORS),
+        }
+    }
+}
+
+/// One signed version of the transaction
+#[derive(Debug, Clone)]
+pub struct Attempt {
+    /// Default when no blockhash could be fetched
+    pub signature: Signature,
+    pub blockhash: Hash,
+    pub last_valid_block_height: u64,
+    /// Times the transaction was submitted, including the first send
+    pub broadcasts: u32,
+    /// Status checks and rebroadcasts that failed along the way
+    pub errors: Vec<String>,
+    pub elapsed: Duration,
+    pub end: AttemptEnd,
+}
+
+/// Everything [`send_with_retry`] did
+#[derive(Debug, Clone, Default)]
+pub struct SendReport {
+    pub attempts: Vec<Attempt>,
+}
+
+impl SendReport {
+    /// Signature of the confirmed attempt, if any
+    pub fn signature(&self) -> Option<Signature> {
+        self.attempts
+            .iter()
+            .find(|attempt| attempt.end == AttemptEnd::Confirmed)
+            .map(|attempt| attempt.signature)
+    }
+
+    /// The confirmed signature, or an error carrying the attempt log
+    pub fn into_result(self) -> Result<Signature> {
+        match self.signature() {
+            Some(signature) => Ok(signature),
+            None => {
+                let last = self.attempts.last().map(|attempt| attempt.end.to_string());
+                Err(anyhow!(
+                    "Transaction not confirmed ({}) after {} attempt(s):\n{}",
+                    last.unwrap_or_default(),
+                    self.attempts.len(),
+                    self
+                ))
+            }
+        }
+    }
+
+    pub fn print(&self) {
+        println!("{}", self);
+    }
+}
+
+impl fmt::Display for SendReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, attempt) in self.attempts.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(
+                f,
+                "   📨 Attempt {}: {} (blockhash {}, valid until height {}) sent {}x in {:.1}s → {}",
+                i + 1,
+                attempt.signature,
+                attempt.blockhash,
+                attempt.last_valid_block_height,
+                attempt.broadcasts,
+                attempt.elapsed.as_secs_f64(),
+                attempt.end
+            )?;
+            for error in &attempt.errors {
+                write!(f, "\n      ⚠️  {}", error)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Sign `instructions` with `payer` (fee payer) and `signers` and send them
+/// until they are confirmed, fail, or `config.max_blockhashes` blockhashes
+/// have expired
+pub async fn send_with_retry(
+    client: &impl LedgerClient,
+    instructions: &[Instruction],
+    payer: &Keypair,
+    signers: &[&Keypair],
+    config: &SendConfig,
+) -> SendReport {
+    let signers = signers_with_payer(payer, signers);
+    let mut report = SendReport::default();
+    for _ in 0..config.max_blockhashes.max(1) {
+        let attempt = attempt(client, instructions, &signers, config).await;
+        let expired = attempt.end == AttemptEnd::Expired;
+        report.attempts.push(attempt);
+        if !expired {
+            break;
+        }
+    }
+    report
+}
+
+/// Sign once and broadcast until the transaction lands or expires
+async fn attempt(
+    client: &impl LedgerClient,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+    config: &SendConfig,
+) -> Attempt {
+    let (blockhash, last_valid_block_height) = match client.get_latest_blockhash_with_expiry().await {
+        Ok(latest) => latest,
+        Err(e) => {
+            return Attempt {
+                signature: Signature::default(),
+                blockhash: Hash::default(),
+                last_valid_block_height: 0,
+                broadcasts: 0,
+                errors: Vec::new(),
+                elapsed: Duration::ZERO,
+                end: AttemptEnd::NoBlockhash(format!("{:#}", e)),
+            }
+        }
+    };
+    let tx = Transaction::new_signed_with_payer(instructions, Some(&signers[0].pubkey()), signers, blockhash);
+    broadcast(client, &tx, last_valid_block_height, config).await
+}
+
+/// Send an already signed transaction and rebroadcast it until it lands or
+/// its blockhash, valid up to `last_valid_block_height`, expires. For
+/// callers that record the signature before sending.
+pub async fn broadcast(
+    client: &impl LedgerClient,
+    tx: &Transaction,
+    last_valid_block_height: u64,
+    config: &SendConfig,
+) -> Attempt {
+    let start = Instant::now();
+    let mut attempt = Attempt {
+        signature: tx.signatures[0],
+        blockhash: tx.message.recent_blockhash,
+        last_valid_block_height,
+        broadcasts: 0,
+        errors: Vec::new(),
+        elapsed: Duration::ZERO,
+        end: AttemptEnd::Unknown,
+    };
+    attempt.end = 'sending: {
+        match client.send_transaction(tx).await {
+            Ok(_) => attempt.broadcasts = 1,
+            Err(e) if refusal(&e).is_some() => break 'sending AttemptEnd::Rejected(format!("{:#}", e)),
+            // The node may have taken it before the connection failed
+            Err(e) => attempt.errors.push(format!("Send failed: {:#}", e)),
+        }
+
+        while attempt.errors.len() < MAX_ERRORS {
+            tokio::time::sleep(config.rebroadcast_interval).await;
+            match status(client, &attempt.signature).await {
+                Ok(Some(end)) => break 'sending end,
+                Ok(None) => {}
+                Err(e) => attempt.errors.push(e),
+            }
+            match client.get_block_height().await {
+                // Past its last valid height the transaction can no longer
+                // land; one more look settles whether it did
+                Ok(height) if height > last_valid_block_height => match status(client, &attempt.signature).await {
+                    Ok(end) => break 'sending end.unwrap_or(AttemptEnd::Expired),
+                    Err(e) => attempt.errors.push(e),
+                },
+                Ok(_) => {}
+                Err(e) => attempt.errors.push(format!("{:#}", e)),
+            }
+            match client.resend_transaction(tx).await {
+                Ok(_) => attempt.broadcasts += 1,
+                Err(e) => attempt.errors.push(format!("Rebroadcast failed: {:#}", e)),
+            }
+        }
+        AttemptEnd::Unknown
+    };
+    attempt.elapsed = start.elapsed();
+    attempt
+}
+
+/// The transaction error a node refused a send with. Transport and other
+/// RPC errors carry none: the transaction may have been delivered anyway.
+pub fn refusal(error: &anyhow::Error) -> Option<TransactionError> {
+    error.chain().find_map(|e| {
+        e.downcast_ref::<ClientError>()
+            .and_then(ClientError::get_transaction_error)
+            .or_else(|| e.downcast_ref::<TransactionError>().cloned())
+    })
+}
+
+async fn status(client: &impl LedgerClient, signature: &Signature) -> Result<Option<AttemptEnd>, String> {
+    match client.get_signature_status(signature).await {
+        Ok(Some(Ok(()))) => Ok(Some(AttemptEnd::Confirmed)),
+        Ok(Some(Err(e))) => Ok(Some(AttemptEnd::Failed(e))),
+        Ok(None) => Ok(None),
+        Err(e) => Err(format!("{:#}", e)),
+    }
+}
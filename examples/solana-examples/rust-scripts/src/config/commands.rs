@@ -0,0 +1,137 @@
+use anyhow::{anyhow, Result};
+use clap::Subcommand;
+use gdx_config::{diff, ConfigWatcher, GlobalConfig, ReloadEvent};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Check a config file and list every violation
+    Validate {
+        path: PathBuf,
+        /// Keep running and re-validate whenever the file changes
+        #[arg(long)]
+        watch: bool,
+        /// Poll interval for --watch, in milliseconds
+        #[arg(long, default_value = "1000", value_parser = clap::value_parser!(u64).range(1..))]
+        interval_ms: u64,
+    },
+    /// Print markets grouped by shard, and the feature flags
+    Show {
+        path: PathBuf,
+        /// Print the normalized config (defaults filled in) as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List changes from one config file to another
+    Diff { old: PathBuf, new: PathBuf },
+}
+
+/// Load a config, with every violation in the error message
+pub fn load(path: &Path) -> Result<GlobalConfig> {
+    gdx_config::load(path).map_err(|e| anyhow!("{} is invalid: {}", path.display(), e))
+}
+
+pub async fn run(command: ConfigCommand) -> Result<()> {
+    match command {
+        ConfigCommand::Validate {
+            path,
+            watch,
+            interval_ms,
+        } => {
+            let config = load(&path)?;
+            println!(
+                "✅ {} is valid ({} markets, {} shards)",
+                path.display(),
+                config.markets.len(),
+                config.shards.len()
+            );
+            if watch {
+                watch_config(path, Duration::from_millis(interval_ms)).await?;
+            }
+            Ok(())
+        }
+        ConfigCommand::Show { path, json } => {
+            let config = load(&path)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&config)?);
+            } else {
+                show(&config);
+            }
+            Ok(())
+        }
+        ConfigCommand::Diff { old, new } => {
+            let before = load(&old)?;
+            let after = load(&new)?;
+            let changes = diff(&before, &after);
+            if changes.is_empty() {
+                println!("✅ No differences");
+            } else {
+                println!("📝 {} change(s):", changes.len());
+                for change in changes {
+                    println!("   {}", change);
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+fn show(config: &GlobalConfig) {
+    println!("🗺️  Shards:");
+    for shard in &config.shards {
+        println!(
+            "   #{} {} {}",
+            shard.id,
+            shard.endpoint,
+            shard.region.as_deref().unwrap_or("")
+        );
+        for market in config.markets_on(shard.id) {
+            let tick = market.tick_size.map(|t| format!("tick {}", t)).unwrap_or_default();
+            let min = market
+                .min_order_size
+                .map(|m| format!("min {}", m))
+                .unwrap_or_default();
+            println!("      {:<12} {:<14} {}", market.symbol, tick, min);
+        }
+    }
+
+    let flags = &config.feature_flags;
+    println!("\n🚩 Feature flags:");
+    println!("   Maintenance Mode: {}", flags.maintenance_mode);
+    println!("   New Matching Algo: {}", flags.enable_new_matching_algo);
+    println!("   Max Leverage: {}x", flags.max_leverage_multiplier);
+    if flags.maintenance_mode {
+        println!("\n⚠️  Maintenance mode is on: trading is disabled");
+    }
+}
+
+async fn watch_config(path: PathBuf, interval: Duration) -> Result<()> {
+    println!("👀 Watching {} (Ctrl-C to stop)", path.display());
+    let mut watcher = ConfigWatcher::new(&path).map_err(|e| anyhow!("{} is invalid: {}", path.display(), e))?;
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = tokio::signal::ctrl_c() => {
+                println!("\n🛑 Stopped");
+                return Ok(());
+            }
+        }
+        match watcher.poll() {
+            None => {}
+            Some(ReloadEvent::Reloaded(changes)) if changes.is_empty() => {
+                println!("🔄 Reloaded, no changes");
+            }
+            Some(ReloadEvent::Reloaded(changes)) => {
+                println!("🔄 Reloaded with {} change(s):", changes.len());
+                for change in changes {
+                    println!("   {}", change);
+                }
+            }
+            Some(ReloadEvent::Rejected(e)) => {
+                println!("❌ Rejected, keeping previous config: {}", e);
+            }
+        }
+    }
+}
@@ -0,0 +1,6 @@
+//! `config` commands over the shared global configuration
+//!
+//! Loading, validation, diffing and hot reload live in the `gdx-config`
+//! crate (`config/gdx-config`) so every service reads the file the same way.
+
+pub mod commands;
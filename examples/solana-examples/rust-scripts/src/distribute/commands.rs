@@ -0,0 +1,123 @@
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, signature::Signer};
+use std::path::PathBuf;
+
+use super::{parse_csv, pay, plan, State};
+use crate::client::sender::{AttemptEnd, SendConfig};
+use crate::keypair::{load_keypair, DEFAULT_KEYPAIR_PATH};
+
+#[derive(Args)]
+pub struct DistributeArgs {
+    /// CSV of recipient,amount[,mint] rows; amounts in SOL or whole tokens
+    csv: PathBuf,
+    /// Transfers per transaction, at most (fewer when they do not fit)
+    #[arg(long, default_value = "8")]
+    per_tx: usize,
+    /// Progress file (default: next to the CSV, <NAME>.state.json)
+    #[arg(long)]
+    state: Option<PathBuf>,
+    /// Only print the plan and the balance check
+    #[arg(long)]
+    dry_run: bool,
+    /// Payer keypair; pays the transfers, fees and new token accounts
+    #[arg(long, default_value = DEFAULT_KEYPAIR_PATH)]
+    keypair: String,
+    /// RPC endpoint
+    #[arg(long, default_value = "http://127.0.0.1:8899")]
+    url: String,
+}
+
+pub async fn run(args: DistributeArgs) -> Result<()> {
+    if args.per_tx == 0 {
+        bail!("--per-tx must be at least 1");
+    }
+    let text = std::fs::read_to_string(&args.csv).with_context(|| format!("Failed to read {}", args.csv.display()))?;
+    let payouts = parse_csv(&text)?;
+    let payer = load_keypair(&args.keypair)?;
+    let path = args.state.unwrap_or_else(|| State::path(&args.csv));
+    let client = RpcClient::new_with_commitment(args.url, CommitmentConfig::confirmed());
+
+    let mut state = match State::resume(&path, &payer.pubkey())? {
+        Some(state) => {
+            println!(
+                "♻️  Resuming from {}: {} paid, {} failed, {} in flight",
+                path.display(),
+                state.paid.len(),
+                state.failed.len(),
+                state.in_flight.len()
+            );
+            state
+        }
+        None => State::new(&payer.pubkey()),
+    };
+    if !args.dry_run {
+        state.resolve_in_flight(&client, &path).await?;
+    }
+
+    let plan = plan(&client, &payouts, &state, &payer.pubkey(), args.per_tx).await?;
+    println!(
+        "📋 {} row(s), {} paid before, {} to pay in {} transaction(s), {} new token account(s)",
+        payouts.len(),
+        payouts.len() - plan.transfers.len(),
+        plan.transfers.len(),
+        plan.batches.len(),
+        plan.new_accounts
+    );
+    for need in &plan.needs {
+        println!("   {} {}", if need.is_short() { "❌" } else { "✅" }, need);
+    }
+    let shortfalls = plan.shortfalls();
+    if !shortfalls.is_empty() {
+        bail!(
+            "Payer {} cannot cover the batch: {} asset(s) short; nothing was sent",
+            payer.pubkey(),
+            shortfalls.len()
+        );
+    }
+    if args.dry_run || plan.batches.is_empty() {
+        return Ok(());
+    }
+
+    let config = SendConfig::default();
+    let mut interrupted = false;
+    for (i, batch) in plan.batches.iter().enumerate() {
+        let transfers: Vec<_> = batch.iter().map(|&t| &plan.transfers[t]).collect();
+        let end = tokio::select! {
+            end = pay(&client, &transfers, &payer, &mut state, &path, &config) => end?,
+            _ = tokio::signal::ctrl_c() => {
+                // Whatever was in flight stays recorded; the next run settles it
+                interrupted = true;
+                break;
+            }
+        };
+        let lines: Vec<String> = transfers.iter().map(|t| t.line.to_string()).collect();
+        println!(
+            "   {}/{} line(s) {} → {}",
+            i + 1,
+            plan.batches.len(),
+            lines.join(","),
+            end
+        );
+        if end == AttemptEnd::Unknown {
+            eprintln!("⚠️  Could not tell whether the last transaction landed; stopping");
+            break;
+        }
+    }
+
+    let paid = plan.transfers.iter().filter(|t| state.paid.contains_key(&t.key)).count();
+    println!("💾 Progress saved to {}", path.display());
+    if interrupted {
+        bail!("Interrupted after {} of {} transfer(s); rerun to resume", paid, plan.transfers.len());
+    }
+    if paid < plan.transfers.len() {
+        bail!(
+            "{} of {} transfer(s) not paid; rerun to retry them",
+            plan.transfers.len() - paid,
+            plan.transfers.len()
+        );
+    }
+    println!("✅ Paid {} transfer(s)", paid);
+    Ok(())
+}
@@ -0,0 +1,530 @@
+//! Pay SOL and SPL tokens to many recipients from a CSV file
+//!
+//! Each row is `recipient,amount[,mint]`. Rows without a mint pay SOL, and
+//! rows with one pay that token into the recipient's associated token
+//! account (ATA). Missing ATAs are created in the same transaction.
+//! Amounts are in whole units (SOL, or tokens at the mint's decimals).
+//! Transfers are packed as many per transaction as fit.
+//!
+//! Progress lives in a [`State`] file. Before a transaction is sent, its
+//! signature and expiry are recorded as in flight. A run that crashed leaves
+//! it there, and the next run looks the signature up before paying anything.
+//! If it landed, its rows count as paid. If its blockhash has expired without
+//! it landing, the rows are paid again. If it may still land, the run stops.
+//! So a row is never paid twice.
+
+pub mod commands;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    instruction::Instruction,
+    message::Message,
+    packet::PACKET_DATA_SIZE,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::Transaction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+use spl_token::state::{Account as TokenAccount, Mint};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::client::sender::{broadcast, AttemptEnd, SendConfig};
+use crate::client::{get_accounts, LedgerClient};
+use crate::persist::{now, save_json};
+
+pub const SOL_DECIMALS: u8 = 9;
+
+/// Fee per signature; every payout transaction has one, the payer's
+pub const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// One CSV row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Payout {
+    /// 1-based line in the file
+    pub line: usize,
+    pub recipient: Pubkey,
+    /// Whole units as written, e.g. `1.5`
+    pub amount: String,
+    /// `None` pays SOL
+    pub mint: Option<Pubkey>,
+    /// Identifies the row in the state file: the row's content plus how
+    /// many identical rows came before it, so reordering the file or adding
+    /// rows does not change it
+    pub key: String,
+}
+
+/// Parse `recipient,amount[,mint]` rows. Blank lines, `#` comments and a
+/// header row starting with `recipient` are skipped.
+pub fn parse_csv(text: &str) -> Result<Vec<Payout>> {
+    let mut payouts = Vec::new();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for (i, raw) in text.lines().enumerate() {
+        let line = i + 1;
+        let row = raw.trim();
+        if row.is_empty() || row.starts_with('#') || row.to_ascii_lowercase().starts_with("recipient") {
+            continue;
+        }
+        let fields: Vec<&str> = row.split(',').map(str::trim).collect();
+        let (recipient, amount, mint) = match fields[..] {
+            [recipient, amount] | [recipient, amount, ""] => (recipient, amount, None),
+            [recipient, amount, mint] => (recipient, amount, Some(mint)),
+            _ => bail!("Line {}: expected recipient,amount[,mint], got {:?}", line, row),
+        };
+        let recipient = Pubkey::from_str(recipient).with_context(|| format!("Line {}: invalid recipient", line))?;
+        let mint = mint
+            .map(Pubkey::from_str)
+            .transpose()
+            .with_context(|| format!("Line {}: invalid mint", line))?;
+        // Validate now; a token's decimals are known once its mint is read
+        let places = amount.split_once('.').map_or(0, |(_, fraction)| fraction.len());
+        let decimals = if mint.is_some() { places.min(u8::MAX as usize) as u8 } else { SOL_DECIMALS };
+        parse_amount(amount, decimals).with_context(|| format!("Line {}: invalid amount", line))?;
+
+        let content = format!(
+            "{}:{}:{}",
+            recipient,
+            mint.map_or("SOL".to_string(), |m| m.to_string()),
+            amount
+        );
+        let count = seen.entry(content.clone()).or_default();
+        let key = format!("{}:{}", content, count);
+        *count += 1;
+        payouts.push(Payout {
+            line,
+            recipient,
+            amount: amount.to_string(),
+            mint,
+            key,
+        });
+    }
+    Ok(payouts)
+}
+
+/// Whole units to base units, exactly: `1.5` at 9 decimals is 1_500_000_000
+pub fn parse_amount(amount: &str, decimals: u8) -> Result<u64> {
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if whole.is_empty() && fraction.is_empty() {
+        bail!("Empty amount");
+    }
+    if !whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+        bail!("{:?} is not a positive decimal number", amount);
+    }
+    if fraction.len() > decimals as usize {
+        bail!("{:?} has more than {} decimal places", amount, decimals);
+    }
+    let digits = format!("{}{:0<width$}", whole, fraction, width = decimals as usize);
+    let units: u64 = digits
+        .trim_start_matches('0')
+        .parse()
+        .or_else(|e| if digits.chars().all(|c| c == '0') { Ok(0) } else { Err(e) })
+        .with_context(|| format!("{:?} is too large", amount))?;
+    if units == 0 {
+        bail!("Amount must be positive");
+    }
+    Ok(units)
+}
+
+/// A payout resolved against the ledger
+#[derive(Debug, Clone)]
+pub struct Transfer {
+    pub key: String,
+    pub line: usize,
+    pub recipient: Pubkey,
+    /// Base units
+    pub amount: u64,
+    pub token: Option<TokenTransfer>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TokenTransfer {
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub source: Pubkey,
+    pub destination: Pubkey,
+    /// Whether this transfer creates the destination ATA; only the first
+    /// transfer to a missing ATA does
+    pub create_account: bool,
+}
+
+impl Transfer {
+    pub fn instructions(&self, payer: &Pubkey) -> Result<Vec<Instruction>> {
+        let Some(token) = &self.token else {
+            return Ok(vec![system_instruction::transfer(payer, &self.recipient, self.amount)]);
+        };
+        let mut instructions = Vec::new();
+        if token.create_account {
+            instructions.push(create_associated_token_account_idempotent(
+                payer,
+                &self.recipient,
+                &token.mint,
+                &spl_token::id(),
+            ));
+        }
+        instructions.push(spl_token::instruction::transfer_checked(
+            &spl_token::id(),
+            &token.source,
+            &token.mint,
+            &token.destination,
+            payer,
+            &[],
+            self.amount,
+            token.decimals,
+        )?);
+        Ok(instructions)
+    }
+}
+
+/// Funds one asset needs against what the payer holds
+#[derive(Debug, Clone)]
+pub struct Need {
+    /// `None` for SOL
+    pub mint: Option<Pubkey>,
+    pub decimals: u8,
+    pub required: u64,
+    pub available: u64,
+}
+
+impl Need {
+    pub fn is_short(&self) -> bool {
+        self.available < self.required
+    }
+}
+
+impl fmt::Display for Need {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scale = 10f64.powi(self.decimals as i32);
+        write!(
+            f,
+            "{}: need {}, have {}",
+            self.mint.map_or("SOL".to_string(), |m| m.to_string()),
+            self.required as f64 / scale,
+            self.available as f64 / scale
+        )
+    }
+}
+
+/// The unpaid rows, ready to pack, and the balance check over all of them
+#[derive(Debug, Clone)]
+pub struct Plan {
+    pub transfers: Vec<Transfer>,
+    pub batches: Vec<Vec<usize>>,
+    /// ATAs the plan creates
+    pub new_accounts: usize,
+    /// SOL first, then one entry per mint
+    pub needs: Vec<Need>,
+}
+
+impl Plan {
+    pub fn shortfalls(&self) -> Vec<&Need> {
+        self.needs.iter().filter(|need| need.is_short()).collect()
+    }
+}
+
+/// Resolve the rows not paid yet against the ledger, pack them at most
+/// `per_tx` per transaction, and total what paying them costs: SOL for the
+/// transfers, fees and new ATAs' rent, and each token from the payer's ATA
+pub async fn plan(
+    client: &impl LedgerClient,
+    payouts: &[Payout],
+    state: &State,
+    payer: &Pubkey,
+    per_tx: usize,
+) -> Result<Plan> {
+    let pending: Vec<&Payout> = payouts.iter().filter(|p| !state.paid.contains_key(&p.key)).collect();
+
+    let mut mints: Vec<Pubkey> = pending.iter().filter_map(|p| p.mint).collect();
+    mints.sort();
+    mints.dedup();
+    let mut decimals = HashMap::new();
+    for (mint, account) in mints.iter().zip(get_accounts(client, &mints).await?) {
+        let Some(account) = account else {
+            bail!("Mint {} not found", mint);
+        };
+        if account.owner != spl_token::id() {
+            bail!("{} is not an SPL Token mint (owner {})", mint, account.owner);
+        }
+        let state = Mint::unpack(&account.data).with_context(|| format!("{} is not a mint", mint))?;
+        decimals.insert(*mint, state.decimals);
+    }
+
+    // Which destination ATAs exist, and what the payer's source ATAs hold
+    let destinations: Vec<Pubkey> = pending
+        .iter()
+        .filter_map(|p| p.mint.map(|mint| get_associated_token_address(&p.recipient, &mint)))
+        .collect();
+    let sources: Vec<Pubkey> = mints.iter().map(|mint| get_associated_token_address(payer, mint)).collect();
+    let mut lookup = destinations.clone();
+    lookup.extend(&sources);
+    lookup.sort();
+    lookup.dedup();
+    let existing: HashSet<Pubkey> = lookup
+        .iter()
+        .zip(get_accounts(client, &lookup).await?)
+        .filter(|(_, account)| account.is_some())
+        .map(|(address, _)| *address)
+        .collect();
+
+    let mut created = HashSet::new();
+    let mut transfers = Vec::new();
+    for payout in pending {
+        let token = payout.mint.map(|mint| {
+            let destination = get_associated_token_address(&payout.recipient, &mint);
+            TokenTransfer {
+                mint,
+                decimals: decimals[&mint],
+                source: get_associated_token_address(payer, &mint),
+                destination,
+                create_account: !existing.contains(&destination) && created.insert(destination),
+            }
+        });
+        let amount = parse_amount(&payout.amount, token.as_ref().map_or(SOL_DECIMALS, |t| t.decimals))
+            .with_context(|| format!("Line {}: invalid amount", payout.line))?;
+        transfers.push(Transfer {
+            key: payout.key.clone(),
+            line: payout.line,
+            recipient: payout.recipient,
+            amount,
+            token,
+        });
+    }
+    let batches = pack(&transfers, payer, per_tx)?;
+
+    let rent = client.get_minimum_balance_for_rent_exemption(TokenAccount::LEN).await?;
+    let sol = transfers.iter().filter(|t| t.token.is_none()).map(|t| t.amount).sum::<u64>()
+        + batches.len() as u64 * LAMPORTS_PER_SIGNATURE
+        + created.len() as u64 * rent;
+    let mut needs = vec![Need {
+        mint: None,
+        decimals: SOL_DECIMALS,
+        required: sol,
+        available: client.get_balance(payer).await?,
+    }];
+    for (mint, source) in mints.iter().zip(&sources) {
+        let required = transfers
+            .iter()
+            .filter(|t| t.token.as_ref().is_some_and(|token| token.mint == *mint))
+            .map(|t| t.amount)
+            .sum();
+        let available = match client.get_account(source).await? {
+            Some(account) => TokenAccount::unpack(&account.data)
+                .with_context(|| format!("{} is not a token account", source))?
+                .amount,
+            None => 0,
+        };
+        needs.push(Need {
+            mint: Some(*mint),
+            decimals: decimals[mint],
+            required,
+            available,
+        });
+    }
+
+    Ok(Plan {
+        transfers,
+        batches,
+        new_accounts: created.len(),
+        needs,
+    })
+}
+
+/// Group transfers (by index) into transactions: as many as fit the packet
+/// size, at most `per_tx`
+pub fn pack(transfers: &[Transfer], payer: &Pubkey, per_tx: usize) -> Result<Vec<Vec<usize>>> {
+    let mut batches: Vec<Vec<usize>> = Vec::new();
+    let mut batch = Vec::new();
+    let mut instructions = Vec::new();
+    for (i, transfer) in transfers.iter().enumerate() {
+        let more = transfer.instructions(payer)?;
+        let mut candidate = instructions.clone();
+        candidate.extend(more.iter().cloned());
+        if !batch.is_empty() && (batch.len() == per_tx || !fits(&candidate, payer)) {
+            batches.push(std::mem::take(&mut batch));
+            candidate = more;
+        }
+        if !fits(&candidate, payer) {
+            bail!("Line {}: the transfer alone does not fit a transaction", transfer.line);
+        }
+        batch.push(i);
+        instructions = candidate;
+    }
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+    Ok(batches)
+}
+
+fn fits(instructions: &[Instruction], payer: &Pubkey) -> bool {
+    let message = Message::new(instructions, Some(payer));
+    1 + 64 + message.serialize().len() <= PACKET_DATA_SIZE
+}
+
+/// A payout transaction that was sent and not seen landing yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InFlight {
+    /// Keys of the rows it pays
+    pub keys: Vec<String>,
+    pub last_valid_block_height: u64,
+}
+
+/// Progress of one payout file, saved before and after every transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct State {
+    pub payer: String,
+    pub started_at: i64,
+    pub updated_at: i64,
+    /// Row key → signature of the transaction that paid it
+    pub paid: BTreeMap<String, String>,
+    /// Signature → the rows of a transaction that may have landed
+    pub in_flight: BTreeMap<String, InFlight>,
+    /// Row key → last error; retried on the next run
+    pub failed: BTreeMap<String, String>,
+}
+
+impl State {
+    pub fn new(payer: &Pubkey) -> Self {
+        let now = now();
+        Self {
+            payer: payer.to_string(),
+            started_at: now,
+            updated_at: now,
+            paid: BTreeMap::new(),
+            in_flight: BTreeMap::new(),
+            failed: BTreeMap::new(),
+        }
+    }
+
+    /// Default location: next to the CSV, `payouts.csv` → `payouts.state.json`
+    pub fn path(csv: &Path) -> PathBuf {
+        csv.with_extension("state.json")
+    }
+
+    /// Load the state if there is one; it must belong to the same payer
+    pub fn resume(path: &Path, payer: &Pubkey) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let raw = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let state: Self = serde_json::from_str(&raw).with_context(|| format!("Invalid state file {}", path.display()))?;
+        if state.payer != payer.to_string() {
+            bail!(
+                "{} was written for payer {}; pass that keypair or a different --state",
+                path.display(),
+                state.payer
+            );
+        }
+        Ok(Some(state))
+    }
+
+    pub fn save(&mut self, path: &Path) -> Result<()> {
+        self.updated_at = now();
+        save_json(path, self)
+    }
+
+    /// Settle transactions a previous run left in flight. Fails if one may
+    /// still land, since paying its rows again could pay them twice.
+    pub async fn resolve_in_flight(&mut self, client: &impl LedgerClient, path: &Path) -> Result<()> {
+        if self.in_flight.is_empty() {
+            return Ok(());
+        }
+        let height = client.get_block_height().await?;
+        for (signature, flight) in std::mem::take(&mut self.in_flight) {
+            let status = client
+                .get_signature_status(&signature.parse().context("Invalid signature in state file")?)
+                .await?;
+            match status {
+                Some(Ok(())) => self.settle(&flight.keys, Ok(&signature)),
+                Some(Err(e)) => self.settle(&flight.keys, Err(e.to_string())),
+                None if height > flight.last_valid_block_height => {}
+                None => {
+                    self.in_flight.insert(signature.clone(), flight.clone());
+                    self.save(path)?;
+                    bail!(
+                        "Transaction {} from an earlier run may still land (valid until block height {}, now {}); rerun in a minute",
+                        signature,
+                        flight.last_valid_block_height,
+                        height
+                    );
+                }
+            }
+        }
+        self.save(path)
+    }
+
+    fn settle(&mut self, keys: &[String], result: Result<&str, String>) {
+        for key in keys {
+            match &result {
+                Ok(signature) => {
+                    self.failed.remove(key);
+                    self.paid.insert(key.clone(), signature.to_string());
+                }
+                Err(e) => {
+                    self.failed.insert(key.clone(), e.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Pay one batch: sign, record it in flight, send until it lands, and
+/// record the outcome. An expired transaction is re-signed, up to
+/// `config.max_blockhashes` times. Returns how the last attempt ended.
+pub async fn pay(
+    client: &impl LedgerClient,
+    transfers: &[&Transfer],
+    payer: &Keypair,
+    state: &mut State,
+    path: &Path,
+    config: &SendConfig,
+) -> Result<AttemptEnd> {
+    let mut instructions = Vec::new();
+    for transfer in transfers {
+        instructions.extend(transfer.instructions(&payer.pubkey())?);
+    }
+    let keys: Vec<String> = transfers.iter().map(|t| t.key.clone()).collect();
+
+    let mut end = AttemptEnd::Expired;
+    for _ in 0..config.max_blockhashes.max(1) {
+        let (blockhash, last_valid_block_height) = client.get_latest_blockhash_with_expiry().await?;
+        let tx = Transaction::new_signed_with_payer(&instructions, Some(&payer.pubkey()), &[payer], blockhash);
+        let signature = tx.signatures[0].to_string();
+        state.in_flight.insert(
+            signature.clone(),
+            InFlight {
+                keys: keys.clone(),
+                last_valid_block_height,
+            },
+        );
+        state.save(path)?;
+
+        end = broadcast(client, &tx, last_valid_block_height, config).await.end;
+        match &end {
+            AttemptEnd::Confirmed => state.settle(&keys, Ok(&signature)),
+            AttemptEnd::Failed(e) => state.settle(&keys, Err(e.to_string())),
+            AttemptEnd::Rejected(e) | AttemptEnd::NoBlockhash(e) => state.settle(&keys, Err(e.clone())),
+            AttemptEnd::Expired => {}
+            // Left in flight; the next run resolves it
+            AttemptEnd::Unknown => {
+                state.save(path)?;
+                return Ok(end);
+            }
+        }
+        state.in_flight.remove(&signature);
+        state.save(path)?;
+        if end != AttemptEnd::Expired {
+            break;
+        }
+    }
+    if end == AttemptEnd::Expired {
+        state.settle(&keys, Err(end.to_string()));
+        state.save(path)?;
+    }
+    Ok(end)
+}
@@ -0,0 +1,262 @@
+use anyhow::{bail, Result};
+use clap::Subcommand;
+use std::time::{Duration, Instant};
+
+use super::stream::{EngineEvent, OrderStream};
+use super::{EngineClient, Order, OrderRequest, OrderSide, OrderType, Visibility, DEFAULT_ENGINE_URL};
+
+#[derive(Subcommand)]
+pub enum EngineCommand {
+    /// Submit an order
+    Order {
+        /// User id (wallet address)
+        #[arg(long)]
+        user: String,
+        /// Market, e.g. BTC-USDT
+        #[arg(long)]
+        symbol: String,
+        #[arg(long, value_enum)]
+        side: OrderSide,
+        #[arg(long = "type", value_enum, default_value = "market")]
+        order_type: OrderType,
+        /// Base asset amount
+        #[arg(long)]
+        quantity: f64,
+        /// Limit price (required for limit orders)
+        #[arg(long)]
+        price: Option<f64>,
+        #[arg(long, default_value = "1")]
+        leverage: u16,
+        #[arg(long, value_enum)]
+        visibility: Option<Visibility>,
+        /// Wait until the engine has matched, failed or cancelled the order
+        #[arg(long)]
+        wait: bool,
+        /// Engine base URL
+        #[arg(long, default_value = DEFAULT_ENGINE_URL)]
+        url: String,
+    },
+    /// Cancel a pending order
+    Cancel {
+        /// Order id
+        id: String,
+        /// Engine base URL
+        #[arg(long, default_value = DEFAULT_ENGINE_URL)]
+        url: String,
+    },
+    /// Show one order, or all orders of a user
+    Orders {
+        /// Order id
+        #[arg(long, conflicts_with = "user", required_unless_present = "user")]
+        id: Option<String>,
+        /// User id
+        #[arg(long)]
+        user: Option<String>,
+        /// Engine base URL
+        #[arg(long, default_value = DEFAULT_ENGINE_URL)]
+        url: String,
+    },
+    /// Override the engine's reference price for a market
+    Price {
+        symbol: String,
+        price: f64,
+        /// Engine base URL
+        #[arg(long, default_value = DEFAULT_ENGINE_URL)]
+        url: String,
+    },
+    /// Show engine health and statistics
+    Stats {
+        /// Engine base URL
+        #[arg(long, default_value = DEFAULT_ENGINE_URL)]
+        url: String,
+    },
+    /// Stream order updates until Ctrl-C
+    Watch {
+        /// Only show orders of this user
+        #[arg(long)]
+        user: Option<String>,
+        /// Only show orders in this market
+        #[arg(long)]
+        symbol: Option<String>,
+        /// Print raw JSON lines instead of a summary
+        #[arg(long)]
+        json: bool,
+        /// Engine base URL (the feed shares its port)
+        #[arg(long, default_value = DEFAULT_ENGINE_URL)]
+        url: String,
+        /// WebSocket URL, if it is not the engine URL with ws://
+        #[arg(long)]
+        ws_url: Option<String>,
+    },
+}
+
+pub async fn run(command: EngineCommand) -> Result<()> {
+    match command {
+        EngineCommand::Order {
+            user,
+            symbol,
+            side,
+            order_type,
+            quantity,
+            price,
+            leverage,
+            visibility,
+            wait,
+            url,
+        } => {
+            if order_type == OrderType::Limit && price.is_none() {
+                bail!("Limit orders need --price");
+            }
+            let request = OrderRequest {
+                user_id: user,
+                symbol,
+                side,
+                order_type,
+                quantity,
+                price,
+                leverage,
+                visibility,
+                trading_view_price: None,
+            };
+            submit(EngineClient::new(url), request, wait).await
+        }
+        EngineCommand::Cancel { id, url } => {
+            let ack = EngineClient::new(url).cancel_order(&id).await?;
+            println!("✅ Order {} {}", ack.order_id, ack.status);
+            Ok(())
+        }
+        EngineCommand::Orders { id, user, url } => {
+            let client = EngineClient::new(url);
+            match (id, user) {
+                (Some(id), _) => print_order(&client.order(&id).await?),
+                (None, Some(user)) => {
+                    let orders = client.user_orders(&user).await?;
+                    println!("📋 {} order(s) for {}", orders.len(), user);
+                    for order in &orders {
+                        print_order(order);
+                    }
+                }
+                (None, None) => unreachable!("clap requires --id or --user"),
+            }
+            Ok(())
+        }
+        EngineCommand::Price { symbol, price, url } => {
+            let update = EngineClient::new(url).update_price(&symbol, price).await?;
+            println!("✅ {} reference price set to {}", update.symbol, update.price);
+            Ok(())
+        }
+        EngineCommand::Stats { url } => stats(EngineClient::new(url)).await,
+        EngineCommand::Watch {
+            user,
+            symbol,
+            json,
+            url,
+            ws_url,
+        } => {
+            let ws_url = ws_url.unwrap_or_else(|| EngineClient::new(url).ws_url());
+            watch(&ws_url, user.as_deref(), symbol.as_deref(), json).await
+        }
+    }
+}
+
+fn print_order(order: &Order) {
+    let price = order.price.map(|p| format!(" @ {}", p)).unwrap_or_default();
+    println!(
+        "\n🧾 {} [{}] {:?} {:?} {} {}{} x{}",
+        order.id, order.status, order.order_type, order.side, order.quantity, order.symbol, price, order.leverage
+    );
+    for fill in &order.fills {
+        println!("   ↳ {} {:?} {} @ {}", fill.id, fill.side, fill.quantity, fill.price);
+    }
+}
+
+async fn submit(client: EngineClient, request: OrderRequest, wait: bool) -> Result<()> {
+    let started = Instant::now();
+    let ack = client.submit_order(&request).await?;
+    println!("📨 Order {} {} ({} ms)", ack.order_id, ack.status, started.elapsed().as_millis());
+
+    if !wait {
+        return Ok(());
+    }
+
+    let deadline = started + Duration::from_secs(10);
+    loop {
+        let order = client.order(&ack.order_id).await?;
+        if order.status.is_final() {
+            println!("⏱️  Final after {} ms", started.elapsed().as_millis());
+            print_order(&order);
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            println!("⏳ Still pending after 10s (limit price not crossed?)");
+            print_order(&order);
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+async fn stats(client: EngineClient) -> Result<()> {
+    let health = client.health().await?;
+    let stats = client.stats().await?;
+
+    println!("⚙️  Matching Engine {}", client.base_url());
+    println!("   Status: {}", health.status);
+    println!("   Settlement Relayer: {}", health.settlement_relayer);
+    println!("   Uptime: {}s", stats.uptime / 1000);
+    println!("\n📊 Orders:");
+    println!("   Received: {}", stats.orders_received);
+    println!("   Matched: {} ({:.1}%)", stats.orders_matched, stats.match_rate);
+    println!("   Failed: {}", stats.orders_failed);
+    println!("   Volume: {:.2}", stats.total_volume);
+    println!("   Average Fill Price: {:.4}", stats.average_fill_price);
+    println!("\n🔗 Settlement:");
+    println!("   Trades Sent: {}", stats.trades_sent_to_relayer);
+    println!(
+        "   Accepted: {} ({:.1}%)",
+        stats.trades_relayer_success, stats.relayer_success_rate
+    );
+    println!("   Rejected: {}", stats.trades_relayer_failed);
+    Ok(())
+}
+
+async fn watch(ws_url: &str, user: Option<&str>, symbol: Option<&str>, json: bool) -> Result<()> {
+    if !json {
+        println!("👀 Watching {} (Ctrl-C to stop)", ws_url);
+    }
+    let mut stream = OrderStream::connect(ws_url).await?;
+
+    loop {
+        let event = tokio::select! {
+            event = stream.next() => event,
+            _ = tokio::signal::ctrl_c() => {
+                if !json {
+                    println!("\n🛑 Stopped");
+                }
+                return Ok(());
+            }
+        };
+
+        let EngineEvent::OrderUpdate(order) = match event {
+            Some(event) => event?,
+            None => bail!("Engine closed the connection"),
+        };
+        if user.is_some_and(|u| u != order.user_id) || symbol.is_some_and(|s| s != order.symbol) {
+            continue;
+        }
+
+        if json {
+            println!("{}", serde_json::to_string(&order)?);
+        } else {
+            let fill = order
+                .fills
+                .last()
+                .map(|f| format!(" filled {} @ {}", f.quantity, f.price))
+                .unwrap_or_default();
+            println!(
+                "   {} {:<10} {:?} {} {}{}",
+                order.status, order.symbol, order.side, order.quantity, order.id, fill
+            );
+        }
+    }
+}
@@ -0,0 +1,295 @@
+//! Client for the mock matching engine (`services/gdx-mock-matching-engine`)
+//!
+//! [`EngineClient`] wraps the REST API and [`stream::OrderStream`] the
+//! WebSocket feed. The feed is served by the same HTTP server, so its URL is
+//! the REST base URL with `ws://` instead of `http://`.
+
+pub mod commands;
+pub mod stream;
+
+use anyhow::{bail, Context, Result};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::fmt;
+
+use crate::settlement::{FillSide, TradeRequest};
+
+pub const DEFAULT_ENGINE_URL: &str = "http://127.0.0.1:3003";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderType {
+    Market,
+    Limit,
+    PegMid,
+    PegBid,
+    PegAsk,
+}
+
+/// Requested book visibility. Sent to the engine as given; the mock engine
+/// does not enforce it yet, so dark and lit orders match the same way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    Dark,
+    Lit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderStatus {
+    Pending,
+    Matched,
+    Settling,
+    Settled,
+    Cancelled,
+    Failed,
+}
+
+impl OrderStatus {
+    /// Whether the engine is done matching this order
+    pub fn is_final(&self) -> bool {
+        !matches!(self, OrderStatus::Pending)
+    }
+}
+
+impl fmt::Display for OrderStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            OrderStatus::Pending => "pending",
+            OrderStatus::Matched => "matched",
+            OrderStatus::Settling => "settling",
+            OrderStatus::Settled => "settled",
+            OrderStatus::Cancelled => "cancelled",
+            OrderStatus::Failed => "failed",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Body of `POST /api/orders`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderRequest {
+    pub user_id: String,
+    pub symbol: String,
+    pub side: OrderSide,
+    #[serde(rename = "type")]
+    pub order_type: OrderType,
+    pub quantity: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price: Option<f64>,
+    pub leverage: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<Visibility>,
+    /// Reference price the engine fills against instead of its own feed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trading_view_price: Option<f64>,
+}
+
+/// Response to order submission and cancellation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderAck {
+    pub order_id: String,
+    pub status: OrderStatus,
+    pub timestamp: i64,
+}
+
+/// A fill produced by the engine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Trade {
+    pub id: String,
+    pub user_id: String,
+    pub symbol: String,
+    pub side: FillSide,
+    pub quantity: f64,
+    pub price: f64,
+    pub timestamp: i64,
+    pub order_id: String,
+}
+
+impl From<&Trade> for TradeRequest {
+    /// The shape the engine forwards to the settlement relayer
+    fn from(trade: &Trade) -> Self {
+        TradeRequest {
+            user_id: trade.user_id.clone(),
+            symbol: trade.symbol.clone(),
+            side: trade.side,
+            quantity: trade.quantity,
+            price: trade.price,
+            timestamp: trade.timestamp,
+            trade_id: Some(trade.id.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Order {
+    pub id: String,
+    pub user_id: String,
+    pub symbol: String,
+    pub side: OrderSide,
+    #[serde(rename = "type")]
+    pub order_type: OrderType,
+    pub quantity: f64,
+    pub price: Option<f64>,
+    pub leverage: u16,
+    pub timestamp: i64,
+    pub status: OrderStatus,
+    pub fills: Vec<Trade>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Health {
+    pub status: String,
+    pub timestamp: i64,
+    pub settlement_relayer: String,
+}
+
+/// Response to `/api/start` and `/api/stop`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineState {
+    pub status: String,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceUpdate {
+    pub symbol: String,
+    pub price: f64,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineStats {
+    pub orders_received: u64,
+    pub orders_matched: u64,
+    pub orders_failed: u64,
+    pub trades_sent_to_relayer: u64,
+    pub trades_relayer_success: u64,
+    pub trades_relayer_failed: u64,
+    pub total_volume: f64,
+    pub average_fill_price: f64,
+    /// Milliseconds
+    pub uptime: i64,
+    /// Percent
+    pub match_rate: f64,
+    /// Percent
+    pub relayer_success_rate: f64,
+    pub start_time: i64,
+    pub last_order_time: i64,
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    error: String,
+}
+
+/// Typed async client for the engine REST API
+#[derive(Debug, Clone)]
+pub struct EngineClient {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl EngineClient {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// WebSocket feed URL (same host and port as the REST API)
+    pub fn ws_url(&self) -> String {
+        if let Some(rest) = self.base_url.strip_prefix("https://") {
+            format!("wss://{}", rest)
+        } else if let Some(rest) = self.base_url.strip_prefix("http://") {
+            format!("ws://{}", rest)
+        } else {
+            self.base_url.clone()
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: reqwest::RequestBuilder, what: &str) -> Result<T> {
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach matching engine at {}", self.base_url))?;
+        let status = response.status();
+        if !status.is_success() {
+            let message = match response.json::<ErrorBody>().await {
+                Ok(body) => body.error,
+                Err(_) => status.canonical_reason().unwrap_or("unknown error").to_string(),
+            };
+            bail!("Failed to {}: {} ({})", what, message, status.as_u16());
+        }
+        response
+            .json()
+            .await
+            .with_context(|| format!("Invalid response to {}", what))
+    }
+
+    pub async fn health(&self) -> Result<Health> {
+        self.send(self.http.get(self.url("/api/health")), "check health").await
+    }
+
+    pub async fn submit_order(&self, order: &OrderRequest) -> Result<OrderAck> {
+        self.send(self.http.post(self.url("/api/orders")).json(order), "submit order")
+            .await
+    }
+
+    /// Cancel a pending order; matched orders cannot be cancelled
+    pub async fn cancel_order(&self, order_id: &str) -> Result<OrderAck> {
+        let url = self.url(&format!("/api/orders/{}", order_id));
+        self.send(self.http.delete(url), "cancel order").await
+    }
+
+    pub async fn order(&self, order_id: &str) -> Result<Order> {
+        let url = self.url(&format!("/api/orders/{}", order_id));
+        self.send(self.http.get(url), "get order").await
+    }
+
+    pub async fn user_orders(&self, user_id: &str) -> Result<Vec<Order>> {
+        let url = self.url(&format!("/api/orders/user/{}", user_id));
+        self.send(self.http.get(url), "get user orders").await
+    }
+
+    /// Override the engine's reference price for `symbol`
+    pub async fn update_price(&self, symbol: &str, price: f64) -> Result<PriceUpdate> {
+        let url = self.url(&format!("/api/prices/{}", symbol));
+        let body = serde_json::json!({ "price": price });
+        self.send(self.http.post(url).json(&body), "update price").await
+    }
+
+    pub async fn start(&self) -> Result<EngineState> {
+        self.send(self.http.post(self.url("/api/start")), "start engine").await
+    }
+
+    pub async fn stop(&self) -> Result<EngineState> {
+        self.send(self.http.post(self.url("/api/stop")), "stop engine").await
+    }
+
+    pub async fn stats(&self) -> Result<EngineStats> {
+        self.send(self.http.get(self.url("/api/stats")), "get stats").await
+    }
+}
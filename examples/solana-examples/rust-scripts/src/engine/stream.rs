@@ -0,0 +1,60 @@
+use anyhow::{Context, Result};
+use futures_util::StreamExt;
+use serde::Deserialize;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+use super::Order;
+
+/// Message on the engine feed
+#[derive(Debug, Clone)]
+pub enum EngineEvent {
+    OrderUpdate(Order),
+}
+
+#[derive(Deserialize)]
+struct Envelope {
+    #[serde(rename = "type")]
+    kind: String,
+    data: serde_json::Value,
+}
+
+/// Connection to the engine WebSocket feed
+pub struct OrderStream {
+    socket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+}
+
+impl OrderStream {
+    pub async fn connect(url: &str) -> Result<Self> {
+        let (socket, _) = connect_async(url)
+            .await
+            .with_context(|| format!("Failed to connect to {}", url))?;
+        Ok(Self { socket })
+    }
+
+    /// Next event, or `None` once the engine closes the connection
+    ///
+    /// Message types this client does not know are skipped.
+    pub async fn next(&mut self) -> Option<Result<EngineEvent>> {
+        while let Some(message) = self.socket.next().await {
+            let text = match message {
+                Ok(Message::Text(text)) => text,
+                Ok(Message::Close(_)) => return None,
+                Ok(_) => continue,
+                Err(e) => return Some(Err(e.into())),
+            };
+            let envelope: Envelope = match serde_json::from_str(&text) {
+                Ok(envelope) => envelope,
+                Err(e) => return Some(Err(anyhow::Error::new(e).context("Invalid engine message"))),
+            };
+            if envelope.kind == "order_update" {
+                return Some(
+                    serde_json::from_value(envelope.data)
+                        .map(EngineEvent::OrderUpdate)
+                        .context("Invalid order update"),
+                );
+            }
+        }
+        None
+    }
+}
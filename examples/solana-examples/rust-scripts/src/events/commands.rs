@@ -0,0 +1,187 @@
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_config::RpcTransactionConfig};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::{option_serializer::OptionSerializer, UiTransactionEncoding};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use super::idl::Idl;
+use super::{EventDecoder, EventListener, EventRecord, ListenerUpdate};
+use crate::pubsub::DEFAULT_WS_URL;
+
+#[derive(Subcommand)]
+pub enum EventsCommand {
+    /// Stream a program's events as they are committed
+    Listen {
+        /// Anchor IDL of the program (e.g. web3/lib/anchor/idl/collateral-vault-idl.json)
+        #[arg(long)]
+        idl: PathBuf,
+        /// Program id, if it differs from the address in the IDL
+        #[arg(long)]
+        program_id: Option<String>,
+        /// Append events to this file as JSON lines
+        #[arg(long)]
+        jsonl: Option<PathBuf>,
+        /// Backfill events from transactions after this signature first
+        #[arg(long)]
+        since: Option<String>,
+        /// RPC endpoint (backfill)
+        #[arg(long, default_value = "http://127.0.0.1:8899")]
+        url: String,
+        /// Validator PubSub endpoint
+        #[arg(long, default_value = DEFAULT_WS_URL)]
+        ws_url: String,
+    },
+    /// Decode the events of one transaction
+    Tx {
+        signature: String,
+        #[arg(long)]
+        idl: PathBuf,
+        /// Program id, if it differs from the address in the IDL
+        #[arg(long)]
+        program_id: Option<String>,
+        /// RPC endpoint
+        #[arg(long, default_value = "http://127.0.0.1:8899")]
+        url: String,
+    },
+}
+
+pub async fn run(command: EventsCommand) -> Result<()> {
+    match command {
+        EventsCommand::Listen {
+            idl,
+            program_id,
+            jsonl,
+            since,
+            url,
+            ws_url,
+        } => {
+            let decoder = load_decoder(&idl, program_id.as_deref())?;
+            println!(
+                "👂 Listening for {} events of {} (Ctrl-C to stop)",
+                decoder.idl().name(),
+                decoder.program_id()
+            );
+            let mut listener = EventListener::new(decoder, ws_url, url);
+            if let Some(since) = since {
+                listener = listener.resume_after(parse_signature(&since)?);
+            }
+
+            let mut file = match &jsonl {
+                Some(path) => Some(
+                    OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(path)
+                        .with_context(|| format!("Failed to open {}", path.display()))?,
+                ),
+                None => None,
+            };
+            // Connection changes and failures go to stderr so stdout stays
+            // clean for the events themselves
+            let on_update = |update: ListenerUpdate| match update {
+                ListenerUpdate::Event(record) => match file.as_mut() {
+                    Some(file) => {
+                        let written = serde_json::to_string(&record)
+                            .map_err(anyhow::Error::from)
+                            .and_then(|line| writeln!(file, "{}", line).map_err(Into::into));
+                        match written {
+                            Ok(()) => println!(
+                                "🎉 [slot {}] {} {}",
+                                record.slot,
+                                record.event.name(),
+                                record.signature
+                            ),
+                            Err(e) => eprintln!("❌ Failed to write {}: {}", record.signature, e),
+                        }
+                    }
+                    None => print_event(&record),
+                },
+                ListenerUpdate::Reconnected => eprintln!("🔄 Reconnected, backfilling missed events"),
+                ListenerUpdate::Disconnected { error, retry_in } => {
+                    eprintln!("⚠️  Disconnected ({}), retrying in {:?}", error, retry_in)
+                }
+                ListenerUpdate::Error(e) => eprintln!("⚠️  Events may be missing: {:#}", e),
+            };
+
+            tokio::select! {
+                result = listener.run(on_update) => result,
+                _ = tokio::signal::ctrl_c() => {
+                    println!("\n🛑 Stopped");
+                    Ok(())
+                }
+            }
+        }
+        EventsCommand::Tx {
+            signature,
+            idl,
+            program_id,
+            url,
+        } => {
+            let decoder = load_decoder(&idl, program_id.as_deref())?;
+            let signature = parse_signature(&signature)?;
+            let client = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+            let tx = client
+                .get_transaction_with_config(
+                    &signature,
+                    RpcTransactionConfig {
+                        encoding: Some(UiTransactionEncoding::Json),
+                        commitment: Some(CommitmentConfig::confirmed()),
+                        max_supported_transaction_version: Some(0),
+                    },
+                )
+                .await
+                .with_context(|| format!("Failed to fetch transaction {}", signature))?;
+            let logs = match tx.transaction.meta.map(|meta| meta.log_messages) {
+                Some(OptionSerializer::Some(logs)) => logs,
+                _ => Vec::new(),
+            };
+
+            let events = decoder.parse_logs(&logs);
+            if events.is_empty() {
+                println!("ℹ️  No {} events in {}", decoder.idl().name(), signature);
+            }
+            for event in events {
+                let event = event?;
+                print_event(&EventRecord {
+                    slot: tx.slot,
+                    signature: signature.to_string(),
+                    program_id: decoder.program_id().to_string(),
+                    event,
+                });
+            }
+            Ok(())
+        }
+    }
+}
+
+fn load_decoder(path: &Path, program_id: Option<&str>) -> Result<EventDecoder> {
+    let idl = Idl::load(path)?;
+    let program_id = match program_id {
+        Some(id) => Pubkey::from_str(id).with_context(|| format!("Invalid program id: {}", id))?,
+        None => idl
+            .program_id()
+            .with_context(|| format!("{} has no program address, pass --program-id", path.display()))?,
+    };
+    EventDecoder::new(idl, program_id)
+}
+
+fn parse_signature(value: &str) -> Result<Signature> {
+    Signature::from_str(value).with_context(|| format!("Invalid signature: {}", value))
+}
+
+fn print_event(record: &EventRecord) {
+    println!("🎉 [slot {}] {} {}", record.slot, record.event.name(), record.signature);
+    let data = record.event.data();
+    match data.as_object() {
+        Some(fields) => {
+            for (name, value) in fields {
+                println!("   {}: {}", name, value);
+            }
+        }
+        None => println!("   {}", data),
+    }
+}
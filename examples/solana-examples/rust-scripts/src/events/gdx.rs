@@ -0,0 +1,93 @@
+//! Typed events of the GDX programs
+//!
+//! Layouts follow the collateral vault IDL
+//! (`web3/lib/anchor/idl/collateral-vault-idl.json`), the only GDX program
+//! that publishes its events so far. Events of other programs decode through
+//! their IDL into [`Event::Other`](super::Event::Other).
+
+use borsh::BorshDeserialize;
+use serde::{Serialize, Serializer};
+use solana_sdk::pubkey::Pubkey;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize, Serialize)]
+pub struct VaultInitialized {
+    #[serde(serialize_with = "display")]
+    pub owner: Pubkey,
+    #[serde(serialize_with = "display")]
+    pub vault: Pubkey,
+    #[serde(serialize_with = "display")]
+    pub token_account: Pubkey,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize, Serialize)]
+pub struct DepositEvent {
+    #[serde(serialize_with = "display")]
+    pub user: Pubkey,
+    #[serde(serialize_with = "display")]
+    pub vault: Pubkey,
+    pub amount: u64,
+    /// Vault total balance after the deposit
+    pub new_balance: u64,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize, Serialize)]
+pub struct WithdrawEvent {
+    #[serde(serialize_with = "display")]
+    pub user: Pubkey,
+    #[serde(serialize_with = "display")]
+    pub vault: Pubkey,
+    pub amount: u64,
+    /// Vault total balance after the withdrawal
+    pub new_balance: u64,
+    pub timestamp: i64,
+}
+
+/// Collateral locked as margin by an authorized program
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize, Serialize)]
+pub struct LockEvent {
+    #[serde(serialize_with = "display")]
+    pub vault: Pubkey,
+    pub amount: u64,
+    pub new_locked_balance: u64,
+    pub new_available_balance: u64,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize, Serialize)]
+pub struct UnlockEvent {
+    #[serde(serialize_with = "display")]
+    pub vault: Pubkey,
+    pub amount: u64,
+    pub new_locked_balance: u64,
+    pub new_available_balance: u64,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize, Serialize)]
+pub struct TransferEvent {
+    #[serde(serialize_with = "display")]
+    pub from_vault: Pubkey,
+    #[serde(serialize_with = "display")]
+    pub to_vault: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize, Serialize)]
+pub struct AuthorizedProgramsUpdated {
+    #[serde(serialize_with = "display_all")]
+    pub authorized_programs: Vec<Pubkey>,
+    pub timestamp: i64,
+}
+
+/// Pubkeys as base58, like the IDL decoder writes them
+fn display<S: Serializer>(value: &impl fmt::Display, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+fn display_all<S: Serializer>(values: &[Pubkey], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(values.iter().map(|value| value.to_string()))
+}
@@ -0,0 +1,273 @@
+//! The subset of the Anchor IDL needed to decode events
+//!
+//! Reads both the current format (`anchor build` 0.30+, events carry their
+//! discriminator and the layout lives in `types`) and the legacy one (fields
+//! inline on the event, discriminator derived from the name).
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use solana_sdk::pubkey::Pubkey;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::gdx::discriminator;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Idl {
+    #[serde(default)]
+    pub address: Option<String>,
+    #[serde(default)]
+    metadata: Option<Metadata>,
+    /// Legacy IDLs put the program name at the top level
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    events: Vec<IdlEvent>,
+    #[serde(default)]
+    types: Vec<IdlTypeDef>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Metadata {
+    name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct IdlEvent {
+    name: String,
+    #[serde(default)]
+    discriminator: Option<[u8; 8]>,
+    #[serde(default)]
+    fields: Option<Vec<IdlField>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct IdlTypeDef {
+    name: String,
+    #[serde(rename = "type")]
+    ty: TypeDefKind,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum TypeDefKind {
+    Struct {
+        #[serde(default)]
+        fields: Option<Fields>,
+    },
+    Enum {
+        variants: Vec<Variant>,
+    },
+    #[serde(other)]
+    Unsupported,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Variant {
+    name: String,
+    #[serde(default)]
+    fields: Option<Fields>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Fields {
+    Named(Vec<IdlField>),
+    Tuple(Vec<IdlType>),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdlField {
+    name: String,
+    #[serde(rename = "type")]
+    ty: IdlType,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum IdlType {
+    Primitive(String),
+    Vec { vec: Box<IdlType> },
+    Option { option: Box<IdlType> },
+    Array { array: (Box<IdlType>, usize) },
+    Defined { defined: Defined },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Defined {
+    Name(String),
+    Ref { name: String },
+}
+
+impl Defined {
+    fn name(&self) -> &str {
+        match self {
+            Defined::Name(name) | Defined::Ref { name } => name,
+        }
+    }
+}
+
+/// How to recognize and decode one event
+#[derive(Debug, Clone)]
+pub struct EventLayout {
+    pub name: String,
+    pub discriminator: [u8; 8],
+    pub fields: Fields,
+}
+
+impl Idl {
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&json).with_context(|| format!("{} is not an Anchor IDL", path.display()))
+    }
+
+    pub fn name(&self) -> &str {
+        self.metadata
+            .as_ref()
+            .map(|m| m.name.as_str())
+            .or(self.name.as_deref())
+            .unwrap_or("unknown")
+    }
+
+    /// Program id the IDL was built for, if it records one
+    pub fn program_id(&self) -> Option<Pubkey> {
+        self.address.as_deref().and_then(|a| Pubkey::from_str(a).ok())
+    }
+
+    pub fn event_layouts(&self) -> Result<Vec<EventLayout>> {
+        self.events
+            .iter()
+            .map(|event| {
+                let fields = match &event.fields {
+                    Some(fields) => Fields::Named(fields.clone()),
+                    None => match self.type_def(&event.name).map(|t| &t.ty) {
+                        Some(TypeDefKind::Struct { fields }) => fields.clone().unwrap_or(Fields::Tuple(Vec::new())),
+                        _ => bail!("Event {} has no struct layout in the IDL", event.name),
+                    },
+                };
+                Ok(EventLayout {
+                    name: event.name.clone(),
+                    discriminator: event
+                        .discriminator
+                        .unwrap_or_else(|| discriminator("event", &event.name)),
+                    fields,
+                })
+            })
+            .collect()
+    }
+
+    fn type_def(&self, name: &str) -> Option<&IdlTypeDef> {
+        self.types.iter().find(|t| t.name == name)
+    }
+
+    /// Borsh-decode `fields` from the front of `data` into JSON
+    ///
+    /// 64-bit integers stay JSON numbers; 128-bit ones become strings, since
+    /// most JSON consumers cannot hold them exactly.
+    pub fn decode_fields(&self, fields: &Fields, data: &mut &[u8]) -> Result<Value> {
+        match fields {
+            Fields::Named(fields) => {
+                let mut object = Map::new();
+                for field in fields {
+                    let value = self
+                        .decode(&field.ty, data)
+                        .with_context(|| format!("field {}", field.name))?;
+                    object.insert(field.name.clone(), value);
+                }
+                Ok(Value::Object(object))
+            }
+            Fields::Tuple(types) => types
+                .iter()
+                .map(|ty| self.decode(ty, data))
+                .collect::<Result<Vec<_>>>()
+                .map(Value::Array),
+        }
+    }
+
+    fn decode(&self, ty: &IdlType, data: &mut &[u8]) -> Result<Value> {
+        Ok(match ty {
+            IdlType::Primitive(name) => match name.as_str() {
+                "bool" => Value::Bool(take::<1>(data)?[0] != 0),
+                "u8" => take::<1>(data)?[0].into(),
+                "i8" => (take::<1>(data)?[0] as i8).into(),
+                "u16" => u16::from_le_bytes(take(data)?).into(),
+                "i16" => i16::from_le_bytes(take(data)?).into(),
+                "u32" => u32::from_le_bytes(take(data)?).into(),
+                "i32" => i32::from_le_bytes(take(data)?).into(),
+                "u64" => u64::from_le_bytes(take(data)?).into(),
+                "i64" => i64::from_le_bytes(take(data)?).into(),
+                "u128" => u128::from_le_bytes(take(data)?).to_string().into(),
+                "i128" => i128::from_le_bytes(take(data)?).to_string().into(),
+                "f32" => f32::from_le_bytes(take(data)?).into(),
+                "f64" => f64::from_le_bytes(take(data)?).into(),
+                "pubkey" | "publicKey" => Pubkey::new_from_array(take(data)?).to_string().into(),
+                "string" => {
+                    let bytes = take_len_prefixed(data)?;
+                    String::from_utf8(bytes.to_vec()).context("invalid UTF-8")?.into()
+                }
+                "bytes" => Value::Array(take_len_prefixed(data)?.iter().map(|&b| b.into()).collect()),
+                other => bail!("unsupported IDL type {}", other),
+            },
+            IdlType::Vec { vec } => {
+                let len = u32::from_le_bytes(take(data)?);
+                (0..len)
+                    .map(|_| self.decode(vec, data))
+                    .collect::<Result<Vec<_>>>()
+                    .map(Value::Array)?
+            }
+            IdlType::Option { option } => match take::<1>(data)?[0] {
+                0 => Value::Null,
+                1 => self.decode(option, data)?,
+                tag => bail!("invalid option tag {}", tag),
+            },
+            IdlType::Array { array: (ty, len) } => (0..*len)
+                .map(|_| self.decode(ty, data))
+                .collect::<Result<Vec<_>>>()
+                .map(Value::Array)?,
+            IdlType::Defined { defined } => {
+                let def = self
+                    .type_def(defined.name())
+                    .ok_or_else(|| anyhow!("type {} is not in the IDL", defined.name()))?;
+                match &def.ty {
+                    TypeDefKind::Struct { fields: None } => Value::Object(Map::new()),
+                    TypeDefKind::Struct { fields: Some(fields) } => self.decode_fields(fields, data)?,
+                    TypeDefKind::Enum { variants } => {
+                        let index = take::<1>(data)?[0] as usize;
+                        let variant = variants
+                            .get(index)
+                            .ok_or_else(|| anyhow!("{} has no variant {}", def.name, index))?;
+                        match &variant.fields {
+                            None => Value::String(variant.name.clone()),
+                            Some(fields) => {
+                                let mut object = Map::new();
+                                object.insert(variant.name.clone(), self.decode_fields(fields, data)?);
+                                Value::Object(object)
+                            }
+                        }
+                    }
+                    TypeDefKind::Unsupported => bail!("type {} has an unsupported kind", def.name),
+                }
+            }
+        })
+    }
+}
+
+fn take<const N: usize>(data: &mut &[u8]) -> Result<[u8; N]> {
+    Ok(take_slice(data, N)?.try_into().expect("slice of length N"))
+}
+
+fn take_len_prefixed<'a>(data: &mut &'a [u8]) -> Result<&'a [u8]> {
+    let len = u32::from_le_bytes(take(data)?) as usize;
+    take_slice(data, len)
+}
+
+fn take_slice<'a>(data: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+    if data.len() < len {
+        bail!("unexpected end of data");
+    }
+    let (head, rest) = data.split_at(len);
+    *data = rest;
+    Ok(head)
+}
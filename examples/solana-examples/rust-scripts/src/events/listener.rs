@@ -0,0 +1,242 @@
+//! Live event stream with gap backfill
+//!
+//! Events arrive through `logsSubscribe`. Anything committed while the
+//! socket was down is recovered after each reconnect by walking
+//! `getSignaturesForAddress` back to the last signature seen and reading
+//! those transactions' logs, oldest first. A window of recent signatures
+//! de-duplicates transactions seen both ways.
+
+use anyhow::{Context, Result};
+use solana_client::{
+    nonblocking::rpc_client::RpcClient, rpc_client::GetConfirmedSignaturesForAddress2Config,
+    rpc_config::RpcTransactionConfig, rpc_config::RpcTransactionLogsFilter,
+};
+use solana_sdk::{clock::Slot, commitment_config::CommitmentConfig, signature::Signature};
+use solana_transaction_status::{option_serializer::OptionSerializer, UiTransactionEncoding};
+use std::collections::{HashSet, VecDeque};
+use std::str::FromStr;
+use std::time::Duration;
+
+use super::{EventDecoder, EventRecord};
+use crate::pubsub::{Notification, Subscriber, Subscription};
+
+/// Page size of `getSignaturesForAddress`
+const SIGNATURE_PAGE: usize = 1000;
+/// Recent signatures remembered for de-duplication
+const SEEN_WINDOW: usize = 10_000;
+
+/// What [`EventListener::run`] hands to its callback
+#[derive(Debug)]
+pub enum ListenerUpdate {
+    Event(EventRecord),
+    /// The socket is back; events missed meanwhile are backfilled next
+    Reconnected,
+    Disconnected {
+        error: String,
+        retry_in: Duration,
+    },
+    /// Events that could not be read; listening goes on, but these events
+    /// are missing from the stream
+    Error(anyhow::Error),
+}
+
+pub struct EventListener {
+    decoder: EventDecoder,
+    ws_url: String,
+    rpc: RpcClient,
+    commitment: CommitmentConfig,
+    /// Newest transaction handled; backfill resumes after it
+    last_signature: Option<Signature>,
+    seen: HashSet<Signature>,
+    seen_order: VecDeque<Signature>,
+}
+
+impl EventListener {
+    pub fn new(decoder: EventDecoder, ws_url: impl Into<String>, rpc_url: impl Into<String>) -> Self {
+        let commitment = CommitmentConfig::confirmed();
+        Self {
+            decoder,
+            ws_url: ws_url.into(),
+            rpc: RpcClient::new_with_commitment(rpc_url.into(), commitment),
+            commitment,
+            last_signature: None,
+            seen: HashSet::new(),
+            seen_order: VecDeque::new(),
+        }
+    }
+
+    /// Backfill everything after `signature` on the first connect, e.g. to
+    /// resume from where a previous run stopped
+    pub fn resume_after(mut self, signature: Signature) -> Self {
+        self.last_signature = Some(signature);
+        self
+    }
+
+    /// Listen until the subscription ends, calling `on_update` for each event
+    /// in commit order, and for connection changes and read failures
+    pub async fn run(mut self, mut on_update: impl FnMut(ListenerUpdate)) -> Result<()> {
+        let program_id = *self.decoder.program_id();
+        let mut notifications = Subscriber::new(self.ws_url.clone())
+            .commitment(self.commitment)
+            .subscribe(Subscription::Logs(RpcTransactionLogsFilter::Mentions(vec![
+                program_id.to_string(),
+            ])))
+            .spawn();
+
+        while let Some(notification) = notifications.recv().await {
+            match notification {
+                Notification::Connected { attempt } => {
+                    if attempt > 0 {
+                        on_update(ListenerUpdate::Reconnected);
+                    }
+                    match self.last_signature {
+                        Some(until) => {
+                            if let Err(e) = self.backfill(until, &mut on_update).await {
+                                let e = e.context(format!("Backfill after {} failed", until));
+                                on_update(ListenerUpdate::Error(e));
+                            }
+                        }
+                        None => match self.latest_signature().await {
+                            Ok(signature) => self.last_signature = signature,
+                            Err(e) => {
+                                let e = e.context("Could not read the latest signature, backfill will start from the first live event");
+                                on_update(ListenerUpdate::Error(e));
+                            }
+                        },
+                    }
+                }
+                Notification::Disconnected { error, retry_in } => {
+                    on_update(ListenerUpdate::Disconnected { error, retry_in });
+                }
+                Notification::Logs {
+                    slot,
+                    signature,
+                    err,
+                    logs,
+                } => {
+                    let Ok(signature) = Signature::from_str(&signature) else {
+                        continue;
+                    };
+                    // Events of a failed transaction were rolled back with it
+                    let logs = if err.is_none() { logs } else { Vec::new() };
+                    self.handle(signature, slot, &logs, &mut on_update);
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Decode the events of every transaction committed after `until`
+    async fn backfill(&mut self, until: Signature, on_update: &mut impl FnMut(ListenerUpdate)) -> Result<()> {
+        let program_id = *self.decoder.program_id();
+        let mut statuses = Vec::new();
+        let mut before = None;
+        loop {
+            let page = self
+                .rpc
+                .get_signatures_for_address_with_config(
+                    &program_id,
+                    GetConfirmedSignaturesForAddress2Config {
+                        before,
+                        until: Some(until),
+                        limit: Some(SIGNATURE_PAGE),
+                        commitment: Some(self.commitment),
+                    },
+                )
+                .await
+                .context("getSignaturesForAddress failed")?;
+            let full = page.len() == SIGNATURE_PAGE;
+            before = match page.last() {
+                Some(status) => Some(Signature::from_str(&status.signature)?),
+                None => None,
+            };
+            statuses.extend(page);
+            if !full {
+                break;
+            }
+        }
+
+        // Newest first from the RPC; replay oldest first
+        for status in statuses.into_iter().rev() {
+            let signature = Signature::from_str(&status.signature)?;
+            if status.err.is_some() || self.seen.contains(&signature) {
+                self.mark_seen(signature);
+                continue;
+            }
+            let tx = self
+                .rpc
+                .get_transaction_with_config(
+                    &signature,
+                    RpcTransactionConfig {
+                        encoding: Some(UiTransactionEncoding::Json),
+                        commitment: Some(self.commitment),
+                        max_supported_transaction_version: Some(0),
+                    },
+                )
+                .await
+                .with_context(|| format!("getTransaction {} failed", signature))?;
+            let logs = match tx.transaction.meta.map(|meta| meta.log_messages) {
+                Some(OptionSerializer::Some(logs)) => logs,
+                _ => Vec::new(),
+            };
+            self.handle(signature, tx.slot, &logs, on_update);
+        }
+        Ok(())
+    }
+
+    /// Newest transaction of the program, if it has any
+    async fn latest_signature(&self) -> Result<Option<Signature>> {
+        let config = GetConfirmedSignaturesForAddress2Config {
+            limit: Some(1),
+            commitment: Some(self.commitment),
+            ..GetConfirmedSignaturesForAddress2Config::default()
+        };
+        let page = self
+            .rpc
+            .get_signatures_for_address_with_config(self.decoder.program_id(), config)
+            .await
+            .context("getSignaturesForAddress failed")?;
+        page.first()
+            .map(|status| Signature::from_str(&status.signature).map_err(Into::into))
+            .transpose()
+    }
+
+    fn handle(
+        &mut self,
+        signature: Signature,
+        slot: Slot,
+        logs: &[String],
+        on_update: &mut impl FnMut(ListenerUpdate),
+    ) {
+        if self.seen.contains(&signature) {
+            return;
+        }
+        self.mark_seen(signature);
+        self.last_signature = Some(signature);
+
+        for event in self.decoder.parse_logs(logs) {
+            let update = match event {
+                Ok(event) => ListenerUpdate::Event(EventRecord {
+                    slot,
+                    signature: signature.to_string(),
+                    program_id: self.decoder.program_id().to_string(),
+                    event,
+                }),
+                Err(e) => ListenerUpdate::Error(e.context(format!("Transaction {}", signature))),
+            };
+            on_update(update);
+        }
+    }
+
+    fn mark_seen(&mut self, signature: Signature) {
+        if self.seen.insert(signature) {
+            self.seen_order.push_back(signature);
+            if self.seen_order.len() > SEEN_WINDOW {
+                if let Some(oldest) = self.seen_order.pop_front() {
+                    self.seen.remove(&oldest);
+                }
+            }
+        }
+    }
+}
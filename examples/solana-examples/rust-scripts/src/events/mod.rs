@@ -0,0 +1,178 @@
+//! Anchor events emitted by the GDX programs
+//!
+//! `emit!` writes `Program data: <base64>` to the transaction log, where the
+//! payload is the event's 8-byte discriminator followed by the Borsh-encoded
+//! struct. [`EventDecoder`] finds those lines for one program (tracking the
+//! invoke stack, so events from other programs in the same transaction are
+//! not misattributed) and decodes them with the layouts from the IDL.
+//!
+//! Events with a layout in [`gdx`] decode into their struct; any other
+//! event of the IDL stays JSON in [`Event::Other`].
+
+pub mod commands;
+pub mod gdx;
+pub mod idl;
+pub mod listener;
+
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::BorshDeserialize;
+use serde::Serialize;
+use serde_json::Value;
+use solana_sdk::{clock::Slot, pubkey::Pubkey};
+use std::collections::HashMap;
+
+use gdx::{
+    AuthorizedProgramsUpdated, DepositEvent, LockEvent, TransferEvent, UnlockEvent, VaultInitialized, WithdrawEvent,
+};
+use idl::{EventLayout, Idl};
+
+pub use listener::{EventListener, ListenerUpdate};
+
+const PROGRAM_DATA: &str = "Program data: ";
+
+/// A decoded event; serializes as `{"name": ..., "data": {...}}`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "name", content = "data")]
+pub enum Event {
+    VaultInitialized(VaultInitialized),
+    DepositEvent(DepositEvent),
+    WithdrawEvent(WithdrawEvent),
+    LockEvent(LockEvent),
+    UnlockEvent(UnlockEvent),
+    TransferEvent(TransferEvent),
+    AuthorizedProgramsUpdated(AuthorizedProgramsUpdated),
+    /// An event without a typed layout, decoded with the IDL
+    #[serde(untagged)]
+    Other { name: String, data: Value },
+}
+
+impl Event {
+    pub fn name(&self) -> &str {
+        match self {
+            Event::VaultInitialized(_) => "VaultInitialized",
+            Event::DepositEvent(_) => "DepositEvent",
+            Event::WithdrawEvent(_) => "WithdrawEvent",
+            Event::LockEvent(_) => "LockEvent",
+            Event::UnlockEvent(_) => "UnlockEvent",
+            Event::TransferEvent(_) => "TransferEvent",
+            Event::AuthorizedProgramsUpdated(_) => "AuthorizedProgramsUpdated",
+            Event::Other { name, .. } => name,
+        }
+    }
+
+    /// The event's fields as JSON
+    pub fn data(&self) -> Value {
+        match self {
+            Event::Other { data, .. } => data.clone(),
+            typed => serde_json::to_value(typed)
+                .map(|mut value| value["data"].take())
+                .unwrap_or(Value::Null),
+        }
+    }
+
+    /// Borsh-decode the body of an event with a typed layout; `None` for
+    /// any other name
+    fn typed(name: &str, body: &[u8]) -> Option<Result<Self>> {
+        fn decode<T: BorshDeserialize>(body: &[u8], wrap: fn(T) -> Event) -> Result<Event> {
+            Ok(wrap(T::try_from_slice(body)?))
+        }
+        let event = match name {
+            "VaultInitialized" => decode(body, Event::VaultInitialized),
+            "DepositEvent" => decode(body, Event::DepositEvent),
+            "WithdrawEvent" => decode(body, Event::WithdrawEvent),
+            "LockEvent" => decode(body, Event::LockEvent),
+            "UnlockEvent" => decode(body, Event::UnlockEvent),
+            "TransferEvent" => decode(body, Event::TransferEvent),
+            "AuthorizedProgramsUpdated" => decode(body, Event::AuthorizedProgramsUpdated),
+            _ => return None,
+        };
+        Some(event)
+    }
+}
+
+/// An event with the transaction it came from
+#[derive(Debug, Clone, Serialize)]
+pub struct EventRecord {
+    pub slot: Slot,
+    pub signature: String,
+    pub program_id: String,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+pub struct EventDecoder {
+    program_id: Pubkey,
+    idl: Idl,
+    layouts: HashMap<[u8; 8], EventLayout>,
+}
+
+impl EventDecoder {
+    pub fn new(idl: Idl, program_id: Pubkey) -> Result<Self> {
+        let layouts = idl
+            .event_layouts()?
+            .into_iter()
+            .map(|layout| (layout.discriminator, layout))
+            .collect();
+        Ok(Self {
+            program_id,
+            idl,
+            layouts,
+        })
+    }
+
+    pub fn program_id(&self) -> &Pubkey {
+        &self.program_id
+    }
+
+    pub fn idl(&self) -> &Idl {
+        &self.idl
+    }
+
+    /// Decode one event payload; `None` if the discriminator is not an
+    /// event of this IDL
+    pub fn decode(&self, payload: &[u8]) -> Option<Result<Event>> {
+        let discriminator: [u8; 8] = payload.get(..8)?.try_into().ok()?;
+        let layout = self.layouts.get(&discriminator)?;
+        let mut body = &payload[8..];
+        let event = match Event::typed(&layout.name, body) {
+            Some(event) => event,
+            None => self.idl.decode_fields(&layout.fields, &mut body).map(|data| Event::Other {
+                name: layout.name.clone(),
+                data,
+            }),
+        };
+        Some(event.with_context(|| format!("Failed to decode {}", layout.name)))
+    }
+
+    /// Events this program emitted, in log order
+    pub fn parse_logs(&self, logs: &[String]) -> Vec<Result<Event>> {
+        let program_id = self.program_id.to_string();
+        let mut stack: Vec<&str> = Vec::new();
+        let mut events = Vec::new();
+
+        for line in logs {
+            if let Some(data) = line.strip_prefix(PROGRAM_DATA) {
+                if stack.last() != Some(&program_id.as_str()) {
+                    continue;
+                }
+                let event = match STANDARD.decode(data.trim()) {
+                    Ok(payload) => self.decode(&payload),
+                    Err(e) => Some(Err(anyhow::Error::new(e).context("Invalid base64 event payload"))),
+                };
+                events.extend(event);
+            } else if let Some(rest) = line.strip_prefix("Program ") {
+                // "Program <id> invoke [n]" / "Program <id> success" / "Program <id> failed: ..."
+                let mut words = rest.split_whitespace();
+                match (words.next(), words.next()) {
+                    (Some(id), Some("invoke")) => stack.push(id),
+                    (Some(_), Some("success")) | (Some(_), Some("failed:")) => {
+                        stack.pop();
+                    }
+                    _ => {}
+                }
+            }
+        }
+        events
+    }
+}
@@ -1,5 +1,5 @@
 use anyhow::Result;
-use solana_client::rpc_client::RpcClient;
+use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::commitment_config::CommitmentConfig;
 
 /// Example: Connect to Solana localnet
@@ -21,7 +21,7 @@ pub async fn run() -> Result<()> {
     );
     
     // Test the connection by getting the version
-    match client.get_version() {
+    match client.get_version().await {
         Ok(version) => {
             println!("✅ Successfully connected to localnet!");
             println!("   RPC URL: {}", rpc_url);
@@ -41,7 +41,7 @@ pub async fn run() -> Result<()> {
     }
     
     // Get the current slot (block number)
-    match client.get_slot() {
+    match client.get_slot().await {
         Ok(slot) => println!("   Current Slot: {}", slot),
         Err(e) => println!("   Warning: Could not get slot: {}", e),
     }
@@ -18,7 +18,7 @@ pub async fn run() -> Result<()> {
     println!("✅ New account created!");
     println!("\n📋 Account Details:");
     println!("   Public Key: {}", pubkey);
-    println!("   Public Key (base58): {}", pubkey.to_string());
+    println!("   Public Key (base58): {}", pubkey);
     
     // Display private key (base58 encoded)
     // In production, NEVER expose private keys!
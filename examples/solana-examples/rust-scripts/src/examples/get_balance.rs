@@ -1,11 +1,13 @@
 use anyhow::{Context, Result};
-use solana_client::rpc_client::RpcClient;
+use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
     pubkey::Pubkey,
 };
 use std::str::FromStr;
 
+use crate::client::LedgerClient;
+
 /// Example: Get account balance
 /// 
 /// This demonstrates how to query the balance of a Solana account.
@@ -25,7 +27,7 @@ pub async fn run(address: String) -> Result<()> {
     );
     
     // Get account balance
-    match client.get_balance(&pubkey) {
+    match LedgerClient::get_balance(&client, &pubkey).await {
         Ok(balance_lamports) => {
             let balance_sol = balance_lamports as f64 / 1_000_000_000.0;
             println!("✅ Account Balance:");
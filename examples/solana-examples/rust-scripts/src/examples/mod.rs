@@ -5,4 +5,5 @@ pub mod create_account;
 pub mod send_transaction;
 pub mod pda_basics;
 pub mod token_basics;
+pub mod token_operations;
 
@@ -1,14 +1,16 @@
 use anyhow::{Context, Result};
-use solana_client::rpc_client::RpcClient;
+use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
     pubkey::Pubkey,
-    signature::{Keypair, Signer},
+    signature::{Keypair, Signature, Signer},
     system_instruction,
-    transaction::Transaction,
 };
 use std::str::FromStr;
 
+use crate::client::sender::{send_with_retry, SendConfig};
+use crate::client::{send, LedgerClient};
+
 /// Example: Send SOL transaction
 /// 
 /// This demonstrates how to send SOL from one account to another.
@@ -40,7 +42,7 @@ pub async fn run(from: String, to: String, amount: f64, private_key: Option<Stri
     );
     
     // Check sender balance
-    let sender_balance = client.get_balance(&from_pubkey)
+    let sender_balance = LedgerClient::get_balance(&client, &from_pubkey).await
         .context("Failed to get sender balance")?;
     
     if sender_balance < lamports {
@@ -93,44 +95,19 @@ pub async fn run(from: String, to: String, amount: f64, private_key: Option<Stri
     
     println!("   ✅ Keypair verified (matches sender address)");
     
-    // Get recent blockhash
-    println!("\n📡 Preparing transaction...");
-    let recent_blockhash = client.get_latest_blockhash()
-        .context("Failed to get recent blockhash")?;
-    
-    // Create transfer instruction
-    let instruction = system_instruction::transfer(&from_pubkey, &to_pubkey, lamports);
-    
-    // Build transaction
-    let mut transaction = Transaction::new_with_payer(
-        &[instruction],
-        Some(&from_pubkey),
-    );
-    
-    // Set recent blockhash
-    transaction.message.recent_blockhash = recent_blockhash;
-    
-    // Sign transaction (use blockhash from transaction message, like ephemeral-vault does)
-    transaction.sign(&[&keypair], transaction.message.recent_blockhash);
-    println!("   ✅ Transaction signed");
-    
-    // Send transaction
-    println!("📤 Sending transaction...");
-    let signature = client.send_transaction(&transaction)?;
+    // Rebroadcast until confirmed; a dropped transaction is re-signed once
+    // its blockhash has expired
+    println!("\n📤 Sending transaction...");
+    let instruction = system_instruction::transfer(&keypair.pubkey(), &to_pubkey, lamports);
+    let report = send_with_retry(&client, &[instruction], &keypair, &[], &SendConfig::default()).await;
+    report.print();
+    let signature = report.signature().context("Transaction was not confirmed")?;
     println!("   Transaction Signature: {}", signature);
-    println!("   Waiting for confirmation...");
-    
-    client.confirm_transaction(&signature)
-        .context("Failed to confirm transaction")?;
-    
     println!("✅ Transaction sent and confirmed!");
     
-    // Wait a bit for balances to update
-    std::thread::sleep(std::time::Duration::from_millis(1000));
-    
     // Verify balances
-    let sender_new_balance = client.get_balance(&from_pubkey)?;
-    let receiver_balance = client.get_balance(&to_pubkey)?;
+    let sender_new_balance = LedgerClient::get_balance(&client, &from_pubkey).await?;
+    let receiver_balance = LedgerClient::get_balance(&client, &to_pubkey).await?;
     
     println!("\n💰 Updated Balances:");
     println!("   Sender:   {:.9} SOL", sender_new_balance as f64 / 1_000_000_000.0);
@@ -139,3 +116,12 @@ pub async fn run(from: String, to: String, amount: f64, private_key: Option<Stri
     Ok(())
 }
 
+/// Transfer `lamports` from `from` to `to` and wait for confirmation
+///
+/// `from` pays the fee, so it needs `lamports` plus the fee.
+pub async fn transfer(client: &impl LedgerClient, from: &Keypair, to: &Pubkey, lamports: u64) -> Result<Signature> {
+    let instruction = system_instruction::transfer(&from.pubkey(), to, lamports);
+    send(client, &[instruction], from, &[])
+        .await
+        .context("Failed to send transfer")
+}
@@ -0,0 +1,132 @@
+use anyhow::{Context, Result};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    system_instruction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+use spl_token::state::Mint;
+
+use super::airdrop::airdrop;
+use crate::client::{send, token_balance, LedgerClient};
+
+/// Example: SPL Token operations on localnet
+///
+/// Creates a mint, mints tokens into the payer's associated token account
+/// (ATA) and transfers some of them to a second wallet's ATA. The same
+/// helpers run against the in-process bank in `cargo test`.
+pub async fn run() -> Result<()> {
+    println!("🪙 SPL Token Operations Example\n");
+
+    let client = RpcClient::new_with_commitment(
+        "http://127.0.0.1:8899".to_string(),
+        CommitmentConfig::confirmed(),
+    );
+
+    println!("1️⃣  Setting up accounts...");
+    let payer = Keypair::new();
+    let (_, payer_balance) = airdrop(&client, &payer.pubkey(), 2_000_000_000).await?;
+    let mint_authority = Keypair::new();
+    let receiver = Keypair::new();
+    println!("   Payer: {} ({:.9} SOL)", payer.pubkey(), payer_balance as f64 / 1_000_000_000.0);
+    println!("   Mint Authority: {}", mint_authority.pubkey());
+    println!("   Receiver: {}", receiver.pubkey());
+
+    println!("\n2️⃣  Creating token mint...");
+    let mint = Keypair::new();
+    let decimals = 9u8;
+    create_mint(&client, &payer, &mint, &mint_authority.pubkey(), decimals).await?;
+    println!("   ✅ Mint created: {}", mint.pubkey());
+    println!("   Decimals: {}", decimals);
+
+    println!("\n3️⃣  Creating associated token accounts...");
+    let payer_ata = create_associated_account(&client, &payer, &payer.pubkey(), &mint.pubkey()).await?;
+    let receiver_ata = create_associated_account(&client, &payer, &receiver.pubkey(), &mint.pubkey()).await?;
+    println!("   ✅ Payer ATA: {}", payer_ata);
+    println!("   ✅ Receiver ATA: {}", receiver_ata);
+
+    let unit = 10u64.pow(decimals as u32);
+    println!("\n4️⃣  Minting 1000 tokens to payer...");
+    mint_to(&client, &payer, &mint.pubkey(), &payer_ata, &mint_authority, 1000 * unit).await?;
+    println!("   ✅ Balance: {} tokens", token_balance(&client, &payer_ata).await? / unit);
+
+    println!("\n5️⃣  Transferring 100 tokens to receiver...");
+    let signature = transfer(&client, &payer, &payer_ata, &receiver_ata, 100 * unit).await?;
+    println!("   ✅ Transfer complete!");
+    println!("   Signature: {}", signature);
+
+    println!("\n6️⃣  Verifying balances...");
+    println!("   Payer balance: {} tokens", token_balance(&client, &payer_ata).await? / unit);
+    println!("   Receiver balance: {} tokens", token_balance(&client, &receiver_ata).await? / unit);
+
+    Ok(())
+}
+
+/// Create and initialize `mint`; `payer` funds the rent
+pub async fn create_mint(
+    client: &impl LedgerClient,
+    payer: &Keypair,
+    mint: &Keypair,
+    authority: &Pubkey,
+    decimals: u8,
+) -> Result<Signature> {
+    let rent = client.get_minimum_balance_for_rent_exemption(Mint::LEN).await?;
+    let instructions = [
+        system_instruction::create_account(&payer.pubkey(), &mint.pubkey(), rent, Mint::LEN as u64, &spl_token::id()),
+        spl_token::instruction::initialize_mint(&spl_token::id(), &mint.pubkey(), authority, None, decimals)?,
+    ];
+    send(client, &instructions, payer, &[mint])
+        .await
+        .context("Failed to create mint")
+}
+
+/// Create `owner`'s associated token account for `mint` unless it exists;
+/// returns its address
+pub async fn create_associated_account(
+    client: &impl LedgerClient,
+    payer: &Keypair,
+    owner: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Pubkey> {
+    let instruction = create_associated_token_account_idempotent(&payer.pubkey(), owner, mint, &spl_token::id());
+    send(client, &[instruction], payer, &[])
+        .await
+        .context("Failed to create associated token account")?;
+    Ok(get_associated_token_address(owner, mint))
+}
+
+/// Mint `amount` base units of `mint` into `destination`
+pub async fn mint_to(
+    client: &impl LedgerClient,
+    payer: &Keypair,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    authority: &Keypair,
+    amount: u64,
+) -> Result<Signature> {
+    let instruction =
+        spl_token::instruction::mint_to(&spl_token::id(), mint, destination, &authority.pubkey(), &[], amount)?;
+    send(client, &[instruction], payer, &[authority])
+        .await
+        .context("Failed to mint tokens")
+}
+
+/// Move `amount` base units between token accounts; `owner` signs and pays
+pub async fn transfer(
+    client: &impl LedgerClient,
+    owner: &Keypair,
+    source: &Pubkey,
+    destination: &Pubkey,
+    amount: u64,
+) -> Result<Signature> {
+    let instruction =
+        spl_token::instruction::transfer(&spl_token::id(), source, destination, &owner.pubkey(), &[], amount)?;
+    send(client, &[instruction], owner, &[])
+        .await
+        .context("Failed to transfer tokens")
+}
@@ -0,0 +1,84 @@
+//! Account layouts of the GDX programs, as specified in the assignments
+//!
+//! All accounts are Anchor accounts: an 8-byte discriminator followed by the
+//! Borsh-encoded struct. Field order must match the program exactly.
+
+use borsh::BorshDeserialize;
+use solana_sdk::pubkey::Pubkey;
+
+use super::AnchorAccount;
+
+/// Position direction. Anchor encodes enums by variant index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshDeserialize)]
+pub enum Side {
+    Long,
+    Short,
+}
+
+impl std::fmt::Display for Side {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Side::Long => f.write_str("Long"),
+            Side::Short => f.write_str("Short"),
+        }
+    }
+}
+
+/// Position management program: one open position per (owner, symbol)
+#[derive(Debug, Clone, BorshDeserialize)]
+pub struct Position {
+    pub owner: Pubkey,
+    pub symbol: String,
+    pub side: Side,
+    /// Base asset amount, `BASE_DECIMALS` fixed point
+    pub size: u64,
+    /// Quote per base, `PRICE_DECIMALS` fixed point
+    pub entry_price: u64,
+    /// Isolated margin in quote token base units
+    pub margin: u64,
+    pub leverage: u8,
+    pub unrealized_pnl: i64,
+    pub realized_pnl: i64,
+    pub funding_accrued: i64,
+    pub liquidation_price: u64,
+    pub last_update: i64,
+    pub bump: u8,
+}
+
+impl AnchorAccount for Position {
+    const NAME: &'static str = "Position";
+}
+
+/// Collateral vault program: one vault per user, seeds `[b"vault", owner]`
+#[derive(Debug, Clone, BorshDeserialize)]
+pub struct CollateralVault {
+    pub owner: Pubkey,
+    pub token_account: Pubkey,
+    pub total_balance: u64,
+    pub locked_balance: u64,
+    pub available_balance: u64,
+    pub total_deposited: u64,
+    pub total_withdrawn: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl AnchorAccount for CollateralVault {
+    const NAME: &'static str = "CollateralVault";
+}
+
+/// Liquidation engine: fund that absorbs bad debt, seeds `[b"insurance_fund"]`
+#[derive(Debug, Clone, BorshDeserialize)]
+pub struct InsuranceFund {
+    pub authority: Pubkey,
+    /// Quote token base units
+    pub balance: u64,
+    pub total_contributions: u64,
+    pub total_bad_debt_covered: u64,
+    /// Basis points
+    pub utilization_ratio: u64,
+}
+
+impl AnchorAccount for InsuranceFund {
+    const NAME: &'static str = "InsuranceFund";
+}
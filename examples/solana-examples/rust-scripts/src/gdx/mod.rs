@@ -0,0 +1,149 @@
+//! Client-side view of the GDX Anchor programs
+//!
+//! Account decoding, PDA derivation and instruction encoding for the
+//! collateral vault, position management and liquidation programs. Program
+//! ids are not fixed (each environment deploys its own), so every helper
+//! takes the program id explicitly.
+
+pub mod accounts;
+
+use anyhow::{bail, Context, Result};
+use borsh::BorshDeserialize;
+use sha2::{Digest, Sha256};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use std::future::Future;
+
+use crate::client::{get_accounts, LedgerClient};
+
+/// Fixed-point decimals of prices (quote per base)
+pub const PRICE_DECIMALS: u32 = 6;
+/// Fixed-point decimals of position sizes (base asset)
+pub const BASE_DECIMALS: u32 = 6;
+/// Decimals of the quote token (USDT) used for margin and collateral
+pub const QUOTE_DECIMALS: u32 = 6;
+
+/// First 8 bytes of `sha256("<namespace>:<name>")`, Anchor's discriminator scheme
+pub fn discriminator(namespace: &str, name: &str) -> [u8; 8] {
+    let hash = Sha256::digest(format!("{}:{}", namespace, name).as_bytes());
+    let mut out = [0u8; 8];
+    out.copy_from_slice(&hash[..8]);
+    out
+}
+
+/// An Anchor `#[account]` struct we can decode
+pub trait AnchorAccount: BorshDeserialize + Sized + Send {
+    /// Rust struct name, which Anchor hashes into the discriminator
+    const NAME: &'static str;
+
+    fn discriminator() -> [u8; 8] {
+        discriminator("account", Self::NAME)
+    }
+
+    /// Decode account data, checking the discriminator. Trailing bytes
+    /// (space reserved for growth) are ignored.
+    fn decode(data: &[u8]) -> Result<Self> {
+        if data.len() < 8 || data[..8] != Self::discriminator() {
+            bail!("Account is not a {}", Self::NAME);
+        }
+        Self::deserialize(&mut &data[8..]).with_context(|| format!("Failed to decode {}", Self::NAME))
+    }
+
+    /// Fetch and decode a single account
+    fn fetch(client: &impl LedgerClient, address: &Pubkey) -> impl Future<Output = Result<Self>> + Send {
+        async move {
+            let Some(account) = client.get_account(address).await? else {
+                bail!("{} {} not found", Self::NAME, address);
+            };
+            Self::decode(&account.data)
+        }
+    }
+
+    /// Fetch and decode many accounts at once, in the order of `addresses`
+    ///
+    /// Missing accounts come back as `None`; so do accounts that fail to
+    /// decode, which are also returned with their error for the caller to
+    /// report.
+    fn fetch_many(
+        client: &impl LedgerClient,
+        addresses: &[Pubkey],
+    ) -> impl Future<Output = Result<(Vec<Option<Self>>, Undecodable)>> + Send {
+        async move {
+            let accounts = get_accounts(client, addresses)
+                .await
+                .with_context(|| format!("Failed to fetch {} accounts", Self::NAME))?;
+            let mut failed = Vec::new();
+            let decoded = addresses
+                .iter()
+                .zip(accounts)
+                .map(|(address, account)| match Self::decode(&account?.data) {
+                    Ok(value) => Some(value),
+                    Err(e) => {
+                        failed.push((*address, e));
+                        None
+                    }
+                })
+                .collect();
+            Ok((decoded, failed))
+        }
+    }
+
+    /// Load every account of this type owned by `program_id`
+    ///
+    /// Accounts that carry the right discriminator but fail to decode are
+    /// returned apart with their error, so one malformed account does not
+    /// hide the rest.
+    fn fetch_all(
+        client: &impl LedgerClient,
+        program_id: &Pubkey,
+    ) -> impl Future<Output = Result<(Vec<(Pubkey, Self)>, Undecodable)>> + Send {
+        async move {
+            let filters = [RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                0,
+                Self::discriminator().to_vec(),
+            ))];
+            let accounts = client
+                .get_program_accounts(program_id, &filters)
+                .await
+                .with_context(|| format!("Failed to load {} accounts of {}", Self::NAME, program_id))?;
+
+            let mut decoded = Vec::with_capacity(accounts.len());
+            let mut failed = Vec::new();
+            for (address, account) in accounts {
+                match Self::decode(&account.data) {
+                    Ok(value) => decoded.push((address, value)),
+                    Err(e) => failed.push((address, e)),
+                }
+            }
+            Ok((decoded, failed))
+        }
+    }
+}
+
+/// Accounts that were found but failed to decode, with the reason
+pub type Undecodable = Vec<(Pubkey, anyhow::Error)>;
+
+/// Build an Anchor instruction: `sha256("global:<name>")[..8]` + Borsh args
+pub fn anchor_instruction(program_id: &Pubkey, name: &str, args: &[u8], accounts: Vec<AccountMeta>) -> Instruction {
+    let mut data = discriminator("global", name).to_vec();
+    data.extend_from_slice(args);
+    Instruction::new_with_bytes(*program_id, &data, accounts)
+}
+
+/// Collateral vault PDA, seeds `[b"vault", owner]`
+pub fn vault_address(vault_program: &Pubkey, owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"vault", owner.as_ref()], vault_program).0
+}
+
+/// Insurance fund PDA of the liquidation engine, seeds `[b"insurance_fund"]`
+pub fn insurance_fund_address(liquidation_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"insurance_fund"], liquidation_program).0
+}
+
+/// Render a fixed-point amount, e.g. `ui_amount(1_500_000, 6) == 1.5`
+pub fn ui_amount(amount: i128, decimals: u32) -> f64 {
+    amount as f64 / 10f64.powi(decimals as i32)
+}
@@ -0,0 +1,347 @@
+//! Postgres indexer for GDX program activity
+//!
+//! Walks confirmed blocks in slot order, decodes the Anchor events of the
+//! configured programs and writes each program's events to its table (see
+//! `migrations/`). The checkpoint is the last indexed block's slot and hash.
+//!
+//! Blocks above the finalized slot can still be abandoned. Each new block's
+//! `previous_blockhash` must match the checkpoint hash; if it does not, the
+//! chain we indexed was dropped, so every row above the newest indexed
+//! finalized block is deleted and indexing resumes from there.
+//!
+//! Blocks come from a [`BlockSource`]: the nonblocking `RpcClient` in the
+//! binary, a scripted chain in tests.
+
+pub mod rows;
+pub mod store;
+
+use anyhow::{bail, Context, Result};
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_config::RpcBlockConfig};
+use solana_sdk::{
+    clock::{Slot, UnixTimestamp},
+    commitment_config::CommitmentConfig,
+};
+use solana_transaction_status::{
+    option_serializer::OptionSerializer, EncodedTransaction, TransactionDetails, UiConfirmedBlock,
+    UiTransactionEncoding,
+};
+use std::future::Future;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::events::{Event, EventDecoder};
+use rows::Columns;
+use store::{Checkpoint, Store};
+
+/// Most slots requested from `getBlocks` at once
+const MAX_SLOT_RANGE: u64 = 500;
+
+/// What a program does, which decides the table its events go to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Role {
+    Vault,
+    Position,
+    Liquidation,
+    Funding,
+    Settlement,
+}
+
+impl Role {
+    pub const ALL: [Role; 5] = [
+        Role::Vault,
+        Role::Position,
+        Role::Liquidation,
+        Role::Funding,
+        Role::Settlement,
+    ];
+
+    pub fn table(self) -> &'static str {
+        match self {
+            Role::Vault => "vault_transactions",
+            Role::Position => "positions",
+            Role::Liquidation => "liquidations",
+            Role::Funding => "funding_payments",
+            Role::Settlement => "settlements",
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "vault" => Ok(Role::Vault),
+            "position" => Ok(Role::Position),
+            "liquidation" => Ok(Role::Liquidation),
+            "funding" => Ok(Role::Funding),
+            "settlement" => Ok(Role::Settlement),
+            other => bail!(
+                "Unknown program role {} (expected vault, position, liquidation, funding or settlement)",
+                other
+            ),
+        }
+    }
+}
+
+/// A program to index and how to decode its events
+pub struct IndexedProgram {
+    pub role: Role,
+    pub decoder: EventDecoder,
+}
+
+/// One decoded event, ready to insert
+#[derive(Debug, Clone)]
+pub struct IndexedEvent {
+    pub role: Role,
+    pub signature: String,
+    /// Position among the indexed events of its transaction
+    pub index: usize,
+    pub program_id: String,
+    pub event: Event,
+    /// Typed columns of the event's table
+    pub columns: Columns,
+}
+
+/// Everything the indexer writes for one block
+#[derive(Debug, Clone)]
+pub struct BlockEvents {
+    pub slot: Slot,
+    pub blockhash: String,
+    pub block_time: Option<UnixTimestamp>,
+    pub events: Vec<IndexedEvent>,
+    /// Events that could not be decoded and fields left NULL, to report;
+    /// the block is indexed without them
+    pub problems: Vec<String>,
+}
+
+/// Decode the events of the indexed programs in one block
+///
+/// Failed transactions are skipped: their events were rolled back.
+pub fn extract(slot: Slot, block: &UiConfirmedBlock, programs: &[IndexedProgram]) -> BlockEvents {
+    let mut events = Vec::new();
+    let mut problems = Vec::new();
+    for tx in block.transactions.iter().flatten() {
+        let Some(meta) = &tx.meta else { continue };
+        if meta.err.is_some() {
+            continue;
+        }
+        let OptionSerializer::Some(logs) = &meta.log_messages else {
+            continue;
+        };
+        let signature = match &tx.transaction {
+            EncodedTransaction::Json(ui) => ui.signatures.first().cloned(),
+            other => other.decode().and_then(|t| t.signatures.first().map(|s| s.to_string())),
+        };
+        let Some(signature) = signature else { continue };
+
+        let mut index = 0;
+        for program in programs {
+            let program_id = program.decoder.program_id().to_string();
+            let invoke = format!("Program {} invoke", program_id);
+            if !logs.iter().any(|line| line.starts_with(&invoke)) {
+                continue;
+            }
+            for event in program.decoder.parse_logs(logs) {
+                match event {
+                    Ok(event) => {
+                        let (columns, skipped) = rows::columns(program.role, &event);
+                        problems.extend(
+                            skipped
+                                .into_iter()
+                                .map(|field| format!("{} {}: {}, left NULL", signature, event.name(), field)),
+                        );
+                        events.push(IndexedEvent {
+                            role: program.role,
+                            signature: signature.clone(),
+                            index,
+                            program_id: program_id.clone(),
+                            event,
+                            columns,
+                        });
+                        index += 1;
+                    }
+                    Err(e) => problems.push(format!("{}: {:#}", signature, e)),
+                }
+            }
+        }
+    }
+    BlockEvents {
+        slot,
+        blockhash: block.blockhash.clone(),
+        block_time: block.block_time,
+        events,
+        problems,
+    }
+}
+
+/// The block queries the indexer makes
+pub trait BlockSource: Send + Sync {
+    fn get_slot(&self, commitment: CommitmentConfig) -> impl Future<Output = Result<Slot>> + Send;
+
+    /// Slots in `from..=to` that have a confirmed block
+    fn get_blocks(&self, from: Slot, to: Slot) -> impl Future<Output = Result<Vec<Slot>>> + Send;
+
+    /// Confirmed block with full JSON transactions and their logs
+    fn get_block(&self, slot: Slot) -> impl Future<Output = Result<UiConfirmedBlock>> + Send;
+}
+
+impl BlockSource for RpcClient {
+    async fn get_slot(&self, commitment: CommitmentConfig) -> Result<Slot> {
+        Ok(self.get_slot_with_commitment(commitment).await?)
+    }
+
+    async fn get_blocks(&self, from: Slot, to: Slot) -> Result<Vec<Slot>> {
+        self.get_blocks_with_commitment(from, Some(to), CommitmentConfig::confirmed())
+            .await
+            .context("getBlocks failed")
+    }
+
+    async fn get_block(&self, slot: Slot) -> Result<UiConfirmedBlock> {
+        self.get_block_with_config(
+            slot,
+            RpcBlockConfig {
+                encoding: Some(UiTransactionEncoding::Json),
+                transaction_details: Some(TransactionDetails::Full),
+                rewards: Some(false),
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            },
+        )
+        .await
+        .with_context(|| format!("getBlock {} failed", slot))
+    }
+}
+
+pub struct Indexer<S = RpcClient> {
+    source: S,
+    store: Store,
+    programs: Vec<IndexedProgram>,
+    poll_interval: Duration,
+    cursor: Option<Cursor>,
+}
+
+/// Where indexing stands
+struct Cursor {
+    /// Slot indexing began at, the resume point if everything is rolled back
+    start: Slot,
+    /// First slot not yet looked at. It runs ahead of the checkpoint across
+    /// skipped slots, which have no block to record.
+    next: Slot,
+    checkpoint: Option<Checkpoint>,
+}
+
+impl Indexer {
+    pub fn new(rpc_url: String, store: Store, programs: Vec<IndexedProgram>, poll_interval: Duration) -> Self {
+        let rpc = RpcClient::new_with_commitment(rpc_url, CommitmentConfig::confirmed());
+        Self::with_source(rpc, store, programs, poll_interval)
+    }
+}
+
+impl<S: BlockSource> Indexer<S> {
+    pub fn with_source(source: S, store: Store, programs: Vec<IndexedProgram>, poll_interval: Duration) -> Self {
+        Self {
+            source,
+            store,
+            programs,
+            poll_interval,
+            cursor: None,
+        }
+    }
+
+    /// Index forever from the checkpoint, or from `start_slot` (default: the
+    /// finalized slot) on a fresh database
+    ///
+    /// RPC and database errors are reported and retried after the poll
+    /// interval; the checkpoint makes every retry pick up where it stopped.
+    pub async fn run(&mut self, start_slot: Option<Slot>) -> Result<()> {
+        loop {
+            if let Err(e) = self.catch_up(start_slot).await {
+                eprintln!("❌ {:#}, retrying in {:?}", e, self.poll_interval);
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Index every block up to the confirmed tip, rolling back first if the
+    /// chain no longer contains the indexed blocks
+    pub async fn catch_up(&mut self, start_slot: Option<Slot>) -> Result<()> {
+        if self.cursor.is_none() {
+            let checkpoint = self.store.checkpoint().await?;
+            let start = match (&checkpoint, start_slot) {
+                (Some(checkpoint), _) => {
+                    println!("▶️  Resuming after slot {}", checkpoint.slot);
+                    checkpoint.slot + 1
+                }
+                (None, Some(slot)) => slot,
+                (None, None) => self
+                    .source
+                    .get_slot(CommitmentConfig::finalized())
+                    .await
+                    .context("Failed to read the finalized slot")?,
+            };
+            if checkpoint.is_none() {
+                println!("▶️  Starting at slot {}", start);
+            }
+            self.cursor = Some(Cursor {
+                start,
+                next: start,
+                checkpoint,
+            });
+        }
+        while self.step().await? {}
+        Ok(())
+    }
+
+    /// Index the next range of slots; `false` when already at the tip
+    async fn step(&mut self) -> Result<bool> {
+        let tip = self.source.get_slot(CommitmentConfig::confirmed()).await?;
+        let finalized = self.source.get_slot(CommitmentConfig::finalized()).await?;
+        let cursor = self.cursor.as_mut().expect("cursor is set before stepping");
+        // A range that failed half way resumes after its last written block
+        let from = cursor.checkpoint.as_ref().map_or(cursor.next, |c| cursor.next.max(c.slot + 1));
+        if from > tip {
+            return Ok(false);
+        }
+
+        let to = tip.min(from + MAX_SLOT_RANGE - 1);
+        let slots = self.source.get_blocks(from, to).await?;
+
+        for slot in slots {
+            let block = self.source.get_block(slot).await?;
+
+            if let Some(previous) = cursor.checkpoint.clone() {
+                if block.previous_blockhash != previous.blockhash {
+                    cursor.checkpoint = self.store.rollback_to(finalized).await?;
+                    cursor.next = cursor.checkpoint.as_ref().map_or(cursor.start, |c| c.slot + 1);
+                    let resumed = cursor
+                        .checkpoint
+                        .as_ref()
+                        .map_or_else(|| format!("slot {}", cursor.start), |c| format!("after slot {}", c.slot));
+                    println!(
+                        "⏪ Slot {} does not extend indexed slot {}: rolled back, resuming {}",
+                        slot, previous.slot, resumed
+                    );
+                    return Ok(true);
+                }
+            }
+
+            let events = extract(slot, &block, &self.programs);
+            for problem in &events.problems {
+                eprintln!("⚠️  Slot {}: {}", slot, problem);
+            }
+            self.store.write_block(&events).await?;
+            if !events.events.is_empty() {
+                println!("📥 Slot {}: {} event(s)", slot, events.events.len());
+            }
+            cursor.checkpoint = Some(Checkpoint {
+                slot,
+                blockhash: block.blockhash,
+            });
+        }
+
+        cursor.next = to + 1;
+        self.store.prune_blocks(finalized).await?;
+        Ok(to < tip)
+    }
+}
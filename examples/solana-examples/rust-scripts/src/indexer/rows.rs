@@ -0,0 +1,218 @@
+//! Typed columns of the event tables
+//!
+//! Collateral vault events map straight from their structs. The other GDX
+//! programs publish no event layout yet, so their columns are read from the
+//! IDL-decoded JSON by field name, accepting the names the assignment specs
+//! use (`position_id` or `position`, `user` or `owner`, ...). A field that
+//! is present but does not fit its column is reported and left NULL.
+
+use serde_json::Value;
+
+use super::Role;
+use crate::events::Event;
+
+/// One column value of an event row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Column {
+    Text(Option<String>),
+    /// Stored as NUMERIC; holds every integer field except a u128 above
+    /// `i128::MAX`
+    Numeric(Option<i128>),
+    Int(Option<i32>),
+}
+
+/// Typed columns of a row, by column name
+pub type Columns = Vec<(&'static str, Column)>;
+
+/// Columns of `event` in the table of `role`, and the fields that were
+/// present but unreadable
+pub fn columns(role: Role, event: &Event) -> (Columns, Vec<String>) {
+    if role == Role::Vault {
+        if let Some(columns) = vault_columns(event) {
+            return (columns, Vec::new());
+        }
+    }
+
+    let data = event.data();
+    let mut reader = Reader {
+        data: &data,
+        columns: Vec::new(),
+        problems: Vec::new(),
+    };
+    match role {
+        Role::Vault => {
+            reader.text("vault", &["vault", "from_vault"]);
+            reader.text("owner", &["user", "owner"]);
+            reader.text("counterparty", &["to_vault"]);
+            reader.numeric("amount", &["amount"]);
+            reader.numeric("balance_after", &["new_balance"]);
+            reader.numeric("available_after", &["new_available_balance"]);
+            reader.numeric("locked_after", &["new_locked_balance"]);
+        }
+        Role::Position => {
+            reader.text("position", &["position_id", "position"]);
+            reader.text("owner", &["user", "owner"]);
+            reader.text("symbol", &["symbol"]);
+            reader.text("side", &["side"]);
+            reader.numeric("size", &["size"]);
+            reader.numeric("entry_price", &["entry_price"]);
+            reader.numeric("margin", &["margin", "collateral"]);
+            reader.int("leverage", &["leverage"]);
+        }
+        Role::Liquidation => {
+            reader.text("position", &["position_id", "position"]);
+            reader.text("owner", &["position_owner", "user", "owner"]);
+            reader.text("liquidator", &["liquidator"]);
+            reader.text("symbol", &["symbol"]);
+            reader.numeric("size", &["liquidated_size", "size"]);
+            reader.numeric("price", &["liquidation_price", "mark_price", "price"]);
+            reader.numeric("reward", &["liquidator_reward", "reward"]);
+            reader.numeric("bad_debt", &["bad_debt"]);
+        }
+        Role::Funding => {
+            reader.text("position", &["position_id", "position"]);
+            reader.text("owner", &["user", "owner"]);
+            reader.text("symbol", &["symbol"]);
+            reader.numeric("rate", &["funding_rate", "rate"]);
+            reader.numeric("amount", &["payment", "amount"]);
+        }
+        Role::Settlement => {
+            reader.text("batch_id", &["batch_id", "batch"]);
+            reader.text("merkle_root", &["merkle_root"]);
+            reader.int("trade_count", &["trade_count"]);
+        }
+    }
+    (reader.columns, reader.problems)
+}
+
+#[derive(Default)]
+struct VaultRow {
+    vault: Option<String>,
+    owner: Option<String>,
+    counterparty: Option<String>,
+    amount: Option<u64>,
+    balance_after: Option<u64>,
+    available_after: Option<u64>,
+    locked_after: Option<u64>,
+}
+
+fn vault_columns(event: &Event) -> Option<Columns> {
+    let row = match event {
+        Event::VaultInitialized(e) => VaultRow {
+            vault: Some(e.vault.to_string()),
+            owner: Some(e.owner.to_string()),
+            ..VaultRow::default()
+        },
+        Event::DepositEvent(e) => VaultRow {
+            vault: Some(e.vault.to_string()),
+            owner: Some(e.user.to_string()),
+            amount: Some(e.amount),
+            balance_after: Some(e.new_balance),
+            ..VaultRow::default()
+        },
+        Event::WithdrawEvent(e) => VaultRow {
+            vault: Some(e.vault.to_string()),
+            owner: Some(e.user.to_string()),
+            amount: Some(e.amount),
+            balance_after: Some(e.new_balance),
+            ..VaultRow::default()
+        },
+        Event::LockEvent(e) => VaultRow {
+            vault: Some(e.vault.to_string()),
+            amount: Some(e.amount),
+            available_after: Some(e.new_available_balance),
+            locked_after: Some(e.new_locked_balance),
+            ..VaultRow::default()
+        },
+        Event::UnlockEvent(e) => VaultRow {
+            vault: Some(e.vault.to_string()),
+            amount: Some(e.amount),
+            available_after: Some(e.new_available_balance),
+            locked_after: Some(e.new_locked_balance),
+            ..VaultRow::default()
+        },
+        Event::TransferEvent(e) => VaultRow {
+            vault: Some(e.from_vault.to_string()),
+            counterparty: Some(e.to_vault.to_string()),
+            amount: Some(e.amount),
+            ..VaultRow::default()
+        },
+        Event::AuthorizedProgramsUpdated(_) => VaultRow::default(),
+        Event::Other { .. } => return None,
+    };
+    let numeric = |value: Option<u64>| Column::Numeric(value.map(i128::from));
+    Some(vec![
+        ("vault", Column::Text(row.vault)),
+        ("owner", Column::Text(row.owner)),
+        ("counterparty", Column::Text(row.counterparty)),
+        ("amount", numeric(row.amount)),
+        ("balance_after", numeric(row.balance_after)),
+        ("available_after", numeric(row.available_after)),
+        ("locked_after", numeric(row.locked_after)),
+    ])
+}
+
+struct Reader<'a> {
+    data: &'a Value,
+    columns: Columns,
+    problems: Vec<String>,
+}
+
+impl<'a> Reader<'a> {
+    /// First of `names` the event has a non-null value for
+    fn field(&self, names: &[&'static str]) -> Option<(&'static str, &'a Value)> {
+        names
+            .iter()
+            .find_map(|&name| self.data.get(name).filter(|v| !v.is_null()).map(|v| (name, v)))
+    }
+
+    fn text(&mut self, column: &'static str, names: &[&'static str]) {
+        let value = match self.field(names) {
+            None => None,
+            Some((_, Value::String(s))) => Some(s.clone()),
+            Some((_, Value::Number(n))) => Some(n.to_string()),
+            // Unit enum variants decode to a string; others to an object
+            // keyed by the variant
+            Some((_, Value::Object(map))) if map.len() == 1 => map.keys().next().cloned(),
+            Some((name, other)) => self.skip(name, "text", other),
+        };
+        self.columns.push((column, Column::Text(value)));
+    }
+
+    fn numeric(&mut self, column: &'static str, names: &[&'static str]) {
+        let value = match self.field(names) {
+            None => None,
+            Some((name, value)) => match integer(value) {
+                Some(n) => Some(n),
+                None => self.skip(name, "an integer", value),
+            },
+        };
+        self.columns.push((column, Column::Numeric(value)));
+    }
+
+    fn int(&mut self, column: &'static str, names: &[&'static str]) {
+        let value = match self.field(names) {
+            None => None,
+            Some((name, value)) => match integer(value).and_then(|n| i32::try_from(n).ok()) {
+                Some(n) => Some(n),
+                None => self.skip(name, "a 32-bit integer", value),
+            },
+        };
+        self.columns.push((column, Column::Int(value)));
+    }
+
+    fn skip<T>(&mut self, name: &str, expected: &str, value: &Value) -> Option<T> {
+        self.problems.push(format!("{} is not {}: {}", name, expected, value));
+        None
+    }
+}
+
+/// JSON numbers, and the decimal strings the IDL decoder writes for 128-bit
+/// integers
+fn integer(value: &Value) -> Option<i128> {
+    match value {
+        Value::Number(n) => n.as_i64().map(i128::from).or_else(|| n.as_u64().map(i128::from)),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
@@ -0,0 +1,213 @@
+//! Postgres side of the indexer
+//!
+//! A block is written in one transaction together with the checkpoint, so
+//! a restart resumes exactly after the last block that made it in.
+
+use anyhow::{Context, Result};
+use solana_sdk::clock::Slot;
+use tokio_postgres::{types::ToSql, Client, NoTls};
+
+use super::rows::Column;
+use super::{BlockEvents, Role};
+
+/// Embedded migrations, applied in order and recorded in `schema_migrations`
+const MIGRATIONS: &[(i32, &str, &str)] = &[(
+    1,
+    "create_tables",
+    include_str!("../../migrations/0001_create_tables.sql"),
+)];
+
+/// Last block the indexer committed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub slot: Slot,
+    pub blockhash: String,
+}
+
+pub struct Store {
+    client: Client,
+}
+
+impl Store {
+    pub async fn connect(database_url: &str) -> Result<Self> {
+        let (client, connection) = tokio_postgres::connect(database_url, NoTls)
+            .await
+            .with_context(|| format!("Failed to connect to {}", database_url))?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                eprintln!("❌ Postgres connection closed: {}", e);
+            }
+        });
+        Ok(Self { client })
+    }
+
+    /// Apply pending migrations; returns how many ran
+    pub async fn migrate(&mut self) -> Result<usize> {
+        self.client
+            .batch_execute(
+                "CREATE TABLE IF NOT EXISTS schema_migrations (
+                    version    INT PRIMARY KEY,
+                    name       TEXT NOT NULL,
+                    applied_at TIMESTAMPTZ NOT NULL DEFAULT now()
+                )",
+            )
+            .await
+            .context("Failed to create schema_migrations")?;
+
+        let mut applied = 0;
+        for &(version, name, sql) in MIGRATIONS {
+            let tx = self.client.transaction().await?;
+            let done = tx
+                .query_opt("SELECT 1 FROM schema_migrations WHERE version = $1", &[&version])
+                .await?
+                .is_some();
+            if done {
+                continue;
+            }
+            tx.batch_execute(sql)
+                .await
+                .with_context(|| format!("Migration {:04}_{} failed", version, name))?;
+            tx.execute(
+                "INSERT INTO schema_migrations (version, name) VALUES ($1, $2)",
+                &[&version, &name],
+            )
+            .await?;
+            tx.commit().await?;
+            applied += 1;
+        }
+        Ok(applied)
+    }
+
+    pub async fn checkpoint(&self) -> Result<Option<Checkpoint>> {
+        let row = self
+            .client
+            .query_opt("SELECT slot, blockhash FROM indexer_checkpoint WHERE id = 1", &[])
+            .await?;
+        Ok(row.map(|row| Checkpoint {
+            slot: row.get::<_, i64>(0) as Slot,
+            blockhash: row.get(1),
+        }))
+    }
+
+    /// Write a block's events and advance the checkpoint to it, atomically
+    pub async fn write_block(&mut self, block: &BlockEvents) -> Result<()> {
+        let tx = self.client.transaction().await?;
+        let slot = block.slot as i64;
+        let block_time = block.block_time.map(|t| t as f64);
+
+        for event in &block.events {
+            let mut columns = vec!["slot", "signature", "event_index", "program_id", "event", "block_time", "data"];
+            let mut values = vec!["$1", "$2", "$3", "$4", "$5", "to_timestamp($6)", "$7"]
+                .into_iter()
+                .map(String::from)
+                .collect::<Vec<_>>();
+            let index = event.index as i32;
+            let name = event.event.name();
+            let data = event.event.data();
+            let mut params: Vec<Box<dyn ToSql + Sync + Send>> = vec![
+                Box::new(slot),
+                Box::new(event.signature.clone()),
+                Box::new(index),
+                Box::new(event.program_id.clone()),
+                Box::new(name.to_string()),
+                Box::new(block_time),
+                Box::new(data),
+            ];
+            for (column, value) in &event.columns {
+                columns.push(column);
+                let n = params.len() + 1;
+                // Numerics travel as decimal text: the driver has no NUMERIC
+                // type, and the cast of a Rust integer cannot fail
+                let (placeholder, param): (String, Box<dyn ToSql + Sync + Send>) = match value {
+                    Column::Text(text) => (format!("${}", n), Box::new(text.clone())),
+                    Column::Numeric(number) => (
+                        format!("${}::TEXT::NUMERIC", n),
+                        Box::new(number.map(|n| n.to_string())),
+                    ),
+                    Column::Int(int) => (format!("${}", n), Box::new(*int)),
+                };
+                values.push(placeholder);
+                params.push(param);
+            }
+            let sql = format!(
+                "INSERT INTO {} ({}) VALUES ({}) ON CONFLICT (signature, event_index) DO NOTHING",
+                event.role.table(),
+                columns.join(", "),
+                values.join(", ")
+            );
+            let params: Vec<&(dyn ToSql + Sync)> = params.iter().map(|p| p.as_ref() as &(dyn ToSql + Sync)).collect();
+            tx.execute(sql.as_str(), &params)
+                .await
+                .with_context(|| format!("Failed to insert {} from {}", name, event.signature))?;
+        }
+
+        tx.execute(
+            "INSERT INTO indexed_blocks (slot, blockhash) VALUES ($1, $2)
+             ON CONFLICT (slot) DO UPDATE SET blockhash = EXCLUDED.blockhash",
+            &[&slot, &block.blockhash],
+        )
+        .await?;
+        tx.execute(
+            "INSERT INTO indexer_checkpoint (id, slot, blockhash) VALUES (1, $1, $2)
+             ON CONFLICT (id) DO UPDATE SET slot = EXCLUDED.slot, blockhash = EXCLUDED.blockhash, updated_at = now()",
+            &[&slot, &block.blockhash],
+        )
+        .await?;
+        tx.commit().await.context("Failed to commit block")
+    }
+
+    /// Drop everything above the newest indexed block at or below `slot`
+    /// and move the checkpoint back to it. Returns the new checkpoint, or
+    /// `None` if no block that old is known (the checkpoint is cleared).
+    pub async fn rollback_to(&mut self, slot: Slot) -> Result<Option<Checkpoint>> {
+        let tx = self.client.transaction().await?;
+        let target = tx
+            .query_opt(
+                "SELECT slot, blockhash FROM indexed_blocks WHERE slot <= $1 ORDER BY slot DESC LIMIT 1",
+                &[&(slot as i64)],
+            )
+            .await?
+            .map(|row| Checkpoint {
+                slot: row.get::<_, i64>(0) as Slot,
+                blockhash: row.get(1),
+            });
+        let keep = target.as_ref().map(|c| c.slot as i64).unwrap_or(-1);
+
+        for role in Role::ALL {
+            tx.execute(
+                format!("DELETE FROM {} WHERE slot > $1", role.table()).as_str(),
+                &[&keep],
+            )
+            .await?;
+        }
+        tx.execute("DELETE FROM indexed_blocks WHERE slot > $1", &[&keep])
+            .await?;
+        match &target {
+            Some(checkpoint) => {
+                tx.execute(
+                    "UPDATE indexer_checkpoint SET slot = $1, blockhash = $2, updated_at = now() WHERE id = 1",
+                    &[&(checkpoint.slot as i64), &checkpoint.blockhash],
+                )
+                .await?;
+            }
+            None => {
+                tx.execute("DELETE FROM indexer_checkpoint", &[]).await?;
+            }
+        }
+        tx.commit().await.context("Failed to commit rollback")?;
+        Ok(target)
+    }
+
+    /// Forget fork-detection hashes below the finalized slot, keeping the
+    /// newest one as the rollback target
+    pub async fn prune_blocks(&self, finalized: Slot) -> Result<()> {
+        self.client
+            .execute(
+                "DELETE FROM indexed_blocks
+                 WHERE slot < (SELECT MAX(slot) FROM indexed_blocks WHERE slot <= $1)",
+                &[&(finalized as i64)],
+            )
+            .await?;
+        Ok(())
+    }
+}
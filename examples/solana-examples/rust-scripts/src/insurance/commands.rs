@@ -0,0 +1,261 @@
+use anyhow::{bail, Context, Result};
+use clap::Subcommand;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+use spl_associated_token_account::get_associated_token_address;
+use std::str::FromStr;
+
+use super::{stress, StressScenario};
+use crate::client::{token_balance, until_ctrl_c};
+use crate::gdx::accounts::{InsuranceFund, Position};
+use crate::gdx::{insurance_fund_address, ui_amount, AnchorAccount, PRICE_DECIMALS, QUOTE_DECIMALS};
+use crate::liquidation::scanner::{load_mark_prices, PriceInputs};
+use crate::oracle::OracleConfig;
+
+#[derive(Subcommand)]
+pub enum InsuranceCommand {
+    /// Decode the insurance fund account and its token balance
+    Show {
+        /// Liquidation engine program id (derives the fund PDA)
+        #[arg(long)]
+        liquidation_program: String,
+        /// Fund account address, if it is not the default PDA
+        #[arg(long)]
+        address: Option<String>,
+        /// Collateral mint (USDT); shows the fund's associated token account balance
+        #[arg(long)]
+        mint: Option<String>,
+        /// Fund token account, if it is not the associated token account
+        #[arg(long)]
+        token_account: Option<String>,
+        /// RPC endpoint
+        #[arg(long, default_value = "http://127.0.0.1:8899")]
+        url: String,
+    },
+    /// Estimate bad debt, fund drawdown and ADL exposure under a price shock
+    Stress {
+        /// Liquidation engine program id (derives the fund PDA)
+        #[arg(long)]
+        liquidation_program: String,
+        /// Position management program id
+        #[arg(long)]
+        position_program: String,
+        /// Price move per market in percent, as SYMBOL=PCT (repeatable)
+        #[arg(long = "shock", allow_hyphen_values = true)]
+        shocks: Vec<String>,
+        /// Price move in percent for markets without --shock
+        #[arg(long, default_value = "0", allow_hyphen_values = true)]
+        shock_all: f64,
+        /// Oracle price account per market, as SYMBOL=ADDRESS (repeatable)
+        #[arg(long = "price")]
+        prices: Vec<String>,
+        /// Fixed base price per market, as SYMBOL=PRICE (repeatable)
+        #[arg(long = "mark")]
+        marks: Vec<String>,
+        /// Use this fund balance (in quote units) instead of the on-chain one
+        #[arg(long)]
+        fund_balance: Option<f64>,
+        /// Maximum oracle price age in seconds
+        #[arg(long, default_value = "30")]
+        max_staleness: i64,
+        /// RPC endpoint
+        #[arg(long, default_value = "http://127.0.0.1:8899")]
+        url: String,
+    },
+}
+
+pub async fn run(command: InsuranceCommand) -> Result<()> {
+    match command {
+        InsuranceCommand::Show {
+            liquidation_program,
+            address,
+            mint,
+            token_account,
+            url,
+        } => show(liquidation_program, address, mint, token_account, url).await,
+        InsuranceCommand::Stress {
+            liquidation_program,
+            position_program,
+            shocks,
+            shock_all,
+            prices,
+            marks,
+            fund_balance,
+            max_staleness,
+            url,
+        } => {
+            let mut scenario = StressScenario {
+                default_shock: shock_all,
+                ..StressScenario::default()
+            };
+            for entry in &shocks {
+                let (symbol, pct) = entry
+                    .split_once('=')
+                    .with_context(|| format!("Expected SYMBOL=PCT, got {:?}", entry))?;
+                let pct: f64 = pct.parse().with_context(|| format!("Invalid shock for {}", symbol))?;
+                scenario.shocks.insert(symbol.to_string(), pct);
+            }
+            let inputs = PriceInputs::parse(&prices, &marks)?;
+            let config = OracleConfig {
+                max_staleness,
+                ..OracleConfig::default()
+            };
+            run_stress(
+                liquidation_program,
+                position_program,
+                scenario,
+                inputs,
+                config,
+                fund_balance,
+                url,
+            )
+            .await
+        }
+    }
+}
+
+fn fund_address(liquidation_program: &str, address: Option<&str>) -> Result<Pubkey> {
+    match address {
+        Some(address) => Pubkey::from_str(address).context("Invalid insurance fund address"),
+        None => {
+            let program = Pubkey::from_str(liquidation_program).context("Invalid liquidation program id")?;
+            Ok(insurance_fund_address(&program))
+        }
+    }
+}
+
+async fn show(
+    liquidation_program: String,
+    address: Option<String>,
+    mint: Option<String>,
+    token_account: Option<String>,
+    url: String,
+) -> Result<()> {
+    println!("🛡️  Insurance Fund");
+    let client = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+    let address = fund_address(&liquidation_program, address.as_deref())?;
+    let fund = InsuranceFund::fetch(&client, &address).await?;
+
+    println!("   Address: {}", address);
+    println!("   Authority: {}", fund.authority);
+    println!("   Balance: {:.6} USDT", ui_amount(fund.balance as i128, QUOTE_DECIMALS));
+    println!(
+        "   Total Contributions: {:.6} USDT",
+        ui_amount(fund.total_contributions as i128, QUOTE_DECIMALS)
+    );
+    println!(
+        "   Bad Debt Covered: {:.6} USDT",
+        ui_amount(fund.total_bad_debt_covered as i128, QUOTE_DECIMALS)
+    );
+    println!("   Utilization: {:.2}%", fund.utilization_ratio as f64 / 100.0);
+
+    let token_account = match (token_account, mint) {
+        (Some(account), _) => Some(Pubkey::from_str(&account).context("Invalid token account")?),
+        (None, Some(mint)) => {
+            let mint = Pubkey::from_str(&mint).context("Invalid mint")?;
+            Some(get_associated_token_address(&address, &mint))
+        }
+        (None, None) => None,
+    };
+
+    if let Some(token_account) = token_account {
+        let held = token_balance(&client, &token_account).await?;
+        println!("\n🪙 Token Account: {}", token_account);
+        println!("   Balance: {} (raw {})", ui_amount(held as i128, QUOTE_DECIMALS), held);
+
+        // The recorded balance and the tokens actually held should agree
+        if held != fund.balance {
+            println!(
+                "   ⚠️  Recorded balance differs from token balance by {:.6} USDT",
+                ui_amount(held as i128 - fund.balance as i128, QUOTE_DECIMALS)
+            );
+        } else {
+            println!("   ✅ Matches recorded balance");
+        }
+    } else {
+        println!("\n💡 Pass --mint <USDT_MINT> to also show the fund's token balance");
+    }
+
+    Ok(())
+}
+
+async fn run_stress(
+    liquidation_program: String,
+    position_program: String,
+    scenario: StressScenario,
+    inputs: PriceInputs,
+    config: OracleConfig,
+    fund_balance: Option<f64>,
+    url: String,
+) -> Result<()> {
+    println!("🌪️  Insurance fund stress test");
+    let client = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+
+    let fund_balance = match fund_balance {
+        Some(balance) => {
+            let raw = (balance * 10f64.powi(QUOTE_DECIMALS as i32)).round();
+            if !(raw >= 0.0 && raw < u64::MAX as f64) {
+                bail!("Invalid --fund-balance {}; expected a non-negative USDT amount", balance);
+            }
+            raw as u64
+        }
+        None => {
+            let address = fund_address(&liquidation_program, None)?;
+            InsuranceFund::fetch(&client, &address).await?.balance
+        }
+    };
+
+    let position_program = Pubkey::from_str(&position_program).context("Invalid position program id")?;
+    let (positions, undecodable) = until_ctrl_c(Position::fetch_all(&client, &position_program)).await?;
+    for (address, e) in &undecodable {
+        eprintln!("   ⚠️  Skipping position {}: {:#}", address, e);
+    }
+    let mut symbols: Vec<String> = positions.iter().map(|(_, p)| p.symbol.clone()).collect();
+    symbols.sort();
+    symbols.dedup();
+    let prices = load_mark_prices(&client, &inputs, &symbols, &config).await;
+    for symbol in symbols.iter().filter(|s| !prices.contains_key(*s)) {
+        println!("   ⚠️  No valid base price for {} — its positions were skipped", symbol);
+    }
+
+    let report = stress(&positions, &prices, &scenario, fund_balance);
+
+    println!("\n📉 Per market:");
+    println!(
+        "   {:<10} {:>7} {:>14} {:>14} {:>6} {:>14} {:>14} {:>9}",
+        "Market", "Shock", "Base", "Shocked", "Bust", "Bad Debt", "Uncovered", "ADL cut"
+    );
+    for market in &report.markets {
+        println!(
+            "   {:<10} {:>6.1}% {:>14.2} {:>14.2} {:>6} {:>14.2} {:>14.2} {:>8.2}%",
+            market.symbol,
+            market.shock_pct,
+            ui_amount(market.base_price as i128, PRICE_DECIMALS),
+            ui_amount(market.shocked_price as i128, PRICE_DECIMALS),
+            format!("{}/{}", market.insolvent_positions, market.positions),
+            ui_amount(market.bad_debt as i128, QUOTE_DECIMALS),
+            ui_amount(market.uncovered as i128, QUOTE_DECIMALS),
+            market.adl_haircut_bps as f64 / 100.0
+        );
+    }
+
+    println!("\n🛡️  Insurance fund:");
+    println!("   Balance: {:.6} USDT", ui_amount(report.fund_balance as i128, QUOTE_DECIMALS));
+    println!("   Bad Debt: {:.6} USDT", ui_amount(report.total_bad_debt as i128, QUOTE_DECIMALS));
+    println!(
+        "   Drawdown: {:.6} USDT ({:.2}%)",
+        ui_amount(report.fund_drawdown as i128, QUOTE_DECIMALS),
+        report.drawdown_bps() as f64 / 100.0
+    );
+
+    if report.fund_exhausted() {
+        println!(
+            "❌ Fund exhausted: {:.6} USDT must be socialized through auto-deleveraging",
+            ui_amount(report.uncovered as i128, QUOTE_DECIMALS)
+        );
+    } else {
+        println!("✅ Fund covers all bad debt in this scenario");
+    }
+
+    Ok(())
+}
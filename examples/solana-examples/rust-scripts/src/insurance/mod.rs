@@ -0,0 +1,158 @@
+//! Insurance fund state and stress modelling
+//!
+//! `stress` replays a price shock against the current open positions: every
+//! position whose equity turns negative leaves bad debt, the insurance fund
+//! covers it while it lasts, and whatever is left must be socialized by
+//! auto-deleveraging (ADL) the profitable side of the same market.
+
+pub mod commands;
+
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::gdx::accounts::Position;
+use crate::liquidation::MarkedPosition;
+
+/// Price moves to apply, in percent (e.g. `-20.0` for a 20% crash)
+#[derive(Debug, Clone, Default)]
+pub struct StressScenario {
+    pub shocks: HashMap<String, f64>,
+    /// Applied to markets without an explicit shock
+    pub default_shock: f64,
+}
+
+impl StressScenario {
+    pub fn shock_for(&self, symbol: &str) -> f64 {
+        self.shocks.get(symbol).copied().unwrap_or(self.default_shock)
+    }
+
+    /// Shocked price in the same fixed point as `price`
+    pub fn apply(&self, symbol: &str, price: u64) -> u64 {
+        let shocked = price as f64 * (1.0 + self.shock_for(symbol) / 100.0);
+        shocked.max(0.0).round() as u64
+    }
+}
+
+/// Stress outcome for one market; amounts in quote token base units
+#[derive(Debug, Clone, Default)]
+pub struct MarketStress {
+    pub symbol: String,
+    pub shock_pct: f64,
+    pub base_price: u64,
+    pub shocked_price: u64,
+    pub positions: usize,
+    pub insolvent_positions: usize,
+    pub bad_debt: u128,
+    /// Share of the bad debt the insurance fund covers
+    pub covered: u128,
+    /// Bad debt left after the fund is exhausted
+    pub uncovered: u128,
+    /// Unrealized profit of the winning side, the pool ADL draws from
+    pub profitable_pnl: u128,
+    /// Share of winners' profit taken by ADL, in basis points
+    pub adl_haircut_bps: u128,
+}
+
+/// Stress outcome across all markets
+#[derive(Debug, Clone, Default)]
+pub struct StressReport {
+    pub markets: Vec<MarketStress>,
+    pub fund_balance: u128,
+    pub total_bad_debt: u128,
+    pub fund_drawdown: u128,
+    pub uncovered: u128,
+}
+
+impl StressReport {
+    pub fn fund_exhausted(&self) -> bool {
+        self.total_bad_debt > self.fund_balance
+    }
+
+    /// Fund drawdown as a share of its balance, in basis points
+    pub fn drawdown_bps(&self) -> u128 {
+        if self.fund_balance == 0 {
+            return if self.total_bad_debt > 0 { 10_000 } else { 0 };
+        }
+        self.fund_drawdown * 10_000 / self.fund_balance
+    }
+}
+
+/// Apply `scenario` to `positions` priced at `prices` (symbol → base price)
+///
+/// Positions in markets without a base price are ignored. When bad debt
+/// exceeds the fund, the fund is split across markets pro rata to their bad
+/// debt, so the markets' `uncovered` amounts add up to the report's.
+pub fn stress(
+    positions: &[(Pubkey, Position)],
+    prices: &HashMap<String, u64>,
+    scenario: &StressScenario,
+    fund_balance: u64,
+) -> StressReport {
+    let mut markets: BTreeMap<String, MarketStress> = BTreeMap::new();
+
+    for (address, position) in positions {
+        if position.size == 0 {
+            continue;
+        }
+        let Some(&base_price) = prices.get(&position.symbol) else {
+            continue;
+        };
+        let shocked_price = scenario.apply(&position.symbol, base_price);
+        let marked = MarkedPosition::mark(*address, position.clone(), None, shocked_price, false);
+
+        let market = markets.entry(position.symbol.clone()).or_insert_with(|| MarketStress {
+            symbol: position.symbol.clone(),
+            shock_pct: scenario.shock_for(&position.symbol),
+            base_price,
+            shocked_price,
+            ..MarketStress::default()
+        });
+        market.positions += 1;
+        if marked.equity < 0 {
+            market.insolvent_positions += 1;
+            market.bad_debt += marked.equity.unsigned_abs();
+        }
+        if marked.unrealized_pnl > 0 {
+            market.profitable_pnl += marked.unrealized_pnl as u128;
+        }
+    }
+
+    let fund_balance = fund_balance as u128;
+    let total_bad_debt: u128 = markets.values().map(|m| m.bad_debt).sum();
+    let fund_drawdown = total_bad_debt.min(fund_balance);
+
+    // Pro rata by bad debt, rounding down, then the units lost to rounding go
+    // to the largest remainders so the shares add up to the drawdown exactly
+    let mut remainders = Vec::new();
+    for market in markets.values_mut() {
+        let share = market.bad_debt * fund_drawdown;
+        if let (Some(covered), Some(remainder)) = (share.checked_div(total_bad_debt), share.checked_rem(total_bad_debt)) {
+            market.covered = covered;
+            remainders.push((remainder, market.symbol.clone()));
+        }
+    }
+    let assigned: u128 = markets.values().map(|m| m.covered).sum();
+    remainders.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    for (_, symbol) in remainders.into_iter().take((fund_drawdown - assigned) as usize) {
+        if let Some(market) = markets.get_mut(&symbol) {
+            market.covered += 1;
+        }
+    }
+
+    for market in markets.values_mut() {
+        market.uncovered = market.bad_debt - market.covered;
+        market.adl_haircut_bps = match (market.uncovered, market.profitable_pnl) {
+            (0, _) => 0,
+            (_, 0) => 10_000,
+            (uncovered, profit) => (uncovered * 10_000 / profit).min(10_000),
+        };
+    }
+
+    StressReport {
+        uncovered: total_bad_debt - fund_drawdown,
+        markets: markets.into_values().collect(),
+        fund_balance,
+        total_bad_debt,
+        fund_drawdown,
+    }
+}
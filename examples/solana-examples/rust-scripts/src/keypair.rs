@@ -0,0 +1,32 @@
+use anyhow::{anyhow, Result};
+use solana_sdk::signature::{read_keypair_file, write_keypair_file, Keypair};
+use std::path::Path;
+
+/// Default Solana CLI wallet, as created by `solana-keygen new`
+pub const DEFAULT_KEYPAIR_PATH: &str = "~/.config/solana/id.json";
+
+/// Expand a leading `~` to `$HOME`
+fn expand(path: &str) -> String {
+    match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{}/{}", home, rest),
+        _ => path.to_string(),
+    }
+}
+
+/// Load a keypair file, expanding a leading `~` to `$HOME`
+pub fn load_keypair(path: &str) -> Result<Keypair> {
+    let expanded = expand(path);
+    read_keypair_file(&expanded).map_err(|e| anyhow!("Failed to read keypair {}: {}", expanded, e))
+}
+
+/// Load a keypair file, or generate a new keypair and save it there if the
+/// file does not exist yet. The flag is `true` for a new keypair.
+pub fn load_or_create_keypair(path: &str) -> Result<(Keypair, bool)> {
+    let expanded = expand(path);
+    if Path::new(&expanded).exists() {
+        return Ok((load_keypair(path)?, false));
+    }
+    let keypair = Keypair::new();
+    write_keypair_file(&keypair, &expanded).map_err(|e| anyhow!("Failed to write keypair {}: {}", expanded, e))?;
+    Ok((keypair, true))
+}
@@ -0,0 +1,23 @@
+pub mod bank;
+pub mod cache;
+pub mod client;
+pub mod config;
+pub mod distribute;
+pub mod engine;
+pub mod events;
+pub mod examples;
+pub mod gdx;
+pub mod indexer;
+pub mod insurance;
+pub mod keypair;
+pub mod liquidation;
+pub mod loadgen;
+pub mod migration;
+pub mod oracle;
+pub mod persist;
+pub mod program;
+pub mod pubsub;
+pub mod settlement;
+pub mod signing;
+pub mod upgrade;
+pub mod wallet;
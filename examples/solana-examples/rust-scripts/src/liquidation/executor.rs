@@ -0,0 +1,68 @@
+use anyhow::{Context, Result};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+};
+
+use super::{Candidate, Recommendation};
+use crate::client::sender::{send_with_retry, SendConfig};
+use crate::client::LedgerClient;
+use crate::gdx::{anchor_instruction, insurance_fund_address, vault_address};
+
+/// Program ids the liquidation instructions touch
+pub struct LiquidationPrograms {
+    pub liquidation: Pubkey,
+    pub vault: Pubkey,
+}
+
+/// Accounts shared by `liquidate_partial` and `liquidate_full`, in the order
+/// of the liquidation engine's `LiquidatePartial` / `LiquidateFull` contexts
+fn liquidation_accounts(
+    programs: &LiquidationPrograms,
+    position: &Pubkey,
+    owner: &Pubkey,
+    price_feed: &Pubkey,
+    liquidator: &Pubkey,
+) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(*position, false),
+        AccountMeta::new(vault_address(&programs.vault, owner), false),
+        AccountMeta::new(insurance_fund_address(&programs.liquidation), false),
+        AccountMeta::new_readonly(*price_feed, false),
+        AccountMeta::new(*liquidator, true),
+    ]
+}
+
+/// Build the instruction that carries out a candidate's recommendation
+pub fn build_instruction(
+    programs: &LiquidationPrograms,
+    candidate: &Candidate,
+    price_feed: &Pubkey,
+    liquidator: &Pubkey,
+) -> Instruction {
+    let marked = &candidate.marked;
+    let accounts = liquidation_accounts(programs, &marked.address, &marked.position.owner, price_feed, liquidator);
+    match candidate.recommendation {
+        Recommendation::Partial { amount } => {
+            anchor_instruction(&programs.liquidation, "liquidate_partial", &amount.to_le_bytes(), accounts)
+        }
+        Recommendation::Full { .. } => anchor_instruction(&programs.liquidation, "liquidate_full", &[], accounts),
+    }
+}
+
+/// Submit one liquidation, rebroadcasting it until it is confirmed; the
+/// error carries the attempt log
+pub async fn execute(
+    client: &impl LedgerClient,
+    programs: &LiquidationPrograms,
+    candidate: &Candidate,
+    price_feed: &Pubkey,
+    liquidator: &Keypair,
+) -> Result<Signature> {
+    let instruction = build_instruction(programs, candidate, price_feed, &liquidator.pubkey());
+    send_with_retry(client, &[instruction], liquidator, &[], &SendConfig::default())
+        .await
+        .into_result()
+        .with_context(|| format!("Liquidation of {} failed", candidate.marked.address))
+}
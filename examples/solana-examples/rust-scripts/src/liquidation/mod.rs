@@ -0,0 +1,238 @@
+//! Liquidation scanner
+//!
+//! Marks every open position to the latest oracle price, computes its margin
+//! ratio and queues the unhealthy ones by urgency. The math follows the
+//! liquidation engine assignment:
+//!
+//! - Margin Ratio = (Margin + Unrealized PnL) / Position Value
+//! - Liquidatable when Margin Ratio < Maintenance Margin Ratio (by leverage tier)
+//! - Partial liquidation closes 50% of size; full liquidation if that is not enough
+
+pub mod executor;
+pub mod scanner;
+
+use solana_sdk::pubkey::Pubkey;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet};
+
+use crate::gdx::accounts::{CollateralVault, Position, Side};
+use crate::gdx::BASE_DECIMALS;
+
+/// Liquidator reward, in basis points of liquidated notional
+pub const LIQUIDATION_FEE_BPS: i128 = 250;
+/// Share of size closed by a partial liquidation, in basis points
+pub const PARTIAL_LIQUIDATION_BPS: u64 = 5_000;
+
+/// Maintenance margin ratio in basis points for a leverage, per leverage tier
+pub fn maintenance_margin_bps(leverage: u16) -> i128 {
+    match leverage {
+        1..=20 => 250,
+        21..=50 => 100,
+        51..=100 => 50,
+        101..=500 => 25,
+        501..=1000 => 10,
+        _ => 250,
+    }
+}
+
+/// Notional value in quote units of `size` base units at `price`
+pub fn notional(size: u64, price: u64) -> i128 {
+    (size as i128).saturating_mul(price as i128) / 10i128.pow(BASE_DECIMALS)
+}
+
+/// Unrealized PnL in quote units at `mark_price`
+pub fn unrealized_pnl(side: Side, size: u64, entry_price: u64, mark_price: u64) -> i128 {
+    let diff = mark_price as i128 - entry_price as i128;
+    let pnl = (size as i128).saturating_mul(diff) / 10i128.pow(BASE_DECIMALS);
+    match side {
+        Side::Long => pnl,
+        Side::Short => -pnl,
+    }
+}
+
+/// What the scanner recommends doing with a liquidatable position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recommendation {
+    /// `liquidate_partial(amount)` restores the position above maintenance
+    Partial { amount: u64 },
+    /// Close everything; `bad_debt` is what the insurance fund must absorb
+    Full { bad_debt: u64 },
+}
+
+impl std::fmt::Display for Recommendation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Recommendation::Partial { amount } => write!(f, "partial({})", amount),
+            Recommendation::Full { bad_debt: 0 } => write!(f, "full"),
+            Recommendation::Full { bad_debt } => write!(f, "full (bad debt {})", bad_debt),
+        }
+    }
+}
+
+/// A position marked to the current price
+#[derive(Debug, Clone)]
+pub struct MarkedPosition {
+    pub address: Pubkey,
+    pub position: Position,
+    pub vault: Option<(Pubkey, CollateralVault)>,
+    pub mark_price: u64,
+    pub notional: i128,
+    pub unrealized_pnl: i128,
+    /// Margin + unrealized PnL - accrued funding (+ free vault collateral in cross mode)
+    pub equity: i128,
+    pub margin_ratio_bps: i128,
+    pub maintenance_bps: i128,
+}
+
+impl MarkedPosition {
+    /// Mark `position` at `mark_price`. With `cross_margin`, the owner's free
+    /// vault balance also backs the position.
+    pub fn mark(
+        address: Pubkey,
+        position: Position,
+        vault: Option<(Pubkey, CollateralVault)>,
+        mark_price: u64,
+        cross_margin: bool,
+    ) -> Self {
+        let notional = notional(position.size, mark_price);
+        let unrealized_pnl = unrealized_pnl(position.side, position.size, position.entry_price, mark_price);
+        // Positive `funding_accrued` is funding the position owes
+        let mut equity = position.margin as i128 + unrealized_pnl - position.funding_accrued as i128;
+        if cross_margin {
+            if let Some((_, vault)) = &vault {
+                equity += vault.available_balance as i128;
+            }
+        }
+        let margin_ratio_bps = if notional > 0 {
+            equity.saturating_mul(10_000) / notional
+        } else {
+            i128::MAX
+        };
+
+        Self {
+            address,
+            maintenance_bps: maintenance_margin_bps(position.leverage as u16),
+            position,
+            vault,
+            mark_price,
+            notional,
+            unrealized_pnl,
+            equity,
+            margin_ratio_bps,
+        }
+    }
+
+    pub fn is_liquidatable(&self) -> bool {
+        self.position.size > 0 && self.margin_ratio_bps < self.maintenance_bps
+    }
+
+    /// Partial if closing half the size (and paying the liquidator fee on it)
+    /// leaves the rest above maintenance, otherwise full.
+    pub fn recommendation(&self) -> Recommendation {
+        if self.equity <= 0 {
+            return Recommendation::Full {
+                bad_debt: saturating_u64(-self.equity),
+            };
+        }
+
+        // Below `size`, so it always fits back into a u64
+        let amount = (self.position.size as u128 * PARTIAL_LIQUIDATION_BPS as u128 / 10_000) as u64;
+        let closed_notional = notional(amount, self.mark_price);
+        let remaining_notional = self.notional - closed_notional;
+        let remaining_equity = self.equity - closed_notional * LIQUIDATION_FEE_BPS / 10_000;
+
+        if amount > 0
+            && remaining_notional > 0
+            && remaining_equity.saturating_mul(10_000) / remaining_notional >= self.maintenance_bps
+        {
+            Recommendation::Partial { amount }
+        } else {
+            let fee = self.notional * LIQUIDATION_FEE_BPS / 10_000;
+            Recommendation::Full {
+                bad_debt: saturating_u64(fee - self.equity),
+            }
+        }
+    }
+}
+
+/// `value` clamped to `0..=u64::MAX`; a bad debt too large for a token
+/// amount is reported as the largest one rather than wrapped
+fn saturating_u64(value: i128) -> u64 {
+    u64::try_from(value.max(0)).unwrap_or(u64::MAX)
+}
+
+/// A queued liquidation; orders by urgency (lowest margin ratio first, then
+/// largest notional)
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub marked: MarkedPosition,
+    pub recommendation: Recommendation,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // BinaryHeap is a max-heap: the most urgent candidate must compare greatest
+        other
+            .marked
+            .margin_ratio_bps
+            .cmp(&self.marked.margin_ratio_bps)
+            .then(self.marked.notional.cmp(&other.marked.notional))
+            .then(other.marked.address.cmp(&self.marked.address))
+    }
+}
+
+/// Priority queue of liquidatable positions, deduplicated by position address
+#[derive(Default)]
+pub struct LiquidationQueue {
+    heap: BinaryHeap<Candidate>,
+    queued: HashSet<Pubkey>,
+}
+
+impl LiquidationQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue the position if it is liquidatable and not already queued
+    pub fn push(&mut self, marked: MarkedPosition) -> bool {
+        if !marked.is_liquidatable() || !self.queued.insert(marked.address) {
+            return false;
+        }
+        let recommendation = marked.recommendation();
+        self.heap.push(Candidate { marked, recommendation });
+        true
+    }
+
+    pub fn pop(&mut self) -> Option<Candidate> {
+        let candidate = self.heap.pop()?;
+        self.queued.remove(&candidate.marked.address);
+        Some(candidate)
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Candidates in priority order, leaving the queue untouched
+    pub fn sorted(&self) -> Vec<Candidate> {
+        self.heap.clone().into_sorted_vec().into_iter().rev().collect()
+    }
+}
@@ -0,0 +1,180 @@
+use anyhow::{anyhow, bail, Context, Result};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use super::{LiquidationQueue, MarkedPosition};
+use crate::client::{get_accounts, LedgerClient};
+use crate::gdx::accounts::{CollateralVault, Position};
+use crate::gdx::{vault_address, AnchorAccount, Undecodable, PRICE_DECIMALS};
+use crate::oracle::{self, validate_consensus, OracleConfig};
+use crate::persist::now;
+
+/// Where the scanner gets mark prices from, per symbol
+#[derive(Debug, Clone, Default)]
+pub struct PriceInputs {
+    /// Pyth (or mock oracle) price account per symbol
+    pub feeds: HashMap<String, Pubkey>,
+    /// Fixed mark prices (`PRICE_DECIMALS` fixed point), taking precedence over feeds
+    pub overrides: HashMap<String, u64>,
+}
+
+impl PriceInputs {
+    /// Parse `SYMBOL=PRICE_ACCOUNT` feeds and `SYMBOL=PRICE` overrides
+    pub fn parse(feeds: &[String], overrides: &[String]) -> Result<Self> {
+        let mut inputs = PriceInputs::default();
+        for entry in feeds {
+            let (symbol, address) = split_pair(entry)?;
+            let pubkey = Pubkey::from_str(address)
+                .with_context(|| format!("Invalid price account for {}", symbol))?;
+            inputs.feeds.insert(symbol.to_string(), pubkey);
+        }
+        for entry in overrides {
+            let (symbol, price) = split_pair(entry)?;
+            let price: f64 = price
+                .parse()
+                .with_context(|| format!("Invalid mark price for {}", symbol))?;
+            let fixed = (price * 10f64.powi(PRICE_DECIMALS as i32)).round();
+            if !(fixed > 0.0 && fixed < u64::MAX as f64) {
+                bail!("Mark price for {} must be positive and finite, got {}", symbol, price);
+            }
+            let fixed = fixed as u64;
+            inputs.overrides.insert(symbol.to_string(), fixed);
+        }
+        Ok(inputs)
+    }
+}
+
+fn split_pair(entry: &str) -> Result<(&str, &str)> {
+    match entry.split_once('=') {
+        Some((key, value)) if !key.is_empty() && !value.is_empty() => Ok((key, value)),
+        _ => bail!("Expected SYMBOL=VALUE, got {:?}", entry),
+    }
+}
+
+/// Resolve a mark price for every symbol in `symbols`
+///
+/// Oracle prices must pass the staleness and confidence checks; a symbol
+/// whose feed fails them is left out rather than marked at a bad price.
+/// All feeds are read in one batched call.
+pub async fn load_mark_prices(
+    client: &impl LedgerClient,
+    inputs: &PriceInputs,
+    symbols: &[String],
+    config: &OracleConfig,
+) -> HashMap<String, u64> {
+    let now = now();
+
+    let mut prices = HashMap::new();
+    let mut feeds = Vec::new();
+    for symbol in symbols {
+        if let Some(price) = inputs.overrides.get(symbol) {
+            prices.insert(symbol.clone(), *price);
+        } else if let Some(feed) = inputs.feeds.get(symbol) {
+            feeds.push((symbol, *feed));
+        }
+    }
+
+    let addresses: Vec<Pubkey> = feeds.iter().map(|(_, feed)| *feed).collect();
+    let accounts = match get_accounts(client, &addresses).await {
+        Ok(accounts) => accounts,
+        Err(e) => {
+            eprintln!("   ⚠️  Failed to fetch price feeds: {:#}", e);
+            return prices;
+        }
+    };
+    for ((symbol, feed), account) in feeds.into_iter().zip(accounts) {
+        let price = match account {
+            Some(account) => oracle::pyth::PythPriceAccount::decode(&account.data).and_then(|p| p.to_price_data()),
+            None => Err(anyhow!("Pyth account {} not found", feed)),
+        };
+        let price = match price {
+            Ok(price) => price,
+            Err(e) => {
+                eprintln!("   ⚠️  {}: {:#}", symbol, e);
+                continue;
+            }
+        };
+        let report = validate_consensus(std::slice::from_ref(&price), now, config);
+        if !report.is_valid() {
+            for violation in &report.violations {
+                eprintln!("   ⚠️  {}: {}", symbol, violation);
+            }
+            continue;
+        }
+        if let Some(fixed) = price.to_fixed(PRICE_DECIMALS) {
+            prices.insert(symbol.clone(), fixed);
+        }
+    }
+    prices
+}
+
+/// Result of one scan over all positions
+pub struct ScanResult {
+    pub positions: Vec<MarkedPosition>,
+    pub queue: LiquidationQueue,
+    /// Symbols with open positions but no usable mark price
+    pub unpriced: Vec<String>,
+    /// Position and vault accounts that failed to decode and were left out
+    pub undecodable: Undecodable,
+}
+
+/// Load every open position and collateral vault, mark them and build the queue
+pub async fn scan(
+    client: &impl LedgerClient,
+    position_program: &Pubkey,
+    vault_program: Option<&Pubkey>,
+    inputs: &PriceInputs,
+    config: &OracleConfig,
+    cross_margin: bool,
+) -> Result<ScanResult> {
+    let (positions, mut undecodable) = Position::fetch_all(client, position_program).await?;
+
+    // Only the vaults of position owners matter; they are PDAs of the owner
+    let mut vaults: HashMap<Pubkey, (Pubkey, CollateralVault)> = HashMap::new();
+    if let Some(vault_program) = vault_program {
+        let mut owners: Vec<Pubkey> = positions.iter().map(|(_, p)| p.owner).collect();
+        owners.sort();
+        owners.dedup();
+        let addresses: Vec<Pubkey> = owners.iter().map(|owner| vault_address(vault_program, owner)).collect();
+        let (found, failed) = CollateralVault::fetch_many(client, &addresses).await?;
+        undecodable.extend(failed);
+        for (address, vault) in addresses.into_iter().zip(found) {
+            if let Some(vault) = vault {
+                vaults.insert(vault.owner, (address, vault));
+            }
+        }
+    }
+
+    let mut symbols: Vec<String> = positions
+        .iter()
+        .filter(|(_, p)| p.size > 0)
+        .map(|(_, p)| p.symbol.clone())
+        .collect();
+    symbols.sort();
+    symbols.dedup();
+    let prices = load_mark_prices(client, inputs, &symbols, config).await;
+    let unpriced = symbols.into_iter().filter(|s| !prices.contains_key(s)).collect();
+
+    let mut marked = Vec::new();
+    let mut queue = LiquidationQueue::new();
+    for (address, position) in positions {
+        if position.size == 0 {
+            continue;
+        }
+        let Some(&mark_price) = prices.get(&position.symbol) else {
+            continue;
+        };
+        let vault = vaults.get(&position.owner).cloned();
+        let position = MarkedPosition::mark(address, position, vault, mark_price, cross_margin);
+        queue.push(position.clone());
+        marked.push(position);
+    }
+
+    Ok(ScanResult {
+        positions: marked,
+        queue,
+        unpriced,
+        undecodable,
+    })
+}
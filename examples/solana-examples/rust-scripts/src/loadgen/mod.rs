@@ -0,0 +1,120 @@
+//! Synthetic order flow for load testing the matching engine
+//!
+//! Orders arrive as a Poisson process (exponential gaps at `rate` per
+//! second). Each order picks a market and user uniformly, is a maker with
+//! probability `maker_ratio` (pegged to its side of the book) and a taker
+//! otherwise (market order), and is dark with probability `dark_ratio`.
+
+pub mod report;
+
+use anyhow::{bail, Context, Result};
+use rand::{rngs::StdRng, Rng};
+use rand_distr::{Distribution, Exp, LogNormal};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::engine::{OrderRequest, OrderSide, OrderType, Visibility};
+
+/// Order size distribution, in base asset units
+#[derive(Debug, Clone)]
+pub enum SizeDistribution {
+    Fixed(f64),
+    Uniform { min: f64, max: f64 },
+    /// Log-normal with the given mean and shape; heavy right tail like real flow
+    LogNormal { mean: f64, sigma: f64 },
+}
+
+impl SizeDistribution {
+    pub fn sample(&self, rng: &mut StdRng) -> f64 {
+        match *self {
+            SizeDistribution::Fixed(size) => size,
+            SizeDistribution::Uniform { min, max } => rng.gen_range(min..=max),
+            SizeDistribution::LogNormal { mean, sigma } => {
+                // E[X] = exp(mu + sigma^2 / 2)
+                let mu = mean.ln() - sigma * sigma / 2.0;
+                LogNormal::new(mu, sigma).map(|d| d.sample(rng)).unwrap_or(mean)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FlowConfig {
+    /// Mean arrival rate, orders per second
+    pub rate: f64,
+    pub markets: Vec<String>,
+    pub users: Vec<String>,
+    /// Share of orders that add liquidity (0.0 - 1.0)
+    pub maker_ratio: f64,
+    /// Share of orders sent dark (0.0 - 1.0)
+    pub dark_ratio: f64,
+    pub size: SizeDistribution,
+    pub leverage: u16,
+}
+
+/// Generates orders and their arrival gaps
+pub struct OrderFlow {
+    config: FlowConfig,
+    arrivals: Exp<f64>,
+}
+
+impl OrderFlow {
+    pub fn new(config: FlowConfig) -> Result<Self> {
+        if config.markets.is_empty() {
+            bail!("No markets to trade");
+        }
+        if config.users.is_empty() {
+            bail!("No users to trade as");
+        }
+        if !(0.0..=1.0).contains(&config.maker_ratio) || !(0.0..=1.0).contains(&config.dark_ratio) {
+            bail!("Maker and dark ratios must be between 0 and 1");
+        }
+        if !(config.rate.is_finite() && config.rate > 0.0) {
+            bail!("Rate must be positive");
+        }
+        let arrivals = Exp::new(config.rate).context("Rate must be positive")?;
+        Ok(Self { config, arrivals })
+    }
+
+    pub fn config(&self) -> &FlowConfig {
+        &self.config
+    }
+
+    /// Time until the next order
+    pub fn next_gap(&self, rng: &mut StdRng) -> Duration {
+        Duration::from_secs_f64(self.arrivals.sample(rng))
+    }
+
+    pub fn next_order(&self, rng: &mut StdRng) -> OrderRequest {
+        let config = &self.config;
+        let side = if rng.gen_bool(0.5) { OrderSide::Buy } else { OrderSide::Sell };
+        let order_type = match (rng.gen_bool(config.maker_ratio), side) {
+            (false, _) => OrderType::Market,
+            (true, OrderSide::Buy) => OrderType::PegBid,
+            (true, OrderSide::Sell) => OrderType::PegAsk,
+        };
+        let visibility = if rng.gen_bool(config.dark_ratio) {
+            Visibility::Dark
+        } else {
+            Visibility::Lit
+        };
+
+        OrderRequest {
+            user_id: config.users[rng.gen_range(0..config.users.len())].clone(),
+            symbol: config.markets[rng.gen_range(0..config.markets.len())].clone(),
+            side,
+            order_type,
+            quantity: config.size.sample(rng),
+            price: None,
+            leverage: config.leverage,
+            visibility: Some(visibility),
+            trading_view_price: None,
+        }
+    }
+}
+
+/// Market symbols from a validated global config file
+pub fn load_markets(path: &Path) -> Result<Vec<String>> {
+    let config = crate::config::commands::load(path)?;
+    Ok(config.markets.into_iter().map(|m| m.symbol).collect())
+}
@@ -0,0 +1,142 @@
+use anyhow::{Context, Result};
+use hdrhistogram::Histogram;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::time::Duration;
+
+/// Latency targets in milliseconds (`section-8-performance-requirements.md`)
+#[derive(Debug, Clone, Copy)]
+pub struct LatencyTarget {
+    pub p50: Option<f64>,
+    pub p95: Option<f64>,
+    pub p99: Option<f64>,
+}
+
+/// `matchingLatency`: 5 / 15 / 50 ms
+pub const MATCHING_TARGET: LatencyTarget = LatencyTarget {
+    p50: Some(5.0),
+    p95: Some(15.0),
+    p99: Some(50.0),
+};
+
+/// `settlementLatency`: 1 s batch window, p95 1.5 s, p99 2 s
+pub const SETTLEMENT_TARGET: LatencyTarget = LatencyTarget {
+    p50: Some(1000.0),
+    p95: Some(1500.0),
+    p99: Some(2000.0),
+};
+
+/// Sustained `ordersPerSecond` target
+pub const ORDERS_PER_SECOND_TARGET: f64 = 2000.0;
+
+/// Latency histogram recorded in microseconds, 1 µs to 1 hour at 3
+/// significant digits
+pub struct LatencyHistogram {
+    /// Kebab-case, used as the report file name
+    pub name: &'static str,
+    histogram: Histogram<u64>,
+}
+
+impl LatencyHistogram {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            histogram: Histogram::new_with_bounds(1, 3_600_000_000, 3).expect("valid histogram bounds"),
+        }
+    }
+
+    pub fn record(&mut self, latency: Duration) {
+        let micros = (latency.as_micros() as u64).clamp(1, self.histogram.high());
+        self.histogram.saturating_record(micros);
+    }
+
+    pub fn len(&self) -> u64 {
+        self.histogram.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.histogram.is_empty()
+    }
+
+    /// Value at `percentile` (0-100) in milliseconds
+    pub fn percentile_ms(&self, percentile: f64) -> f64 {
+        self.histogram.value_at_percentile(percentile) as f64 / 1000.0
+    }
+
+    pub fn summary(&self) -> String {
+        if self.is_empty() {
+            return format!("{:<22} no samples", self.name);
+        }
+        format!(
+            "{:<22} n={:<8} p50 {:>9.2}  p95 {:>9.2}  p99 {:>9.2}  p99.9 {:>9.2}  max {:>9.2} ms",
+            self.name,
+            self.len(),
+            self.percentile_ms(50.0),
+            self.percentile_ms(95.0),
+            self.percentile_ms(99.0),
+            self.percentile_ms(99.9),
+            self.histogram.max() as f64 / 1000.0
+        )
+    }
+
+    /// Percentiles that miss `target`, as `"p95 23.10 > 15"` strings
+    pub fn misses(&self, target: &LatencyTarget) -> Vec<String> {
+        [(50.0, target.p50), (95.0, target.p95), (99.0, target.p99)]
+            .into_iter()
+            .filter_map(|(percentile, limit)| {
+                let limit = limit?;
+                let value = self.percentile_ms(percentile);
+                (value > limit).then(|| format!("p{} {:.2} > {}", percentile, value, limit))
+            })
+            .collect()
+    }
+
+    /// Percentile distribution in HdrHistogram's `.hgrm` text format
+    /// (values in milliseconds), loadable by the HdrHistogram plotter
+    pub fn to_hgrm(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{:>12} {:>14} {:>10} {:>14}\n",
+            "Value", "Percentile", "TotalCount", "1/(1-Percentile)"
+        );
+        let mut total = 0u64;
+        for step in self.histogram.iter_quantiles(5) {
+            total += step.count_since_last_iteration();
+            let quantile = step.quantile_iterated_to();
+            let inverse = if quantile < 1.0 {
+                format!("{:>14.2}", 1.0 / (1.0 - quantile))
+            } else {
+                String::new()
+            };
+            let _ = writeln!(
+                out,
+                "{:>12.3} {:>14.12} {:>10} {}",
+                step.value_iterated_to() as f64 / 1000.0,
+                quantile,
+                total,
+                inverse
+            );
+        }
+        let _ = writeln!(
+            out,
+            "#[Mean    = {:>12.3}, StdDeviation   = {:>12.3}]",
+            self.histogram.mean() / 1000.0,
+            self.histogram.stdev() / 1000.0
+        );
+        let _ = writeln!(
+            out,
+            "#[Max     = {:>12.3}, Total count    = {:>12}]",
+            self.histogram.max() as f64 / 1000.0,
+            self.histogram.len()
+        );
+        out
+    }
+
+    /// Write `<dir>/<name>.hgrm`
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        let path = dir.join(format!("{}.hgrm", self.name));
+        std::fs::write(&path, self.to_hgrm()).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
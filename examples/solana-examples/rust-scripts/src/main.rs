@@ -1,8 +1,7 @@
 use clap::{Parser, Subcommand};
+use solana_examples_scripts::{config, distribute, engine, events, examples, insurance, migration, oracle, program, pubsub, settlement, signing, upgrade, wallet};
 use std::process;
 
-mod examples;
-
 #[derive(Parser)]
 #[command(name = "solana-examples")]
 #[command(about = "Solana learning examples for localnet", long_about = None)]
@@ -46,6 +45,71 @@ enum Commands {
     PdaBasics,
     /// Demonstrate SPL Token operations
     TokenBasics,
+    /// Create a mint, mint tokens and transfer them on localnet
+    TokenOperations,
+    /// Read and validate Pyth / Switchboard oracle prices
+    Oracle {
+        #[command(subcommand)]
+        command: oracle::commands::OracleCommand,
+    },
+    /// Inspect and stress-test the liquidation insurance fund
+    Insurance {
+        #[command(subcommand)]
+        command: insurance::commands::InsuranceCommand,
+    },
+    /// Net engine fills into settlement batches and benchmark throughput
+    Settlement {
+        #[command(subcommand)]
+        command: settlement::commands::SettlementCommand,
+    },
+    /// Talk to the mock matching engine (REST + WebSocket feed)
+    Engine {
+        #[command(subcommand)]
+        command: engine::commands::EngineCommand,
+    },
+    /// Validate, show and diff the global market/shard configuration
+    Config {
+        #[command(subcommand)]
+        command: config::commands::ConfigCommand,
+    },
+    /// Decode Anchor events from program logs, live or per transaction
+    Events {
+        #[command(subcommand)]
+        command: events::commands::EventsCommand,
+    },
+    /// Stream account, program and log changes from the validator WebSocket
+    Watch {
+        #[command(subcommand)]
+        command: pubsub::commands::WatchCommand,
+    },
+    /// Deploy, upgrade and inspect programs of the upgradeable loader
+    Program {
+        #[command(subcommand)]
+        command: program::commands::ProgramCommand,
+    },
+    /// Multisig upgrade proposals: propose, approve, execute after the timelock
+    Upgrade {
+        #[command(subcommand)]
+        command: upgrade::commands::UpgradeCommand,
+    },
+    /// Scan account layouts and migrate outdated accounts with checkpoints
+    Migrate {
+        #[command(subcommand)]
+        command: migration::commands::MigrateCommand,
+    },
+    /// Pay SOL and tokens to every recipient in a CSV, resumably
+    Distribute(distribute::commands::DistributeArgs),
+    /// Seed phrases and deterministic test wallets (m/44'/501'/i'/0')
+    Wallet {
+        #[command(subcommand)]
+        command: wallet::commands::WalletCommand,
+    },
+    /// Grind vanity keypairs whose address starts or ends with a pattern
+    Grind(wallet::commands::GrindArgs),
+    /// Sign a message off-chain to prove ownership of a keypair
+    SignMessage(signing::commands::SignMessageArgs),
+    /// Check an off-chain message signature
+    VerifyMessage(signing::commands::VerifyMessageArgs),
 }
 
 #[tokio::main]
@@ -62,6 +126,22 @@ async fn main() {
         }
         Commands::PdaBasics => examples::pda_basics::run().await,
         Commands::TokenBasics => examples::token_basics::run().await,
+        Commands::TokenOperations => examples::token_operations::run().await,
+        Commands::Oracle { command } => oracle::commands::run(command).await,
+        Commands::Insurance { command } => insurance::commands::run(command).await,
+        Commands::Settlement { command } => settlement::commands::run(command).await,
+        Commands::Engine { command } => engine::commands::run(command).await,
+        Commands::Config { command } => config::commands::run(command).await,
+        Commands::Events { command } => events::commands::run(command).await,
+        Commands::Watch { command } => pubsub::commands::run(command).await,
+        Commands::Program { command } => program::commands::run(command).await,
+        Commands::Upgrade { command } => upgrade::commands::run(command).await,
+        Commands::Migrate { command } => migration::commands::run(command).await,
+        Commands::Distribute(args) => distribute::commands::run(args).await,
+        Commands::Wallet { command } => wallet::commands::run(command).await,
+        Commands::Grind(args) => wallet::commands::run_grind(args).await,
+        Commands::SignMessage(args) => signing::commands::run_sign(args).await,
+        Commands::VerifyMessage(args) => signing::commands::run_verify(args).await,
     };
 
     if let Err(e) = result {
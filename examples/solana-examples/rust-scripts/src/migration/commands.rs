@@ -0,0 +1,296 @@
+use anyhow::{bail, Context, Result};
+use clap::Subcommand;
+use futures_util::{stream, StreamExt};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signer};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use super::{
+    group, max_per_tx, migrate_instruction, scan, AccountNames, Checkpoint, Layout, MigrateAccount, Target,
+    DEFAULT_MIGRATE_ACCOUNTS,
+};
+use crate::keypair::{load_keypair, DEFAULT_KEYPAIR_PATH};
+use crate::client::{send, until_ctrl_c};
+
+#[derive(Subcommand)]
+pub enum MigrateCommand {
+    /// Count a program's accounts per layout (discriminator, length, version)
+    Scan {
+        program_id: String,
+        /// Only accounts of this type: name (e.g. Position) or discriminator in hex
+        #[arg(long)]
+        account: Option<String>,
+        /// Byte offset of the u32 version field (AccountVersion), counting the discriminator
+        #[arg(long)]
+        version_offset: Option<usize>,
+        /// Current version; marks the layouts that need migrating
+        #[arg(long)]
+        target_version: Option<u32>,
+        /// Current data length; marks the layouts that need migrating
+        #[arg(long)]
+        target_len: Option<usize>,
+        /// Extra account struct names to recognize (repeatable)
+        #[arg(long = "name")]
+        names: Vec<String>,
+        /// RPC endpoint
+        #[arg(long, default_value = "http://127.0.0.1:8899")]
+        url: String,
+    },
+    /// Send migrate_account for every outdated account, resuming from the checkpoint
+    Run {
+        program_id: String,
+        /// Account type to migrate: name (e.g. Position) or discriminator in hex
+        #[arg(long)]
+        account: String,
+        /// Byte offset of the u32 version field (AccountVersion), counting the discriminator
+        #[arg(long)]
+        version_offset: Option<usize>,
+        /// Accounts below this version (or without one) are migrated; needs --version-offset
+        #[arg(long, requires = "version_offset")]
+        target_version: Option<u32>,
+        /// Accounts of any other data length are migrated
+        #[arg(long, required_unless_present = "target_version")]
+        target_len: Option<usize>,
+        /// migrate_account instructions per transaction (default: as many as fit)
+        #[arg(long)]
+        per_tx: Option<usize>,
+        /// Accounts of each migrate_account instruction, in order: migrated, authority, <PUBKEY> or
+        /// <PUBKEY>:w. The assignment defines none; the default (migrated,authority,system program) is a guess
+        #[arg(long, value_delimiter = ',')]
+        accounts: Vec<MigrateAccount>,
+        /// Transactions in flight per wave; the checkpoint is saved after each wave
+        #[arg(long, default_value = "8")]
+        concurrency: usize,
+        /// Checkpoint file (default: migrations/<PROGRAM_ID>-<ACCOUNT>.json)
+        #[arg(long)]
+        checkpoint: Option<PathBuf>,
+        /// Only print the plan
+        #[arg(long)]
+        dry_run: bool,
+        /// Extra account struct names to recognize (repeatable)
+        #[arg(long = "name")]
+        names: Vec<String>,
+        /// Migration authority keypair, signs and pays
+        #[arg(long, default_value = DEFAULT_KEYPAIR_PATH)]
+        keypair: String,
+        /// RPC endpoint
+        #[arg(long, default_value = "http://127.0.0.1:8899")]
+        url: String,
+    },
+}
+
+pub async fn run(command: MigrateCommand) -> Result<()> {
+    match command {
+        MigrateCommand::Scan {
+            program_id,
+            account,
+            version_offset,
+            target_version,
+            target_len,
+            names,
+            url,
+        } => {
+            let program_id = Pubkey::from_str(&program_id).context("Invalid program id")?;
+            let names = AccountNames::new(&names);
+            let disc = account.as_deref().map(|a| names.discriminator(a)).transpose()?;
+            let target = (target_version.is_some() || target_len.is_some()).then_some(Target {
+                version: target_version,
+                len: target_len,
+            });
+
+            let client = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+            let accounts = until_ctrl_c(scan(&client, &program_id, disc)).await?;
+            let groups = group(&accounts, version_offset);
+            println!("🔍 {} account(s) of {} in {} layout(s)", accounts.len(), program_id, groups.len());
+            let mut outdated = 0;
+            for (layout, count) in &groups {
+                let mark = match target {
+                    Some(target) if target.is_outdated(layout) => {
+                        outdated += count;
+                        "⏳"
+                    }
+                    Some(_) => "✅",
+                    None => "  ",
+                };
+                println!("   {} {}", mark, describe(layout, *count, &names));
+            }
+            if target.is_some() {
+                println!("\n📊 {} account(s) need migrating", outdated);
+            }
+            Ok(())
+        }
+        MigrateCommand::Run {
+            program_id,
+            account,
+            version_offset,
+            target_version,
+            target_len,
+            per_tx,
+            accounts: metas,
+            concurrency,
+            checkpoint,
+            dry_run,
+            names,
+            keypair,
+            url,
+        } => {
+            let program_id = Pubkey::from_str(&program_id).context("Invalid program id")?;
+            let names = AccountNames::new(&names);
+            let disc = names.discriminator(&account)?;
+            let target = Target {
+                version: target_version,
+                len: target_len,
+            };
+            let metas = if metas.is_empty() {
+                DEFAULT_MIGRATE_ACCOUNTS.to_vec()
+            } else {
+                metas
+            };
+            if !metas.contains(&MigrateAccount::Migrated) {
+                bail!("--accounts must include the migrated account");
+            }
+            let authority = load_keypair(&keypair)?;
+            let path = checkpoint.unwrap_or_else(|| Checkpoint::path("migrations".as_ref(), &program_id, &account));
+            let mut progress = match Checkpoint::resume(&path, &program_id, &account, target)? {
+                Some(progress) => {
+                    println!(
+                        "♻️  Resuming from {}: {} done, {} failed before",
+                        path.display(),
+                        progress.completed.len(),
+                        progress.failed.len()
+                    );
+                    progress
+                }
+                None => Checkpoint::new(&program_id, &account, target),
+            };
+
+            let client = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+            let accounts = until_ctrl_c(scan(&client, &program_id, Some(disc))).await?;
+            let mut skipped = 0;
+            let mut pending = Vec::new();
+            for (address, data) in &accounts {
+                if !target.is_outdated(&Layout::detect(data, version_offset)) {
+                    continue;
+                }
+                if progress.completed.contains_key(&address.to_string()) {
+                    // Done per the checkpoint but still reads as old: the
+                    // program did not migrate it, and resending would not help
+                    skipped += 1;
+                } else {
+                    pending.push(*address);
+                }
+            }
+
+            let fit = max_per_tx(&program_id, &authority.pubkey(), &metas);
+            let per_tx = per_tx.unwrap_or(fit);
+            if per_tx == 0 || per_tx > fit {
+                bail!("--per-tx must be between 1 and {}", fit);
+            }
+            let txs: Vec<&[Pubkey]> = pending.chunks(per_tx).collect();
+            println!(
+                "📋 {} {} account(s) scanned, {} to migrate in {} transaction(s) of up to {}",
+                accounts.len(),
+                names.name(&disc),
+                pending.len(),
+                txs.len(),
+                per_tx
+            );
+            if skipped > 0 {
+                eprintln!(
+                    "⚠️  {} account(s) are marked done in the checkpoint but still have an old layout; not retouched",
+                    skipped
+                );
+            }
+            if dry_run || txs.is_empty() {
+                return Ok(());
+            }
+
+            let concurrency = concurrency.max(1);
+            let mut interrupted = false;
+            let mut sent = 0;
+            for wave in txs.chunks(concurrency) {
+                let mut results = stream::iter(wave.iter().copied())
+                    .map(|batch| {
+                        let instructions: Vec<_> = batch
+                            .iter()
+                            .map(|address| migrate_instruction(&program_id, address, &authority.pubkey(), &metas))
+                            .collect();
+                        let client = &client;
+                        let authority = &authority;
+                        async move { (batch, send(client, &instructions, authority, &[]).await) }
+                    })
+                    .buffer_unordered(concurrency);
+                loop {
+                    tokio::select! {
+                        next = results.next() => {
+                            let Some((batch, result)) = next else { break };
+                            for address in batch {
+                                let address = address.to_string();
+                                match &result {
+                                    Ok(signature) => {
+                                        progress.failed.remove(&address);
+                                        progress.completed.insert(address, signature.to_string());
+                                    }
+                                    Err(e) => {
+                                        progress.failed.insert(address, e.to_string());
+                                    }
+                                }
+                            }
+                            if let Err(e) = &result {
+                                eprintln!("⚠️  Batch of {} failed: {}", batch.len(), e);
+                            }
+                        }
+                        _ = tokio::signal::ctrl_c(), if !interrupted => {
+                            println!("\n🛑 Stopping after this wave...");
+                            interrupted = true;
+                        }
+                    }
+                }
+                progress.save(&path)?;
+                sent += wave.len();
+                println!(
+                    "   {}/{} transaction(s), {} account(s) migrated in total",
+                    sent,
+                    txs.len(),
+                    progress.completed.len()
+                );
+                if interrupted {
+                    break;
+                }
+            }
+
+            let done = pending
+                .iter()
+                .filter(|a| progress.completed.contains_key(&a.to_string()))
+                .count();
+            let failed = pending
+                .iter()
+                .filter(|a| progress.failed.contains_key(&a.to_string()))
+                .count();
+            println!("💾 Checkpoint saved to {}", path.display());
+            if done < pending.len() {
+                bail!(
+                    "{} account(s) failed, {} not sent yet; rerun to resume",
+                    failed,
+                    pending.len() - done - failed
+                );
+            }
+            println!("✅ Migrated {} account(s)", pending.len());
+            Ok(())
+        }
+    }
+}
+
+fn describe(layout: &Layout, count: usize, names: &AccountNames) -> String {
+    let version = layout
+        .version
+        .map_or_else(|| "-".to_string(), |v| v.to_string());
+    format!(
+        "{:<24} len {:>6}  version {:>4}  {:>8} account(s)",
+        names.name(&layout.discriminator),
+        layout.len,
+        version,
+        count
+    )
+}
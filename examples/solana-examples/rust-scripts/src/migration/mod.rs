@@ -0,0 +1,294 @@
+//! Plan and run `migrate_account` over every account of a program
+//!
+//! After an upgrade changes an account layout, each existing account has to
+//! be passed through the program's `migrate_account` instruction once. A scan
+//! groups accounts by layout: Anchor discriminator, data length and, when the
+//! layout carries an `AccountVersion`, the `version` field read at a fixed
+//! offset. Accounts still on an old layout are packed several per
+//! transaction and sent in waves.
+//!
+//! Progress goes to a checkpoint file after every wave. A rerun skips the
+//! accounts the checkpoint lists as done and rescans the chain, so accounts
+//! already on the new layout are never touched twice, even if the last wave
+//! finished after the checkpoint was written.
+
+pub mod commands;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::{
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    message::Message,
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+    system_program,
+};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::client::LedgerClient;
+use crate::gdx::{anchor_instruction, discriminator};
+use crate::persist::{now, save_json};
+
+/// Account structs the scan recognizes by discriminator
+pub const KNOWN_ACCOUNTS: &[&str] = &["Position", "CollateralVault", "InsuranceFund", "AccountVersion"];
+
+/// What identifies a layout: discriminator, data length and version field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Layout {
+    pub discriminator: [u8; 8],
+    pub len: usize,
+    /// `None` when no version offset is given or the data is too short to hold it
+    pub version: Option<u32>,
+}
+
+impl Layout {
+    /// Detect the layout of account data; the version is a little-endian
+    /// `u32` at `version_offset`
+    pub fn detect(data: &[u8], version_offset: Option<usize>) -> Self {
+        let mut discriminator = [0u8; 8];
+        let head = data.len().min(8);
+        discriminator[..head].copy_from_slice(&data[..head]);
+        let version = version_offset
+            .and_then(|offset| data.get(offset..offset + 4))
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()));
+        Self {
+            discriminator,
+            len: data.len(),
+            version,
+        }
+    }
+}
+
+/// Resolve discriminators to account names
+#[derive(Debug, Clone)]
+pub struct AccountNames(Vec<(String, [u8; 8])>);
+
+impl AccountNames {
+    /// [`KNOWN_ACCOUNTS`] plus `extra`
+    pub fn new(extra: &[String]) -> Self {
+        let names = KNOWN_ACCOUNTS
+            .iter()
+            .map(|name| name.to_string())
+            .chain(extra.iter().cloned())
+            .map(|name| {
+                let disc = discriminator("account", &name);
+                (name, disc)
+            })
+            .collect();
+        Self(names)
+    }
+
+    pub fn name(&self, disc: &[u8; 8]) -> String {
+        self.0
+            .iter()
+            .find(|(_, d)| d == disc)
+            .map_or_else(|| hex(disc), |(name, _)| name.clone())
+    }
+
+    /// An account name, or a discriminator as 16 hex characters
+    pub fn discriminator(&self, name: &str) -> Result<[u8; 8]> {
+        if let Some((_, disc)) = self.0.iter().find(|(n, _)| n == name) {
+            return Ok(*disc);
+        }
+        if name.len() == 16 && name.chars().all(|c| c.is_ascii_hexdigit()) {
+            let mut disc = [0u8; 8];
+            for (i, byte) in disc.iter_mut().enumerate() {
+                *byte = u8::from_str_radix(&name[2 * i..2 * i + 2], 16)?;
+            }
+            return Ok(disc);
+        }
+        // Anchor hashes any struct name, so unknown names still work
+        Ok(discriminator("account", name))
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Every account of `program_id`, optionally only those with `disc`
+pub async fn scan(
+    client: &impl LedgerClient,
+    program_id: &Pubkey,
+    disc: Option<[u8; 8]>,
+) -> Result<Vec<(Pubkey, Vec<u8>)>> {
+    let filters: Vec<_> = disc
+        .map(|d| RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, d.to_vec())))
+        .into_iter()
+        .collect();
+    let accounts = client.get_program_accounts(program_id, &filters).await?;
+    Ok(accounts.into_iter().map(|(address, account)| (address, account.data)).collect())
+}
+
+/// Account count per layout
+pub fn group(accounts: &[(Pubkey, Vec<u8>)], version_offset: Option<usize>) -> BTreeMap<Layout, usize> {
+    let mut groups = BTreeMap::new();
+    for (_, data) in accounts {
+        *groups.entry(Layout::detect(data, version_offset)).or_default() += 1;
+    }
+    groups
+}
+
+/// Which layouts are out of date
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Target {
+    /// Accounts with a lower (or unreadable) version need migrating
+    pub version: Option<u32>,
+    /// Accounts of any other data length need migrating
+    pub len: Option<usize>,
+}
+
+impl Target {
+    pub fn is_outdated(&self, layout: &Layout) -> bool {
+        self.version
+            .is_some_and(|target| layout.version.is_none_or(|version| version < target))
+            || self.len.is_some_and(|len| layout.len != len)
+    }
+}
+
+/// One account of the `migrate_account` instruction
+///
+/// The assignment only gives the handler, `migrate_account(ctx, old_account:
+/// Pubkey)`, and defines no accounts context, so the account list is an
+/// assumption. [`DEFAULT_MIGRATE_ACCOUNTS`] guesses one; a program whose
+/// context differs passes its own with `--accounts`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrateAccount {
+    /// The account being migrated, writable
+    Migrated,
+    /// The migration authority, writable signer
+    Authority,
+    Readonly(Pubkey),
+    Writable(Pubkey),
+}
+
+/// Assumed accounts: the migrated account, the authority that signs and pays
+/// for any reallocation, and the system program
+pub const DEFAULT_MIGRATE_ACCOUNTS: &[MigrateAccount] = &[
+    MigrateAccount::Migrated,
+    MigrateAccount::Authority,
+    MigrateAccount::Readonly(system_program::ID),
+];
+
+impl FromStr for MigrateAccount {
+    type Err = anyhow::Error;
+
+    /// `migrated`, `authority`, `<PUBKEY>` or `<PUBKEY>:w`
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "migrated" => Ok(MigrateAccount::Migrated),
+            "authority" => Ok(MigrateAccount::Authority),
+            _ => {
+                let (key, writable) = s.strip_suffix(":w").map_or((s, false), |key| (key, true));
+                let key = Pubkey::from_str(key).with_context(|| {
+                    format!("Invalid account {} (expected migrated, authority, <PUBKEY> or <PUBKEY>:w)", s)
+                })?;
+                Ok(if writable {
+                    MigrateAccount::Writable(key)
+                } else {
+                    MigrateAccount::Readonly(key)
+                })
+            }
+        }
+    }
+}
+
+/// `migrate_account(old_account)` over `accounts`
+///
+/// The encoding is assumed too: an Anchor instruction, so the
+/// `global:migrate_account` discriminator followed by `old_account`, the
+/// migrated account's address.
+pub fn migrate_instruction(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    authority: &Pubkey,
+    accounts: &[MigrateAccount],
+) -> Instruction {
+    let metas = accounts
+        .iter()
+        .map(|meta| match meta {
+            MigrateAccount::Migrated => AccountMeta::new(*account, false),
+            MigrateAccount::Authority => AccountMeta::new(*authority, true),
+            MigrateAccount::Readonly(key) => AccountMeta::new_readonly(*key, false),
+            MigrateAccount::Writable(key) => AccountMeta::new(*key, false),
+        })
+        .collect();
+    anchor_instruction(program_id, "migrate_account", account.as_ref(), metas)
+}
+
+/// Most `migrate_account` instructions that fit one transaction
+pub fn max_per_tx(program_id: &Pubkey, authority: &Pubkey, accounts: &[MigrateAccount]) -> usize {
+    let mut instructions = Vec::new();
+    loop {
+        let account = Pubkey::new_unique();
+        instructions.push(migrate_instruction(program_id, &account, authority, accounts));
+        let message = Message::new_with_blockhash(&instructions, Some(authority), &Hash::default());
+        if 1 + 64 + message.serialize().len() > PACKET_DATA_SIZE {
+            return instructions.len() - 1;
+        }
+    }
+}
+
+/// Progress of one migration, saved after every wave
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub program: String,
+    /// Account name or discriminator (hex) being migrated
+    pub account: String,
+    pub target: Target,
+    pub started_at: i64,
+    pub updated_at: i64,
+    /// Migrated account → signature of the transaction that migrated it
+    pub completed: BTreeMap<String, String>,
+    /// Account → last error; retried on the next run
+    pub failed: BTreeMap<String, String>,
+}
+
+impl Checkpoint {
+    pub fn new(program: &Pubkey, account: &str, target: Target) -> Self {
+        let now = now();
+        Self {
+            program: program.to_string(),
+            account: account.to_string(),
+            target,
+            started_at: now,
+            updated_at: now,
+            completed: BTreeMap::new(),
+            failed: BTreeMap::new(),
+        }
+    }
+
+    /// Default location, `<dir>/<program>-<account>.json`
+    pub fn path(dir: &Path, program: &Pubkey, account: &str) -> PathBuf {
+        dir.join(format!("{}-{}.json", program, account))
+    }
+
+    /// Load a checkpoint if there is one; it must describe the same migration
+    pub fn resume(path: &Path, program: &Pubkey, account: &str, target: Target) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let raw = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let checkpoint: Self =
+            serde_json::from_str(&raw).with_context(|| format!("Invalid checkpoint {}", path.display()))?;
+        if checkpoint.program != program.to_string() || checkpoint.account != account || checkpoint.target != target {
+            bail!(
+                "{} belongs to another migration ({} {} {:?}); pass a different --checkpoint",
+                path.display(),
+                checkpoint.program,
+                checkpoint.account,
+                checkpoint.target
+            );
+        }
+        Ok(Some(checkpoint))
+    }
+
+    pub fn save(&mut self, path: &Path) -> Result<()> {
+        self.updated_at = now();
+        save_json(path, self)
+    }
+}
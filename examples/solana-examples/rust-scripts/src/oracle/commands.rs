@@ -0,0 +1,421 @@
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signer};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use super::feed::{self, RandomWalk};
+use super::mock::{self, MockPrice};
+use super::{fetch_pyth, fetch_switchboard, fixture, validate_consensus, OracleConfig, PriceData};
+use crate::keypair::{load_keypair, DEFAULT_KEYPAIR_PATH};
+use crate::persist::now;
+
+#[derive(Subcommand)]
+pub enum OracleCommand {
+    /// Read Pyth / Switchboard price accounts and validate them against each other
+    Read {
+        /// Pyth price account address (repeatable)
+        #[arg(long)]
+        pyth: Vec<String>,
+        /// Switchboard aggregator account address (repeatable)
+        #[arg(long)]
+        switchboard: Vec<String>,
+        /// Maximum price age in seconds
+        #[arg(long, default_value = "30")]
+        max_staleness: i64,
+        /// Maximum confidence interval in basis points of price
+        #[arg(long, default_value = "100")]
+        max_confidence: u64,
+        /// Maximum deviation from the consensus median in basis points
+        #[arg(long, default_value = "100")]
+        max_deviation: u64,
+        /// RPC endpoint
+        #[arg(long, default_value = "http://127.0.0.1:8899")]
+        url: String,
+    },
+    /// Save oracle accounts as JSON fixtures for `solana-test-validator --account`
+    SaveFixtures {
+        /// Account addresses to capture
+        #[arg(required = true)]
+        addresses: Vec<String>,
+        /// Output directory
+        #[arg(long, default_value = "fixtures/oracle")]
+        out: PathBuf,
+        /// RPC endpoint to capture from (e.g. https://api.devnet.solana.com)
+        #[arg(long, default_value = "http://127.0.0.1:8899")]
+        url: String,
+    },
+    /// Write one price to the localnet mock oracle
+    Push {
+        /// Market symbol, e.g. BTC-USDT
+        symbol: String,
+        /// Price in quote units, e.g. 65000.5
+        price: f64,
+        /// Confidence interval in quote units
+        #[arg(long, default_value = "0")]
+        conf: f64,
+        /// Price exponent
+        #[arg(long, default_value = "-8", allow_hyphen_values = true)]
+        expo: i32,
+        /// Publish time override (unix seconds); defaults to now
+        #[arg(long)]
+        timestamp: Option<i64>,
+        /// Authority keypair that owns the feed
+        #[arg(long, default_value = DEFAULT_KEYPAIR_PATH)]
+        keypair: String,
+        /// RPC endpoint
+        #[arg(long, default_value = "http://127.0.0.1:8899")]
+        url: String,
+    },
+    /// Continuously publish prices to the mock oracle from a scenario or random walk
+    Feed {
+        /// Market symbol, e.g. BTC-USDT
+        symbol: String,
+        /// Starting price
+        #[arg(long)]
+        start: f64,
+        /// JSON scenario file; without it a seeded random walk is published
+        #[arg(long)]
+        scenario: Option<PathBuf>,
+        /// Delay between updates in milliseconds
+        #[arg(long, default_value = "400")]
+        interval_ms: u64,
+        /// Maximum random-walk move per update, in basis points
+        #[arg(long, default_value = "10")]
+        volatility_bps: f64,
+        /// Random-walk seed
+        #[arg(long, default_value = "42")]
+        seed: u64,
+        /// Number of random-walk updates (0 = until Ctrl-C)
+        #[arg(long, default_value = "0")]
+        steps: u64,
+        /// Default confidence interval, in basis points of price
+        #[arg(long, default_value = "5")]
+        conf_bps: f64,
+        /// Price exponent
+        #[arg(long, default_value = "-8", allow_hyphen_values = true)]
+        expo: i32,
+        /// Authority keypair that owns the feed
+        #[arg(long, default_value = DEFAULT_KEYPAIR_PATH)]
+        keypair: String,
+        /// RPC endpoint
+        #[arg(long, default_value = "http://127.0.0.1:8899")]
+        url: String,
+    },
+    /// Generate a mock oracle account fixture for `solana-test-validator --account`
+    Fixture {
+        /// Market symbol, e.g. BTC-USDT
+        symbol: String,
+        /// Price in quote units
+        price: f64,
+        /// Confidence interval in quote units
+        #[arg(long, default_value = "0")]
+        conf: f64,
+        /// Price exponent
+        #[arg(long, default_value = "-8", allow_hyphen_values = true)]
+        expo: i32,
+        /// Publish time (unix seconds); defaults to now
+        #[arg(long)]
+        timestamp: Option<i64>,
+        /// Authority allowed to push later updates
+        #[arg(long, default_value = DEFAULT_KEYPAIR_PATH)]
+        keypair: String,
+        /// Output directory
+        #[arg(long, default_value = "fixtures/oracle")]
+        out: PathBuf,
+    },
+}
+
+pub async fn run(command: OracleCommand) -> Result<()> {
+    match command {
+        OracleCommand::Read {
+            pyth,
+            switchboard,
+            max_staleness,
+            max_confidence,
+            max_deviation,
+            url,
+        } => {
+            let config = OracleConfig {
+                max_staleness,
+                max_confidence,
+                max_deviation,
+            };
+            read(pyth, switchboard, config, url).await
+        }
+        OracleCommand::SaveFixtures { addresses, out, url } => save_fixtures(addresses, out, url).await,
+        OracleCommand::Push {
+            symbol,
+            price,
+            conf,
+            expo,
+            timestamp,
+            keypair,
+            url,
+        } => push(symbol, price, conf, expo, timestamp, keypair, url).await,
+        OracleCommand::Feed {
+            symbol,
+            start,
+            scenario,
+            interval_ms,
+            volatility_bps,
+            seed,
+            steps,
+            conf_bps,
+            expo,
+            keypair,
+            url,
+        } => {
+            let options = FeedOptions {
+                start,
+                scenario,
+                interval: Duration::from_millis(interval_ms),
+                volatility_bps,
+                seed,
+                steps,
+                conf_bps,
+                expo,
+            };
+            feed(symbol, options, keypair, url).await
+        }
+        OracleCommand::Fixture {
+            symbol,
+            price,
+            conf,
+            expo,
+            timestamp,
+            keypair,
+            out,
+        } => generate_fixture(symbol, price, conf, expo, timestamp, keypair, out).await,
+    }
+}
+
+/// Fetch every configured feed, print the normalized prices and the
+/// consensus verdict. Fails if no feed produced a usable price.
+async fn read(pyth: Vec<String>, switchboard: Vec<String>, config: OracleConfig, url: String) -> Result<()> {
+    if pyth.is_empty() && switchboard.is_empty() {
+        anyhow::bail!("Provide at least one --pyth or --switchboard account");
+    }
+
+    println!("🔮 Reading oracle prices...");
+    let client = RpcClient::new_with_commitment(url.clone(), CommitmentConfig::confirmed());
+    println!("   RPC URL: {}", url);
+
+    let mut prices = Vec::new();
+    let feeds = pyth
+        .iter()
+        .map(|a| (a, true))
+        .chain(switchboard.iter().map(|a| (a, false)));
+    for (address, is_pyth) in feeds {
+        let pubkey = Pubkey::from_str(address).context("Invalid oracle account address")?;
+        let result = if is_pyth {
+            fetch_pyth(&client, &pubkey).await
+        } else {
+            fetch_switchboard(&client, &pubkey).await
+        };
+        match result {
+            Ok(price) => {
+                print_price(&pubkey, &price);
+                prices.push(price);
+            }
+            Err(e) => println!("\n   ⚠️  {}: {:#}", pubkey, e),
+        }
+    }
+
+    let report = validate_consensus(&prices, now(), &config);
+
+    println!("\n⚖️  Consensus:");
+    for violation in &report.violations {
+        println!("   ❌ {}", violation);
+    }
+    match report.price {
+        Some(price) => {
+            let sources: Vec<String> = report.accepted.iter().map(|s| s.to_string()).collect();
+            println!(
+                "   Price: {} (raw {} × 10^{})",
+                price as f64 * 10f64.powi(report.expo),
+                price,
+                report.expo
+            );
+            println!("   Sources: {}", sources.join(", "));
+        }
+        None => anyhow::bail!("❌ No oracle source produced a valid price"),
+    }
+
+    if report.is_valid() {
+        println!("✅ All checks passed");
+    } else {
+        println!("⚠️  {} check(s) failed", report.violations.len());
+    }
+
+    Ok(())
+}
+
+fn print_price(address: &Pubkey, price: &PriceData) {
+    println!("\n📈 {} ({})", price.source, address);
+    println!("   Price: {} ± {}", price.ui_price(), price.ui_confidence());
+    println!("   Raw: {} (conf {}, expo {})", price.price, price.confidence, price.expo);
+    println!("   Published: {}", price.timestamp);
+}
+
+/// Capture accounts so localnet can be started with real oracle data:
+/// `solana-test-validator --account <ADDRESS> <DIR>/<ADDRESS>.json`
+async fn save_fixtures(addresses: Vec<String>, out: PathBuf, url: String) -> Result<()> {
+    println!("💾 Saving oracle account fixtures from {}...", url);
+    let client = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+
+    let mut args = Vec::new();
+    for address in &addresses {
+        let pubkey = Pubkey::from_str(address).context("Invalid account address")?;
+        let path = fixture::save_account(&client, &pubkey, &out).await?;
+        println!("   ✅ {}", path.display());
+        args.push(format!("--account {} {}", pubkey, path.display()));
+    }
+
+    println!("\n💡 Start localnet with these accounts:");
+    println!("   solana-test-validator --reset {}", args.join(" "));
+
+    Ok(())
+}
+
+async fn push(
+    symbol: String,
+    price: f64,
+    conf: f64,
+    expo: i32,
+    timestamp: Option<i64>,
+    keypair: String,
+    url: String,
+) -> Result<()> {
+    println!("📤 Pushing mock oracle price...");
+    let authority = load_keypair(&keypair)?;
+    let client = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+
+    let slot = client.get_slot().await.context("Failed to get current slot")?;
+    let price = MockPrice::from_ui(price, conf, expo, timestamp.unwrap_or_else(now), slot)?;
+    let address = mock::price_account_address(&authority.pubkey(), &symbol)?;
+    let signature = mock::push_price(&client, &authority, &symbol, &price).await?;
+
+    println!("✅ {} price updated", symbol);
+    println!("   Price Account: {}", address);
+    println!("   Raw: {} (conf {}, expo {})", price.price, price.conf, price.expo);
+    println!("   Slot: {}", slot);
+    println!("   Transaction Signature: {}", signature);
+    println!("\n💡 Read it back:");
+    println!("   cargo run -- oracle read --pyth {}", address);
+
+    Ok(())
+}
+
+struct FeedOptions {
+    start: f64,
+    scenario: Option<PathBuf>,
+    interval: Duration,
+    volatility_bps: f64,
+    seed: u64,
+    steps: u64,
+    conf_bps: f64,
+    expo: i32,
+}
+
+/// Publish a price path until it ends or Ctrl-C is pressed
+async fn feed(symbol: String, options: FeedOptions, keypair: String, url: String) -> Result<()> {
+    let authority = load_keypair(&keypair)?;
+    let client = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+    let address = mock::price_account_address(&authority.pubkey(), &symbol)?;
+
+    // (delay, price, conf) for every update, the start price first
+    let updates: Box<dyn Iterator<Item = (Duration, f64, Option<f64>)>> = match &options.scenario {
+        Some(path) => {
+            let steps = feed::load_scenario(path)?;
+            println!("📜 Replaying {} scenario steps from {}", steps.len(), path.display());
+            let mut current = options.start;
+            let interval = options.interval;
+            let path: Vec<_> = steps
+                .iter()
+                .map(|step| {
+                    current = feed::apply_step(current, step);
+                    let delay = step.delay_ms.map(Duration::from_millis).unwrap_or(interval);
+                    (delay, current, step.conf)
+                })
+                .collect();
+            Box::new(std::iter::once((Duration::ZERO, options.start, None)).chain(path))
+        }
+        None => {
+            println!(
+                "🎲 Random walk: ±{} bps per update, seed {}",
+                options.volatility_bps, options.seed
+            );
+            let mut walk = RandomWalk::new(options.start, options.volatility_bps, options.seed);
+            let interval = options.interval;
+            let walk = std::iter::repeat_with(move || (interval, walk.next_price(), None));
+            let start = std::iter::once((Duration::ZERO, options.start, None));
+            if options.steps == 0 {
+                Box::new(start.chain(walk))
+            } else {
+                Box::new(start.chain(walk.take(options.steps as usize)))
+            }
+        }
+    };
+
+    println!("📡 Feeding {} → {} (Ctrl-C to stop)", symbol, address);
+    let mut published = 0u64;
+    for (delay, price, conf) in updates {
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = tokio::signal::ctrl_c() => {
+                println!("\n🛑 Stopped");
+                break;
+            }
+        }
+
+        let conf = conf.unwrap_or(price * options.conf_bps / 10_000.0);
+        let slot = client.get_slot().await.context("Failed to get current slot")?;
+        let update = MockPrice::from_ui(price, conf, options.expo, now(), slot)?;
+        let signature = mock::push_price(&client, &authority, &symbol, &update).await?;
+        published += 1;
+        println!("   slot {:>8}  {:>16.4} ± {:<10.4} {}", slot, price, conf, signature);
+    }
+
+    println!("✅ Published {} price updates", published);
+    Ok(())
+}
+
+/// Write a ready-to-load mock oracle account, so localnet starts with a price
+/// already in place and `oracle push` can keep updating it.
+async fn generate_fixture(
+    symbol: String,
+    price: f64,
+    conf: f64,
+    expo: i32,
+    timestamp: Option<i64>,
+    keypair: String,
+    out: PathBuf,
+) -> Result<()> {
+    println!("🧪 Generating mock oracle fixture for {}...", symbol);
+    let authority = load_keypair(&keypair)?;
+    let address = mock::price_account_address(&authority.pubkey(), &symbol)?;
+
+    let price = MockPrice::from_ui(price, conf, expo, timestamp.unwrap_or_else(now), 0)?;
+    // Generous fixed balance; the test validator does not charge rent anyway
+    let account = price.to_account(&authority.pubkey(), 1_000_000_000);
+
+    std::fs::create_dir_all(&out)
+        .with_context(|| format!("Failed to create fixture directory {}", out.display()))?;
+    let path = out.join(format!("{}.json", symbol));
+    fixture::write_account_fixture(&path, &address, &account)?;
+
+    println!("✅ Fixture written: {}", path.display());
+    println!("   Price Account: {}", address);
+    println!("\n💡 Start localnet with the mock oracle and this feed:");
+    println!(
+        "   solana-test-validator --reset --bpf-program {} mock_oracle.so --account {} {}",
+        mock::PROGRAM_ID,
+        address,
+        path.display()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+//! Price paths for `oracle feed`
+//!
+//! A feed is either a scripted scenario loaded from JSON or a seeded random
+//! walk. Both are deterministic, so a liquidation or funding test can be
+//! re-run against exactly the same sequence of prices.
+
+use anyhow::{bail, Context, Result};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::Deserialize;
+use std::path::Path;
+
+/// One step of a scripted scenario
+///
+/// Example scenario: a 20% crash in one slot followed by a partial recovery
+///
+/// ```json
+/// [
+///   { "price": 65000 },
+///   { "change_pct": -20, "delay_ms": 400 },
+///   { "change_pct": 5, "delay_ms": 2000, "conf": 150 }
+/// ]
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScenarioStep {
+    /// Absolute price to publish
+    pub price: Option<f64>,
+    /// Move relative to the previous price, in percent
+    pub change_pct: Option<f64>,
+    /// Confidence to publish; defaults to the feed's `--conf-bps` of price
+    pub conf: Option<f64>,
+    /// Delay before publishing this step; defaults to the feed interval
+    pub delay_ms: Option<u64>,
+}
+
+pub fn load_scenario(path: &Path) -> Result<Vec<ScenarioStep>> {
+    let raw = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read scenario {}", path.display()))?;
+    let steps: Vec<ScenarioStep> = serde_json::from_str(&raw)
+        .with_context(|| format!("Invalid scenario {}", path.display()))?;
+
+    for (i, step) in steps.iter().enumerate() {
+        if step.price.is_some() == step.change_pct.is_some() {
+            bail!("Scenario step {} must set exactly one of `price` or `change_pct`", i);
+        }
+    }
+    Ok(steps)
+}
+
+/// Resolve a step to an absolute price given the previous one
+pub fn apply_step(previous: f64, step: &ScenarioStep) -> f64 {
+    match (step.price, step.change_pct) {
+        (Some(price), _) => price,
+        (None, Some(pct)) => previous * (1.0 + pct / 100.0),
+        (None, None) => previous,
+    }
+}
+
+/// Seeded random walk with a fixed maximum move per step
+pub struct RandomWalk {
+    rng: StdRng,
+    price: f64,
+    volatility_bps: f64,
+}
+
+impl RandomWalk {
+    pub fn new(start: f64, volatility_bps: f64, seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            price: start,
+            volatility_bps,
+        }
+    }
+
+    /// Next price, moving up to `volatility_bps` in either direction
+    pub fn next_price(&mut self) -> f64 {
+        let step: f64 = self.rng.gen_range(-1.0..=1.0);
+        self.price *= 1.0 + step * self.volatility_bps / 10_000.0;
+        self.price
+    }
+}
@@ -0,0 +1,44 @@
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::json;
+use solana_sdk::{account::Account, pubkey::Pubkey};
+use std::path::{Path, PathBuf};
+
+use crate::client::LedgerClient;
+
+/// Write an account in the JSON format accepted by
+/// `solana-test-validator --account <ADDRESS> <FILE>`.
+///
+/// This is the same shape that `solana account --output json` produces, so
+/// fixtures can be captured from devnet/mainnet and replayed on localnet.
+pub fn write_account_fixture(path: &Path, address: &Pubkey, account: &Account) -> Result<()> {
+    let fixture = json!({
+        "pubkey": address.to_string(),
+        "account": {
+            "lamports": account.lamports,
+            "data": [STANDARD.encode(&account.data), "base64"],
+            "owner": account.owner.to_string(),
+            "executable": account.executable,
+            "rentEpoch": account.rent_epoch,
+            "space": account.data.len(),
+        }
+    });
+
+    std::fs::write(path, serde_json::to_string_pretty(&fixture)?)
+        .with_context(|| format!("Failed to write fixture {}", path.display()))
+}
+
+/// Fetch an account from the cluster and save it as `<dir>/<address>.json`
+pub async fn save_account(client: &impl LedgerClient, address: &Pubkey, dir: &Path) -> Result<PathBuf> {
+    let account = client
+        .get_account(address)
+        .await
+        .with_context(|| format!("Failed to fetch account {}", address))?
+        .with_context(|| format!("Account {} not found", address))?;
+
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create fixture directory {}", dir.display()))?;
+    let path = dir.join(format!("{}.json", address));
+    write_account_fixture(&path, address, &account)?;
+    Ok(path)
+}
@@ -0,0 +1,163 @@
+//! Client for the localnet mock oracle program (`programs/mock-oracle`)
+//!
+//! The mock program owns Pyth-layout price accounts and lets one authority
+//! overwrite them, so prices on localnet can be scripted deterministically.
+//! Price accounts are derived with `create_account_with_seed` from the
+//! authority and the market symbol, so the same wallet always writes the
+//! same address for `BTC-USDT`.
+
+use anyhow::{bail, Context, Result};
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    system_instruction,
+};
+use std::str::FromStr;
+
+use super::pyth::{self, PriceStatus};
+use crate::client::{send, LedgerClient};
+
+/// Program id the mock oracle is loaded at on localnet:
+/// `solana-test-validator --bpf-program <ID> mock_oracle.so`
+pub const PROGRAM_ID: &str = "GdxMockPyth11111111111111111111111111111111";
+/// Pyth price account followed by the 32-byte write authority
+pub const ACCOUNT_SIZE: usize = pyth::PRICE_ACCOUNT_SIZE + 32;
+/// Only the header up to and including the aggregate price is rewritten
+const HEADER_SIZE: usize = pyth::OFFSET_AGG_PUB_SLOT + 8;
+
+pub fn program_id() -> Pubkey {
+    Pubkey::from_str(PROGRAM_ID).unwrap()
+}
+
+/// A price to write into a mock Pyth account
+#[derive(Debug, Clone)]
+pub struct MockPrice {
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub timestamp: i64,
+    pub slot: u64,
+    pub status: PriceStatus,
+}
+
+impl MockPrice {
+    /// Build a trading price from UI values, e.g. `65000.5` at expo `-8`.
+    /// Fails if either value is not finite, the confidence is negative, or
+    /// a value does not fit the account's integers at `expo`.
+    pub fn from_ui(price: f64, conf: f64, expo: i32, timestamp: i64, slot: u64) -> Result<Self> {
+        let scale = 10f64.powi(expo.checked_neg().context("Exponent out of range")?);
+        let raw_price = (price * scale).round();
+        if !raw_price.is_finite() || raw_price < i64::MIN as f64 || raw_price >= i64::MAX as f64 {
+            bail!("Price {} does not fit a 64-bit price at expo {}", price, expo);
+        }
+        if conf.is_nan() || conf < 0.0 {
+            bail!("Confidence must be a non-negative number, got {}", conf);
+        }
+        let raw_conf = (conf * scale).round();
+        if !raw_conf.is_finite() || raw_conf >= u64::MAX as f64 {
+            bail!("Confidence {} does not fit a 64-bit value at expo {}", conf, expo);
+        }
+        Ok(Self {
+            price: raw_price as i64,
+            conf: raw_conf as u64,
+            expo,
+            timestamp,
+            slot,
+            status: PriceStatus::Trading,
+        })
+    }
+
+    /// Encode the Pyth v2 price account header (up to the aggregate price)
+    pub fn encode_header(&self) -> Vec<u8> {
+        let mut data = vec![0u8; HEADER_SIZE];
+        put(&mut data, pyth::OFFSET_MAGIC, &pyth::MAGIC.to_le_bytes());
+        put(&mut data, pyth::OFFSET_VERSION, &pyth::VERSION.to_le_bytes());
+        put(&mut data, pyth::OFFSET_ACCOUNT_TYPE, &pyth::ACCOUNT_TYPE_PRICE.to_le_bytes());
+        put(&mut data, pyth::OFFSET_SIZE, &(pyth::PRICE_ACCOUNT_SIZE as u32).to_le_bytes());
+        put(&mut data, pyth::OFFSET_EXPO, &self.expo.to_le_bytes());
+        put(&mut data, pyth::OFFSET_VALID_SLOT, &self.slot.to_le_bytes());
+        put(&mut data, pyth::OFFSET_TIMESTAMP, &self.timestamp.to_le_bytes());
+        put(&mut data, pyth::OFFSET_AGG_PRICE, &self.price.to_le_bytes());
+        put(&mut data, pyth::OFFSET_AGG_CONF, &self.conf.to_le_bytes());
+        put(&mut data, pyth::OFFSET_AGG_STATUS, &self.status.as_u32().to_le_bytes());
+        put(&mut data, pyth::OFFSET_AGG_PUB_SLOT, &self.slot.to_le_bytes());
+        data
+    }
+
+    /// A complete mock-oracle account, for `solana-test-validator --account`
+    pub fn to_account(&self, authority: &Pubkey, lamports: u64) -> Account {
+        let mut data = vec![0u8; ACCOUNT_SIZE];
+        put(&mut data, 0, &self.encode_header());
+        put(&mut data, pyth::PRICE_ACCOUNT_SIZE, authority.as_ref());
+        Account {
+            lamports,
+            data,
+            owner: program_id(),
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+}
+
+fn put(data: &mut [u8], offset: usize, bytes: &[u8]) {
+    data[offset..offset + bytes.len()].copy_from_slice(bytes);
+}
+
+/// Address of the mock price account for `symbol` written by `authority`
+pub fn price_account_address(authority: &Pubkey, symbol: &str) -> Result<Pubkey> {
+    Pubkey::create_with_seed(authority, symbol, &program_id())
+        .with_context(|| format!("Cannot derive price account for symbol {:?}", symbol))
+}
+
+/// Instruction overwriting `bytes` at `offset` of a mock price account
+pub fn write_instruction(price_account: &Pubkey, authority: &Pubkey, offset: u32, bytes: &[u8]) -> Instruction {
+    let mut data = offset.to_le_bytes().to_vec();
+    data.extend_from_slice(bytes);
+    Instruction::new_with_bytes(
+        program_id(),
+        &data,
+        vec![
+            AccountMeta::new(*price_account, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// Write `price` to the feed for `symbol`, creating the account on first use
+pub async fn push_price(
+    client: &impl LedgerClient,
+    authority: &Keypair,
+    symbol: &str,
+    price: &MockPrice,
+) -> Result<Signature> {
+    let authority_key = authority.pubkey();
+    let address = price_account_address(&authority_key, symbol)?;
+
+    let mut instructions = Vec::new();
+    if client.get_account(&address).await?.is_none() {
+        let lamports = client.get_minimum_balance_for_rent_exemption(ACCOUNT_SIZE).await?;
+        instructions.push(system_instruction::create_account_with_seed(
+            &authority_key,
+            &address,
+            &authority_key,
+            symbol,
+            lamports,
+            ACCOUNT_SIZE as u64,
+            &program_id(),
+        ));
+    }
+    instructions.push(write_instruction(&address, &authority_key, 0, &price.encode_header()));
+
+    match send(client, &instructions, authority, &[]).await {
+        Ok(signature) => Ok(signature),
+        Err(e) => bail!(
+            "Failed to push price for {}: {}\n\
+             Is the mock oracle loaded? solana-test-validator --bpf-program {} mock_oracle.so",
+            symbol,
+            e,
+            PROGRAM_ID
+        ),
+    }
+}
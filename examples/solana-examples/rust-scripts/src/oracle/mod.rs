@@ -0,0 +1,118 @@
+//! Oracle price feed reader
+//!
+//! Decodes Pyth v2 price accounts and Switchboard v2 aggregator accounts
+//! straight from RPC account data and normalizes both into `PriceData`, the
+//! shape used by the oracle integration program.
+
+pub mod commands;
+pub mod feed;
+pub mod fixture;
+pub mod mock;
+pub mod pyth;
+pub mod switchboard;
+pub mod validate;
+
+use anyhow::{Context, Result};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::client::LedgerClient;
+
+pub use validate::{validate_consensus, ConsensusReport, OracleConfig, Violation};
+
+/// Where a price came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceSource {
+    Pyth,
+    Switchboard,
+    Internal,
+}
+
+impl std::fmt::Display for PriceSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            PriceSource::Pyth => "Pyth",
+            PriceSource::Switchboard => "Switchboard",
+            PriceSource::Internal => "Internal",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A normalized price: `price * 10^expo`, with confidence at the same exponent
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceData {
+    pub price: i64,
+    pub confidence: u64,
+    pub expo: i32,
+    /// Unix timestamp (seconds) at which the price was published
+    pub timestamp: i64,
+    pub source: PriceSource,
+}
+
+impl PriceData {
+    /// Price as a floating point number, for display only
+    pub fn ui_price(&self) -> f64 {
+        self.price as f64 * 10f64.powi(self.expo)
+    }
+
+    /// Confidence as a floating point number, for display only
+    pub fn ui_confidence(&self) -> f64 {
+        self.confidence as f64 * 10f64.powi(self.expo)
+    }
+
+    /// Price as an unsigned fixed-point integer with `decimals` decimals,
+    /// truncating extra precision. `None` for non-positive or overflowing prices.
+    pub fn to_fixed(&self, decimals: u32) -> Option<u64> {
+        if self.price <= 0 {
+            return None;
+        }
+        let shift = self.expo + decimals as i32;
+        let value = if shift >= 0 {
+            (self.price as u128).checked_mul(10u128.checked_pow(shift as u32)?)?
+        } else {
+            self.price as u128 / 10u128.checked_pow(shift.unsigned_abs())?
+        };
+        u64::try_from(value).ok()
+    }
+}
+
+/// Fetch and decode a Pyth price account
+pub async fn fetch_pyth(client: &impl LedgerClient, address: &Pubkey) -> Result<PriceData> {
+    let data = fetch_data(client, address, "Pyth account").await?;
+    pyth::PythPriceAccount::decode(&data)?.to_price_data()
+}
+
+/// Fetch and decode a Switchboard aggregator account
+pub async fn fetch_switchboard(client: &impl LedgerClient, address: &Pubkey) -> Result<PriceData> {
+    let data = fetch_data(client, address, "Switchboard aggregator").await?;
+    switchboard::AggregatorAccount::decode(&data)?.to_price_data()
+}
+
+async fn fetch_data(client: &impl LedgerClient, address: &Pubkey, what: &str) -> Result<Vec<u8>> {
+    client
+        .get_account(address)
+        .await
+        .with_context(|| format!("Failed to fetch {} {}", what, address))?
+        .map(|account| account.data)
+        .with_context(|| format!("{} {} not found", what, address))
+}
+
+pub(crate) fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+pub(crate) fn read_i32(data: &[u8], offset: usize) -> i32 {
+    i32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+pub(crate) fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+pub(crate) fn read_i64(data: &[u8], offset: usize) -> i64 {
+    i64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+pub(crate) fn read_i128(data: &[u8], offset: usize) -> i128 {
+    i128::from_le_bytes(data[offset..offset + 16].try_into().unwrap())
+}
@@ -0,0 +1,121 @@
+use anyhow::{bail, Result};
+
+use super::{read_i32, read_i64, read_u32, read_u64, PriceData, PriceSource};
+
+/// Magic number at the start of every Pyth v2 account
+pub const MAGIC: u32 = 0xa1b2_c3d4;
+/// Account layout version we know how to read
+pub const VERSION: u32 = 2;
+/// Account type tag for price accounts (mapping = 1, product = 2, price = 3)
+pub const ACCOUNT_TYPE_PRICE: u32 = 3;
+/// Size of a full Pyth v2 price account (header + 32 publisher components)
+pub const PRICE_ACCOUNT_SIZE: usize = 3312;
+
+// Byte offsets into the legacy Pyth v2 `PriceAccount` layout
+pub(crate) const OFFSET_MAGIC: usize = 0;
+pub(crate) const OFFSET_VERSION: usize = 4;
+pub(crate) const OFFSET_ACCOUNT_TYPE: usize = 8;
+pub(crate) const OFFSET_SIZE: usize = 12;
+pub(crate) const OFFSET_EXPO: usize = 20;
+pub(crate) const OFFSET_VALID_SLOT: usize = 40;
+pub(crate) const OFFSET_TIMESTAMP: usize = 96;
+pub(crate) const OFFSET_AGG_PRICE: usize = 208;
+pub(crate) const OFFSET_AGG_CONF: usize = 216;
+pub(crate) const OFFSET_AGG_STATUS: usize = 224;
+pub(crate) const OFFSET_AGG_PUB_SLOT: usize = 232;
+
+/// Aggregate price status as published by Pyth
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceStatus {
+    Unknown,
+    Trading,
+    Halted,
+    Auction,
+    Ignored,
+}
+
+impl PriceStatus {
+    pub fn from_u32(value: u32) -> Self {
+        match value {
+            1 => PriceStatus::Trading,
+            2 => PriceStatus::Halted,
+            3 => PriceStatus::Auction,
+            4 => PriceStatus::Ignored,
+            _ => PriceStatus::Unknown,
+        }
+    }
+
+    pub fn as_u32(self) -> u32 {
+        match self {
+            PriceStatus::Unknown => 0,
+            PriceStatus::Trading => 1,
+            PriceStatus::Halted => 2,
+            PriceStatus::Auction => 3,
+            PriceStatus::Ignored => 4,
+        }
+    }
+}
+
+/// The fields of a Pyth price account that we care about
+#[derive(Debug, Clone)]
+pub struct PythPriceAccount {
+    pub expo: i32,
+    pub valid_slot: u64,
+    pub timestamp: i64,
+    pub price: i64,
+    pub conf: u64,
+    pub status: PriceStatus,
+    pub pub_slot: u64,
+}
+
+impl PythPriceAccount {
+    /// Decode the raw account data of a Pyth v2 price account
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if data.len() < OFFSET_AGG_PUB_SLOT + 8 {
+            bail!("Pyth price account too short: {} bytes", data.len());
+        }
+
+        let magic = read_u32(data, OFFSET_MAGIC);
+        if magic != MAGIC {
+            bail!("Not a Pyth account (magic {:#x})", magic);
+        }
+
+        let version = read_u32(data, OFFSET_VERSION);
+        if version != VERSION {
+            bail!("Unsupported Pyth account version {}", version);
+        }
+
+        let account_type = read_u32(data, OFFSET_ACCOUNT_TYPE);
+        if account_type != ACCOUNT_TYPE_PRICE {
+            bail!("Pyth account is not a price account (type {})", account_type);
+        }
+
+        Ok(Self {
+            expo: read_i32(data, OFFSET_EXPO),
+            valid_slot: read_u64(data, OFFSET_VALID_SLOT),
+            timestamp: read_i64(data, OFFSET_TIMESTAMP),
+            price: read_i64(data, OFFSET_AGG_PRICE),
+            conf: read_u64(data, OFFSET_AGG_CONF),
+            status: PriceStatus::from_u32(read_u32(data, OFFSET_AGG_STATUS)),
+            pub_slot: read_u64(data, OFFSET_AGG_PUB_SLOT),
+        })
+    }
+
+    /// Normalize to the shared `PriceData` shape
+    ///
+    /// Only a `Trading` aggregate is a usable price; anything else means the
+    /// publishers could not agree and the value must not be consumed.
+    pub fn to_price_data(&self) -> Result<PriceData> {
+        if self.status != PriceStatus::Trading {
+            bail!("Pyth aggregate status is {:?}, not Trading", self.status);
+        }
+
+        Ok(PriceData {
+            price: self.price,
+            confidence: self.conf,
+            expo: self.expo,
+            timestamp: self.timestamp,
+            source: PriceSource::Pyth,
+        })
+    }
+}
@@ -0,0 +1,118 @@
+use anyhow::{bail, Result};
+
+use super::{read_i128, read_i64, read_u32, read_u64, PriceData, PriceSource};
+
+/// Anchor discriminator of the Switchboard v2 `AggregatorAccountData` account
+pub const AGGREGATOR_DISCRIMINATOR: [u8; 8] = [217, 230, 65, 101, 201, 162, 27, 125];
+
+// Byte offsets into the packed `AggregatorAccountData` layout (discriminator
+// included). `latest_confirmed_round` starts at 341.
+pub(crate) const OFFSET_MIN_ORACLE_RESULTS: usize = 236;
+pub(crate) const OFFSET_ROUND_NUM_SUCCESS: usize = 341;
+pub(crate) const OFFSET_ROUND_OPEN_SLOT: usize = 350;
+pub(crate) const OFFSET_ROUND_OPEN_TIMESTAMP: usize = 358;
+pub(crate) const OFFSET_ROUND_RESULT: usize = 366;
+pub(crate) const OFFSET_ROUND_STD_DEVIATION: usize = 386;
+
+/// Size of a `SwitchboardDecimal` (i128 mantissa + u32 scale)
+const DECIMAL_SIZE: usize = 20;
+
+/// A Switchboard fixed-point decimal: `mantissa * 10^-scale`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwitchboardDecimal {
+    pub mantissa: i128,
+    pub scale: u32,
+}
+
+impl SwitchboardDecimal {
+    fn read(data: &[u8], offset: usize) -> Self {
+        Self {
+            mantissa: read_i128(data, offset),
+            scale: read_u32(data, offset + 16),
+        }
+    }
+
+    /// Re-express the decimal with the given scale, truncating extra digits
+    pub fn rescale(&self, scale: u32) -> Option<i128> {
+        if scale >= self.scale {
+            10i128
+                .checked_pow(scale - self.scale)
+                .and_then(|factor| self.mantissa.checked_mul(factor))
+        } else {
+            10i128
+                .checked_pow(self.scale - scale)
+                .map(|factor| self.mantissa / factor)
+        }
+    }
+}
+
+/// The latest confirmed round of a Switchboard aggregator
+#[derive(Debug, Clone)]
+pub struct AggregatorAccount {
+    pub min_oracle_results: u32,
+    pub num_success: u32,
+    pub round_open_slot: u64,
+    pub round_open_timestamp: i64,
+    pub result: SwitchboardDecimal,
+    pub std_deviation: SwitchboardDecimal,
+}
+
+impl AggregatorAccount {
+    /// Decode the raw account data of a Switchboard v2 aggregator
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if data.len() < OFFSET_ROUND_STD_DEVIATION + DECIMAL_SIZE {
+            bail!("Switchboard aggregator account too short: {} bytes", data.len());
+        }
+
+        if data[..8] != AGGREGATOR_DISCRIMINATOR {
+            bail!("Not a Switchboard aggregator account (discriminator mismatch)");
+        }
+
+        Ok(Self {
+            min_oracle_results: read_u32(data, OFFSET_MIN_ORACLE_RESULTS),
+            num_success: read_u32(data, OFFSET_ROUND_NUM_SUCCESS),
+            round_open_slot: read_u64(data, OFFSET_ROUND_OPEN_SLOT),
+            round_open_timestamp: read_i64(data, OFFSET_ROUND_OPEN_TIMESTAMP),
+            result: SwitchboardDecimal::read(data, OFFSET_ROUND_RESULT),
+            std_deviation: SwitchboardDecimal::read(data, OFFSET_ROUND_STD_DEVIATION),
+        })
+    }
+
+    /// Normalize to the shared `PriceData` shape
+    ///
+    /// The result and standard deviation are brought to a common scale and
+    /// narrowed to `i64`, dropping precision until the value fits.
+    pub fn to_price_data(&self) -> Result<PriceData> {
+        if self.num_success < self.min_oracle_results {
+            bail!(
+                "Switchboard round has {} successful responses, needs {}",
+                self.num_success,
+                self.min_oracle_results
+            );
+        }
+
+        let mut scale = self.result.scale;
+        loop {
+            let price = self.result.rescale(scale).and_then(|v| i64::try_from(v).ok());
+            let conf = self
+                .std_deviation
+                .rescale(scale)
+                .and_then(|v| u64::try_from(v.unsigned_abs()).ok());
+
+            if let (Some(price), Some(confidence)) = (price, conf) {
+                return Ok(PriceData {
+                    price,
+                    confidence,
+                    expo: -(scale as i32),
+                    timestamp: self.round_open_timestamp,
+                    source: PriceSource::Switchboard,
+                });
+            }
+
+            if scale == 0 {
+                bail!("Switchboard result does not fit in a 64-bit price");
+            }
+            scale -= 1;
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use super::{PriceData, PriceSource};
+
+/// Validation thresholds, mirroring the on-chain `OracleConfig`
+#[derive(Debug, Clone)]
+pub struct OracleConfig {
+    /// Maximum age of a price in seconds
+    pub max_staleness: i64,
+    /// Maximum confidence interval relative to price, in basis points
+    pub max_confidence: u64,
+    /// Maximum deviation of a source from the consensus median, in basis points
+    pub max_deviation: u64,
+}
+
+impl Default for OracleConfig {
+    fn default() -> Self {
+        Self {
+            max_staleness: 30,
+            max_confidence: 100,
+            max_deviation: 100,
+        }
+    }
+}
+
+/// A single failed check against one price source
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    Stale { source: PriceSource, age: i64 },
+    NonPositive { source: PriceSource },
+    ConfidenceTooWide { source: PriceSource, bps: u64 },
+    Deviation { source: PriceSource, bps: u64 },
+    /// The exponent is too far from the other sources' to compare prices
+    Unscalable { source: PriceSource, expo: i32 },
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::Stale { source, age } => write!(f, "{} price is stale ({}s old)", source, age),
+            Violation::NonPositive { source } => write!(f, "{} price is not positive", source),
+            Violation::ConfidenceTooWide { source, bps } => {
+                write!(f, "{} confidence band too wide ({} bps)", source, bps)
+            }
+            Violation::Deviation { source, bps } => {
+                write!(f, "{} deviates {} bps from consensus", source, bps)
+            }
+            Violation::Unscalable { source, expo } => {
+                write!(f, "{} exponent {} cannot be compared with the other sources", source, expo)
+            }
+        }
+    }
+}
+
+/// Outcome of validating a set of prices
+#[derive(Debug, Clone)]
+pub struct ConsensusReport {
+    /// Median of the sources that passed the per-source checks, at `expo`
+    pub price: Option<i64>,
+    pub expo: i32,
+    pub accepted: Vec<PriceSource>,
+    pub violations: Vec<Violation>,
+}
+
+impl ConsensusReport {
+    pub fn is_valid(&self) -> bool {
+        self.price.is_some() && self.violations.is_empty()
+    }
+}
+
+/// Age of a price in seconds relative to `now` (unix time)
+pub fn check_staleness(price: &PriceData, now: i64, config: &OracleConfig) -> Option<Violation> {
+    let age = now - price.timestamp;
+    (age > config.max_staleness).then_some(Violation::Stale {
+        source: price.source,
+        age,
+    })
+}
+
+/// Confidence interval as a fraction of price, in basis points
+pub fn confidence_bps(price: &PriceData) -> Option<u64> {
+    if price.price <= 0 {
+        return None;
+    }
+    let bps = price.confidence as u128 * 10_000 / price.price as u128;
+    Some(u64::try_from(bps).unwrap_or(u64::MAX))
+}
+
+pub fn check_confidence(price: &PriceData, config: &OracleConfig) -> Option<Violation> {
+    match confidence_bps(price) {
+        None => Some(Violation::NonPositive { source: price.source }),
+        Some(bps) if bps > config.max_confidence => Some(Violation::ConfidenceTooWide {
+            source: price.source,
+            bps,
+        }),
+        Some(_) => None,
+    }
+}
+
+/// Run staleness and confidence checks per source, then the cross-source
+/// deviation check against the median of the survivors.
+///
+/// Sources that fail a per-source check are excluded from the median so one
+/// bad feed cannot drag the consensus. With a single surviving source the
+/// deviation check is skipped and that source is used as-is.
+pub fn validate_consensus(prices: &[PriceData], now: i64, config: &OracleConfig) -> ConsensusReport {
+    let mut violations = Vec::new();
+    let mut survivors = Vec::new();
+
+    for price in prices {
+        let failed: Vec<Violation> = [check_staleness(price, now, config), check_confidence(price, config)]
+            .into_iter()
+            .flatten()
+            .collect();
+        if failed.is_empty() {
+            survivors.push(price);
+        } else {
+            violations.extend(failed);
+        }
+    }
+
+    // Compare everything at the finest exponent among the survivors. The
+    // exponents come from account data, so a source too coarse to rescale
+    // is a violation rather than an overflow.
+    let expo = survivors.iter().map(|p| p.expo).min().unwrap_or(0);
+    let mut scaled: Vec<(PriceSource, i128)> = Vec::new();
+    for price in &survivors {
+        match rescale(price.price, price.expo, expo) {
+            Some(value) => scaled.push((price.source, value)),
+            None => violations.push(Violation::Unscalable {
+                source: price.source,
+                expo: price.expo,
+            }),
+        }
+    }
+    scaled.sort_by_key(|(_, value)| *value);
+
+    let median = match scaled.len() {
+        0 => None,
+        n if n % 2 == 1 => Some(scaled[n / 2].1),
+        n => Some(scaled[n / 2 - 1].1 + (scaled[n / 2].1 - scaled[n / 2 - 1].1) / 2),
+    };
+
+    let mut accepted = Vec::new();
+    if let Some(median) = median {
+        for (source, value) in &scaled {
+            let bps = (value - median).unsigned_abs().saturating_mul(10_000) / median.unsigned_abs().max(1);
+            let bps = u64::try_from(bps).unwrap_or(u64::MAX);
+            if scaled.len() > 1 && bps > config.max_deviation {
+                violations.push(Violation::Deviation { source: *source, bps });
+            } else {
+                accepted.push(*source);
+            }
+        }
+    }
+
+    ConsensusReport {
+        price: median.and_then(|m| i64::try_from(m).ok()),
+        expo,
+        accepted,
+        violations,
+    }
+}
+
+/// Express `value * 10^from` at exponent `to` (where `to <= from`); `None`
+/// if the result does not fit in an `i128`
+fn rescale(value: i64, from: i32, to: i32) -> Option<i128> {
+    let shift = u32::try_from(from as i64 - to as i64).ok()?;
+    (value as i128).checked_mul(10i128.checked_pow(shift)?)
+}
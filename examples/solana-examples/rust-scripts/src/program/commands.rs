@@ -0,0 +1,569 @@
+use anyhow::{bail, Context, Result};
+use clap::Subcommand;
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
+use solana_client::{
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    commitment_config::CommitmentConfig,
+    native_token::lamports_to_sol,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use super::{elf_hash, fetch, prepare_buffer, verify_deployed, LoaderAccount};
+use crate::client::{send, LedgerClient};
+use crate::keypair::{load_keypair, load_or_create_keypair, DEFAULT_KEYPAIR_PATH};
+
+#[derive(Subcommand)]
+pub enum ProgramCommand {
+    /// Deploy a new program from its .so
+    Deploy {
+        /// Program ELF, e.g. target/deploy/collateral_vault.so
+        program: PathBuf,
+        /// Program id keypair, created if missing (default: <NAME>-keypair.json next to the .so)
+        #[arg(long)]
+        program_keypair: Option<String>,
+        /// Buffer keypair, created if missing; rerun with the same one to resume (default: <NAME>-buffer.json next to the .so)
+        #[arg(long)]
+        buffer: Option<String>,
+        /// Maximum program size in bytes, room for later upgrades (default: twice the .so)
+        #[arg(long)]
+        max_len: Option<usize>,
+        /// Upgrade authority keypair (default: --keypair)
+        #[arg(long)]
+        upgrade_authority: Option<String>,
+        /// Buffer writes in flight at once
+        #[arg(long, default_value = "16")]
+        concurrency: usize,
+        /// Fee payer keypair
+        #[arg(long, default_value = DEFAULT_KEYPAIR_PATH)]
+        keypair: String,
+        /// RPC endpoint
+        #[arg(long, default_value = "http://127.0.0.1:8899")]
+        url: String,
+    },
+    /// Replace a deployed program with a new .so
+    Upgrade {
+        program_id: String,
+        /// New program ELF
+        program: PathBuf,
+        /// Buffer keypair, created if missing; rerun with the same one to resume (default: <NAME>-buffer.json next to the .so)
+        #[arg(long)]
+        buffer: Option<String>,
+        /// Upgrade authority keypair (default: --keypair)
+        #[arg(long)]
+        upgrade_authority: Option<String>,
+        /// Buffer writes in flight at once
+        #[arg(long, default_value = "16")]
+        concurrency: usize,
+        /// Fee payer keypair
+        #[arg(long, default_value = DEFAULT_KEYPAIR_PATH)]
+        keypair: String,
+        /// RPC endpoint
+        #[arg(long, default_value = "http://127.0.0.1:8899")]
+        url: String,
+    },
+    /// Show a program or buffer, or list the buffers of an authority
+    Show {
+        /// Program id or buffer address
+        #[arg(required_unless_present = "buffers")]
+        address: Option<String>,
+        /// Compare the deployed bytes with this .so
+        #[arg(long)]
+        so: Option<PathBuf>,
+        /// List buffers instead, e.g. ones left by interrupted uploads
+        #[arg(long, conflicts_with = "address")]
+        buffers: bool,
+        /// Buffer authority to list buffers for (default: the --keypair pubkey)
+        #[arg(long, requires = "buffers")]
+        authority: Option<String>,
+        /// Keypair whose buffers are listed
+        #[arg(long, default_value = DEFAULT_KEYPAIR_PATH)]
+        keypair: String,
+        /// RPC endpoint
+        #[arg(long, default_value = "http://127.0.0.1:8899")]
+        url: String,
+    },
+    /// Change the upgrade authority of a program or the authority of a buffer
+    SetAuthority {
+        /// Program id or buffer address
+        address: String,
+        /// New authority
+        #[arg(long, required_unless_present = "make_final")]
+        new_authority: Option<String>,
+        /// Make the program immutable (programs only, irreversible)
+        #[arg(long = "final", conflicts_with = "new_authority")]
+        make_final: bool,
+        /// Current authority keypair (default: --keypair)
+        #[arg(long)]
+        authority: Option<String>,
+        /// Fee payer keypair
+        #[arg(long, default_value = DEFAULT_KEYPAIR_PATH)]
+        keypair: String,
+        /// RPC endpoint
+        #[arg(long, default_value = "http://127.0.0.1:8899")]
+        url: String,
+    },
+    /// Close a buffer or program and reclaim its rent
+    Close {
+        /// Program id or buffer address
+        address: String,
+        /// Receives the lamports (default: the --keypair pubkey)
+        #[arg(long)]
+        recipient: Option<String>,
+        /// Authority keypair (default: --keypair)
+        #[arg(long)]
+        authority: Option<String>,
+        /// Required to close a program; its id can never be deployed again
+        #[arg(long)]
+        bypass_warning: bool,
+        /// Fee payer keypair
+        #[arg(long, default_value = DEFAULT_KEYPAIR_PATH)]
+        keypair: String,
+        /// RPC endpoint
+        #[arg(long, default_value = "http://127.0.0.1:8899")]
+        url: String,
+    },
+    /// Write the bytes of a deployed program or buffer to a file
+    Dump {
+        /// Program id or buffer address
+        address: String,
+        /// Output file
+        out: PathBuf,
+        /// RPC endpoint
+        #[arg(long, default_value = "http://127.0.0.1:8899")]
+        url: String,
+    },
+}
+
+pub async fn run(command: ProgramCommand) -> Result<()> {
+    match command {
+        ProgramCommand::Deploy {
+            program,
+            program_keypair,
+            buffer,
+            max_len,
+            upgrade_authority,
+            concurrency,
+            keypair,
+            url,
+        } => {
+            let program_keypair = program_keypair.unwrap_or_else(|| sibling(&program, "keypair"));
+            let buffer = buffer.unwrap_or_else(|| sibling(&program, "buffer"));
+            let session = Session::open(&keypair, upgrade_authority.as_deref(), url)?;
+            deploy(&session, &program, &program_keypair, &buffer, max_len, concurrency).await
+        }
+        ProgramCommand::Upgrade {
+            program_id,
+            program,
+            buffer,
+            upgrade_authority,
+            concurrency,
+            keypair,
+            url,
+        } => {
+            let buffer = buffer.unwrap_or_else(|| sibling(&program, "buffer"));
+            let session = Session::open(&keypair, upgrade_authority.as_deref(), url)?;
+            upgrade(&session, &program_id, &program, &buffer, concurrency).await
+        }
+        ProgramCommand::Show {
+            address,
+            so,
+            buffers,
+            authority,
+            keypair,
+            url,
+        } => {
+            if buffers {
+                let authority = match authority {
+                    Some(authority) => parse_pubkey(&authority, "authority")?,
+                    None => load_keypair(&keypair)?.pubkey(),
+                };
+                list_buffers(&authority, url).await
+            } else {
+                show(&address.unwrap_or_default(), so.as_deref(), url).await
+            }
+        }
+        ProgramCommand::SetAuthority {
+            address,
+            new_authority,
+            make_final,
+            authority,
+            keypair,
+            url,
+        } => {
+            let new_authority = match (new_authority, make_final) {
+                (Some(new_authority), false) => Some(parse_pubkey(&new_authority, "new authority")?),
+                _ => None,
+            };
+            let session = Session::open(&keypair, authority.as_deref(), url)?;
+            set_authority(&session, &address, new_authority).await
+        }
+        ProgramCommand::Close {
+            address,
+            recipient,
+            authority,
+            bypass_warning,
+            keypair,
+            url,
+        } => {
+            let session = Session::open(&keypair, authority.as_deref(), url)?;
+            close(&session, &address, recipient.as_deref(), bypass_warning).await
+        }
+        ProgramCommand::Dump { address, out, url } => dump(&address, &out, url).await,
+    }
+}
+
+fn client(url: String) -> RpcClient {
+    RpcClient::new_with_commitment(url, CommitmentConfig::confirmed())
+}
+
+/// What every signing subcommand needs
+struct Session {
+    client: RpcClient,
+    payer: Keypair,
+    /// Upgrade or buffer authority; the payer unless given separately
+    authority: Keypair,
+}
+
+impl Session {
+    fn open(keypair: &str, authority: Option<&str>, url: String) -> Result<Self> {
+        Ok(Self {
+            client: client(url),
+            payer: load_keypair(keypair)?,
+            authority: load_keypair(authority.unwrap_or(keypair))?,
+        })
+    }
+}
+
+fn parse_pubkey(value: &str, name: &str) -> Result<Pubkey> {
+    Pubkey::from_str(value).with_context(|| format!("Invalid {}: {}", name, value))
+}
+
+/// `<dir>/<stem>-<suffix>.json` next to a .so, where `anchor build` keeps
+/// the program keypair
+fn sibling(program: &Path, suffix: &str) -> String {
+    let stem = program.file_stem().and_then(|s| s.to_str()).unwrap_or("program");
+    program
+        .with_file_name(format!("{}-{}.json", stem, suffix))
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn read_elf(path: &Path) -> Result<Vec<u8>> {
+    let elf = std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    if !elf.starts_with(b"\x7fELF") {
+        bail!("{} is not an ELF file", path.display());
+    }
+    Ok(elf)
+}
+
+fn load_buffer(path: &str) -> Result<Keypair> {
+    let (buffer, created) = load_or_create_keypair(path)?;
+    if created {
+        println!("🔑 New buffer keypair saved to {}", path);
+    }
+    Ok(buffer)
+}
+
+async fn deploy(
+    session: &Session,
+    path: &Path,
+    program_keypair: &str,
+    buffer: &str,
+    max_len: Option<usize>,
+    concurrency: usize,
+) -> Result<()> {
+    let Session {
+        client,
+        payer,
+        authority,
+    } = session;
+    let elf = read_elf(path)?;
+    let (program, created) = load_or_create_keypair(program_keypair)?;
+    if created {
+        println!("🔑 New program keypair saved to {}", program_keypair);
+    }
+    let buffer = load_buffer(buffer)?;
+    let max_len = max_len.unwrap_or(elf.len() * 2);
+    if max_len < elf.len() {
+        bail!("--max-len {} is smaller than the program ({} bytes)", max_len, elf.len());
+    }
+
+    println!("🚀 Deploying {}", path.display());
+    println!("   Program Id: {}", program.pubkey());
+    println!("   Size: {} bytes (max {})", elf.len(), max_len);
+    println!("   Upgrade Authority: {}", authority.pubkey());
+
+    if LedgerClient::get_account(client, &program.pubkey()).await?.is_some() {
+        bail!(
+            "{} already exists; use `program upgrade` to replace a deployed program",
+            program.pubkey()
+        );
+    }
+
+    prepare_buffer(client, payer, authority, &buffer, &elf, concurrency).await?;
+
+    let lamports = client
+        .get_minimum_balance_for_rent_exemption(UpgradeableLoaderState::size_of_program())
+        .await
+        .context("Failed to get rent exemption")?;
+    let instructions = bpf_loader_upgradeable::deploy_with_max_program_len(
+        &payer.pubkey(),
+        &program.pubkey(),
+        &buffer.pubkey(),
+        &authority.pubkey(),
+        lamports,
+        max_len,
+    )?;
+    let signature = send(client, &instructions, payer, &[&program, authority])
+        .await
+        .context("Deploy failed")?;
+    println!("🎉 Deployed: {}", signature);
+
+    verify_deployed(client, &program.pubkey(), &elf_hash(&elf)).await?;
+    Ok(())
+}
+
+async fn upgrade(session: &Session, program_id: &str, path: &Path, buffer: &str, concurrency: usize) -> Result<()> {
+    let Session {
+        client,
+        payer,
+        authority,
+    } = session;
+    let program_id = parse_pubkey(program_id, "program id")?;
+    let elf = read_elf(path)?;
+
+    let Some(LoaderAccount::Program {
+        authority: current,
+        data,
+        ..
+    }) = fetch(client, &program_id).await?
+    else {
+        bail!("{} is not a deployed program", program_id);
+    };
+    match current {
+        Some(current) if current == authority.pubkey() => {}
+        Some(current) => bail!("Upgrade authority is {}, not {}", current, authority.pubkey()),
+        None => bail!("{} is immutable", program_id),
+    }
+    if elf_hash(&data) == elf_hash(&elf) {
+        println!("✅ {} already runs this ELF (sha256 {})", program_id, elf_hash(&elf));
+        return Ok(());
+    }
+
+    let buffer = load_buffer(buffer)?;
+    println!("🚀 Upgrading {} with {}", program_id, path.display());
+    println!("   Size: {} bytes (max {})", elf.len(), data.len());
+
+    if elf.len() > data.len() {
+        let additional = (elf.len() - data.len()) as u32;
+        let extend = bpf_loader_upgradeable::extend_program(&program_id, Some(&payer.pubkey()), additional);
+        send(client, &[extend], payer, &[])
+            .await
+            .context("Failed to extend program")?;
+        println!("📏 Extended program data by {} bytes", additional);
+    }
+
+    prepare_buffer(client, payer, authority, &buffer, &elf, concurrency).await?;
+
+    let instruction =
+        bpf_loader_upgradeable::upgrade(&program_id, &buffer.pubkey(), &authority.pubkey(), &payer.pubkey());
+    let signature = send(client, &[instruction], payer, &[authority])
+        .await
+        .context("Upgrade failed")?;
+    println!("🎉 Upgraded: {}", signature);
+
+    verify_deployed(client, &program_id, &elf_hash(&elf)).await?;
+    Ok(())
+}
+
+async fn show(address: &str, so: Option<&Path>, url: String) -> Result<()> {
+    let address = parse_pubkey(address, "address")?;
+    let client = client(url);
+    let account = fetch(&client, &address)
+        .await?
+        .with_context(|| format!("Account {} not found", address))?;
+
+    let authority = account
+        .authority()
+        .map_or_else(|| "none (immutable)".to_string(), |a| a.to_string());
+    match &account {
+        LoaderAccount::Program {
+            programdata_address,
+            slot,
+            data,
+            lamports,
+            ..
+        } => {
+            println!("📦 Program {}", address);
+            println!("   ProgramData: {}", programdata_address);
+            println!("   Upgrade Authority: {}", authority);
+            println!("   Last Deployed Slot: {}", slot);
+            println!("   Data Length: {} bytes (max {})", super::trim_padding(data).len(), data.len());
+            println!("   Balance: {:.9} SOL", lamports_to_sol(*lamports));
+        }
+        LoaderAccount::Buffer { data, lamports, .. } => {
+            println!("🗃️  Buffer {}", address);
+            println!("   Authority: {}", authority);
+            println!("   Data Length: {} bytes", data.len());
+            println!("   Balance: {:.9} SOL", lamports_to_sol(*lamports));
+        }
+    }
+    let deployed = elf_hash(account.data());
+    println!("   SHA-256: {}", deployed);
+
+    if let Some(so) = so {
+        let local = elf_hash(&read_elf(so)?);
+        if local == deployed {
+            println!("✅ Matches {}", so.display());
+        } else {
+            println!("❌ Differs from {} (sha256 {})", so.display(), local);
+        }
+    }
+    Ok(())
+}
+
+async fn list_buffers(authority: &Pubkey, url: String) -> Result<()> {
+    // Buffer state tag (1u32) followed by Some(authority)
+    let mut prefix = vec![1, 0, 0, 0, 1];
+    prefix.extend_from_slice(authority.as_ref());
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, prefix))]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice: Some(UiDataSliceConfig { offset: 0, length: 0 }),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+    let client = client(url);
+    let buffers = client
+        .get_program_accounts_with_config(&bpf_loader_upgradeable::id(), config)
+        .await
+        .context("Failed to list buffers")?;
+
+    println!("🗃️  Buffers of {}: {}", authority, buffers.len());
+    let mut total = 0;
+    for (address, account) in &buffers {
+        println!("   {} {:.9} SOL", address, lamports_to_sol(account.lamports));
+        total += account.lamports;
+    }
+    if !buffers.is_empty() {
+        println!("\n💡 {:.9} SOL reclaimable with `program close <BUFFER>`", lamports_to_sol(total));
+    }
+    Ok(())
+}
+
+async fn set_authority(session: &Session, address: &str, new_authority: Option<Pubkey>) -> Result<()> {
+    let Session {
+        client,
+        payer,
+        authority,
+    } = session;
+    let address = parse_pubkey(address, "address")?;
+    let account = fetch(client, &address)
+        .await?
+        .with_context(|| format!("Account {} not found", address))?;
+    if account.authority() != Some(authority.pubkey()) {
+        bail!("{} is not the authority of {}", authority.pubkey(), address);
+    }
+
+    let instruction = match (&account, new_authority) {
+        (LoaderAccount::Program { .. }, new) => {
+            bpf_loader_upgradeable::set_upgrade_authority(&address, &authority.pubkey(), new.as_ref())
+        }
+        (LoaderAccount::Buffer { .. }, Some(new)) => {
+            bpf_loader_upgradeable::set_buffer_authority(&address, &authority.pubkey(), &new)
+        }
+        (LoaderAccount::Buffer { .. }, None) => bail!("Buffers always need an authority; pass --new-authority"),
+    };
+    let signature = send(client, &[instruction], payer, &[authority])
+        .await
+        .context("Failed to set authority")?;
+    match new_authority {
+        Some(new) => println!("✅ Authority of {} is now {}: {}", address, new, signature),
+        None => println!("🔒 {} is now immutable: {}", address, signature),
+    }
+    Ok(())
+}
+
+async fn close(session: &Session, address: &str, recipient: Option<&str>, bypass_warning: bool) -> Result<()> {
+    let Session {
+        client,
+        payer,
+        authority,
+    } = session;
+    let address = parse_pubkey(address, "address")?;
+    let recipient = match recipient {
+        Some(recipient) => parse_pubkey(recipient, "recipient")?,
+        None => payer.pubkey(),
+    };
+    let account = fetch(client, &address)
+        .await?
+        .with_context(|| format!("Account {} not found", address))?;
+    if account.authority() != Some(authority.pubkey()) {
+        bail!("{} is not the authority of {}", authority.pubkey(), address);
+    }
+
+    let (instruction, lamports) = match &account {
+        LoaderAccount::Buffer { lamports, .. } => (
+            bpf_loader_upgradeable::close(&address, &recipient, &authority.pubkey()),
+            *lamports,
+        ),
+        LoaderAccount::Program {
+            programdata_address,
+            lamports,
+            ..
+        } => {
+            if !bypass_warning {
+                bail!(
+                    "Closing {} is permanent: the program id can never be reused. Pass --bypass-warning to proceed",
+                    address
+                );
+            }
+            (
+                bpf_loader_upgradeable::close_any(
+                    programdata_address,
+                    &recipient,
+                    Some(&authority.pubkey()),
+                    Some(&address),
+                ),
+                *lamports,
+            )
+        }
+    };
+    let signature = send(client, &[instruction], payer, &[authority])
+        .await
+        .context("Close failed")?;
+    println!(
+        "🧹 Closed {}, ~{:.9} SOL to {}: {}",
+        address,
+        lamports_to_sol(lamports),
+        recipient,
+        signature
+    );
+    Ok(())
+}
+
+async fn dump(address: &str, out: &Path, url: String) -> Result<()> {
+    let address = parse_pubkey(address, "address")?;
+    let client = client(url);
+    let account = fetch(&client, &address)
+        .await?
+        .with_context(|| format!("Account {} not found", address))?;
+    std::fs::write(out, account.data()).with_context(|| format!("Failed to write {}", out.display()))?;
+    println!(
+        "💾 Wrote {} bytes to {} (sha256 {})",
+        account.data().len(),
+        out.display(),
+        elf_hash(account.data())
+    );
+    Ok(())
+}
@@ -0,0 +1,274 @@
+//! Deploy and manage programs owned by the BPF upgradeable loader
+//!
+//! A deploy or upgrade first copies the ELF into a buffer account with one
+//! `Write` instruction per chunk, then hands the buffer to the loader, which
+//! moves it into the program's programdata account. Chunks are independent
+//! writes at fixed offsets, so they are sent in parallel, and an interrupted
+//! upload resumes by comparing the buffer with the local file and resending
+//! only the chunks that differ.
+//!
+//! Programdata is zero-padded up to the program's maximum length, so hashes
+//! are taken over the bytes without trailing zeros ([`elf_hash`]); a local
+//! `.so` and its deployed copy then hash the same.
+
+pub mod commands;
+
+use anyhow::{bail, Context, Result};
+use futures_util::{stream, StreamExt};
+use sha2::{Digest, Sha256};
+use solana_sdk::{
+    account::Account,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    clock::Slot,
+    hash::Hash,
+    message::Message,
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use crate::client::{send, LedgerClient};
+
+/// Rounds of writes before an upload gives up; each round resends only the
+/// chunks the buffer does not hold yet
+const MAX_WRITE_ROUNDS: usize = 5;
+
+/// A loader-owned account, decoded
+#[derive(Debug, Clone)]
+pub enum LoaderAccount {
+    Program {
+        program_id: Pubkey,
+        programdata_address: Pubkey,
+        /// `None` once the program is immutable
+        authority: Option<Pubkey>,
+        /// Slot of the last deploy or upgrade
+        slot: Slot,
+        /// Program bytes, zero-padded to the maximum length
+        data: Vec<u8>,
+        /// Program plus programdata rent
+        lamports: u64,
+    },
+    Buffer {
+        address: Pubkey,
+        authority: Option<Pubkey>,
+        data: Vec<u8>,
+        lamports: u64,
+    },
+}
+
+impl LoaderAccount {
+    pub fn authority(&self) -> Option<Pubkey> {
+        match self {
+            LoaderAccount::Program { authority, .. } | LoaderAccount::Buffer { authority, .. } => *authority,
+        }
+    }
+
+    pub fn data(&self) -> &[u8] {
+        match self {
+            LoaderAccount::Program { data, .. } | LoaderAccount::Buffer { data, .. } => data,
+        }
+    }
+}
+
+/// Fetch a loader account, or `None` if nothing lives at `address`
+///
+/// A program id resolves to its programdata account. Programdata addresses
+/// themselves are rejected: every operation goes through the program id.
+pub async fn fetch(client: &impl LedgerClient, address: &Pubkey) -> Result<Option<LoaderAccount>> {
+    let Some(account) = client.get_account(address).await? else {
+        return Ok(None);
+    };
+    if account.owner != bpf_loader_upgradeable::id() {
+        bail!("{} is not owned by the upgradeable loader (owner {})", address, account.owner);
+    }
+    match loader_state(address, &account)? {
+        UpgradeableLoaderState::Buffer { authority_address } => Ok(Some(LoaderAccount::Buffer {
+            address: *address,
+            authority: authority_address,
+            data: account.data[UpgradeableLoaderState::size_of_buffer_metadata()..].to_vec(),
+            lamports: account.lamports,
+        })),
+        UpgradeableLoaderState::Program { programdata_address } => {
+            let programdata = client
+                .get_account(&programdata_address)
+                .await?
+                .with_context(|| format!("Programdata account {} of {} is missing", programdata_address, address))?;
+            let UpgradeableLoaderState::ProgramData {
+                slot,
+                upgrade_authority_address,
+            } = loader_state(&programdata_address, &programdata)?
+            else {
+                bail!("{} is not a programdata account", programdata_address);
+            };
+            Ok(Some(LoaderAccount::Program {
+                program_id: *address,
+                programdata_address,
+                authority: upgrade_authority_address,
+                slot,
+                data: programdata.data[UpgradeableLoaderState::size_of_programdata_metadata()..].to_vec(),
+                lamports: account.lamports + programdata.lamports,
+            }))
+        }
+        UpgradeableLoaderState::ProgramData { .. } => {
+            bail!("{} is a programdata account, pass its program id instead", address)
+        }
+        UpgradeableLoaderState::Uninitialized => bail!("{} is an uninitialized loader account", address),
+    }
+}
+
+fn loader_state(address: &Pubkey, account: &Account) -> Result<UpgradeableLoaderState> {
+    account
+        .deserialize_data()
+        .with_context(|| format!("Failed to decode loader state of {}", address))
+}
+
+/// Program bytes without the zero padding the loader appends
+pub fn trim_padding(data: &[u8]) -> &[u8] {
+    let end = data.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+    &data[..end]
+}
+
+/// SHA-256 (hex) of program bytes, ignoring trailing zero padding
+pub fn elf_hash(data: &[u8]) -> String {
+    Sha256::digest(trim_padding(data))
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Check that a program runs the bytes with this [`elf_hash`]; returns the
+/// slot it was deployed at
+pub async fn verify_deployed(client: &impl LedgerClient, program_id: &Pubkey, expected: &str) -> Result<Slot> {
+    let Some(LoaderAccount::Program { data, slot, .. }) = fetch(client, program_id).await? else {
+        bail!("{} is not a program", program_id);
+    };
+    let deployed = elf_hash(&data);
+    if deployed != expected {
+        bail!("Deployed program hashes to {}, expected {}", deployed, expected);
+    }
+    println!("✅ Verified: deployed ELF matches (sha256 {}, slot {})", deployed, slot);
+    Ok(slot)
+}
+
+/// Largest chunk that fits in one `Write` transaction
+pub fn chunk_size(payer: &Pubkey, authority: &Pubkey, buffer: &Pubkey) -> usize {
+    let empty = bpf_loader_upgradeable::write(buffer, authority, 0, Vec::new());
+    let message = Message::new_with_blockhash(&[empty], Some(payer), &Hash::default());
+    let signatures = message.header.num_required_signatures as usize;
+    let size = 1 + 64 * signatures + message.serialize().len();
+    // The instruction data length prefix grows from 1 to 2 bytes past 127
+    PACKET_DATA_SIZE - size - 1
+}
+
+/// Offsets of the chunks where `buffer` does not hold `elf` yet
+pub fn stale_chunks(elf: &[u8], buffer: &[u8], chunk: usize) -> Vec<usize> {
+    (0..elf.len())
+        .step_by(chunk)
+        .filter(|&offset| {
+            let end = (offset + chunk).min(elf.len());
+            buffer.get(offset..end) != Some(&elf[offset..end])
+        })
+        .collect()
+}
+
+/// Make `buffer` hold exactly `elf`, creating the account if needed
+///
+/// An existing buffer is resumed: only chunks that differ from `elf` are
+/// written. Writes run `concurrency` at a time; failed chunks are retried in
+/// the next round. Returns once every chunk of the buffer matches.
+pub async fn prepare_buffer(
+    client: &impl LedgerClient,
+    payer: &Keypair,
+    authority: &Keypair,
+    buffer: &Keypair,
+    elf: &[u8],
+    concurrency: usize,
+) -> Result<()> {
+    let address = buffer.pubkey();
+    match fetch(client, &address).await? {
+        None => {
+            let len = UpgradeableLoaderState::size_of_buffer(elf.len());
+            let lamports = client.get_minimum_balance_for_rent_exemption(len).await?;
+            let instructions =
+                bpf_loader_upgradeable::create_buffer(&payer.pubkey(), &address, &authority.pubkey(), lamports, elf.len())?;
+            send(client, &instructions, payer, &[buffer])
+                .await
+                .context("Failed to create buffer")?;
+            println!("🆕 Created buffer {} ({} bytes)", address, elf.len());
+        }
+        Some(LoaderAccount::Buffer {
+            authority: buffer_authority,
+            data,
+            ..
+        }) => {
+            if buffer_authority != Some(authority.pubkey()) {
+                bail!(
+                    "Buffer {} belongs to {}, not {}",
+                    address,
+                    buffer_authority.map_or_else(|| "nobody".to_string(), |a| a.to_string()),
+                    authority.pubkey()
+                );
+            }
+            if data.len() != elf.len() {
+                bail!(
+                    "Buffer {} holds {} bytes but the program is {} bytes; close it or use another buffer",
+                    address,
+                    data.len(),
+                    elf.len()
+                );
+            }
+            println!("♻️  Resuming buffer {}", address);
+        }
+        Some(LoaderAccount::Program { .. }) => bail!("{} is a program, not a buffer", address),
+    }
+
+    let chunk = chunk_size(&payer.pubkey(), &authority.pubkey(), &address);
+    let total = elf.len().div_ceil(chunk);
+    for round in 0.. {
+        let data = match fetch(client, &address).await? {
+            Some(LoaderAccount::Buffer { data, .. }) => data,
+            _ => bail!("Buffer {} disappeared during the upload", address),
+        };
+        let stale = stale_chunks(elf, &data, chunk);
+        if stale.is_empty() {
+            break;
+        }
+        if round == MAX_WRITE_ROUNDS {
+            bail!(
+                "{} of {} chunk(s) still missing after {} rounds; rerun with the same buffer to resume",
+                stale.len(),
+                total,
+                MAX_WRITE_ROUNDS
+            );
+        }
+        println!("⬆️  Writing {} of {} chunk(s) of {} bytes", stale.len(), total, chunk);
+
+        let step = stale.len().div_ceil(10);
+        let mut done = 0;
+        let mut failed = 0;
+        let mut writes = stream::iter(stale.iter().copied())
+            .map(|offset| {
+                let end = (offset + chunk).min(elf.len());
+                let write =
+                    bpf_loader_upgradeable::write(&address, &authority.pubkey(), offset as u32, elf[offset..end].to_vec());
+                async move { (offset, send(client, &[write], payer, &[authority]).await) }
+            })
+            .buffer_unordered(concurrency.max(1));
+        while let Some((offset, result)) = writes.next().await {
+            done += 1;
+            if let Err(e) = result {
+                failed += 1;
+                eprintln!("⚠️  Chunk at offset {}: {}", offset, e);
+            }
+            if done % step == 0 || done == stale.len() {
+                println!("   {}/{} sent", done, stale.len());
+            }
+        }
+        if failed > 0 {
+            println!("🔁 {} write(s) failed, checking the buffer again", failed);
+        }
+    }
+
+    println!("✅ Buffer holds the program (sha256 {})", elf_hash(elf));
+    Ok(())
+}
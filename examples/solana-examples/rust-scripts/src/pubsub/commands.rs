@@ -0,0 +1,176 @@
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use solana_client::rpc_config::RpcTransactionLogsFilter;
+use solana_sdk::{account::Account, native_token::lamports_to_sol, program_pack::Pack, pubkey::Pubkey};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use super::{Notification, Subscriber, Subscription, DEFAULT_WS_URL};
+use crate::gdx::accounts::{CollateralVault, InsuranceFund, Position};
+use crate::gdx::{ui_amount, AnchorAccount, BASE_DECIMALS, PRICE_DECIMALS, QUOTE_DECIMALS};
+
+#[derive(Subcommand)]
+pub enum WatchCommand {
+    /// Stream every change to one account
+    Account {
+        address: String,
+        /// Validator PubSub endpoint
+        #[arg(long, default_value = DEFAULT_WS_URL)]
+        ws_url: String,
+    },
+    /// Stream changes to every account owned by a program
+    Program {
+        program_id: String,
+        /// Validator PubSub endpoint
+        #[arg(long, default_value = DEFAULT_WS_URL)]
+        ws_url: String,
+    },
+    /// Stream transaction logs
+    Logs {
+        /// Only transactions that mention this address (e.g. a program id)
+        #[arg(long)]
+        mentions: Option<String>,
+        /// Include vote transactions when no --mentions filter is given
+        #[arg(long)]
+        votes: bool,
+        /// Validator PubSub endpoint
+        #[arg(long, default_value = DEFAULT_WS_URL)]
+        ws_url: String,
+    },
+}
+
+pub async fn run(command: WatchCommand) -> Result<()> {
+    let (ws_url, subscription) = match command {
+        WatchCommand::Account { address, ws_url } => (ws_url, Subscription::Account(parse_pubkey(&address)?)),
+        WatchCommand::Program { program_id, ws_url } => (
+            ws_url,
+            Subscription::Program {
+                program_id: parse_pubkey(&program_id)?,
+                filters: Vec::new(),
+            },
+        ),
+        WatchCommand::Logs { mentions, votes, ws_url } => {
+            let filter = match mentions {
+                Some(address) => RpcTransactionLogsFilter::Mentions(vec![parse_pubkey(&address)?.to_string()]),
+                None if votes => RpcTransactionLogsFilter::AllWithVotes,
+                None => RpcTransactionLogsFilter::All,
+            };
+            (ws_url, Subscription::Logs(filter))
+        }
+    };
+
+    println!("👀 Watching {} (Ctrl-C to stop)", ws_url);
+    let mut notifications = Subscriber::new(ws_url).subscribe(subscription).spawn();
+    // Last seen lamports per account, to show balance deltas
+    let mut balances: HashMap<Pubkey, u64> = HashMap::new();
+
+    loop {
+        let notification = tokio::select! {
+            notification = notifications.recv() => match notification {
+                Some(notification) => notification,
+                None => return Ok(()),
+            },
+            _ = tokio::signal::ctrl_c() => {
+                println!("\n🛑 Stopped");
+                return Ok(());
+            }
+        };
+
+        match notification {
+            Notification::Connected { attempt: 0 } => println!("✅ Subscribed"),
+            Notification::Connected { .. } => println!("🔄 Reconnected and resubscribed"),
+            Notification::Disconnected { error, retry_in } => {
+                println!("⚠️  Disconnected ({}), retrying in {:?}", error, retry_in);
+            }
+            Notification::Account { address, slot, account }
+            | Notification::Program {
+                address, slot, account, ..
+            } => {
+                let delta = balances
+                    .insert(address, account.lamports)
+                    .filter(|&previous| previous != account.lamports)
+                    .map(|previous| {
+                        format!(
+                            " ({:+} SOL)",
+                            lamports_to_sol(account.lamports) - lamports_to_sol(previous)
+                        )
+                    })
+                    .unwrap_or_default();
+                println!("📦 [slot {}] {}{}", slot, address, delta);
+                println!("   {}", describe(&account));
+            }
+            Notification::Logs {
+                slot,
+                signature,
+                err,
+                logs,
+            } => {
+                let status = if err.is_some() { "❌" } else { "✅" };
+                println!("📜 [slot {}] {} {}", slot, status, signature);
+                if let Some(err) = err {
+                    println!("   Error: {}", err);
+                }
+                for line in logs {
+                    println!("   {}", line);
+                }
+            }
+            Notification::Slot(info) => println!("⏱️  Slot {} (root {})", info.slot, info.root),
+            Notification::Signature { signature, slot, err } => match err {
+                None => println!("✅ [slot {}] {} confirmed", slot, signature),
+                Some(err) => println!("❌ [slot {}] {} failed: {}", slot, signature, err),
+            },
+        }
+    }
+}
+
+fn parse_pubkey(value: &str) -> Result<Pubkey> {
+    Pubkey::from_str(value).with_context(|| format!("Invalid address: {}", value))
+}
+
+/// One-line summary of an account: GDX accounts and SPL token accounts are
+/// decoded, anything else shows its balance and size
+fn describe(account: &Account) -> String {
+    let data = &account.data;
+    if let Ok(position) = Position::decode(data) {
+        return format!(
+            "Position {} {} {} {:.6} @ {:.6}, margin {:.6} USDT, liq {:.6}",
+            position.owner,
+            position.symbol,
+            position.side,
+            ui_amount(position.size as i128, BASE_DECIMALS),
+            ui_amount(position.entry_price as i128, PRICE_DECIMALS),
+            ui_amount(position.margin as i128, QUOTE_DECIMALS),
+            ui_amount(position.liquidation_price as i128, PRICE_DECIMALS),
+        );
+    }
+    if let Ok(vault) = CollateralVault::decode(data) {
+        return format!(
+            "CollateralVault {} total {:.6} USDT, available {:.6}, locked {:.6}",
+            vault.owner,
+            ui_amount(vault.total_balance as i128, QUOTE_DECIMALS),
+            ui_amount(vault.available_balance as i128, QUOTE_DECIMALS),
+            ui_amount(vault.locked_balance as i128, QUOTE_DECIMALS),
+        );
+    }
+    if let Ok(fund) = InsuranceFund::decode(data) {
+        return format!(
+            "InsuranceFund balance {:.6} USDT, bad debt covered {:.6}",
+            ui_amount(fund.balance as i128, QUOTE_DECIMALS),
+            ui_amount(fund.total_bad_debt_covered as i128, QUOTE_DECIMALS),
+        );
+    }
+    if account.owner == spl_token::id() {
+        if let Ok(token) = spl_token::state::Account::unpack(data) {
+            return format!("Token account of {} (mint {}): {} raw", token.owner, token.mint, token.amount);
+        }
+        if let Ok(mint) = spl_token::state::Mint::unpack(data) {
+            return format!("Mint supply {} raw, {} decimals", mint.supply, mint.decimals);
+        }
+    }
+    format!(
+        "{} SOL, {} bytes, owner {}",
+        lamports_to_sol(account.lamports),
+        data.len(),
+        account.owner
+    )
+}
@@ -0,0 +1,311 @@
+//! Push updates from the validator's PubSub WebSocket
+//!
+//! A [`Subscriber`] collects subscriptions and [`Subscriber::spawn`] runs
+//! them on a background task that owns the connection. When the socket
+//! drops, the task reconnects with exponential backoff and subscribes again
+//! to everything that is still live; [`Notification::Connected`] and
+//! [`Notification::Disconnected`] mark the gaps, so consumers that must not
+//! miss state can re-read it after a reconnect.
+
+pub mod commands;
+
+use anyhow::{Context, Result};
+use futures_util::{future, stream::select_all, stream::BoxStream, StreamExt};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    nonblocking::pubsub_client::PubsubClient,
+    rpc_config::{
+        RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSignatureSubscribeConfig, RpcTransactionLogsConfig,
+        RpcTransactionLogsFilter,
+    },
+    rpc_filter::RpcFilterType,
+    rpc_response::{RpcSignatureResult, SlotInfo},
+};
+use solana_sdk::{
+    account::Account, clock::Slot, commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature,
+    transaction::TransactionError,
+};
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Validator PubSub endpoint (`8900:8900` in docker-compose)
+pub const DEFAULT_WS_URL: &str = "ws://127.0.0.1:8900";
+
+const MIN_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+pub enum Subscription {
+    /// `accountSubscribe`: every change to one account
+    Account(Pubkey),
+    /// `programSubscribe`: every change to accounts owned by a program,
+    /// optionally narrowed by `getProgramAccounts`-style filters
+    Program {
+        program_id: Pubkey,
+        filters: Vec<RpcFilterType>,
+    },
+    /// `logsSubscribe`: logs of every committed transaction matching the filter
+    Logs(RpcTransactionLogsFilter),
+    /// `slotSubscribe`: every processed slot
+    Slot,
+    /// `signatureSubscribe`: one notification when the transaction lands
+    Signature(Signature),
+}
+
+#[derive(Debug, Clone)]
+pub enum Notification {
+    Account {
+        address: Pubkey,
+        slot: Slot,
+        account: Account,
+    },
+    Program {
+        program_id: Pubkey,
+        address: Pubkey,
+        slot: Slot,
+        account: Account,
+    },
+    Logs {
+        slot: Slot,
+        signature: String,
+        err: Option<TransactionError>,
+        logs: Vec<String>,
+    },
+    Slot(SlotInfo),
+    Signature {
+        signature: Signature,
+        slot: Slot,
+        err: Option<TransactionError>,
+    },
+    /// (Re)connected and subscribed; `attempt` is 0 for the first connection
+    Connected { attempt: u32 },
+    /// Connection lost; the next attempt starts after `retry_in`
+    Disconnected { error: String, retry_in: Duration },
+}
+
+/// Subscriptions to run over one reconnecting connection
+#[derive(Debug, Clone)]
+pub struct Subscriber {
+    url: String,
+    commitment: CommitmentConfig,
+    subscriptions: Vec<Subscription>,
+}
+
+impl Subscriber {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            commitment: CommitmentConfig::confirmed(),
+            subscriptions: Vec::new(),
+        }
+    }
+
+    pub fn commitment(mut self, commitment: CommitmentConfig) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    pub fn subscribe(mut self, subscription: Subscription) -> Self {
+        self.subscriptions.push(subscription);
+        self
+    }
+
+    /// Connect in the background and stream notifications
+    ///
+    /// The task stops when the returned [`Notifications`] is dropped, or once
+    /// every subscription is a `Signature` that has already been notified.
+    pub fn spawn(self) -> Notifications {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let task = tokio::spawn(run(self, sender));
+        Notifications { receiver, task }
+    }
+}
+
+/// Receiving end of a spawned [`Subscriber`]
+pub struct Notifications {
+    receiver: mpsc::UnboundedReceiver<Notification>,
+    task: JoinHandle<()>,
+}
+
+impl Notifications {
+    /// Next notification, or `None` once the subscriber has stopped
+    pub async fn recv(&mut self) -> Option<Notification> {
+        self.receiver.recv().await
+    }
+}
+
+impl Drop for Notifications {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+enum Exit {
+    /// Socket closed or errored; reconnect
+    Closed,
+    /// Nothing left to listen for, or nobody listening
+    Done,
+}
+
+async fn run(mut subscriber: Subscriber, sender: mpsc::UnboundedSender<Notification>) {
+    let mut backoff = MIN_BACKOFF;
+    let mut attempt = 0;
+    loop {
+        let error = match session(&mut subscriber, &sender, attempt, &mut backoff).await {
+            Ok(Exit::Done) => return,
+            Ok(Exit::Closed) => "connection closed".to_string(),
+            Err(e) => format!("{:#}", e),
+        };
+        let notification = Notification::Disconnected {
+            error,
+            retry_in: backoff,
+        };
+        if sender.send(notification).is_err() {
+            return;
+        }
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+        attempt += 1;
+    }
+}
+
+/// One connection: subscribe to everything live, forward until the socket drops
+async fn session(
+    subscriber: &mut Subscriber,
+    sender: &mpsc::UnboundedSender<Notification>,
+    attempt: u32,
+    backoff: &mut Duration,
+) -> Result<Exit> {
+    if subscriber.subscriptions.is_empty() {
+        return Ok(Exit::Done);
+    }
+    let client = PubsubClient::new(&subscriber.url)
+        .await
+        .with_context(|| format!("Failed to connect to {}", subscriber.url))?;
+
+    let commitment = subscriber.commitment;
+    let account_config = RpcAccountInfoConfig {
+        encoding: Some(UiAccountEncoding::Base64),
+        commitment: Some(commitment),
+        ..RpcAccountInfoConfig::default()
+    };
+
+    let mut streams: Vec<BoxStream<'_, Notification>> = Vec::new();
+    for subscription in &subscriber.subscriptions {
+        let stream = match subscription {
+            Subscription::Account(address) => {
+                let address = *address;
+                let (stream, _) = client
+                    .account_subscribe(&address, Some(account_config.clone()))
+                    .await
+                    .with_context(|| format!("accountSubscribe {} failed", address))?;
+                stream
+                    .filter_map(move |response| {
+                        future::ready(response.value.decode().map(|account| Notification::Account {
+                            address,
+                            slot: response.context.slot,
+                            account,
+                        }))
+                    })
+                    .boxed()
+            }
+            Subscription::Program { program_id, filters } => {
+                let program_id = *program_id;
+                let config = RpcProgramAccountsConfig {
+                    filters: (!filters.is_empty()).then(|| filters.clone()),
+                    account_config: account_config.clone(),
+                    ..RpcProgramAccountsConfig::default()
+                };
+                let (stream, _) = client
+                    .program_subscribe(&program_id, Some(config))
+                    .await
+                    .with_context(|| format!("programSubscribe {} failed", program_id))?;
+                stream
+                    .filter_map(move |response| {
+                        let keyed = response.value;
+                        let notification = match (Pubkey::from_str(&keyed.pubkey), keyed.account.decode()) {
+                            (Ok(address), Some(account)) => Some(Notification::Program {
+                                program_id,
+                                address,
+                                slot: response.context.slot,
+                                account,
+                            }),
+                            _ => None,
+                        };
+                        future::ready(notification)
+                    })
+                    .boxed()
+            }
+            Subscription::Logs(filter) => {
+                let config = RpcTransactionLogsConfig {
+                    commitment: Some(commitment),
+                };
+                let (stream, _) = client
+                    .logs_subscribe(filter.clone(), config)
+                    .await
+                    .context("logsSubscribe failed")?;
+                stream
+                    .map(|response| Notification::Logs {
+                        slot: response.context.slot,
+                        signature: response.value.signature,
+                        err: response.value.err,
+                        logs: response.value.logs,
+                    })
+                    .boxed()
+            }
+            Subscription::Slot => {
+                let (stream, _) = client.slot_subscribe().await.context("slotSubscribe failed")?;
+                stream.map(Notification::Slot).boxed()
+            }
+            Subscription::Signature(signature) => {
+                let signature = *signature;
+                let config = RpcSignatureSubscribeConfig {
+                    commitment: Some(commitment),
+                    enable_received_notification: Some(false),
+                };
+                let (stream, _) = client
+                    .signature_subscribe(&signature, Some(config))
+                    .await
+                    .with_context(|| format!("signatureSubscribe {} failed", signature))?;
+                stream
+                    .filter_map(move |response| {
+                        let notification = match response.value {
+                            RpcSignatureResult::ProcessedSignature(result) => Some(Notification::Signature {
+                                signature,
+                                slot: response.context.slot,
+                                err: result.err,
+                            }),
+                            RpcSignatureResult::ReceivedSignature(_) => None,
+                        };
+                        future::ready(notification)
+                    })
+                    .boxed()
+            }
+        };
+        streams.push(stream);
+    }
+
+    *backoff = MIN_BACKOFF;
+    if sender.send(Notification::Connected { attempt }).is_err() {
+        return Ok(Exit::Done);
+    }
+
+    let mut merged = select_all(streams);
+    while let Some(notification) = merged.next().await {
+        // Signature subscriptions are one-shot: the server drops them after
+        // notifying, so they must not be resubscribed after a reconnect
+        let mut finished = false;
+        if let Notification::Signature { signature, .. } = &notification {
+            subscriber
+                .subscriptions
+                .retain(|s| !matches!(s, Subscription::Signature(sig) if sig == signature));
+            finished = subscriber.subscriptions.is_empty();
+        }
+        if sender.send(notification).is_err() || finished {
+            return Ok(Exit::Done);
+        }
+    }
+    Ok(Exit::Closed)
+}
@@ -0,0 +1,315 @@
+use anyhow::{bail, Context, Result};
+use clap::Subcommand;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Instant;
+
+use super::netting::NetPositionCalculator;
+use super::packing::{pack, Batch, Limits, SettlementPrograms};
+use super::{Fill, FillSide, TradeRequest};
+use crate::client::sender::{send_with_retry, SendConfig};
+use crate::client::LedgerClient;
+use crate::gdx::{ui_amount, BASE_DECIMALS, PRICE_DECIMALS, QUOTE_DECIMALS};
+use crate::keypair::{load_keypair, DEFAULT_KEYPAIR_PATH};
+
+#[derive(Subcommand)]
+pub enum SettlementCommand {
+    /// Net a JSONL file of engine fills and pack it into settlement batches
+    Net {
+        /// One `TradeRequest` JSON object per line
+        fills: PathBuf,
+        /// Settlement program id
+        #[arg(long)]
+        settlement_program: String,
+        /// Collateral vault program id
+        #[arg(long)]
+        vault_program: String,
+        /// Store each batch as <DIR>/<batch id>.json
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Rebuild a stored batch by id and optionally submit it
+    Replay {
+        /// Batch id
+        id: String,
+        /// Directory the batch was stored in
+        #[arg(long, default_value = "batches")]
+        dir: PathBuf,
+        /// Settlement program id
+        #[arg(long)]
+        settlement_program: String,
+        /// Collateral vault program id
+        #[arg(long)]
+        vault_program: String,
+        /// Submit the transaction instead of only printing it
+        #[arg(long)]
+        send: bool,
+        /// Relayer keypair
+        #[arg(long, default_value = DEFAULT_KEYPAIR_PATH)]
+        keypair: String,
+        /// RPC endpoint
+        #[arg(long, default_value = "http://127.0.0.1:8899")]
+        url: String,
+    },
+    /// Measure netting and packing throughput on synthetic fills
+    Bench {
+        /// Number of fills
+        #[arg(long, default_value = "100000")]
+        fills: usize,
+        /// Number of distinct users
+        #[arg(long, default_value = "1000")]
+        users: usize,
+        /// Number of markets
+        #[arg(long, default_value = "4")]
+        markets: usize,
+        /// RNG seed, for repeatable runs
+        #[arg(long, default_value = "42")]
+        seed: u64,
+    },
+}
+
+pub async fn run(command: SettlementCommand) -> Result<()> {
+    match command {
+        SettlementCommand::Net {
+            fills,
+            settlement_program,
+            vault_program,
+            out,
+        } => {
+            let programs = parse_programs(&settlement_program, &vault_program)?;
+            net(&fills, &programs, out.as_deref())
+        }
+        SettlementCommand::Replay {
+            id,
+            dir,
+            settlement_program,
+            vault_program,
+            send,
+            keypair,
+            url,
+        } => {
+            let programs = parse_programs(&settlement_program, &vault_program)?;
+            let relayer = load_keypair(&keypair)?;
+            let client = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+            replay(&client, &id, &dir, &programs, &relayer, send).await
+        }
+        SettlementCommand::Bench {
+            fills,
+            users,
+            markets,
+            seed,
+        } => bench(fills, users, markets, seed),
+    }
+}
+
+fn parse_programs(settlement: &str, vault: &str) -> Result<SettlementPrograms> {
+    Ok(SettlementPrograms {
+        settlement: Pubkey::from_str(settlement).context("Invalid settlement program id")?,
+        vault: Pubkey::from_str(vault).context("Invalid vault program id")?,
+    })
+}
+
+fn load_fills(path: &Path) -> Result<Vec<Fill>> {
+    let raw = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    raw.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let request: TradeRequest =
+                serde_json::from_str(line).with_context(|| format!("Line {}: invalid fill", i + 1))?;
+            Fill::from_request(&request, i).with_context(|| format!("Line {}", i + 1))
+        })
+        .collect()
+}
+
+fn print_batches(batches: &[Batch]) {
+    println!(
+        "   {:<44} {:>7} {:>7} {:>9} {:>6}",
+        "Batch", "Entries", "Trades", "CU", "Bytes"
+    );
+    for batch in batches {
+        println!(
+            "   {:<44} {:>7} {:>7} {:>9} {:>6}",
+            batch.id,
+            batch.entries.len(),
+            batch.trade_count(),
+            batch.compute_units,
+            batch.estimated_size
+        );
+    }
+}
+
+fn net(path: &Path, programs: &SettlementPrograms, out: Option<&Path>) -> Result<()> {
+    println!("🧮 Netting fills from {}", path.display());
+    let fills = load_fills(path)?;
+    let mut calculator = NetPositionCalculator::new();
+    calculator.extend(&fills);
+
+    let positions = calculator.net_positions()?;
+    println!("\n📊 Net positions ({} fills → {} entries):", fills.len(), positions.len());
+    for position in &positions {
+        println!(
+            "   {} {:<10} size {:>+14.6} quote {:>+16.6} ({} trades)",
+            position.user,
+            position.symbol,
+            ui_amount(position.size_delta as i128, BASE_DECIMALS),
+            ui_amount(position.quote_delta as i128, QUOTE_DECIMALS),
+            position.trade_ids.len()
+        );
+    }
+
+    println!("\n💸 Collateral transfers:");
+    for transfer in calculator.collateral_transfers()? {
+        println!(
+            "   {} {:>+16.6} USDT",
+            transfer.user,
+            ui_amount(transfer.amount as i128, QUOTE_DECIMALS)
+        );
+    }
+
+    for (symbol, imbalance) in calculator.market_imbalance() {
+        if imbalance != 0 {
+            println!(
+                "   ⚠️  {} does not net to zero ({:+.6}) — counterparties missing from this file",
+                symbol,
+                ui_amount(imbalance, BASE_DECIMALS)
+            );
+        }
+    }
+
+    let batches = pack(positions, programs, &Limits::default())?;
+    println!("\n📦 {} batch(es):", batches.len());
+    print_batches(&batches);
+
+    if let Some(dir) = out {
+        for batch in &batches {
+            batch.save(dir)?;
+        }
+        println!("\n💾 Stored {} batch(es) in {}", batches.len(), dir.display());
+    }
+
+    Ok(())
+}
+
+async fn replay(
+    client: &impl LedgerClient,
+    id: &str,
+    dir: &Path,
+    programs: &SettlementPrograms,
+    relayer: &Keypair,
+    send: bool,
+) -> Result<()> {
+    let batch = Batch::load(dir, id)?;
+    println!("🔁 Batch {}", batch.id);
+    println!("   Merkle Root: {}", batch.merkle_root);
+    println!("   Entries: {} ({} trades)", batch.entries.len(), batch.trade_count());
+    println!("   Compute Units: {}", batch.compute_units);
+
+    let instructions = batch.instructions(programs, &relayer.pubkey())?;
+
+    let blockhash = client.get_latest_blockhash().await?;
+    let tx = Transaction::new_signed_with_payer(&instructions, Some(&relayer.pubkey()), &[relayer], blockhash);
+    let size = 1 + 64 * tx.signatures.len() + tx.message_data().len();
+    println!("   Transaction Size: {} bytes (estimated {})", size, batch.estimated_size);
+    if size > Limits::default().max_tx_bytes {
+        bail!("Transaction is {} bytes, over the packet limit", size);
+    }
+
+    if !send {
+        println!("\n💡 Pass --send to submit this batch");
+        return Ok(());
+    }
+
+    println!("\n📤 Sending...");
+    let report = send_with_retry(client, &instructions, relayer, &[], &SendConfig::default()).await;
+    report.print();
+    let signature = report.signature().context("Settlement transaction was not confirmed")?;
+    println!("✅ Settled: {}", signature);
+    Ok(())
+}
+
+fn bench(fill_count: usize, users: usize, markets: usize, seed: u64) -> Result<()> {
+    if users < 2 || markets == 0 {
+        bail!("Need at least 2 users and 1 market");
+    }
+    println!("⏱️  Settlement benchmark: {} fills, {} users, {} markets", fill_count, users, markets);
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let wallets: Vec<Pubkey> = (0..users).map(|_| Pubkey::new_from_array(rng.gen())).collect();
+    let symbols: Vec<String> = (0..markets).map(|i| format!("MKT{}-USDT", i)).collect();
+    let programs = SettlementPrograms {
+        settlement: Pubkey::new_from_array(rng.gen()),
+        vault: Pubkey::new_from_array(rng.gen()),
+    };
+
+    // Every match produces a long fill and the opposite short fill
+    let mut fills = Vec::with_capacity(fill_count);
+    while fills.len() < fill_count {
+        let symbol = &symbols[rng.gen_range(0..markets)];
+        let buyer = rng.gen_range(0..users);
+        let seller = (buyer + rng.gen_range(1..users)) % users;
+        let quantity = rng.gen_range(1..=1_000) * 10u64.pow(BASE_DECIMALS - 3);
+        let price = rng.gen_range(90_000..=110_000) * 10u64.pow(PRICE_DECIMALS - 3);
+        let trade_id = format!("bench-{}", fills.len() / 2);
+        for (user, side) in [(buyer, FillSide::Long), (seller, FillSide::Short)] {
+            fills.push(Fill {
+                trade_id: trade_id.clone(),
+                user: wallets[user],
+                symbol: symbol.clone(),
+                side,
+                quantity,
+                price,
+                timestamp: 0,
+            });
+        }
+    }
+    fills.truncate(fill_count);
+
+    let start = Instant::now();
+    let mut calculator = NetPositionCalculator::new();
+    calculator.extend(&fills);
+    let positions = calculator.net_positions()?;
+    let netting = start.elapsed();
+
+    let start = Instant::now();
+    let entries = positions.len();
+    let batches = pack(positions, &programs, &Limits::default())?;
+    let packing = start.elapsed();
+
+    let total = netting + packing;
+    println!("\n📊 Results:");
+    println!(
+        "   Netting: {:.1} ms ({:.0} fills/s)",
+        netting.as_secs_f64() * 1000.0,
+        fill_count as f64 / netting.as_secs_f64()
+    );
+    println!(
+        "   Packing: {:.1} ms ({:.0} entries/s)",
+        packing.as_secs_f64() * 1000.0,
+        entries as f64 / packing.as_secs_f64()
+    );
+    println!(
+        "   Total: {:.1} ms ({:.0} fills/s)",
+        total.as_secs_f64() * 1000.0,
+        fill_count as f64 / total.as_secs_f64()
+    );
+    println!(
+        "   Compression: {} fills → {} entries → {} transactions ({:.1} fills/tx)",
+        fill_count,
+        entries,
+        batches.len(),
+        fill_count as f64 / batches.len().max(1) as f64
+    );
+    let avg_entries = entries as f64 / batches.len().max(1) as f64;
+    println!("   Average Batch: {:.1} entries", avg_entries);
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use sha2::{Digest, Sha256};
+
+/// Leaf hash of a settled trade id
+pub fn leaf(trade_id: &str) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0u8]);
+    hasher.update(trade_id.as_bytes());
+    hasher.finalize().into()
+}
+
+fn node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([1u8]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Merkle root over `leaves`, duplicating the last node of odd levels
+///
+/// Leaves and inner nodes are domain-separated so a leaf can never be
+/// passed off as an inner node. An empty set hashes to all zeroes.
+pub fn root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    if leaves.is_empty() {
+        return [0u8; 32];
+    }
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| node(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+            .collect();
+    }
+    level[0]
+}
+
+/// Sibling path proving `leaves[index]` is under `root(leaves)`
+pub fn proof(leaves: &[[u8; 32]], mut index: usize) -> Vec<[u8; 32]> {
+    let mut path = Vec::new();
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        let sibling = if index.is_multiple_of(2) { index + 1 } else { index - 1 };
+        path.push(*level.get(sibling).unwrap_or(&level[index]));
+        level = level
+            .chunks(2)
+            .map(|pair| node(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+            .collect();
+        index /= 2;
+    }
+    path
+}
+
+/// Check a proof produced by [`proof`]
+pub fn verify(root_hash: &[u8; 32], leaf_hash: &[u8; 32], mut index: usize, path: &[[u8; 32]]) -> bool {
+    let mut hash = *leaf_hash;
+    for sibling in path {
+        hash = if index.is_multiple_of(2) {
+            node(&hash, sibling)
+        } else {
+            node(sibling, &hash)
+        };
+        index /= 2;
+    }
+    &hash == root_hash
+}
@@ -0,0 +1,105 @@
+//! Settlement netting and batch building
+//!
+//! Takes matched fills from the matching engine, nets them per (user,
+//! market), derives the quote collateral each user pays or receives, and
+//! packs the result into as few settlement transactions as the size and
+//! compute limits allow.
+//!
+//! Fills → [`netting::NetPositionCalculator`] → [`packing::pack`] → [`packing::Batch`]
+//!
+//! Every batch is atomic (one transaction, and all of a user's entries land
+//! in the same batch) and identified by a content hash, so it can be stored
+//! and replayed by id.
+
+pub mod commands;
+pub mod merkle;
+pub mod netting;
+pub mod packing;
+pub mod relayer;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+use crate::gdx::{BASE_DECIMALS, PRICE_DECIMALS};
+
+/// Fill direction as reported by the matching engine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum FillSide {
+    Long,
+    Short,
+}
+
+/// A matched fill, in the shape the mock engine posts to the relayer
+/// (`TradeRequest` in `settlement-integration.ts`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeRequest {
+    pub user_id: String,
+    pub symbol: String,
+    pub side: FillSide,
+    pub quantity: f64,
+    pub price: f64,
+    pub timestamp: i64,
+    pub trade_id: Option<String>,
+}
+
+/// A fill in on-chain units
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fill {
+    pub trade_id: String,
+    pub user: Pubkey,
+    pub symbol: String,
+    pub side: FillSide,
+    /// Base asset amount, `BASE_DECIMALS` fixed point
+    pub quantity: u64,
+    /// Quote per base, `PRICE_DECIMALS` fixed point
+    pub price: u64,
+    pub timestamp: i64,
+}
+
+impl Fill {
+    /// Convert an engine fill; the engine's `user_id` must be a wallet address
+    pub fn from_request(request: &TradeRequest, index: usize) -> Result<Self> {
+        let trade_id = request
+            .trade_id
+            .clone()
+            .unwrap_or_else(|| format!("{}-{}", request.timestamp, index));
+        let user = Pubkey::from_str(&request.user_id)
+            .with_context(|| format!("Trade {}: user_id {:?} is not a wallet address", trade_id, request.user_id))?;
+        let quantity = fixed(request.quantity, BASE_DECIMALS).with_context(|| format!("Trade {}: invalid quantity", trade_id))?;
+        let price = fixed(request.price, PRICE_DECIMALS).with_context(|| format!("Trade {}: invalid price", trade_id))?;
+        Ok(Self {
+            trade_id,
+            user,
+            symbol: request.symbol.clone(),
+            side: request.side,
+            quantity,
+            price,
+            timestamp: request.timestamp,
+        })
+    }
+
+    /// Position change: positive for longs, negative for shorts
+    pub fn signed_quantity(&self) -> i128 {
+        match self.side {
+            FillSide::Long => self.quantity as i128,
+            FillSide::Short => -(self.quantity as i128),
+        }
+    }
+
+    /// Quote flow for this fill: a long pays, a short receives
+    pub fn quote_delta(&self) -> i128 {
+        -self.signed_quantity() * self.price as i128 / 10i128.pow(BASE_DECIMALS)
+    }
+}
+
+/// A positive amount in whole units to `decimals` fixed point
+fn fixed(value: f64, decimals: u32) -> Result<u64> {
+    let max = (u64::MAX / 10u64.pow(decimals)) as f64;
+    if !value.is_finite() || value <= 0.0 || value > max {
+        bail!("{} is not a positive amount of at most {}", value, max);
+    }
+    Ok((value * 10f64.powi(decimals as i32)).round() as u64)
+}
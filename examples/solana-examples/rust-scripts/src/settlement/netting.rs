@@ -0,0 +1,123 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::BTreeMap;
+
+use super::Fill;
+
+/// Net result of all fills of one user in one market
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetPosition {
+    pub user: Pubkey,
+    pub symbol: String,
+    /// Net size change, `BASE_DECIMALS` fixed point (positive = long)
+    pub size_delta: i64,
+    /// Net quote collateral change in token base units (positive = credit)
+    pub quote_delta: i64,
+    /// Gross traded base amount, for reporting
+    pub gross_quantity: u64,
+    pub trade_ids: Vec<String>,
+}
+
+/// Net collateral movement of one user across all markets
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollateralTransfer {
+    pub user: Pubkey,
+    /// Token base units; positive credits the user's vault, negative debits it
+    pub amount: i64,
+}
+
+/// Nets fills per (user, market)
+///
+/// Example: a user trading +10, -3 and +5 in one market settles a single
+/// +12 position change. Output is ordered by user then symbol, so the same
+/// fills always produce the same entries.
+#[derive(Debug, Default)]
+pub struct NetPositionCalculator {
+    positions: BTreeMap<(Pubkey, String), Accumulator>,
+    fills: usize,
+}
+
+#[derive(Debug, Default)]
+struct Accumulator {
+    size_delta: i128,
+    quote_delta: i128,
+    gross_quantity: u128,
+    trade_ids: Vec<String>,
+}
+
+impl NetPositionCalculator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, fill: &Fill) {
+        let entry = self.positions.entry((fill.user, fill.symbol.clone())).or_default();
+        entry.size_delta += fill.signed_quantity();
+        entry.quote_delta += fill.quote_delta();
+        entry.gross_quantity += fill.quantity as u128;
+        entry.trade_ids.push(fill.trade_id.clone());
+        self.fills += 1;
+    }
+
+    pub fn extend<'a>(&mut self, fills: impl IntoIterator<Item = &'a Fill>) {
+        for fill in fills {
+            self.add(fill);
+        }
+    }
+
+    /// Number of fills added so far
+    pub fn fill_count(&self) -> usize {
+        self.fills
+    }
+
+    /// Net positions, skipping pairs whose fills cancelled out entirely.
+    /// Fails if a net amount does not fit the 64-bit fields settled on chain.
+    pub fn net_positions(&self) -> Result<Vec<NetPosition>> {
+        self.positions
+            .iter()
+            .filter(|(_, acc)| acc.size_delta != 0 || acc.quote_delta != 0)
+            .map(|((user, symbol), acc)| {
+                let overflow = |what: &str| format!("Net {} of {} in {} overflows 64 bits", what, user, symbol);
+                Ok(NetPosition {
+                    user: *user,
+                    symbol: symbol.clone(),
+                    size_delta: i64::try_from(acc.size_delta).with_context(|| overflow("size"))?,
+                    quote_delta: i64::try_from(acc.quote_delta).with_context(|| overflow("quote amount"))?,
+                    gross_quantity: u64::try_from(acc.gross_quantity).with_context(|| overflow("gross quantity"))?,
+                    trade_ids: acc.trade_ids.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Per-user collateral movement summed over all markets. Fails if a
+    /// total does not fit in an `i64`.
+    pub fn collateral_transfers(&self) -> Result<Vec<CollateralTransfer>> {
+        let mut totals: BTreeMap<Pubkey, i128> = BTreeMap::new();
+        for ((user, _), acc) in &self.positions {
+            *totals.entry(*user).or_default() += acc.quote_delta;
+        }
+        totals
+            .into_iter()
+            .filter(|(_, amount)| *amount != 0)
+            .map(|(user, amount)| {
+                Ok(CollateralTransfer {
+                    user,
+                    amount: i64::try_from(amount)
+                        .with_context(|| format!("Net collateral transfer of {} overflows 64 bits", user))?,
+                })
+            })
+            .collect()
+    }
+
+    /// Net size per market; zero for every market whose fills have both
+    /// counterparties in this set
+    pub fn market_imbalance(&self) -> BTreeMap<String, i128> {
+        let mut imbalance: BTreeMap<String, i128> = BTreeMap::new();
+        for ((_, symbol), acc) in &self.positions {
+            *imbalance.entry(symbol.clone()).or_default() += acc.size_delta;
+        }
+        imbalance
+    }
+}
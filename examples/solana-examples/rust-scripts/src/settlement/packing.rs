@@ -0,0 +1,246 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::merkle;
+use super::netting::NetPosition;
+use crate::gdx::{anchor_instruction, vault_address};
+use crate::persist::save_json;
+
+/// Program ids a settlement batch touches
+#[derive(Debug, Clone)]
+pub struct SettlementPrograms {
+    pub settlement: Pubkey,
+    pub vault: Pubkey,
+}
+
+impl SettlementPrograms {
+    /// Settlement program position account, seeds `[b"position", owner, symbol]`
+    pub fn position_address(&self, owner: &Pubkey, symbol: &str) -> Pubkey {
+        Pubkey::find_program_address(&[b"position", owner.as_ref(), symbol.as_bytes()], &self.settlement).0
+    }
+
+    /// Per-market settlement vault, seeds `[b"settlement_vault", symbol]`
+    pub fn settlement_vault_address(&self, symbol: &str) -> Pubkey {
+        Pubkey::find_program_address(&[b"settlement_vault", symbol.as_bytes()], &self.settlement).0
+    }
+
+    /// Accounts one entry needs: position, user collateral vault, market vault
+    fn entry_accounts(&self, entry: &NetPosition) -> [Pubkey; 3] {
+        [
+            self.position_address(&entry.user, &entry.symbol),
+            vault_address(&self.vault, &entry.user),
+            self.settlement_vault_address(&entry.symbol),
+        ]
+    }
+}
+
+/// Transaction limits the packer must respect
+#[derive(Debug, Clone)]
+pub struct Limits {
+    /// Serialized transaction size limit (packet size)
+    pub max_tx_bytes: usize,
+    /// Compute unit cap per transaction
+    pub max_compute_units: u32,
+    /// Compute units of `settle_batch` itself, before any entry
+    pub base_compute_units: u32,
+    /// Compute units per settled entry
+    pub compute_units_per_entry: u32,
+    /// Account locks per transaction
+    pub max_accounts: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_tx_bytes: 1232,
+            max_compute_units: 1_400_000,
+            base_compute_units: 20_000,
+            compute_units_per_entry: 30_000,
+            max_accounts: 64,
+        }
+    }
+}
+
+/// Bytes per entry in `settle_batch` instruction data (`size_delta`, `quote_delta`)
+const ENTRY_DATA_SIZE: usize = 16;
+/// Discriminator + batch id + Merkle root + entry vector length
+const BATCH_DATA_HEADER: usize = 8 + 32 + 32 + 4;
+
+fn compact_len(n: usize) -> usize {
+    match n {
+        0..=0x7f => 1,
+        0x80..=0x3fff => 2,
+        _ => 3,
+    }
+}
+
+/// Serialized size of a legacy transaction with one signer, a compute
+/// budget instruction and a `settle_batch` instruction
+fn estimate_tx_size(unique_accounts: usize, entries: usize) -> usize {
+    // relayer + settlement program + compute budget program
+    let keys = unique_accounts + 3;
+    let settle_accounts = 1 + 3 * entries;
+    let settle_data = BATCH_DATA_HEADER + ENTRY_DATA_SIZE * entries;
+
+    let signatures = compact_len(1) + 64;
+    let header = 3;
+    let account_keys = compact_len(keys) + 32 * keys;
+    let blockhash = 32;
+    let compute_budget_ix = 1 + compact_len(0) + compact_len(5) + 5;
+    let settle_ix = 1 + compact_len(settle_accounts) + settle_accounts + compact_len(settle_data) + settle_data;
+
+    signatures + header + account_keys + blockhash + compact_len(2) + compute_budget_ix + settle_ix
+}
+
+/// One atomic settlement transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Batch {
+    /// Content hash (base58); the same entries always produce the same id
+    pub id: String,
+    /// Merkle root (base58) over the trade ids settled by this batch
+    pub merkle_root: String,
+    pub entries: Vec<NetPosition>,
+    pub compute_units: u32,
+    pub estimated_size: usize,
+}
+
+impl Batch {
+    fn new(entries: Vec<NetPosition>, limits: &Limits, unique_accounts: usize) -> Self {
+        let leaves: Vec<[u8; 32]> = entries
+            .iter()
+            .flat_map(|e| e.trade_ids.iter())
+            .map(|id| merkle::leaf(id))
+            .collect();
+        let merkle_root = merkle::root(&leaves);
+
+        let mut hasher = Sha256::new();
+        hasher.update(merkle_root);
+        for entry in &entries {
+            hasher.update(entry.user.as_ref());
+            hasher.update(entry.symbol.as_bytes());
+            hasher.update(entry.size_delta.to_le_bytes());
+            hasher.update(entry.quote_delta.to_le_bytes());
+        }
+        let id: [u8; 32] = hasher.finalize().into();
+
+        Self {
+            id: bs58::encode(id).into_string(),
+            merkle_root: bs58::encode(merkle_root).into_string(),
+            compute_units: limits.base_compute_units + limits.compute_units_per_entry * entries.len() as u32,
+            estimated_size: estimate_tx_size(unique_accounts, entries.len()),
+            entries,
+        }
+    }
+
+    pub fn trade_count(&self) -> usize {
+        self.entries.iter().map(|e| e.trade_ids.len()).sum()
+    }
+
+    /// Compute budget + `settle_batch(batch_id, merkle_root, entries)`
+    pub fn instructions(&self, programs: &SettlementPrograms, relayer: &Pubkey) -> Result<Vec<Instruction>> {
+        let id = decode_32(&self.id).context("Invalid batch id")?;
+        let root = decode_32(&self.merkle_root).context("Invalid Merkle root")?;
+
+        let mut args = Vec::with_capacity(BATCH_DATA_HEADER + ENTRY_DATA_SIZE * self.entries.len());
+        args.extend_from_slice(&id);
+        args.extend_from_slice(&root);
+        args.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+        let mut accounts = vec![AccountMeta::new(*relayer, true)];
+        for entry in &self.entries {
+            args.extend_from_slice(&entry.size_delta.to_le_bytes());
+            args.extend_from_slice(&entry.quote_delta.to_le_bytes());
+            accounts.extend(programs.entry_accounts(entry).iter().map(|key| AccountMeta::new(*key, false)));
+        }
+
+        Ok(vec![
+            ComputeBudgetInstruction::set_compute_unit_limit(self.compute_units),
+            anchor_instruction(&programs.settlement, "settle_batch", &args, accounts),
+        ])
+    }
+
+    /// Store as `<dir>/<id>.json` so the batch can be replayed by id
+    pub fn save(&self, dir: &Path) -> Result<PathBuf> {
+        let path = dir.join(format!("{}.json", self.id));
+        save_json(&path, self)?;
+        Ok(path)
+    }
+
+    pub fn load(dir: &Path, id: &str) -> Result<Self> {
+        let path = dir.join(format!("{}.json", id));
+        let raw = std::fs::read_to_string(&path).with_context(|| format!("No stored batch {}", id))?;
+        serde_json::from_str(&raw).with_context(|| format!("Invalid batch file {}", path.display()))
+    }
+}
+
+fn decode_32(value: &str) -> Result<[u8; 32]> {
+    let bytes = bs58::decode(value).into_vec()?;
+    bytes.try_into().map_err(|_| anyhow::anyhow!("expected 32 bytes"))
+}
+
+struct Bin {
+    entries: Vec<NetPosition>,
+    accounts: HashSet<Pubkey>,
+}
+
+/// Pack net positions into the fewest batches that fit `limits`
+///
+/// All entries of one user go into the same batch so that user's collateral
+/// moves atomically. Users are placed largest-first into the first batch
+/// with room (first-fit decreasing), counting shared vault accounts once.
+pub fn pack(positions: Vec<NetPosition>, programs: &SettlementPrograms, limits: &Limits) -> Result<Vec<Batch>> {
+    let mut by_user: BTreeMap<Pubkey, Vec<NetPosition>> = BTreeMap::new();
+    for position in positions {
+        by_user.entry(position.user).or_default().push(position);
+    }
+    let mut groups: Vec<Vec<NetPosition>> = by_user.into_values().collect();
+    groups.sort_by_key(|group| std::cmp::Reverse(group.len()));
+
+    let fits = |entries: usize, accounts: usize| {
+        accounts + 3 <= limits.max_accounts
+            && estimate_tx_size(accounts, entries) <= limits.max_tx_bytes
+            && limits.base_compute_units as u64 + limits.compute_units_per_entry as u64 * entries as u64
+                <= limits.max_compute_units as u64
+    };
+
+    let mut bins: Vec<Bin> = Vec::new();
+    for group in groups {
+        let group_accounts: HashSet<Pubkey> = group.iter().flat_map(|e| programs.entry_accounts(e)).collect();
+
+        let slot = bins.iter().position(|bin| {
+            let accounts = bin.accounts.union(&group_accounts).count();
+            fits(bin.entries.len() + group.len(), accounts)
+        });
+        match slot {
+            Some(i) => {
+                bins[i].accounts.extend(group_accounts);
+                bins[i].entries.extend(group);
+            }
+            None => {
+                if !fits(group.len(), group_accounts.len()) {
+                    bail!(
+                        "User {} has {} entries, more than fit in one transaction",
+                        group[0].user,
+                        group.len()
+                    );
+                }
+                bins.push(Bin {
+                    entries: group,
+                    accounts: group_accounts,
+                });
+            }
+        }
+    }
+
+    Ok(bins
+        .into_iter()
+        .map(|bin| Batch::new(bin.entries, limits, bin.accounts.len()))
+        .collect())
+}
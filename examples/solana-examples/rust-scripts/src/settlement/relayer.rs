@@ -0,0 +1,66 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_RELAYER_URL: &str = "http://127.0.0.1:8080";
+
+/// Settlement batch as reported by the relayer (`/settlement/status/:id`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchStatus {
+    pub batch_id: String,
+    pub status: String,
+    pub tx_signature: Option<String>,
+    pub trade_count: u64,
+    pub created_at: String,
+}
+
+impl BatchStatus {
+    /// The batch transaction has landed on chain
+    pub fn is_on_chain(&self) -> bool {
+        self.tx_signature.is_some()
+    }
+}
+
+/// Read-only client for the settlement relayer's status endpoints
+#[derive(Debug, Clone)]
+pub struct RelayerClient {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl RelayerClient {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub async fn health(&self) -> bool {
+        let url = format!("{}/health", self.base_url);
+        matches!(self.http.get(url).send().await, Ok(response) if response.status().is_success())
+    }
+
+    async fn get_batch(&self, path: String) -> Result<Option<BatchStatus>> {
+        let url = format!("{}{}", self.base_url, path);
+        let response = self
+            .http
+            .get(&url)
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach settlement relayer at {}", self.base_url))?;
+        match response.status() {
+            status if status.is_success() => Ok(Some(response.json().await.context("Invalid batch status")?)),
+            reqwest::StatusCode::NOT_FOUND => Ok(None),
+            status => bail!("Relayer returned {} for {}", status, url),
+        }
+    }
+
+    pub async fn batch(&self, batch_id: &str) -> Result<Option<BatchStatus>> {
+        self.get_batch(format!("/settlement/status/{}", batch_id)).await
+    }
+
+    /// Batch a trade was settled in; `None` until the relayer has batched it
+    pub async fn batch_by_trade(&self, trade_id: &str) -> Result<Option<BatchStatus>> {
+        self.get_batch(format!("/settlement/batch-by-trade/{}", trade_id)).await
+    }
+}
@@ -0,0 +1,91 @@
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use super::{format, sign_message, verify_message, Encoding};
+use crate::keypair::{load_keypair, DEFAULT_KEYPAIR_PATH};
+
+#[derive(Args)]
+pub struct MessageArgs {
+    /// Message text
+    #[arg(required_unless_present = "file")]
+    message: Option<String>,
+    /// Read the message from a file instead, byte for byte
+    #[arg(long, conflicts_with = "message")]
+    file: Option<PathBuf>,
+    /// Sign the bare message bytes, as browser wallets' signMessage does,
+    /// instead of the off-chain message format
+    #[arg(long)]
+    raw: bool,
+}
+
+impl MessageArgs {
+    fn bytes(&self) -> Result<Vec<u8>> {
+        match (&self.message, &self.file) {
+            (_, Some(path)) => std::fs::read(path).with_context(|| format!("Failed to read {}", path.display())),
+            (Some(message), None) => Ok(message.as_bytes().to_vec()),
+            (None, None) => bail!("Give a message or --file"),
+        }
+    }
+
+    fn encoding(&self) -> Encoding {
+        if self.raw {
+            Encoding::Raw
+        } else {
+            Encoding::Offchain
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct SignMessageArgs {
+    #[command(flatten)]
+    message: MessageArgs,
+    /// Keypair to sign with
+    #[arg(long, default_value = DEFAULT_KEYPAIR_PATH)]
+    keypair: String,
+}
+
+#[derive(Args)]
+pub struct VerifyMessageArgs {
+    #[command(flatten)]
+    message: MessageArgs,
+    /// Address that should have signed
+    #[arg(long)]
+    pubkey: String,
+    /// Signature to check (base58)
+    #[arg(long)]
+    signature: String,
+}
+
+pub async fn run_sign(args: SignMessageArgs) -> Result<()> {
+    let keypair = load_keypair(&args.keypair)?;
+    let message = args.message.bytes()?;
+    let encoding = args.message.encoding();
+    let signature = sign_message(&keypair, &message, encoding)?;
+    eprintln!("✍️  Signed by {}", keypair.pubkey());
+    eprintln!("   {}", describe(&message, encoding)?);
+    println!("{}", signature);
+    Ok(())
+}
+
+pub async fn run_verify(args: VerifyMessageArgs) -> Result<()> {
+    let pubkey = Pubkey::from_str(&args.pubkey).context("Invalid pubkey")?;
+    let message = args.message.bytes()?;
+    let encoding = args.message.encoding();
+    if !verify_message(&pubkey, &message, &args.signature, encoding)? {
+        bail!("❌ Signature does not match {} for this message", pubkey);
+    }
+    println!("✅ Valid signature by {}", pubkey);
+    println!("   {}", describe(&message, encoding)?);
+    Ok(())
+}
+
+fn describe(message: &[u8], encoding: Encoding) -> Result<String> {
+    Ok(match encoding {
+        Encoding::Offchain => format!("Off-chain message v0, {:?}, {} bytes", format(message)?, message.len()),
+        Encoding::Raw => format!("Raw message, {} bytes", message.len()),
+    })
+}
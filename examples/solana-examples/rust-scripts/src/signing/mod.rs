@@ -0,0 +1,80 @@
+//! Prove wallet ownership by signing messages off-chain
+//!
+//! [`sign_message`] signs text in the Solana off-chain message format: the
+//! signing domain `\xffsolana offchain`, a header version (0), a format byte
+//! and the length, then the text. The prefix keeps a signed message from
+//! ever being a valid transaction. Version 0 allows printable ASCII or UTF-8
+//! up to 1212 bytes, which hardware wallets can display, and UTF-8 up to
+//! 65515 bytes otherwise. Signatures are base58.
+//!
+//! Browser wallets (`signMessage` in Phantom and others) sign the bare text
+//! instead, and that is what the wallet-linking flow in
+//! `section-6-account-authentication.md` verifies; [`Encoding::Raw`] covers
+//! those signatures.
+
+pub mod commands;
+
+use anyhow::{anyhow, bail, Context, Result};
+use solana_sdk::{
+    offchain_message::{MessageFormat, OffchainMessage},
+    pubkey::Pubkey,
+    sanitize::SanitizeError,
+    signature::{Signature, Signer},
+};
+use std::str::FromStr;
+
+/// What the signature covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// The off-chain message: signing domain, header and text
+    Offchain,
+    /// The text bytes alone, as browser wallets sign
+    Raw,
+}
+
+/// The text in the off-chain format; fails for empty, oversized or non
+/// UTF-8 messages
+pub fn offchain_message(message: &[u8]) -> Result<OffchainMessage> {
+    OffchainMessage::new(0, message).map_err(|e| match e {
+        SanitizeError::ValueOutOfBounds => anyhow!(
+            "Message is {} bytes; off-chain messages hold at most {}",
+            message.len(),
+            solana_sdk::offchain_message::v0::OffchainMessage::MAX_LEN
+        ),
+        _ if message.is_empty() => anyhow!("Message is empty"),
+        _ => anyhow!("Message is not valid UTF-8"),
+    })
+}
+
+/// The bytes a signature covers under `encoding`
+pub fn signed_bytes(message: &[u8], encoding: Encoding) -> Result<Vec<u8>> {
+    match encoding {
+        Encoding::Offchain => offchain_message(message)?
+            .serialize()
+            .map_err(|e| anyhow!("Failed to serialize message: {}", e)),
+        Encoding::Raw if message.is_empty() => bail!("Message is empty"),
+        Encoding::Raw => Ok(message.to_vec()),
+    }
+}
+
+/// Format version 0 picks for `message`
+pub fn format(message: &[u8]) -> Result<MessageFormat> {
+    Ok(offchain_message(message)?.get_format())
+}
+
+/// Sign `message` with `signer`; returns the base58 signature
+pub fn sign_message(signer: &dyn Signer, message: &[u8], encoding: Encoding) -> Result<String> {
+    let bytes = signed_bytes(message, encoding)?;
+    let signature = signer
+        .try_sign_message(&bytes)
+        .map_err(|e| anyhow!("Failed to sign: {}", e))?;
+    Ok(signature.to_string())
+}
+
+/// Whether `signature` (base58) is `signer`'s over `message`. A malformed
+/// signature is an error; a well-formed one that does not match is `false`.
+pub fn verify_message(signer: &Pubkey, message: &[u8], signature: &str, encoding: Encoding) -> Result<bool> {
+    let signature = Signature::from_str(signature.trim()).context("Invalid signature; expected 64 bytes of base58")?;
+    let bytes = signed_bytes(message, encoding)?;
+    Ok(signature.verify(signer.as_ref(), &bytes))
+}
@@ -0,0 +1,521 @@
+use anyhow::{bail, Context, Result};
+use clap::Subcommand;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    bpf_loader_upgradeable,
+    commitment_config::CommitmentConfig,
+    pubkey::Pubkey,
+    signature::{Signature, Signer},
+};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use super::{format_duration, MemberSignature, MultisigConfig, UpgradeProposal, UpgradeStatus};
+use crate::client::{send, LedgerClient};
+use crate::keypair::{load_keypair, DEFAULT_KEYPAIR_PATH};
+use crate::persist::now;
+use crate::program::{self, elf_hash, LoaderAccount};
+
+#[derive(Subcommand)]
+pub enum UpgradeCommand {
+    /// Propose upgrading a program to a buffer written with `program` tooling
+    Propose {
+        /// Program id
+        program_id: String,
+        /// Buffer holding the new program; its authority must be the multisig upgrade authority
+        buffer: String,
+        /// What the upgrade changes
+        #[arg(long)]
+        description: String,
+        /// Local build the buffer must match
+        #[arg(long)]
+        so: Option<PathBuf>,
+        /// Multisig config (members, threshold, upgrade_authority, timelock_secs)
+        #[arg(long, default_value = "multisig.json")]
+        multisig: PathBuf,
+        /// Directory proposals are stored in
+        #[arg(long, default_value = "upgrades")]
+        dir: PathBuf,
+        /// Proposer keypair (must be a member)
+        #[arg(long, default_value = DEFAULT_KEYPAIR_PATH)]
+        keypair: String,
+        /// RPC endpoint
+        #[arg(long, default_value = "http://127.0.0.1:8899")]
+        url: String,
+    },
+    /// Approve a proposal, or sign it offline for someone else to add
+    Approve {
+        /// Proposal id
+        id: u64,
+        /// Only print this keypair's signature as PUBKEY=SIGNATURE@UNIX_TIME; no RPC, file left untouched
+        #[arg(long, conflicts_with = "signatures")]
+        sign_only: bool,
+        /// Add a signature produced with --sign-only, as PUBKEY=SIGNATURE@UNIX_TIME (repeatable)
+        #[arg(long = "signature")]
+        signatures: Vec<String>,
+        #[arg(long, default_value = "multisig.json")]
+        multisig: PathBuf,
+        #[arg(long, default_value = "upgrades")]
+        dir: PathBuf,
+        /// Member keypair
+        #[arg(long, default_value = DEFAULT_KEYPAIR_PATH)]
+        keypair: String,
+        /// RPC endpoint (buffer check)
+        #[arg(long, default_value = "http://127.0.0.1:8899")]
+        url: String,
+    },
+    /// Upgrade the program once the threshold is met and the timelock is over
+    Execute {
+        /// Proposal id
+        id: u64,
+        /// Upgrade authority keypair
+        #[arg(long)]
+        upgrade_authority: String,
+        #[arg(long, default_value = "multisig.json")]
+        multisig: PathBuf,
+        #[arg(long, default_value = "upgrades")]
+        dir: PathBuf,
+        /// Fee payer keypair
+        #[arg(long, default_value = DEFAULT_KEYPAIR_PATH)]
+        keypair: String,
+        /// RPC endpoint
+        #[arg(long, default_value = "http://127.0.0.1:8899")]
+        url: String,
+    },
+    /// Cancel a proposal before execution (any one member)
+    Cancel {
+        /// Proposal id
+        id: u64,
+        /// Also close the buffer and refund its rent to --keypair (needs the upgrade authority)
+        #[arg(long)]
+        close_buffer: bool,
+        /// Upgrade authority keypair, for --close-buffer
+        #[arg(long, requires = "close_buffer")]
+        upgrade_authority: Option<String>,
+        #[arg(long, default_value = "multisig.json")]
+        multisig: PathBuf,
+        #[arg(long, default_value = "upgrades")]
+        dir: PathBuf,
+        /// Member keypair
+        #[arg(long, default_value = DEFAULT_KEYPAIR_PATH)]
+        keypair: String,
+        /// RPC endpoint
+        #[arg(long, default_value = "http://127.0.0.1:8899")]
+        url: String,
+    },
+    /// Show a proposal's approvals and timelock, or list all proposals
+    Status {
+        /// Proposal id (default: list all)
+        id: Option<u64>,
+        /// Also check the buffer on chain against the approved hash
+        #[arg(long)]
+        check: bool,
+        #[arg(long, default_value = "multisig.json")]
+        multisig: PathBuf,
+        #[arg(long, default_value = "upgrades")]
+        dir: PathBuf,
+        /// RPC endpoint (with --check)
+        #[arg(long, default_value = "http://127.0.0.1:8899")]
+        url: String,
+    },
+}
+
+pub async fn run(command: UpgradeCommand) -> Result<()> {
+    match command {
+        UpgradeCommand::Propose {
+            program_id,
+            buffer,
+            description,
+            so,
+            multisig,
+            dir,
+            keypair,
+            url,
+        } => {
+            let config = MultisigConfig::load(&multisig)?;
+            let proposer = load_keypair(&keypair)?.pubkey();
+            if !config.is_member(&proposer) {
+                bail!("{} is not a multisig member", proposer);
+            }
+            let program_id = parse_pubkey(&program_id, "program id")?;
+            let buffer = parse_pubkey(&buffer, "buffer")?;
+            let client = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+            let (buffer_hash, len) = check_target(&client, &config, &program_id, &buffer, so.as_deref()).await?;
+
+            let proposal = UpgradeProposal::new(
+                UpgradeProposal::next_id(&dir)?,
+                &proposer,
+                &program_id,
+                &buffer,
+                buffer_hash,
+                description,
+                config.threshold,
+            );
+            let path = proposal.save(&dir)?;
+            println!("📝 Proposal #{} saved to {}", proposal.id, path.display());
+            println!("   Program: {}", proposal.program);
+            println!("   Buffer: {} ({} bytes)", proposal.new_buffer, len);
+            println!("   SHA-256: {}", proposal.buffer_hash);
+            println!(
+                "   Needs {} of {} approvals, then a {} timelock",
+                config.threshold,
+                config.members.len(),
+                format_duration(config.timelock_secs)
+            );
+            println!("\n💡 Members approve with `upgrade approve {}`", proposal.id);
+            Ok(())
+        }
+        UpgradeCommand::Approve {
+            id,
+            sign_only,
+            signatures,
+            multisig,
+            dir,
+            keypair,
+            url,
+        } => {
+            let mut proposal = UpgradeProposal::load(&dir, id)?;
+            if sign_only {
+                // Meant for an offline machine: no config, no RPC
+                let member = load_keypair(&keypair)?;
+                let signed_at = now();
+                println!("{}", String::from_utf8_lossy(&proposal.message(signed_at)));
+                println!("{}={}@{}", member.pubkey(), proposal.sign(&member, signed_at), signed_at);
+                return Ok(());
+            }
+            let config = MultisigConfig::load(&multisig)?;
+            approve(&config, &mut proposal, &signatures, &keypair, url).await?;
+            proposal.save(&dir)?;
+            print_status(&proposal, &config);
+            Ok(())
+        }
+        UpgradeCommand::Execute {
+            id,
+            upgrade_authority,
+            multisig,
+            dir,
+            keypair,
+            url,
+        } => {
+            let config = MultisigConfig::load(&multisig)?;
+            let mut proposal = UpgradeProposal::load(&dir, id)?;
+            execute(&config, &mut proposal, &dir, &upgrade_authority, &keypair, url).await
+        }
+        UpgradeCommand::Cancel {
+            id,
+            close_buffer,
+            upgrade_authority,
+            multisig,
+            dir,
+            keypair,
+            url,
+        } => {
+            let config = MultisigConfig::load(&multisig)?;
+            let mut proposal = UpgradeProposal::load(&dir, id)?;
+            cancel(&config, &mut proposal, &keypair)?;
+            proposal.save(&dir)?;
+            println!("🛑 Proposal #{} cancelled", proposal.id);
+            if close_buffer {
+                let authority = upgrade_authority.context("--close-buffer needs --upgrade-authority")?;
+                close(&config, &proposal, &authority, &keypair, url).await?;
+            }
+            Ok(())
+        }
+        UpgradeCommand::Status {
+            id,
+            check,
+            multisig,
+            dir,
+            url,
+        } => {
+            let config = MultisigConfig::load(&multisig)?;
+            match id {
+                Some(id) => {
+                    let proposal = UpgradeProposal::load(&dir, id)?;
+                    print_status(&proposal, &config);
+                    if check {
+                        let client = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+                        check_buffer(&client, &proposal, &config).await?;
+                        println!("✅ Buffer still matches the approved hash");
+                    }
+                    Ok(())
+                }
+                None => list(&dir, &config),
+            }
+        }
+    }
+}
+
+fn parse_pubkey(value: &str, name: &str) -> Result<Pubkey> {
+    Pubkey::from_str(value).with_context(|| format!("Invalid {}: {}", name, value))
+}
+
+/// The buffer must still hold the approved bytes and belong to the
+/// multisig upgrade authority; returns its length
+async fn check_buffer(client: &impl LedgerClient, proposal: &UpgradeProposal, config: &MultisigConfig) -> Result<usize> {
+    let buffer = parse_pubkey(&proposal.new_buffer, "buffer")?;
+    let Some(LoaderAccount::Buffer { authority, data, .. }) = program::fetch(client, &buffer).await? else {
+        bail!("Buffer {} no longer exists", buffer);
+    };
+    if authority != Some(config.upgrade_authority()?) {
+        bail!("Buffer {} is not owned by the upgrade authority {}", buffer, config.upgrade_authority);
+    }
+    let hash = elf_hash(&data);
+    if hash != proposal.buffer_hash {
+        bail!("Buffer {} changed: sha256 {}, approved {}", buffer, hash, proposal.buffer_hash);
+    }
+    Ok(data.len())
+}
+
+/// Check that the multisig can upgrade `program_id` with `buffer`, and that
+/// the buffer matches the local build if one is given; returns the buffer's
+/// hash and length
+async fn check_target(
+    client: &impl LedgerClient,
+    config: &MultisigConfig,
+    program_id: &Pubkey,
+    buffer: &Pubkey,
+    so: Option<&Path>,
+) -> Result<(String, usize)> {
+    let authority = config.upgrade_authority()?;
+    match program::fetch(client, program_id).await? {
+        Some(LoaderAccount::Program {
+            authority: Some(current),
+            ..
+        }) if current == authority => {}
+        Some(LoaderAccount::Program { authority: current, .. }) => bail!(
+            "Upgrade authority of {} is {}, not the multisig's {}",
+            program_id,
+            current.map_or_else(|| "none (immutable)".to_string(), |a| a.to_string()),
+            authority
+        ),
+        _ => bail!("{} is not a deployed program", program_id),
+    }
+    let Some(LoaderAccount::Buffer {
+        authority: buffer_authority,
+        data,
+        ..
+    }) = program::fetch(client, buffer).await?
+    else {
+        bail!("{} is not a buffer", buffer);
+    };
+    if buffer_authority != Some(authority) {
+        bail!(
+            "Buffer authority must be the upgrade authority {}; run `program set-authority {} --new-authority {}`",
+            authority,
+            buffer,
+            authority
+        );
+    }
+    let buffer_hash = elf_hash(&data);
+    if let Some(so) = so {
+        let local = std::fs::read(so).with_context(|| format!("Failed to read {}", so.display()))?;
+        if elf_hash(&local) != buffer_hash {
+            bail!(
+                "Buffer does not match {}: sha256 {} vs {}",
+                so.display(),
+                buffer_hash,
+                elf_hash(&local)
+            );
+        }
+        println!("✅ Buffer matches {}", so.display());
+    }
+    Ok((buffer_hash, data.len()))
+}
+
+async fn approve(
+    config: &MultisigConfig,
+    proposal: &mut UpgradeProposal,
+    signatures: &[String],
+    keypair: &str,
+    url: String,
+) -> Result<()> {
+    proposal.ensure_open()?;
+    let mut collected = Vec::new();
+    if signatures.is_empty() {
+        let member = load_keypair(keypair)?;
+        let signed_at = now();
+        collected.push((member.pubkey(), proposal.sign(&member, signed_at), signed_at));
+    }
+    for entry in signatures {
+        let (signer, signature, signed_at) = entry
+            .split_once('=')
+            .and_then(|(signer, rest)| rest.rsplit_once('@').map(|(signature, at)| (signer, signature, at)))
+            .with_context(|| format!("Expected PUBKEY=SIGNATURE@UNIX_TIME, got {}", entry))?;
+        let signature = Signature::from_str(signature).with_context(|| format!("Invalid signature from {}", signer))?;
+        let signed_at = signed_at
+            .parse::<i64>()
+            .with_context(|| format!("Invalid signing time from {}: {}", signer, signed_at))?;
+        collected.push((parse_pubkey(signer, "signer")?, signature, signed_at));
+    }
+
+    // Approving a buffer that changed since the proposal would be signing blind
+    let client = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+    check_buffer(&client, proposal, config).await?;
+
+    for (signer, signature, signed_at) in collected {
+        if proposal.add_approval(config, &signer, &signature, signed_at)? {
+            println!("✍️  Approval from {} added", signer);
+        } else {
+            println!("ℹ️  {} already approved", signer);
+        }
+    }
+    Ok(())
+}
+
+async fn execute(
+    config: &MultisigConfig,
+    proposal: &mut UpgradeProposal,
+    dir: &Path,
+    upgrade_authority: &str,
+    keypair: &str,
+    url: String,
+) -> Result<()> {
+    proposal.ensure_executable(config, now())?;
+    let payer = load_keypair(keypair)?;
+    let authority = load_keypair(upgrade_authority)?;
+    if authority.pubkey() != config.upgrade_authority()? {
+        bail!(
+            "{} is not the multisig upgrade authority {}",
+            authority.pubkey(),
+            config.upgrade_authority
+        );
+    }
+    let program_id = parse_pubkey(&proposal.program, "program id")?;
+    let buffer = parse_pubkey(&proposal.new_buffer, "buffer")?;
+
+    let client = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+    let len = check_buffer(&client, proposal, config).await?;
+    let Some(LoaderAccount::Program { data, .. }) = program::fetch(&client, &program_id).await? else {
+        bail!("{} is not a deployed program", program_id);
+    };
+    println!("🚀 Executing proposal #{}: {}", proposal.id, proposal.description);
+    if len > data.len() {
+        let additional = (len - data.len()) as u32;
+        let extend = bpf_loader_upgradeable::extend_program(&program_id, Some(&payer.pubkey()), additional);
+        send(&client, &[extend], &payer, &[])
+            .await
+            .context("Failed to extend program")?;
+        println!("📏 Extended program data by {} bytes", additional);
+    }
+
+    let instruction = bpf_loader_upgradeable::upgrade(&program_id, &buffer, &authority.pubkey(), &payer.pubkey());
+    let signature = send(&client, &[instruction], &payer, &[&authority])
+        .await
+        .context("Upgrade failed")?;
+    println!("🎉 Upgraded: {}", signature);
+
+    // Recorded before verifying: the upgrade has happened either way
+    proposal.status = UpgradeStatus::Executed;
+    proposal.executed_at = Some(now());
+    proposal.execution_signature = Some(signature.to_string());
+    proposal.save(dir)?;
+    program::verify_deployed(&client, &program_id, &proposal.buffer_hash).await?;
+    Ok(())
+}
+
+fn cancel(config: &MultisigConfig, proposal: &mut UpgradeProposal, keypair: &str) -> Result<()> {
+    if matches!(proposal.status, UpgradeStatus::Executed | UpgradeStatus::Cancelled) {
+        bail!("Proposal #{} is already {}", proposal.id, proposal.status);
+    }
+    let member = load_keypair(keypair)?;
+    if !config.is_member(&member.pubkey()) {
+        bail!("{} is not a multisig member", member.pubkey());
+    }
+    proposal.cancellation = Some(MemberSignature {
+        signer: member.pubkey().to_string(),
+        signature: member.sign_message(&proposal.cancel_message()).to_string(),
+        signed_at: now(),
+    });
+    proposal.status = UpgradeStatus::Cancelled;
+    Ok(())
+}
+
+async fn close(
+    config: &MultisigConfig,
+    proposal: &UpgradeProposal,
+    upgrade_authority: &str,
+    keypair: &str,
+    url: String,
+) -> Result<()> {
+    let payer = load_keypair(keypair)?;
+    let authority = load_keypair(upgrade_authority)?;
+    if authority.pubkey() != config.upgrade_authority()? {
+        bail!(
+            "{} is not the multisig upgrade authority {}",
+            authority.pubkey(),
+            config.upgrade_authority
+        );
+    }
+    let buffer = parse_pubkey(&proposal.new_buffer, "buffer")?;
+    let client = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+    let instruction = bpf_loader_upgradeable::close(&buffer, &payer.pubkey(), &authority.pubkey());
+    let signature = send(&client, &[instruction], &payer, &[&authority])
+        .await
+        .context("Failed to close buffer")?;
+    println!("🧹 Closed buffer {}, rent refunded to {}: {}", buffer, payer.pubkey(), signature);
+    Ok(())
+}
+
+fn print_status(proposal: &UpgradeProposal, config: &MultisigConfig) {
+    let now = now();
+    let report = proposal.check_approvals(config);
+    println!("📋 Upgrade proposal #{}: {}", proposal.id, proposal.status_at(config, now));
+    println!("   Program: {}", proposal.program);
+    println!("   Buffer: {}", proposal.new_buffer);
+    println!("   SHA-256: {}", proposal.buffer_hash);
+    println!("   Description: {}", proposal.description);
+    println!("   Proposer: {}", proposal.proposer);
+    println!("   Approvals: {} of {}", report.valid.len(), config.threshold);
+    for signer in &report.valid {
+        println!("     ✅ {}", signer);
+    }
+    for (signer, reason) in &report.invalid {
+        println!("     ❌ {} ({})", signer, reason);
+    }
+    if report.valid.len() < config.threshold as usize {
+        for member in &report.missing {
+            println!("     ⏳ {}", member);
+        }
+    }
+    match (proposal.status_at(config, now), proposal.timelock_until(config)) {
+        (UpgradeStatus::TimelockActive, Some(until)) => {
+            println!("   Timelock: {} left (until unix time {})", format_duration(until - now), until)
+        }
+        (UpgradeStatus::Approved, _) => println!("   Timelock: over, ready to execute"),
+        (UpgradeStatus::Executed, _) => {
+            if let Some(signature) = &proposal.execution_signature {
+                println!("   Executed: {}", signature);
+            }
+        }
+        (UpgradeStatus::Cancelled, _) => {
+            if let Some(cancellation) = &proposal.cancellation {
+                println!("   Cancelled by: {}", cancellation.signer);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn list(dir: &Path, config: &MultisigConfig) -> Result<()> {
+    let proposals = UpgradeProposal::load_all(dir)?;
+    if proposals.is_empty() {
+        println!("No upgrade proposals in {}", dir.display());
+        return Ok(());
+    }
+    let now = now();
+    println!("📋 {} upgrade proposal(s):", proposals.len());
+    for proposal in &proposals {
+        let report = proposal.check_approvals(config);
+        println!(
+            "   #{:<4} {:<16} {}/{}  {}  {}",
+            proposal.id,
+            proposal.status_at(config, now).to_string(),
+            report.valid.len(),
+            config.threshold,
+            proposal.program,
+            proposal.description
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,388 @@
+//! Multisig program upgrades, coordinated off-chain
+//!
+//! Follows the flow of the program upgrade assignment (`UpgradeProposal`,
+//! `MultisigConfig`, propose → approve → timelock → execute, or cancel)
+//! without an on-chain governance program. A proposal is a JSON file passed
+//! between multisig members. Each approval is an ed25519 signature over the
+//! proposal's [`UpgradeProposal::message`] and the time it was signed, so
+//! members can sign on an offline machine and hand back only
+//! `PUBKEY=SIGNATURE@UNIX_TIME`.
+//!
+//! Reaching the threshold starts the timelock. Before executing, the client
+//! re-checks every signature against the local [`MultisigConfig`] (never the
+//! copy in the proposal file), recomputes the timelock from the signed
+//! approval times, waits it out and compares the buffer with the hash that
+//! was approved. Only then does the upgrade authority sign the loader's
+//! `Upgrade` instruction.
+
+pub mod commands;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::persist::{now, save_json};
+
+/// Timelock the assignment asks for: 48 hours
+pub const DEFAULT_TIMELOCK_SECS: i64 = 48 * 60 * 60;
+
+fn default_timelock() -> i64 {
+    DEFAULT_TIMELOCK_SECS
+}
+
+/// Who may approve upgrades, and the key that signs them once approved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultisigConfig {
+    pub members: Vec<String>,
+    pub threshold: u8,
+    /// Upgrade authority of the programs and the buffers
+    pub upgrade_authority: String,
+    /// Delay between reaching the threshold and execution
+    #[serde(default = "default_timelock")]
+    pub timelock_secs: i64,
+}
+
+impl MultisigConfig {
+    /// Load and validate; member keys are normalized to base58
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut config: Self =
+            serde_json::from_str(&raw).with_context(|| format!("Invalid multisig config {}", path.display()))?;
+
+        let mut seen = HashSet::new();
+        for member in &mut config.members {
+            let key = Pubkey::from_str(member).with_context(|| format!("Invalid member {}", member))?;
+            if !seen.insert(key) {
+                bail!("Member {} listed twice", key);
+            }
+            *member = key.to_string();
+        }
+        config.upgrade_authority = config.upgrade_authority()?.to_string();
+        if config.threshold == 0 || config.threshold as usize > config.members.len() {
+            bail!(
+                "Threshold {} needs between 1 and {} (the member count)",
+                config.threshold,
+                config.members.len()
+            );
+        }
+        if config.timelock_secs < 0 {
+            bail!("timelock_secs cannot be negative");
+        }
+        Ok(config)
+    }
+
+    pub fn upgrade_authority(&self) -> Result<Pubkey> {
+        Pubkey::from_str(&self.upgrade_authority).context("Invalid upgrade_authority")
+    }
+
+    pub fn is_member(&self, key: &Pubkey) -> bool {
+        self.members.iter().any(|m| *m == key.to_string())
+    }
+}
+
+/// Lifecycle of a proposal
+///
+/// `Proposed` until the threshold is reached, then `TimelockActive`, which
+/// reads as `Approved` (executable) once the timelock is over. The stored
+/// status is informational; [`UpgradeProposal::ensure_executable`] works from
+/// the signatures alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UpgradeStatus {
+    Proposed,
+    Approved,
+    TimelockActive,
+    Executed,
+    Cancelled,
+}
+
+impl std::fmt::Display for UpgradeStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            UpgradeStatus::Proposed => "Proposed",
+            UpgradeStatus::Approved => "Approved",
+            UpgradeStatus::TimelockActive => "Timelock active",
+            UpgradeStatus::Executed => "Executed",
+            UpgradeStatus::Cancelled => "Cancelled",
+        })
+    }
+}
+
+/// A member's signature over a proposal message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemberSignature {
+    pub signer: String,
+    pub signature: String,
+    /// Part of the signed message for approvals
+    pub signed_at: i64,
+}
+
+/// Signatures checked against a multisig config
+#[derive(Debug, Default)]
+pub struct ApprovalReport {
+    pub valid: Vec<Pubkey>,
+    /// When the valid approval that completed the threshold was signed
+    pub threshold_reached_at: Option<i64>,
+    /// Signer and why the signature does not count
+    pub invalid: Vec<(String, String)>,
+    /// Members that have not signed
+    pub missing: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpgradeProposal {
+    pub id: u64,
+    pub proposer: String,
+    pub program: String,
+    pub new_buffer: String,
+    /// [`crate::program::elf_hash`] of the buffer when it was proposed
+    pub buffer_hash: String,
+    pub description: String,
+    pub proposed_at: i64,
+    pub approvals: Vec<MemberSignature>,
+    pub approval_threshold: u8,
+    pub status: UpgradeStatus,
+    pub executed_at: Option<i64>,
+    pub execution_signature: Option<String>,
+    pub cancellation: Option<MemberSignature>,
+}
+
+impl UpgradeProposal {
+    pub fn new(
+        id: u64,
+        proposer: &Pubkey,
+        program: &Pubkey,
+        new_buffer: &Pubkey,
+        buffer_hash: String,
+        description: String,
+        approval_threshold: u8,
+    ) -> Self {
+        Self {
+            id,
+            proposer: proposer.to_string(),
+            program: program.to_string(),
+            new_buffer: new_buffer.to_string(),
+            buffer_hash,
+            description,
+            proposed_at: now(),
+            approvals: Vec::new(),
+            approval_threshold,
+            status: UpgradeStatus::Proposed,
+            executed_at: None,
+            execution_signature: None,
+            cancellation: None,
+        }
+    }
+
+    pub fn path(dir: &Path, id: u64) -> PathBuf {
+        dir.join(format!("{}.json", id))
+    }
+
+    pub fn load(dir: &Path, id: u64) -> Result<Self> {
+        let path = Self::path(dir, id);
+        let raw = std::fs::read_to_string(&path).with_context(|| format!("No upgrade proposal {}", id))?;
+        serde_json::from_str(&raw).with_context(|| format!("Invalid proposal file {}", path.display()))
+    }
+
+    /// Every proposal in `dir`, by id
+    pub fn load_all(dir: &Path) -> Result<Vec<Self>> {
+        let mut proposals = Vec::new();
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Ok(proposals);
+        };
+        for entry in entries {
+            let path = entry?.path();
+            let id = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|s| s.parse::<u64>().ok());
+            if let (Some(id), Some("json")) = (id, path.extension().and_then(|e| e.to_str())) {
+                proposals.push(Self::load(dir, id)?);
+            }
+        }
+        proposals.sort_by_key(|p| p.id);
+        Ok(proposals)
+    }
+
+    pub fn next_id(dir: &Path) -> Result<u64> {
+        Ok(Self::load_all(dir)?.last().map_or(1, |p| p.id + 1))
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<PathBuf> {
+        let path = Self::path(dir, self.id);
+        save_json(&path, self)?;
+        Ok(path)
+    }
+
+    /// The text a member signs to approve at `signed_at`; it pins the
+    /// program, the buffer, the buffer's hash and the approval time the
+    /// timelock is computed from
+    pub fn message(&self, signed_at: i64) -> Vec<u8> {
+        format!(
+            "GDX program upgrade proposal #{}\nprogram: {}\nbuffer: {}\nsha256: {}\ndescription: {}\nsigned at: {}\n",
+            self.id, self.program, self.new_buffer, self.buffer_hash, self.description, signed_at
+        )
+        .into_bytes()
+    }
+
+    /// The text a member signs to cancel
+    pub fn cancel_message(&self) -> Vec<u8> {
+        format!(
+            "GDX cancel upgrade proposal #{}\nprogram: {}\nbuffer: {}\n",
+            self.id, self.program, self.new_buffer
+        )
+        .into_bytes()
+    }
+
+    pub fn sign(&self, keypair: &Keypair, signed_at: i64) -> Signature {
+        keypair.sign_message(&self.message(signed_at))
+    }
+
+    /// End of the timelock under `config`, if the threshold is reached
+    pub fn timelock_until(&self, config: &MultisigConfig) -> Option<i64> {
+        self.check_approvals(config)
+            .threshold_reached_at
+            .map(|at| at.saturating_add(config.timelock_secs))
+    }
+
+    /// Status as of `now`, turning a finished timelock into `Approved`
+    pub fn status_at(&self, config: &MultisigConfig, now: i64) -> UpgradeStatus {
+        match (self.status, self.timelock_until(config)) {
+            (UpgradeStatus::TimelockActive, Some(until)) if now >= until => UpgradeStatus::Approved,
+            (status, _) => status,
+        }
+    }
+
+    /// Check the collected signatures against `config`
+    ///
+    /// The threshold counts as reached when the `threshold`-th earliest valid
+    /// approval was signed, so one member backdating a signature cannot
+    /// shorten the timelock.
+    pub fn check_approvals(&self, config: &MultisigConfig) -> ApprovalReport {
+        let mut report = ApprovalReport::default();
+        let mut signed_at = Vec::new();
+        for approval in &self.approvals {
+            let reason = match (Pubkey::from_str(&approval.signer), Signature::from_str(&approval.signature)) {
+                (Ok(signer), Ok(signature)) => {
+                    if !config.is_member(&signer) {
+                        Some("not a multisig member")
+                    } else if report.valid.contains(&signer) {
+                        Some("signed twice")
+                    } else if !signature.verify(signer.as_ref(), &self.message(approval.signed_at)) {
+                        Some("signature does not match the proposal")
+                    } else {
+                        report.valid.push(signer);
+                        signed_at.push(approval.signed_at);
+                        None
+                    }
+                }
+                _ => Some("malformed signer or signature"),
+            };
+            if let Some(reason) = reason {
+                report.invalid.push((approval.signer.clone(), reason.to_string()));
+            }
+        }
+        signed_at.sort_unstable();
+        report.threshold_reached_at = (config.threshold as usize)
+            .checked_sub(1)
+            .and_then(|index| signed_at.get(index))
+            .copied();
+        report.missing = config
+            .members
+            .iter()
+            .filter(|m| !report.valid.iter().any(|v| v.to_string() == **m))
+            .cloned()
+            .collect();
+        report
+    }
+
+    /// Add a member's approval signed at `signed_at`; marks the timelock as
+    /// started when it completes the threshold. Returns `false` if the member
+    /// had already approved.
+    pub fn add_approval(
+        &mut self,
+        config: &MultisigConfig,
+        signer: &Pubkey,
+        signature: &Signature,
+        signed_at: i64,
+    ) -> Result<bool> {
+        self.ensure_open()?;
+        if !config.is_member(signer) {
+            bail!("{} is not a multisig member", signer);
+        }
+        if !signature.verify(signer.as_ref(), &self.message(signed_at)) {
+            bail!("Signature of {} does not match proposal #{}", signer, self.id);
+        }
+        if self.check_approvals(config).valid.contains(signer) {
+            return Ok(false);
+        }
+        self.approvals.push(MemberSignature {
+            signer: signer.to_string(),
+            signature: signature.to_string(),
+            signed_at,
+        });
+        if self.status == UpgradeStatus::Proposed
+            && self.check_approvals(config).valid.len() >= config.threshold as usize
+        {
+            self.status = UpgradeStatus::TimelockActive;
+        }
+        Ok(true)
+    }
+
+    /// Check that `config` is the one the proposal was made under and that
+    /// the proposal can execute at `now`; the timelock is recomputed from the
+    /// verified approvals
+    pub fn ensure_executable(&self, config: &MultisigConfig, now: i64) -> Result<()> {
+        self.ensure_open()?;
+        if self.approval_threshold != config.threshold {
+            bail!(
+                "Proposal #{} expects a threshold of {}, the multisig config has {}",
+                self.id,
+                self.approval_threshold,
+                config.threshold
+            );
+        }
+        let report = self.check_approvals(config);
+        if report.valid.len() < config.threshold as usize {
+            bail!(
+                "Proposal #{} has {} of {} valid approvals",
+                self.id,
+                report.valid.len(),
+                config.threshold
+            );
+        }
+        match report.threshold_reached_at.map(|at| at.saturating_add(config.timelock_secs)) {
+            Some(until) if now >= until => Ok(()),
+            Some(until) => bail!(
+                "Timelock active for another {} (until unix time {})",
+                format_duration(until - now),
+                until
+            ),
+            None => bail!("Proposal #{} never reached its threshold", self.id),
+        }
+    }
+
+    pub fn ensure_open(&self) -> Result<()> {
+        match self.status {
+            UpgradeStatus::Executed => bail!("Proposal #{} was already executed", self.id),
+            UpgradeStatus::Cancelled => bail!("Proposal #{} was cancelled", self.id),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// `1d 3h 20m` style
+pub fn format_duration(secs: i64) -> String {
+    let secs = secs.max(0);
+    let (days, hours, minutes) = (secs / 86_400, secs % 86_400 / 3_600, secs % 3_600 / 60);
+    match (days, hours) {
+        (0, 0) => format!("{}m {}s", minutes, secs % 60),
+        (0, _) => format!("{}h {}m", hours, minutes),
+        _ => format!("{}d {}h {}m", days, hours, minutes),
+    }
+}
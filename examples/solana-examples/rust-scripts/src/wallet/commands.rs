@@ -0,0 +1,247 @@
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Args, Subcommand};
+use serde_json::json;
+use solana_sdk::signature::{write_keypair_file, Signer};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::grind::{grind, Pattern, Progress};
+use super::{derive_keypair, derive_users, generate_phrase, parse_phrase, seed, DerivationPath};
+
+#[derive(Subcommand)]
+pub enum WalletCommand {
+    /// Generate a new BIP39 seed phrase
+    New {
+        /// Words in the phrase: 12, 15, 18, 21 or 24
+        #[arg(long, default_value = "12")]
+        words: usize,
+        /// Write the phrase to this file instead of printing it
+        #[arg(long)]
+        outfile: Option<PathBuf>,
+    },
+    /// Derive test users m/44'/501'/i'/0' from a seed phrase
+    Derive {
+        /// File holding the seed phrase
+        #[arg(long)]
+        phrase_file: PathBuf,
+        /// BIP39 passphrase, if the phrase was set up with one
+        #[arg(long, default_value = "")]
+        passphrase: String,
+        /// Users to derive
+        #[arg(long, default_value = "1", value_parser = clap::value_parser!(u32).range(1..))]
+        count: u32,
+        /// First account index
+        #[arg(long, default_value = "0")]
+        start: u32,
+        /// Write each keypair to <DIR>/user-<INDEX>.json
+        #[arg(long)]
+        out_dir: Option<PathBuf>,
+        /// Print index, path and pubkey as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Recover the keypair at one derivation path from a seed phrase
+    Import {
+        /// File holding the seed phrase
+        #[arg(long)]
+        phrase_file: PathBuf,
+        /// BIP39 passphrase, if the phrase was set up with one
+        #[arg(long, default_value = "")]
+        passphrase: String,
+        /// Derivation path; all indices hardened
+        #[arg(long, default_value = "m/44'/501'/0'/0'")]
+        path: String,
+        /// Keypair file to write
+        #[arg(long)]
+        outfile: PathBuf,
+    },
+}
+
+#[derive(Args)]
+pub struct GrindArgs {
+    /// Characters the address must start with, e.g. GDX
+    #[arg(long, default_value = "", required_unless_present = "suffix")]
+    prefix: String,
+    /// Characters the address must end with
+    #[arg(long, default_value = "")]
+    suffix: String,
+    /// Match letters in either case
+    #[arg(long)]
+    case_insensitive: bool,
+    /// Matching keypairs to find
+    #[arg(long, default_value = "1", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    count: usize,
+    /// Threads generating keypairs (default: one per CPU)
+    #[arg(long)]
+    threads: Option<usize>,
+    /// Directory for the keypair files, named <PUBKEY>.json
+    #[arg(long, default_value = ".")]
+    out_dir: PathBuf,
+}
+
+pub async fn run(command: WalletCommand) -> Result<()> {
+    match command {
+        WalletCommand::New { words, outfile } => {
+            let phrase = generate_phrase(words)?;
+            match outfile {
+                Some(path) => {
+                    create_new(&path, &phrase)?;
+                    println!("✅ {}-word seed phrase written to {}", words, path.display());
+                }
+                None => {
+                    println!("🔑 {}", phrase);
+                    println!("\n⚠️  Anyone with this phrase controls every wallet derived from it.");
+                }
+            }
+            Ok(())
+        }
+        WalletCommand::Derive {
+            phrase_file,
+            passphrase,
+            count,
+            start,
+            out_dir,
+            json,
+        } => {
+            let seed = seed(&parse_phrase(&read_phrase(&phrase_file)?)?, &passphrase);
+            let users = derive_users(&seed, start, count)?;
+            if let Some(dir) = &out_dir {
+                std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+                for (i, (_, keypair)) in (start..).zip(&users) {
+                    let path = dir.join(format!("user-{}.json", i));
+                    write_keypair_file(keypair, &path)
+                        .map_err(|e| anyhow!("Failed to write keypair {}: {}", path.display(), e))?;
+                }
+            }
+            if json {
+                let users: Vec<_> = (start..)
+                    .zip(&users)
+                    .map(|(i, (path, keypair))| {
+                        json!({ "index": i, "path": path.to_string(), "pubkey": keypair.pubkey().to_string() })
+                    })
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&users)?);
+            } else {
+                for (i, (path, keypair)) in (start..).zip(&users) {
+                    println!("   {:>6}  {:<22} {}", i, path.to_string(), keypair.pubkey());
+                }
+                if let Some(dir) = &out_dir {
+                    println!("💾 {} keypair(s) written to {}", users.len(), dir.display());
+                }
+            }
+            Ok(())
+        }
+        WalletCommand::Import {
+            phrase_file,
+            passphrase,
+            path,
+            outfile,
+        } => {
+            let seed = seed(&parse_phrase(&read_phrase(&phrase_file)?)?, &passphrase);
+            let path: DerivationPath = path.parse()?;
+            let keypair = derive_keypair(&seed, &path)?;
+            if outfile.exists() {
+                bail!("{} already exists; not overwriting it", outfile.display());
+            }
+            write_keypair_file(&keypair, &outfile)
+                .map_err(|e| anyhow!("Failed to write keypair {}: {}", outfile.display(), e))?;
+            println!("✅ {} at {} written to {}", keypair.pubkey(), path, outfile.display());
+            Ok(())
+        }
+    }
+}
+
+/// Generate keypairs until `count` addresses match, reporting progress
+/// every second; Ctrl-C keeps what was found so far
+pub async fn run_grind(args: GrindArgs) -> Result<()> {
+    let pattern = Pattern::new(&args.prefix, &args.suffix, args.case_insensitive)?;
+    let threads = args
+        .threads
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+    let expected = pattern.expected_attempts() * args.count as f64;
+    println!(
+        "⛏️  Grinding {} address(es) starting with {:?} and ending with {:?}{} on {} thread(s)",
+        args.count,
+        pattern.prefix,
+        pattern.suffix,
+        if pattern.case_insensitive { ", any case" } else { "" },
+        threads
+    );
+    println!("   ~{:.0} keypair(s) expected", expected);
+    std::fs::create_dir_all(&args.out_dir).with_context(|| format!("Failed to create {}", args.out_dir.display()))?;
+
+    let progress = Arc::new(Progress::default());
+    let start = Instant::now();
+    let mut work = tokio::task::spawn_blocking({
+        let progress = progress.clone();
+        let pattern = pattern.clone();
+        move || grind(&pattern, threads, args.count, &progress)
+    });
+    let mut ticker = tokio::time::interval(Duration::from_secs(1));
+    ticker.tick().await;
+    let found = loop {
+        tokio::select! {
+            found = &mut work => break found?,
+            _ = ticker.tick() => {
+                let attempts = progress.attempts.load(Ordering::Relaxed);
+                let rate = attempts as f64 / start.elapsed().as_secs_f64();
+                let remaining = (expected - attempts as f64).max(0.0) / rate.max(1.0);
+                println!(
+                    "   {:>12} keypair(s), {:>9.0} keys/sec, ~{} left",
+                    attempts,
+                    rate,
+                    human_duration(remaining)
+                );
+            }
+            _ = tokio::signal::ctrl_c(), if !progress.stop.load(Ordering::Relaxed) => {
+                println!("\n🛑 Stopping...");
+                progress.stop.store(true, Ordering::Relaxed);
+            }
+        }
+    };
+
+    let attempts = progress.attempts.load(Ordering::Relaxed);
+    for keypair in &found {
+        let path = args.out_dir.join(format!("{}.json", keypair.pubkey()));
+        write_keypair_file(keypair, &path).map_err(|e| anyhow!("Failed to write keypair {}: {}", path.display(), e))?;
+        println!("✅ {} → {}", keypair.pubkey(), path.display());
+    }
+    println!(
+        "📊 {} keypair(s) in {:.1}s ({:.0} keys/sec)",
+        attempts,
+        start.elapsed().as_secs_f64(),
+        attempts as f64 / start.elapsed().as_secs_f64()
+    );
+    if found.len() < args.count {
+        bail!("Stopped after {} of {} match(es)", found.len(), args.count);
+    }
+    Ok(())
+}
+
+fn human_duration(secs: f64) -> String {
+    match secs {
+        s if s < 120.0 => format!("{:.0}s", s),
+        s if s < 7_200.0 => format!("{:.0}m", s / 60.0),
+        s if s < 172_800.0 => format!("{:.1}h", s / 3_600.0),
+        s => format!("{:.0}d", s / 86_400.0),
+    }
+}
+
+fn read_phrase(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+/// Write a secret to a new file, readable by the owner only
+fn create_new(path: &Path, contents: &str) -> Result<()> {
+    use std::io::Write;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("Failed to create {} (it must not exist yet)", path.display()))?;
+    writeln!(file, "{}", contents).with_context(|| format!("Failed to write {}", path.display()))
+}
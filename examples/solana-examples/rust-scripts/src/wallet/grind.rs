@@ -0,0 +1,127 @@
+//! Vanity addresses: generate random keypairs until the address matches
+//!
+//! Every character of a pattern cuts the odds by up to 58, so a 3-character
+//! prefix such as `GDX` takes about 195,000 keypairs and each extra
+//! character multiplies that by 58. [`Pattern::expected_attempts`] gives the
+//! estimate up front. The first character of an address is not quite
+//! uniform, so prefixes starting with a high character take somewhat longer
+//! than estimated.
+
+use anyhow::{bail, Result};
+use solana_sdk::signature::{Keypair, Signer};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+
+pub const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Keypairs a thread generates between looks at the stop flag
+const BATCH: u64 = 256;
+
+/// What the address must start and end with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    pub prefix: String,
+    pub suffix: String,
+    pub case_insensitive: bool,
+}
+
+impl Pattern {
+    /// Fails if a character can never appear in an address: base58 has no
+    /// `0`, `O`, `I` or `l`. Ignoring case, `o`, `i` and `L` still match.
+    pub fn new(prefix: &str, suffix: &str, case_insensitive: bool) -> Result<Self> {
+        if prefix.is_empty() && suffix.is_empty() {
+            bail!("Give a prefix, a suffix or both");
+        }
+        for c in prefix.chars().chain(suffix.chars()) {
+            if variants(c, case_insensitive) == 0 {
+                bail!(
+                    "{:?} never appears in an address; base58 has no 0, O, I or l{}",
+                    c,
+                    if case_insensitive { "" } else { " (try --case-insensitive)" }
+                );
+            }
+        }
+        // Addresses are at most 44 characters
+        if prefix.len() + suffix.len() > 44 {
+            bail!("Prefix and suffix together are longer than an address");
+        }
+        let normalize = |s: &str| if case_insensitive { s.to_lowercase() } else { s.to_string() };
+        Ok(Self {
+            prefix: normalize(prefix),
+            suffix: normalize(suffix),
+            case_insensitive,
+        })
+    }
+
+    pub fn matches(&self, address: &str) -> bool {
+        if self.case_insensitive {
+            let address = address.to_lowercase();
+            address.starts_with(&self.prefix) && address.ends_with(&self.suffix)
+        } else {
+            address.starts_with(&self.prefix) && address.ends_with(&self.suffix)
+        }
+    }
+
+    /// Chance that one random address matches
+    pub fn probability(&self) -> f64 {
+        self.prefix
+            .chars()
+            .chain(self.suffix.chars())
+            .map(|c| variants(c, self.case_insensitive) as f64 / 58.0)
+            .product()
+    }
+
+    /// Keypairs to generate for one match, on average
+    pub fn expected_attempts(&self) -> f64 {
+        1.0 / self.probability()
+    }
+}
+
+/// Base58 characters that match `c`
+fn variants(c: char, case_insensitive: bool) -> usize {
+    if !case_insensitive {
+        return BASE58_ALPHABET.contains(c) as usize;
+    }
+    let mut cases = vec![c.to_ascii_lowercase(), c.to_ascii_uppercase()];
+    cases.dedup();
+    cases.into_iter().filter(|c| BASE58_ALPHABET.contains(*c)).count()
+}
+
+/// Counters shared with whoever reports progress
+#[derive(Debug, Default)]
+pub struct Progress {
+    pub attempts: AtomicU64,
+    /// Set when enough matches are found, or by the caller to stop early
+    pub stop: AtomicBool,
+}
+
+/// Generate keypairs on `threads` threads until `count` match or
+/// `progress.stop` is set. Returns the matches found, at most `count`.
+pub fn grind(pattern: &Pattern, threads: usize, count: usize, progress: &Progress) -> Vec<Keypair> {
+    let found = Mutex::new(Vec::new());
+    std::thread::scope(|scope| {
+        for _ in 0..threads.max(1) {
+            scope.spawn(|| {
+                while !progress.stop.load(Ordering::Relaxed) {
+                    let mut generated = 0;
+                    while generated < BATCH {
+                        let keypair = Keypair::new();
+                        generated += 1;
+                        if pattern.matches(&keypair.pubkey().to_string()) {
+                            let mut found = found.lock().unwrap();
+                            if found.len() < count {
+                                found.push(keypair);
+                            }
+                            if found.len() >= count {
+                                progress.stop.store(true, Ordering::Relaxed);
+                                break;
+                            }
+                        }
+                    }
+                    progress.attempts.fetch_add(generated, Ordering::Relaxed);
+                }
+            });
+        }
+    });
+    found.into_inner().unwrap()
+}
@@ -0,0 +1,134 @@
+//! Deterministic wallets: BIP39 seed phrases and SLIP-0010 key derivation
+//!
+//! A phrase ([`generate_phrase`], or one imported with [`parse_phrase`]) and
+//! an optional passphrase give a 64-byte BIP39 seed. Keys are derived from
+//! the seed along Solana's `m/44'/501'/i'/0'` path ([`path`]), as wallets
+//! such as Phantom and `solana-keygen recover` do, so user `i` of a test
+//! set is always the same keypair. SLIP-0010 only defines hardened
+//! derivation for ed25519, so every index in the path is hardened.
+//!
+//! [`grind`] finds random keypairs with vanity addresses instead.
+
+pub mod commands;
+pub mod grind;
+
+use anyhow::{anyhow, bail, Result};
+use bip39::{Language, Mnemonic, MnemonicType, Seed};
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+use solana_sdk::signature::{keypair_from_seed, Keypair};
+use std::fmt;
+use std::str::FromStr;
+
+/// BIP44 coin type registered for Solana
+pub const SOLANA_COIN_TYPE: u32 = 501;
+
+/// Child indices at or above this are hardened, written `i'`
+pub const HARDENED: u32 = 1 << 31;
+
+const SLIP10_ED25519_KEY: &[u8] = b"ed25519 seed";
+
+/// A new random phrase of 12, 15, 18, 21 or 24 English words
+pub fn generate_phrase(words: usize) -> Result<String> {
+    let mtype = MnemonicType::for_word_count(words).map_err(|e| anyhow!("{}", e))?;
+    Ok(Mnemonic::new(mtype, Language::English).into_phrase())
+}
+
+/// Check an imported phrase's words and checksum. Extra whitespace and
+/// upper case are tolerated.
+pub fn parse_phrase(phrase: &str) -> Result<Mnemonic> {
+    let normalized = phrase.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    Mnemonic::from_phrase(&normalized, Language::English).map_err(|e| anyhow!("Invalid seed phrase: {}", e))
+}
+
+/// The BIP39 seed of a phrase; a different passphrase gives unrelated keys
+pub fn seed(mnemonic: &Mnemonic, passphrase: &str) -> [u8; 64] {
+    let mut seed = [0u8; 64];
+    seed.copy_from_slice(Seed::new(mnemonic, passphrase).as_bytes());
+    seed
+}
+
+/// A derivation path of hardened indices, e.g. `m/44'/501'/0'/0'`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivationPath(pub Vec<u32>);
+
+/// Solana's path for account `index`: `m/44'/501'/index'/0'`
+pub fn path(index: u32) -> DerivationPath {
+    DerivationPath(vec![44, SOLANA_COIN_TYPE, index, 0])
+}
+
+impl FromStr for DerivationPath {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.trim().split('/');
+        if parts.next() != Some("m") {
+            bail!("Derivation path {:?} must start with m/", s);
+        }
+        let mut indices = Vec::new();
+        for part in parts {
+            let Some(index) = part.strip_suffix('\'').or_else(|| part.strip_suffix('h')) else {
+                bail!("{:?} in {:?} is not hardened; ed25519 keys only derive hardened (i')", part, s);
+            };
+            let index: u32 = index.parse().map_err(|_| anyhow!("Invalid index {:?} in {:?}", part, s))?;
+            if index >= HARDENED {
+                bail!("Index {} in {:?} is out of range", index, s);
+            }
+            indices.push(index);
+        }
+        Ok(Self(indices))
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "m")?;
+        for index in &self.0 {
+            write!(f, "/{}'", index)?;
+        }
+        Ok(())
+    }
+}
+
+/// SLIP-0010 ed25519 derivation: the 32-byte private key and chain code at
+/// `path` below `seed`
+pub fn derive_key(seed: &[u8], path: &DerivationPath) -> ([u8; 32], [u8; 32]) {
+    let (mut key, mut chain_code) = hmac_halves(SLIP10_ED25519_KEY, &[seed]);
+    for index in &path.0 {
+        (key, chain_code) = hmac_halves(&chain_code, &[&[0], &key, &(index | HARDENED).to_be_bytes()]);
+    }
+    (key, chain_code)
+}
+
+fn hmac_halves(key: &[u8], data: &[&[u8]]) -> ([u8; 32], [u8; 32]) {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC takes keys of any length");
+    for part in data {
+        mac.update(part);
+    }
+    let out = mac.finalize().into_bytes();
+    let mut left = [0u8; 32];
+    let mut right = [0u8; 32];
+    left.copy_from_slice(&out[..32]);
+    right.copy_from_slice(&out[32..]);
+    (left, right)
+}
+
+/// The keypair at `path` below `seed`
+pub fn derive_keypair(seed: &[u8], path: &DerivationPath) -> Result<Keypair> {
+    let (key, _) = derive_key(seed, path);
+    keypair_from_seed(&key).map_err(|e| anyhow!("Failed to build keypair at {}: {}", path, e))
+}
+
+/// Test users `start..start + count`: user `i` is the keypair at
+/// `m/44'/501'/i'/0'`
+pub fn derive_users(seed: &[u8], start: u32, count: u32) -> Result<Vec<(DerivationPath, Keypair)>> {
+    let Some(end) = start.checked_add(count).filter(|end| *end <= HARDENED) else {
+        bail!("Indices {}..{}+{} exceed the hardened range", start, start, count);
+    };
+    (start..end)
+        .map(|i| {
+            let path = path(i);
+            derive_keypair(seed, &path).map(|keypair| (path, keypair))
+        })
+        .collect()
+}
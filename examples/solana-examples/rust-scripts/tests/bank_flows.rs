@@ -0,0 +1,337 @@
+//! The example flows, run against the in-process bank
+
+use solana_examples_scripts::bank::{processor, Bank, ProgramTest, GENESIS_UNIX_TIMESTAMP, LAMPORTS_PER_SIGNATURE};
+use solana_examples_scripts::client::{send, token_balance, LedgerClient};
+use solana_examples_scripts::examples::{airdrop::airdrop, send_transaction, token_operations};
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+    program_error::ProgramError,
+    system_instruction, system_program,
+    sysvar::{rent::Rent, Sysvar},
+};
+use solana_sdk::{
+    clock::MAX_PROCESSING_AGE,
+    hash::Hash,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::get_associated_token_address;
+
+const SOL: u64 = 1_000_000_000;
+
+async fn funded(bank: &Bank, lamports: u64) -> Keypair {
+    let keypair = Keypair::new();
+    airdrop(bank, &keypair.pubkey(), lamports).await.unwrap();
+    keypair
+}
+
+/// A vault program: creates the `[b"vault", owner]` PDA and stamps it with
+/// the owner and the clock's unix timestamp
+fn vault_program(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [owner, vault, system] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let bump = *data.first().ok_or(ProgramError::InvalidInstructionData)?;
+    let space = 40;
+    invoke_signed(
+        &system_instruction::create_account(
+            owner.key,
+            vault.key,
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            program_id,
+        ),
+        &[owner.clone(), vault.clone(), system.clone()],
+        &[&[b"vault", owner.key.as_ref(), &[bump]]],
+    )?;
+    let mut state = vault.try_borrow_mut_data()?;
+    state[..32].copy_from_slice(owner.key.as_ref());
+    state[32..].copy_from_slice(&Clock::get()?.unix_timestamp.to_le_bytes());
+    Ok(())
+}
+
+/// A bank running [`vault_program`] at a new address
+async fn with_vault() -> (Bank, Pubkey) {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::default();
+    program_test.add_program("vault", program_id, processor!(vault_program));
+    (Bank::start(program_test).await, program_id)
+}
+
+/// Move to the next slot, which brings a new blockhash
+async fn next_slot(bank: &Bank) {
+    let slot = bank.get_slot().await.unwrap();
+    bank.warp_to_slot(slot + 1).await.unwrap();
+}
+
+fn open_vault(program_id: &Pubkey, owner: &Pubkey, bump: u8) -> Instruction {
+    let (vault, _) = Pubkey::find_program_address(&[b"vault", owner.as_ref()], program_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &[bump],
+        vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+#[tokio::test]
+async fn airdrop_credits_the_account() {
+    let bank = Bank::new().await;
+    let to = Pubkey::new_unique();
+
+    let (before, after) = airdrop(&bank, &to, 2 * SOL).await.unwrap();
+    assert_eq!((before, after), (0, 2 * SOL));
+    let (_, after) = airdrop(&bank, &to, SOL).await.unwrap();
+    assert_eq!(after, 3 * SOL);
+}
+
+#[tokio::test]
+async fn transfer_moves_lamports_and_charges_the_fee() {
+    let bank = Bank::new().await;
+    let from = funded(&bank, 2 * SOL).await;
+    let to = Pubkey::new_unique();
+
+    let signature = send_transaction::transfer(&bank, &from, &to, SOL / 2).await.unwrap();
+
+    assert_eq!(bank.get_balance(&to).await.unwrap(), SOL / 2);
+    assert_eq!(bank.get_balance(&from.pubkey()).await.unwrap(), 2 * SOL - SOL / 2 - LAMPORTS_PER_SIGNATURE);
+    assert_eq!(bank.get_signature_status(&signature).await.unwrap(), Some(Ok(())));
+}
+
+#[tokio::test]
+async fn failed_transfer_still_pays_the_fee() {
+    let bank = Bank::new().await;
+    let from = funded(&bank, SOL).await;
+    let to = Pubkey::new_unique();
+
+    let error = send_transaction::transfer(&bank, &from, &to, 2 * SOL).await.unwrap_err();
+
+    assert!(format!("{:#}", error).contains("insufficient lamports"), "{:#}", error);
+    assert_eq!(bank.get_balance(&to).await.unwrap(), 0);
+    assert_eq!(bank.get_balance(&from.pubkey()).await.unwrap(), SOL - LAMPORTS_PER_SIGNATURE);
+}
+
+#[tokio::test]
+async fn transfer_below_rent_exemption_is_rejected() {
+    let bank = Bank::new().await;
+    let from = funded(&bank, SOL).await;
+    let tx = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(&from.pubkey(), &Pubkey::new_unique(), 1)],
+        Some(&from.pubkey()),
+        &[&from],
+        bank.get_latest_blockhash().await.unwrap(),
+    );
+
+    assert_eq!(
+        bank.process_transaction(&tx).await.unwrap().result,
+        Err(TransactionError::InsufficientFundsForRent { account_index: 1 })
+    );
+}
+
+#[tokio::test]
+async fn mint_and_transfer_tokens_between_associated_accounts() {
+    let bank = Bank::new().await;
+    let payer = funded(&bank, 2 * SOL).await;
+    let authority = Keypair::new();
+    let receiver = Keypair::new();
+    let mint = Keypair::new();
+
+    token_operations::create_mint(&bank, &payer, &mint, &authority.pubkey(), 6)
+        .await
+        .unwrap();
+    let payer_ata = token_operations::create_associated_account(&bank, &payer, &payer.pubkey(), &mint.pubkey())
+        .await
+        .unwrap();
+    let receiver_ata = token_operations::create_associated_account(&bank, &payer, &receiver.pubkey(), &mint.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(payer_ata, get_associated_token_address(&payer.pubkey(), &mint.pubkey()));
+
+    token_operations::mint_to(&bank, &payer, &mint.pubkey(), &payer_ata, &authority, 1_000_000)
+        .await
+        .unwrap();
+    token_operations::transfer(&bank, &payer, &payer_ata, &receiver_ata, 250_000)
+        .await
+        .unwrap();
+
+    assert_eq!(token_balance(&bank, &payer_ata).await.unwrap(), 750_000);
+    assert_eq!(token_balance(&bank, &receiver_ata).await.unwrap(), 250_000);
+    let account = bank.get_account(&receiver_ata).await.unwrap().unwrap();
+    let state = spl_token::state::Account::unpack(&account.data).unwrap();
+    assert_eq!(state.owner, receiver.pubkey());
+    assert_eq!(account.owner, spl_token::id());
+}
+
+#[tokio::test]
+async fn creating_an_associated_account_twice_is_idempotent() {
+    let bank = Bank::new().await;
+    let payer = funded(&bank, SOL).await;
+    let mint = Keypair::new();
+    token_operations::create_mint(&bank, &payer, &mint, &payer.pubkey(), 0)
+        .await
+        .unwrap();
+
+    let first = token_operations::create_associated_account(&bank, &payer, &payer.pubkey(), &mint.pubkey())
+        .await
+        .unwrap();
+    // Same instruction, so a new blockhash to make it a new transaction
+    next_slot(&bank).await;
+    let second = token_operations::create_associated_account(&bank, &payer, &payer.pubkey(), &mint.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(first, second);
+}
+
+#[tokio::test]
+async fn minting_without_the_authority_fails() {
+    let bank = Bank::new().await;
+    let payer = funded(&bank, SOL).await;
+    let mint = Keypair::new();
+    token_operations::create_mint(&bank, &payer, &mint, &payer.pubkey(), 0)
+        .await
+        .unwrap();
+    let ata = token_operations::create_associated_account(&bank, &payer, &payer.pubkey(), &mint.pubkey())
+        .await
+        .unwrap();
+
+    let impostor = Keypair::new();
+    let result = token_operations::mint_to(&bank, &payer, &mint.pubkey(), &ata, &impostor, 1).await;
+
+    assert!(result.is_err());
+    assert_eq!(token_balance(&bank, &ata).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn program_creates_its_pda_with_invoke_signed() {
+    let (bank, program_id) = with_vault().await;
+    let owner = funded(&bank, SOL).await;
+    let (vault, bump) = Pubkey::find_program_address(&[b"vault", owner.pubkey().as_ref()], &program_id);
+
+    send(&bank, &[open_vault(&program_id, &owner.pubkey(), bump)], &owner, &[])
+        .await
+        .unwrap();
+
+    let account = bank.get_account(&vault).await.unwrap().unwrap();
+    assert_eq!(account.owner, program_id);
+    assert_eq!(account.lamports, bank.get_minimum_balance_for_rent_exemption(40).await.unwrap());
+    assert_eq!(&account.data[..32], owner.pubkey().as_ref());
+    assert_eq!(account.data[32..], GENESIS_UNIX_TIMESTAMP.to_le_bytes());
+
+    // The PDA exists now, so opening it again fails and changes nothing
+    next_slot(&bank).await;
+    let result = send(&bank, &[open_vault(&program_id, &owner.pubkey(), bump)], &owner, &[]).await;
+    assert!(result.is_err());
+    assert_eq!(bank.get_account(&vault).await.unwrap().unwrap(), account);
+}
+
+#[tokio::test]
+async fn pda_signature_needs_the_right_seeds() {
+    let (bank, program_id) = with_vault().await;
+    let owner = funded(&bank, SOL).await;
+    let (vault, bump) = Pubkey::find_program_address(&[b"vault", owner.pubkey().as_ref()], &program_id);
+
+    let result = send(&bank, &[open_vault(&program_id, &owner.pubkey(), bump.wrapping_sub(1))], &owner, &[]).await;
+
+    assert!(result.is_err());
+    assert!(bank.get_account(&vault).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn clock_follows_warps_and_overrides() {
+    let (bank, program_id) = with_vault().await;
+    let owner = funded(&bank, SOL).await;
+    let (vault, bump) = Pubkey::find_program_address(&[b"vault", owner.pubkey().as_ref()], &program_id);
+
+    let slot = bank.get_slot().await.unwrap();
+    bank.warp_to_slot(slot + 1_000).await.unwrap();
+    assert_eq!(bank.get_slot().await.unwrap(), slot + 1_000);
+    assert_eq!(bank.clock().await.unwrap().unix_timestamp, GENESIS_UNIX_TIMESTAMP + 400);
+    bank.set_unix_timestamp(1_800_000_000).await.unwrap();
+
+    send(&bank, &[open_vault(&program_id, &owner.pubkey(), bump)], &owner, &[])
+        .await
+        .unwrap();
+
+    let account = bank.get_account(&vault).await.unwrap().unwrap();
+    assert_eq!(account.data[32..], 1_800_000_000i64.to_le_bytes());
+}
+
+#[tokio::test]
+async fn blockhash_expires_after_max_processing_age() {
+    let bank = Bank::new().await;
+    let from = funded(&bank, SOL).await;
+    let to = Pubkey::new_unique();
+    let transfer = |blockhash| {
+        Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(&from.pubkey(), &to, SOL / 10)],
+            Some(&from.pubkey()),
+            &[&from],
+            blockhash,
+        )
+    };
+
+    let blockhash = bank.get_latest_blockhash().await.unwrap();
+    for _ in 0..MAX_PROCESSING_AGE / 2 {
+        next_slot(&bank).await;
+    }
+    assert_eq!(bank.process_transaction(&transfer(blockhash)).await.unwrap().result, Ok(()));
+
+    let blockhash = bank.get_latest_blockhash().await.unwrap();
+    for _ in 0..=MAX_PROCESSING_AGE {
+        next_slot(&bank).await;
+    }
+    let expired = bank.process_transaction(&transfer(blockhash)).await.unwrap();
+    assert_eq!(expired.result, Err(TransactionError::BlockhashNotFound));
+    assert!(!expired.executed);
+    let unknown = bank.process_transaction(&transfer(Hash::new_unique())).await.unwrap();
+    assert_eq!(unknown.result, Err(TransactionError::BlockhashNotFound));
+
+    // A fresh blockhash goes through
+    let blockhash = bank.get_latest_blockhash().await.unwrap();
+    assert_eq!(bank.process_transaction(&transfer(blockhash)).await.unwrap().result, Ok(()));
+    assert_eq!(bank.get_balance(&to).await.unwrap(), 2 * SOL / 10);
+}
+
+#[tokio::test]
+async fn the_same_transaction_is_processed_once() {
+    let bank = Bank::new().await;
+    let from = funded(&bank, SOL).await;
+    let tx = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(&from.pubkey(), &Pubkey::new_unique(), SOL / 10)],
+        Some(&from.pubkey()),
+        &[&from],
+        bank.get_latest_blockhash().await.unwrap(),
+    );
+
+    assert_eq!(bank.process_transaction(&tx).await.unwrap().result, Ok(()));
+    assert_eq!(
+        bank.process_transaction(&tx).await.unwrap().result,
+        Err(TransactionError::AlreadyProcessed)
+    );
+}
+
+#[tokio::test]
+async fn unsigned_transaction_is_rejected() {
+    let bank = Bank::new().await;
+    let from = funded(&bank, SOL).await;
+    let mut tx = Transaction::new_with_payer(
+        &[system_instruction::transfer(&from.pubkey(), &Pubkey::new_unique(), SOL / 10)],
+        Some(&from.pubkey()),
+    );
+    tx.message.recent_blockhash = bank.get_latest_blockhash().await.unwrap();
+
+    assert_eq!(
+        bank.process_transaction(&tx).await.unwrap().result,
+        Err(TransactionError::SignatureFailure)
+    );
+    assert_eq!(bank.get_balance(&from.pubkey()).await.unwrap(), SOL);
+}
@@ -0,0 +1,151 @@
+//! Cache writer slot guards: writes older than the cached slot are dropped
+//!
+//! Needs a Redis server; point `GDX_TEST_REDIS_URL` at a scratch database,
+//! e.g. `redis://127.0.0.1:6379/15` with docker-compose up. The database is
+//! flushed. Without it the tests are skipped.
+
+use redis::aio::MultiplexedConnection;
+use solana_examples_scripts::cache::{
+    positions_key, price_key, vault_key, CacheWriter, PositionUpdate, PriceUpdate, VaultUpdate,
+};
+use std::collections::HashMap;
+use tokio::sync::{Mutex, MutexGuard};
+
+/// The tests share one database and the position owners set, so they take
+/// turns
+static REDIS: Mutex<()> = Mutex::const_new(());
+
+/// Writer and a plain connection to read back with, on a flushed database
+async fn redis() -> Option<(MutexGuard<'static, ()>, CacheWriter, MultiplexedConnection)> {
+    let Ok(url) = std::env::var("GDX_TEST_REDIS_URL") else {
+        eprintln!("GDX_TEST_REDIS_URL is not set, skipping");
+        return None;
+    };
+    let turn = REDIS.lock().await;
+    let mut conn = redis::Client::open(url.as_str())
+        .unwrap()
+        .get_multiplexed_async_connection()
+        .await
+        .unwrap();
+    redis::cmd("FLUSHDB").query_async::<_, ()>(&mut conn).await.unwrap();
+    Some((turn, CacheWriter::connect(&url).await.unwrap(), conn))
+}
+
+async fn hash(conn: &mut MultiplexedConnection, key: &str) -> HashMap<String, String> {
+    redis::cmd("HGETALL").arg(key).query_async(conn).await.unwrap()
+}
+
+fn price(price: i64, slot: u64) -> PriceUpdate {
+    PriceUpdate {
+        symbol: "BTC-USDT".to_string(),
+        price,
+        confidence: 10,
+        expo: -8,
+        timestamp: 1_760_000_000,
+        source: "pyth".to_string(),
+        slot,
+    }
+}
+
+fn position(owner: &str, symbol: &str, size: u64, slot: u64) -> PositionUpdate {
+    PositionUpdate {
+        owner: owner.to_string(),
+        symbol: symbol.to_string(),
+        position: format!("{owner}-{symbol}"),
+        side: "long".to_string(),
+        size,
+        entry_price: 64_000_000_000,
+        margin: 1_000_000,
+        leverage: 5,
+        liquidation_price: 52_000_000_000,
+        slot,
+        closed: size == 0,
+    }
+}
+
+/// Sizes of the cached positions of `owner`, by symbol
+async fn sizes(conn: &mut MultiplexedConnection, owner: &str) -> HashMap<String, u64> {
+    hash(conn, &positions_key(owner))
+        .await
+        .into_iter()
+        .map(|(symbol, json)| {
+            let update: serde_json::Value = serde_json::from_str(&json).unwrap();
+            (symbol, update["size"].as_u64().unwrap())
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn stale_price_and_vault_writes_are_dropped() {
+    let Some((_turn, mut writer, mut conn)) = redis().await else { return };
+
+    assert!(writer.write_price(&price(100, 10)).await.unwrap());
+    assert!(!writer.write_price(&price(90, 9)).await.unwrap());
+    let cached = hash(&mut conn, &price_key("BTC-USDT")).await;
+    assert_eq!((cached["price"].as_str(), cached["slot"].as_str()), ("100", "10"));
+
+    // The same slot is not older, so it is written
+    assert!(writer.write_price(&price(101, 10)).await.unwrap());
+    assert!(writer.write_price(&price(102, 11)).await.unwrap());
+    assert_eq!(hash(&mut conn, &price_key("BTC-USDT")).await["price"], "102");
+
+    let vault = |available: u64, slot: u64| VaultUpdate {
+        owner: "owner-1".to_string(),
+        vault: "vault-1".to_string(),
+        total_balance: 5_000,
+        available_balance: available,
+        locked_balance: 5_000 - available,
+        slot,
+    };
+    assert!(writer.write_vault(&vault(4_000, 20)).await.unwrap());
+    assert!(!writer.write_vault(&vault(5_000, 19)).await.unwrap());
+    let cached = hash(&mut conn, &vault_key("owner-1")).await;
+    assert_eq!((cached["available_balance"].as_str(), cached["slot"].as_str()), ("4000", "20"));
+}
+
+#[tokio::test]
+async fn a_stale_position_write_cannot_undo_a_newer_one() {
+    let Some((_turn, mut writer, mut conn)) = redis().await else { return };
+
+    assert!(writer.write_position(&position("alice", "BTC", 3, 20)).await.unwrap());
+    assert!(!writer.write_position(&position("alice", "BTC", 0, 19)).await.unwrap());
+    assert_eq!(sizes(&mut conn, "alice").await, HashMap::from([("BTC".to_string(), 3)]));
+
+    // Closed at 21: an open update read at 20 must not bring it back
+    assert!(writer.write_position(&position("alice", "BTC", 0, 21)).await.unwrap());
+    assert!(!writer.write_position(&position("alice", "BTC", 3, 20)).await.unwrap());
+    assert!(sizes(&mut conn, "alice").await.is_empty());
+}
+
+#[tokio::test]
+async fn a_snapshot_keeps_newer_updates_and_drops_what_it_lacks() {
+    let Some((_turn, mut writer, mut conn)) = redis().await else { return };
+
+    // Before the snapshot at 25
+    writer.write_position(&position("alice", "BTC", 3, 20)).await.unwrap();
+    writer.write_position(&position("bob", "SOL", 9, 22)).await.unwrap();
+    // After it
+    writer.write_position(&position("alice", "ETH", 7, 30)).await.unwrap();
+    writer.write_position(&position("bob", "BTC", 0, 31)).await.unwrap();
+
+    // bob's SOL closed unseen, so the snapshot lacks it; it still has bob's
+    // BTC, closed after it, and an older alice ETH
+    let snapshot = [
+        position("alice", "BTC", 4, 25),
+        position("alice", "ETH", 6, 25),
+        position("bob", "BTC", 2, 25),
+        position("carol", "SOL", 1, 25),
+    ];
+    writer.replace_positions(25, &snapshot).await.unwrap();
+
+    assert_eq!(
+        sizes(&mut conn, "alice").await,
+        HashMap::from([("BTC".to_string(), 4), ("ETH".to_string(), 7)])
+    );
+    assert!(sizes(&mut conn, "bob").await.is_empty());
+    assert_eq!(sizes(&mut conn, "carol").await, HashMap::from([("SOL".to_string(), 1)]));
+
+    // Updates older than the snapshot are now stale too
+    assert!(!writer.write_position(&position("carol", "SOL", 8, 24)).await.unwrap());
+    assert!(writer.write_position(&position("carol", "SOL", 8, 26)).await.unwrap());
+}
@@ -0,0 +1,344 @@
+//! CSV payouts against the in-process bank
+
+use anyhow::{bail, Result};
+use solana_client::rpc_filter::RpcFilterType;
+use solana_examples_scripts::bank::{Bank, LAMPORTS_PER_SIGNATURE};
+use solana_examples_scripts::client::sender::{AttemptEnd, SendConfig};
+use solana_examples_scripts::client::{token_balance, LedgerClient};
+use solana_examples_scripts::distribute::{self, parse_amount, parse_csv, pay, InFlight, Plan, State};
+use solana_examples_scripts::examples::{airdrop::airdrop, token_operations};
+use solana_sdk::{
+    account::Account,
+    clock::Slot,
+    hash::Hash,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    system_instruction,
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::get_associated_token_address;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const SOL: u64 = 1_000_000_000;
+
+fn config() -> SendConfig {
+    SendConfig {
+        rebroadcast_interval: Duration::from_millis(1),
+        max_blockhashes: 2,
+    }
+}
+
+fn state_path() -> PathBuf {
+    std::env::temp_dir().join(format!("distribute-{}.state.json", Pubkey::new_unique()))
+}
+
+/// A funded payer holding `tokens` of a new 6-decimal mint
+async fn payer_with_tokens(bank: &Bank, tokens: u64) -> (Keypair, Pubkey) {
+    let payer = Keypair::new();
+    airdrop(bank, &payer.pubkey(), 10 * SOL).await.unwrap();
+    let mint = Keypair::new();
+    token_operations::create_mint(bank, &payer, &mint, &payer.pubkey(), 6)
+        .await
+        .unwrap();
+    let ata = token_operations::create_associated_account(bank, &payer, &payer.pubkey(), &mint.pubkey())
+        .await
+        .unwrap();
+    token_operations::mint_to(bank, &payer, &mint.pubkey(), &ata, &payer, tokens)
+        .await
+        .unwrap();
+    (payer, mint.pubkey())
+}
+
+/// A bank behind a connection that drops every send response: the
+/// transaction is delivered, the caller gets a transport error
+struct LostResponses(Bank);
+
+impl LedgerClient for LostResponses {
+    async fn get_balance(&self, address: &Pubkey) -> Result<u64> {
+        self.0.get_balance(address).await
+    }
+
+    async fn get_account(&self, address: &Pubkey) -> Result<Option<Account>> {
+        self.0.get_account(address).await
+    }
+
+    async fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        self.0.get_multiple_accounts(addresses).await
+    }
+
+    async fn get_program_accounts(&self, program_id: &Pubkey, filters: &[RpcFilterType]) -> Result<Vec<(Pubkey, Account)>> {
+        self.0.get_program_accounts(program_id, filters).await
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64> {
+        self.0.get_minimum_balance_for_rent_exemption(data_len).await
+    }
+
+    async fn get_latest_blockhash(&self) -> Result<Hash> {
+        self.0.get_latest_blockhash().await
+    }
+
+    async fn get_latest_blockhash_with_expiry(&self) -> Result<(Hash, u64)> {
+        self.0.get_latest_blockhash_with_expiry().await
+    }
+
+    async fn get_slot(&self) -> Result<Slot> {
+        self.0.get_slot().await
+    }
+
+    async fn get_block_height(&self) -> Result<u64> {
+        self.0.get_block_height().await
+    }
+
+    async fn request_airdrop(&self, to: &Pubkey, lamports: u64) -> Result<Signature> {
+        self.0.request_airdrop(to, lamports).await
+    }
+
+    async fn send_transaction(&self, tx: &Transaction) -> Result<Signature> {
+        self.0.send_transaction(tx).await?;
+        bail!("error sending request: connection reset by peer")
+    }
+
+    async fn resend_transaction(&self, tx: &Transaction) -> Result<Signature> {
+        self.send_transaction(tx).await
+    }
+
+    async fn get_signature_status(&self, signature: &Signature) -> Result<Option<Result<(), TransactionError>>> {
+        self.0.get_signature_status(signature).await
+    }
+
+    async fn send_and_confirm_transaction(&self, tx: &Transaction) -> Result<Signature> {
+        self.0.send_and_confirm_transaction(tx).await
+    }
+}
+
+async fn pay_all(bank: &Bank, plan: &Plan, payer: &Keypair, state: &mut State, path: &Path) {
+    for batch in &plan.batches {
+        let transfers: Vec<_> = batch.iter().map(|&t| &plan.transfers[t]).collect();
+        let end = pay(bank, &transfers, payer, state, path, &config()).await.unwrap();
+        assert_eq!(end, AttemptEnd::Confirmed);
+    }
+}
+
+#[test]
+fn csv_rows_get_keys_that_survive_reordering() {
+    let a = Pubkey::new_unique();
+    let b = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let csv = format!("recipient,amount,mint\n# QA wallets\n{a},1.5\n\n{b},2,{mint}\n{a},1.5,\n");
+
+    let payouts = parse_csv(&csv).unwrap();
+
+    assert_eq!(payouts.len(), 3);
+    assert_eq!((payouts[0].line, payouts[0].mint), (3, None));
+    assert_eq!(payouts[1].mint, Some(mint));
+    // Identical rows are told apart by how many came before
+    assert_eq!(payouts[0].key, format!("{a}:SOL:1.5:0"));
+    assert_eq!(payouts[2].key, format!("{a}:SOL:1.5:1"));
+
+    let reordered = parse_csv(&format!("{b},2,{mint}\n{a},1.5")).unwrap();
+    assert_eq!(reordered[0].key, payouts[1].key);
+    assert_eq!(reordered[1].key, payouts[0].key);
+}
+
+#[test]
+fn invalid_rows_name_their_line() {
+    let a = Pubkey::new_unique();
+    for (csv, expected) in [
+        (format!("{a}"), "Line 1: expected recipient,amount[,mint]"),
+        ("nope,1".to_string(), "Line 1: invalid recipient"),
+        (format!("\n{a},-1"), "Line 2: invalid amount"),
+        (format!("{a},0.0000000001"), "Line 1: invalid amount"),
+        (format!("{a},1,mint"), "Line 1: invalid mint"),
+    ] {
+        let error = parse_csv(&csv).unwrap_err().to_string();
+        assert!(error.starts_with(expected), "{}: {}", csv, error);
+    }
+}
+
+#[test]
+fn amounts_convert_to_base_units_exactly() {
+    assert_eq!(parse_amount("1.5", 9).unwrap(), 1_500_000_000);
+    assert_eq!(parse_amount("0.000001", 6).unwrap(), 1);
+    assert_eq!(parse_amount(".25", 2).unwrap(), 25);
+    assert_eq!(parse_amount("18446744073709551615", 0).unwrap(), u64::MAX);
+    assert!(parse_amount("18446744073709551616", 0).is_err());
+    assert!(parse_amount("1.0000001", 6).is_err());
+    assert!(parse_amount("0", 9).is_err());
+    assert!(parse_amount("1e3", 9).is_err());
+}
+
+#[tokio::test]
+async fn pays_sol_and_tokens_and_creates_missing_accounts_once() {
+    let bank = Bank::new().await;
+    let (payer, mint) = payer_with_tokens(&bank, 10_000_000).await;
+    let recipients: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+    let mut csv = String::new();
+    for recipient in &recipients {
+        csv += &format!("{recipient},0.1\n{recipient},1.25,{mint}\n");
+    }
+    // A second token payout to the same new account
+    csv += &format!("{},0.75,{mint}\n", recipients[0]);
+    let payouts = parse_csv(&csv).unwrap();
+    let path = state_path();
+    let mut state = State::new(&payer.pubkey());
+
+    let plan = distribute::plan(&bank, &payouts, &state, &payer.pubkey(), 4).await.unwrap();
+
+    assert_eq!(plan.transfers.len(), 11);
+    assert_eq!(plan.new_accounts, 5);
+    assert_eq!(plan.batches.iter().map(Vec::len).max(), Some(4));
+    assert!(plan.shortfalls().is_empty());
+    let rent = bank
+        .get_minimum_balance_for_rent_exemption(spl_token::state::Account::LEN)
+        .await
+        .unwrap();
+    let fees = plan.batches.len() as u64 * LAMPORTS_PER_SIGNATURE;
+    assert_eq!(plan.needs[0].required, 5 * SOL / 10 + fees + 5 * rent);
+    assert_eq!(plan.needs[1].required, 7_000_000);
+
+    let before = bank.get_balance(&payer.pubkey()).await.unwrap();
+    pay_all(&bank, &plan, &payer, &mut state, &path).await;
+
+    for recipient in &recipients {
+        assert_eq!(bank.get_balance(recipient).await.unwrap(), SOL / 10);
+    }
+    let first = get_associated_token_address(&recipients[0], &mint);
+    assert_eq!(token_balance(&bank, &first).await.unwrap(), 2_000_000);
+    assert_eq!(before - bank.get_balance(&payer.pubkey()).await.unwrap(), plan.needs[0].required);
+    assert_eq!(state.paid.len(), 11);
+    assert!(state.in_flight.is_empty());
+
+    // A rerun from the saved state has nothing left to pay
+    let state = State::resume(&path, &payer.pubkey()).unwrap().unwrap();
+    let again = distribute::plan(&bank, &payouts, &state, &payer.pubkey(), 4).await.unwrap();
+    assert!(again.transfers.is_empty() && again.batches.is_empty());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn the_balance_check_covers_the_whole_batch() {
+    let bank = Bank::new().await;
+    let (payer, mint) = payer_with_tokens(&bank, 1_000_000).await;
+    let csv = format!(
+        "{},0.6,{mint}\n{},0.6,{mint}\n{},1\n",
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique()
+    );
+    let payouts = parse_csv(&csv).unwrap();
+
+    let plan = distribute::plan(&bank, &payouts, &State::new(&payer.pubkey()), &payer.pubkey(), 8)
+        .await
+        .unwrap();
+
+    let short = plan.shortfalls();
+    assert_eq!(short.len(), 1);
+    assert_eq!(short[0].mint, Some(mint));
+    assert_eq!((short[0].required, short[0].available), (1_200_000, 1_000_000));
+}
+
+#[tokio::test]
+async fn a_transaction_left_in_flight_is_not_paid_twice() {
+    let bank = Bank::new().await;
+    let payer = Keypair::new();
+    airdrop(&bank, &payer.pubkey(), SOL).await.unwrap();
+    let to = Pubkey::new_unique();
+    let payouts = parse_csv(&format!("{to},0.2")).unwrap();
+    let path = state_path();
+
+    // A run that crashed after sending: the transaction landed, but the
+    // state file still has it in flight
+    let (blockhash, last_valid_block_height) = bank.get_latest_blockhash_with_expiry().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(&payer.pubkey(), &to, SOL / 5)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        blockhash,
+    );
+    bank.send_transaction(&tx).await.unwrap();
+    let mut state = State::new(&payer.pubkey());
+    state.in_flight.insert(
+        tx.signatures[0].to_string(),
+        InFlight {
+            keys: vec![payouts[0].key.clone()],
+            last_valid_block_height,
+        },
+    );
+
+    state.resolve_in_flight(&bank, &path).await.unwrap();
+
+    assert_eq!(state.paid.get(&payouts[0].key), Some(&tx.signatures[0].to_string()));
+    assert!(state.in_flight.is_empty());
+    let plan = distribute::plan(&bank, &payouts, &state, &payer.pubkey(), 8).await.unwrap();
+    assert!(plan.transfers.is_empty());
+    assert_eq!(bank.get_balance(&to).await.unwrap(), SOL / 5);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn a_transaction_that_may_still_land_stops_the_run() {
+    let bank = Bank::new().await;
+    let payer = Keypair::new();
+    let path = state_path();
+    let mut state = State::new(&payer.pubkey());
+    let height = bank.get_block_height().await.unwrap();
+    state.in_flight.insert(
+        Signature::new_unique().to_string(),
+        InFlight {
+            keys: vec!["row".to_string()],
+            last_valid_block_height: height + 150,
+        },
+    );
+
+    let error = state.resolve_in_flight(&bank, &path).await.unwrap_err();
+
+    assert!(error.to_string().contains("may still land"), "{}", error);
+    assert_eq!(state.in_flight.len(), 1);
+    assert!(state.paid.is_empty());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn a_send_that_errors_after_delivery_is_not_paid_twice() {
+    let client = LostResponses(Bank::new().await);
+    let payer = Keypair::new();
+    airdrop(&client.0, &payer.pubkey(), SOL).await.unwrap();
+    let to = Pubkey::new_unique();
+    let payouts = parse_csv(&format!("{to},0.2")).unwrap();
+    let path = state_path();
+    let mut state = State::new(&payer.pubkey());
+    let plan = distribute::plan(&client, &payouts, &state, &payer.pubkey(), 8).await.unwrap();
+    let transfers: Vec<_> = plan.transfers.iter().collect();
+
+    let end = pay(&client, &transfers, &payer, &mut state, &path, &config()).await.unwrap();
+
+    assert_eq!(end, AttemptEnd::Confirmed);
+    assert!(state.paid.contains_key(&payouts[0].key));
+    assert!(state.failed.is_empty() && state.in_flight.is_empty());
+    let again = distribute::plan(&client, &payouts, &state, &payer.pubkey(), 8).await.unwrap();
+    assert!(again.transfers.is_empty());
+    assert_eq!(client.0.get_balance(&to).await.unwrap(), SOL / 5);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn a_transaction_the_node_refuses_fails_its_rows() {
+    let bank = Bank::new().await;
+    // Never funded, so it cannot pay the fee
+    let payer = Keypair::new();
+    let payouts = parse_csv(&format!("{},0.2", Pubkey::new_unique())).unwrap();
+    let path = state_path();
+    let mut state = State::new(&payer.pubkey());
+    let plan = distribute::plan(&bank, &payouts, &state, &payer.pubkey(), 8).await.unwrap();
+    let transfers: Vec<_> = plan.transfers.iter().collect();
+
+    let end = pay(&bank, &transfers, &payer, &mut state, &path, &config()).await.unwrap();
+
+    assert!(matches!(end, AttemptEnd::Rejected(_)), "{}", end);
+    assert!(state.failed.contains_key(&payouts[0].key));
+    assert!(state.paid.is_empty() && state.in_flight.is_empty());
+    std::fs::remove_file(&path).unwrap();
+}
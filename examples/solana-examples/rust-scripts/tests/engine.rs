@@ -0,0 +1,126 @@
+//! Matching engine client: request and response bodies in the mock engine's shapes
+
+use serde_json::json;
+use solana_examples_scripts::engine::{
+    EngineClient, EngineStats, Order, OrderAck, OrderRequest, OrderSide, OrderStatus, OrderType, Visibility,
+};
+use solana_examples_scripts::settlement::{FillSide, TradeRequest};
+
+#[test]
+fn order_requests_use_the_engine_field_names() {
+    let limit = OrderRequest {
+        user_id: "user-1".to_string(),
+        symbol: "BTC-USDT".to_string(),
+        side: OrderSide::Buy,
+        order_type: OrderType::PegMid,
+        quantity: 0.25,
+        price: Some(64_000.5),
+        leverage: 10,
+        visibility: Some(Visibility::Dark),
+        trading_view_price: None,
+    };
+
+    assert_eq!(
+        serde_json::to_value(&limit).unwrap(),
+        json!({
+            "userId": "user-1",
+            "symbol": "BTC-USDT",
+            "side": "buy",
+            "type": "peg_mid",
+            "quantity": 0.25,
+            "price": 64_000.5,
+            "leverage": 10,
+            "visibility": "dark",
+        })
+    );
+
+    let market = OrderRequest {
+        side: OrderSide::Sell,
+        order_type: OrderType::Market,
+        price: None,
+        visibility: None,
+        trading_view_price: Some(63_999.0),
+        ..limit
+    };
+    let body = serde_json::to_value(&market).unwrap();
+    assert_eq!(body["type"], "market");
+    assert_eq!(body["tradingViewPrice"], 63_999.0);
+    assert!(body.get("price").is_none() && body.get("visibility").is_none());
+}
+
+#[test]
+fn orders_and_their_fills_decode() {
+    let order: Order = serde_json::from_value(json!({
+        "id": "ord-1",
+        "userId": "user-1",
+        "symbol": "ETH-USDT",
+        "side": "sell",
+        "type": "limit",
+        "quantity": 2,
+        "price": 3_100.25,
+        "leverage": 5,
+        "timestamp": 1_760_000_000_123i64,
+        "status": "settling",
+        "fills": [{
+            "id": "trade-1",
+            "userId": "user-1",
+            "symbol": "ETH-USDT",
+            "side": "SHORT",
+            "quantity": 2,
+            "price": 3_100.25,
+            "timestamp": 1_760_000_000_456i64,
+            "orderId": "ord-1",
+        }],
+    }))
+    .unwrap();
+
+    assert_eq!((order.side, order.order_type), (OrderSide::Sell, OrderType::Limit));
+    assert_eq!(order.status, OrderStatus::Settling);
+    assert!(order.status.is_final());
+    let request = TradeRequest::from(&order.fills[0]);
+    assert_eq!(request.side, FillSide::Short);
+    assert_eq!(request.trade_id.as_deref(), Some("trade-1"));
+    assert_eq!((request.quantity, request.price), (2.0, 3_100.25));
+
+    // A market order has no price
+    let pending: Order = serde_json::from_value(json!({
+        "id": "ord-2", "userId": "u", "symbol": "BTC-USDT", "side": "buy", "type": "market",
+        "quantity": 1, "leverage": 1, "timestamp": 0, "status": "pending", "fills": [],
+    }))
+    .unwrap();
+    assert_eq!(pending.price, None);
+    assert!(!pending.status.is_final());
+}
+
+#[test]
+fn acks_and_stats_decode() {
+    let ack: OrderAck =
+        serde_json::from_value(json!({ "orderId": "ord-1", "status": "cancelled", "timestamp": 1 })).unwrap();
+    assert_eq!(ack.status, OrderStatus::Cancelled);
+    assert!(serde_json::from_value::<OrderAck>(json!({ "orderId": "x", "status": "open", "timestamp": 1 })).is_err());
+
+    let stats: EngineStats = serde_json::from_value(json!({
+        "ordersReceived": 10,
+        "ordersMatched": 8,
+        "ordersFailed": 2,
+        "tradesSentToRelayer": 8,
+        "tradesRelayerSuccess": 7,
+        "tradesRelayerFailed": 1,
+        "totalVolume": 12.5,
+        "averageFillPrice": 64_000,
+        "uptime": 60_000,
+        "matchRate": 80,
+        "relayerSuccessRate": 87.5,
+        "startTime": 1_760_000_000_000i64,
+        "lastOrderTime": 1_760_000_060_000i64,
+    }))
+    .unwrap();
+    assert_eq!((stats.orders_matched, stats.trades_relayer_failed), (8, 1));
+    assert_eq!(stats.relayer_success_rate, 87.5);
+}
+
+#[test]
+fn the_feed_shares_the_rest_host() {
+    assert_eq!(EngineClient::new("http://127.0.0.1:3003/").ws_url(), "ws://127.0.0.1:3003");
+    assert_eq!(EngineClient::new("https://engine.example").ws_url(), "wss://engine.example");
+}
@@ -0,0 +1,192 @@
+//! Event decoding: the collateral vault IDL, legacy IDLs and log attribution
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::json;
+use solana_examples_scripts::events::gdx::{DepositEvent, TransferEvent};
+use solana_examples_scripts::events::idl::Idl;
+use solana_examples_scripts::events::{Event, EventDecoder, EventRecord};
+use solana_examples_scripts::gdx::discriminator;
+use solana_sdk::pubkey::Pubkey;
+use std::path::Path;
+
+const VAULT_IDL: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../../web3/lib/anchor/idl/collateral-vault-idl.json"
+);
+/// `DepositEvent` discriminator as recorded in the IDL
+const DEPOSIT: [u8; 8] = [120, 248, 61, 83, 31, 142, 107, 144];
+const TRANSFER: [u8; 8] = [100, 10, 46, 113, 8, 28, 179, 125];
+
+fn vault_decoder() -> EventDecoder {
+    let idl = Idl::load(Path::new(VAULT_IDL)).unwrap();
+    let program_id = idl.program_id().unwrap();
+    EventDecoder::new(idl, program_id).unwrap()
+}
+
+fn payload(discriminator: [u8; 8], fields: &[&[u8]]) -> Vec<u8> {
+    let mut payload = discriminator.to_vec();
+    for field in fields {
+        payload.extend_from_slice(field);
+    }
+    payload
+}
+
+fn program_data(payload: &[u8]) -> String {
+    format!("Program data: {}", STANDARD.encode(payload))
+}
+
+#[test]
+fn vault_events_decode_into_their_structs() {
+    let decoder = vault_decoder();
+    let (user, vault) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let deposit = payload(
+        DEPOSIT,
+        &[
+            user.as_ref(),
+            vault.as_ref(),
+            &1_500_000u64.to_le_bytes(),
+            &4_000_000u64.to_le_bytes(),
+            &1_760_000_000i64.to_le_bytes(),
+        ],
+    );
+
+    let event = decoder.decode(&deposit).unwrap().unwrap();
+    assert_eq!(
+        event,
+        Event::DepositEvent(DepositEvent {
+            user,
+            vault,
+            amount: 1_500_000,
+            new_balance: 4_000_000,
+            timestamp: 1_760_000_000,
+        })
+    );
+    assert_eq!(event.name(), "DepositEvent");
+    assert_eq!(
+        event.data(),
+        json!({
+            "user": user.to_string(),
+            "vault": vault.to_string(),
+            "amount": 1_500_000,
+            "new_balance": 4_000_000,
+            "timestamp": 1_760_000_000,
+        })
+    );
+
+    // JSONL records keep the flat name / data shape
+    let record = EventRecord {
+        slot: 7,
+        signature: "sig".to_string(),
+        program_id: decoder.program_id().to_string(),
+        event,
+    };
+    let line = serde_json::to_value(&record).unwrap();
+    assert_eq!(line["name"], "DepositEvent");
+    assert_eq!(line["data"]["amount"], 1_500_000);
+    assert_eq!(line["slot"], 7);
+
+    // A truncated or padded body is an error, not a silently wrong event
+    assert!(decoder.decode(&deposit[..deposit.len() - 1]).unwrap().is_err());
+    assert!(decoder.decode(&[deposit.as_slice(), &[0]].concat()).unwrap().is_err());
+    // Not an event of this IDL
+    assert!(decoder.decode(&payload([0; 8], &[&[1, 2, 3]])).is_none());
+}
+
+#[test]
+fn only_events_emitted_by_the_program_itself_are_parsed() {
+    let decoder = vault_decoder();
+    let program_id = decoder.program_id().to_string();
+    let other = Pubkey::new_unique().to_string();
+    let (from_vault, to_vault) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let transfer = payload(
+        TRANSFER,
+        &[from_vault.as_ref(), to_vault.as_ref(), &250u64.to_le_bytes(), &9i64.to_le_bytes()],
+    );
+
+    let logs = vec![
+        format!("Program {} invoke [1]", other),
+        program_data(&transfer),
+        format!("Program {} invoke [2]", program_id),
+        "Program log: Instruction: TransferCollateral".to_string(),
+        program_data(&transfer),
+        format!("Program {} consumed 5000 of 200000 compute units", program_id),
+        format!("Program {} success", program_id),
+        program_data(&transfer),
+        format!("Program {} success", other),
+        format!("Program {} invoke [1]", program_id),
+        "Program data: not base64!".to_string(),
+        format!("Program {} failed: custom program error: 0x1", program_id),
+    ];
+
+    let events = decoder.parse_logs(&logs);
+    assert_eq!(events.len(), 2);
+    assert_eq!(
+        events[0].as_ref().unwrap(),
+        &Event::TransferEvent(TransferEvent {
+            from_vault,
+            to_vault,
+            amount: 250,
+            timestamp: 9,
+        })
+    );
+    assert!(events[1].is_err());
+}
+
+#[test]
+fn events_without_a_typed_layout_decode_from_a_legacy_idl() {
+    let idl: Idl = serde_json::from_value(json!({
+        "name": "position_manager",
+        "events": [{
+            "name": "PositionOpened",
+            "fields": [
+                { "name": "owner", "type": "publicKey" },
+                { "name": "side", "type": { "defined": "Side" } },
+                { "name": "size", "type": "u64" },
+                { "name": "pnl", "type": "i128" },
+                { "name": "referrer", "type": { "option": "publicKey" } },
+                { "name": "fills", "type": { "vec": "u32" } },
+                { "name": "symbol", "type": "string" },
+            ],
+        }],
+        "types": [{
+            "name": "Side",
+            "type": { "kind": "enum", "variants": [{ "name": "Long" }, { "name": "Short" }] },
+        }],
+    }))
+    .unwrap();
+    let owner = Pubkey::new_unique();
+    let decoder = EventDecoder::new(idl, Pubkey::new_unique()).unwrap();
+    let opened = payload(
+        discriminator("event", "PositionOpened"),
+        &[
+            owner.as_ref(),
+            &[1],
+            &3u64.to_le_bytes(),
+            &(-5i128).to_le_bytes(),
+            &[0],
+            &2u32.to_le_bytes(),
+            &10u32.to_le_bytes(),
+            &20u32.to_le_bytes(),
+            &7u32.to_le_bytes(),
+            b"BTC-USD",
+        ],
+    );
+
+    let event = decoder.decode(&opened).unwrap().unwrap();
+    assert_eq!(
+        event,
+        Event::Other {
+            name: "PositionOpened".to_string(),
+            data: json!({
+                "owner": owner.to_string(),
+                "side": "Short",
+                "size": 3,
+                "pnl": "-5",
+                "referrer": null,
+                "fills": [10, 20],
+                "symbol": "BTC-USD",
+            }),
+        }
+    );
+    assert!(decoder.decode(&opened[..opened.len() - 2]).unwrap().is_err());
+}
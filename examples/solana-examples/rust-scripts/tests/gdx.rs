@@ -0,0 +1,57 @@
+//! Anchor account loading: undecodable accounts are returned to the caller
+
+use solana_examples_scripts::bank::Bank;
+use solana_examples_scripts::gdx::accounts::CollateralVault;
+use solana_examples_scripts::gdx::AnchorAccount;
+use solana_sdk::{account::Account, pubkey::Pubkey, rent::Rent};
+
+/// A `CollateralVault` laid out the way the program stores it
+fn vault_data(owner: &Pubkey, balance: u64) -> Vec<u8> {
+    let mut data = CollateralVault::discriminator().to_vec();
+    data.extend_from_slice(owner.as_ref());
+    data.extend_from_slice(Pubkey::new_unique().as_ref());
+    for field in [balance, 0, balance, balance, 0] {
+        data.extend_from_slice(&field.to_le_bytes());
+    }
+    data.extend_from_slice(&0i64.to_le_bytes());
+    data.push(255);
+    data
+}
+
+fn put(bank: &Bank, program_id: &Pubkey, data: Vec<u8>) -> Pubkey {
+    let address = Pubkey::new_unique();
+    bank.set_account(
+        address,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: *program_id,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    address
+}
+
+#[tokio::test]
+async fn accounts_that_fail_to_decode_come_back_with_their_error() {
+    let bank = Bank::new().await;
+    let program_id = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let good = put(&bank, &program_id, vault_data(&owner, 500));
+    // Right discriminator, truncated body
+    let truncated = put(&bank, &program_id, vault_data(&owner, 1)[..40].to_vec());
+
+    let (vaults, undecodable) = CollateralVault::fetch_all(&bank, &program_id).await.unwrap();
+    assert_eq!(vaults.len(), 1);
+    assert_eq!((vaults[0].0, vaults[0].1.owner, vaults[0].1.total_balance), (good, owner, 500));
+    assert_eq!(undecodable.len(), 1);
+    assert_eq!(undecodable[0].0, truncated);
+
+    let missing = Pubkey::new_unique();
+    let (found, undecodable) = CollateralVault::fetch_many(&bank, &[truncated, missing, good]).await.unwrap();
+    assert!(found[0].is_none() && found[1].is_none());
+    assert_eq!(found[2].as_ref().map(|vault| vault.available_balance), Some(500));
+    // A missing account is not a decode failure
+    assert_eq!(undecodable.iter().map(|(address, _)| *address).collect::<Vec<_>>(), vec![truncated]);
+}
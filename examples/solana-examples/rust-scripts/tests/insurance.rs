@@ -0,0 +1,106 @@
+//! Insurance fund stress: bad debt, pro-rata fund coverage and ADL haircuts
+
+use solana_examples_scripts::gdx::accounts::{Position, Side};
+use solana_examples_scripts::insurance::{stress, StressScenario};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+
+const PRICE: u64 = 60_000_000_000;
+
+/// One unit of base at `entry_price`, so PnL is the price move in quote units
+fn position(symbol: &str, side: Side, entry_price: u64, margin: u64) -> (Pubkey, Position) {
+    let position = Position {
+        owner: Pubkey::new_unique(),
+        symbol: symbol.to_string(),
+        side,
+        size: 1_000_000,
+        entry_price,
+        margin,
+        leverage: 20,
+        unrealized_pnl: 0,
+        realized_pnl: 0,
+        funding_accrued: 0,
+        liquidation_price: 0,
+        last_update: 0,
+        bump: 255,
+    };
+    (Pubkey::new_unique(), position)
+}
+
+/// A long that is `bad_debt` underwater at `PRICE`
+fn insolvent(symbol: &str, bad_debt: u64) -> (Pubkey, Position) {
+    position(symbol, Side::Long, PRICE + 3_000_000_000 + bad_debt, 3_000_000_000)
+}
+
+fn prices(symbols: &[&str]) -> HashMap<String, u64> {
+    symbols.iter().map(|s| (s.to_string(), PRICE)).collect()
+}
+
+#[test]
+fn market_shares_add_up_to_the_fund_drawdown() {
+    let symbols = ["BTC-USDT", "ETH-USDT", "SOL-USDT"];
+    let positions: Vec<_> = symbols.iter().map(|s| insolvent(s, 1_000_001)).collect();
+
+    let report = stress(&positions, &prices(&symbols), &StressScenario::default(), 2_000_000);
+
+    assert_eq!(report.total_bad_debt, 3_000_003);
+    assert_eq!(report.fund_drawdown, 2_000_000);
+    assert_eq!(report.uncovered, 1_000_003);
+    let covered: Vec<u128> = report.markets.iter().map(|m| m.covered).collect();
+    assert_eq!(covered, vec![666_667, 666_667, 666_666]);
+    assert_eq!(report.markets.iter().map(|m| m.covered).sum::<u128>(), report.fund_drawdown);
+    assert_eq!(report.markets.iter().map(|m| m.uncovered).sum::<u128>(), report.uncovered);
+    assert!(report.fund_exhausted());
+}
+
+#[test]
+fn remainders_go_to_the_largest_fractions() {
+    let symbols = ["BTC-USDT", "ETH-USDT"];
+    let positions = vec![insolvent("BTC-USDT", 1), insolvent("ETH-USDT", 2)];
+
+    // 2 units over bad debts of 1 and 2: exact shares 0.67 and 1.33
+    let report = stress(&positions, &prices(&symbols), &StressScenario::default(), 2);
+
+    let covered: Vec<u128> = report.markets.iter().map(|m| m.covered).collect();
+    assert_eq!(covered, vec![1, 1]);
+    assert_eq!(report.uncovered, 1);
+    assert_eq!(report.markets[1].uncovered, 1);
+}
+
+#[test]
+fn shocks_create_bad_debt_and_adl_haircuts() {
+    let positions = vec![
+        position("BTC-USDT", Side::Long, PRICE, 3_000_000_000),
+        position("BTC-USDT", Side::Short, PRICE, 3_000_000_000),
+    ];
+    let scenario = StressScenario {
+        shocks: HashMap::from([("BTC-USDT".to_string(), -10.0)]),
+        default_shock: 0.0,
+    };
+
+    // A 6,000 USDT drop wipes out the long's 3,000 USDT margin
+    let report = stress(&positions, &prices(&["BTC-USDT"]), &scenario, 1_000_000_000);
+
+    let market = &report.markets[0];
+    assert_eq!(market.shocked_price, 54_000_000_000);
+    assert_eq!(market.insolvent_positions, 1);
+    assert_eq!(market.bad_debt, 3_000_000_000);
+    assert_eq!(market.covered, 1_000_000_000);
+    assert_eq!(market.uncovered, 2_000_000_000);
+    assert_eq!(market.profitable_pnl, 6_000_000_000);
+    assert_eq!(market.adl_haircut_bps, 3_333);
+    assert_eq!(report.drawdown_bps(), 10_000);
+}
+
+#[test]
+fn a_fund_that_covers_everything_leaves_nothing_uncovered() {
+    let symbols = ["BTC-USDT", "ETH-USDT"];
+    let positions = vec![insolvent("BTC-USDT", 500), insolvent("ETH-USDT", 700)];
+
+    let report = stress(&positions, &prices(&symbols), &StressScenario::default(), 10_000);
+
+    assert!(!report.fund_exhausted());
+    assert_eq!(report.uncovered, 0);
+    assert!(report.markets.iter().all(|m| m.covered == m.bad_debt && m.adl_haircut_bps == 0));
+    assert_eq!(report.drawdown_bps(), 1_200);
+}
@@ -0,0 +1,134 @@
+//! Position marking, margin ratios and the liquidation queue order
+
+use solana_examples_scripts::gdx::accounts::{CollateralVault, Position, Side};
+use solana_examples_scripts::liquidation::{LiquidationQueue, MarkedPosition, Recommendation};
+use solana_sdk::pubkey::Pubkey;
+
+const USDT: u64 = 1_000_000;
+const BTC: u64 = 1_000_000;
+
+fn position(side: Side, size: u64, entry_price: u64, margin: u64, funding_accrued: i64) -> Position {
+    Position {
+        owner: Pubkey::new_unique(),
+        symbol: "BTC-USDT".to_string(),
+        side,
+        size,
+        entry_price,
+        margin,
+        leverage: 20,
+        unrealized_pnl: 0,
+        realized_pnl: 0,
+        funding_accrued,
+        liquidation_price: 0,
+        last_update: 0,
+        bump: 255,
+    }
+}
+
+fn vault(owner: Pubkey, available_balance: u64) -> (Pubkey, CollateralVault) {
+    let vault = CollateralVault {
+        owner,
+        token_account: Pubkey::new_unique(),
+        total_balance: available_balance,
+        locked_balance: 0,
+        available_balance,
+        total_deposited: available_balance,
+        total_withdrawn: 0,
+        created_at: 0,
+        bump: 255,
+    };
+    (Pubkey::new_unique(), vault)
+}
+
+fn mark(position: Position, mark_price: u64) -> MarkedPosition {
+    MarkedPosition::mark(Pubkey::new_unique(), position, None, mark_price, false)
+}
+
+#[test]
+fn equity_is_margin_plus_pnl_minus_funding_owed() {
+    let long = mark(position(Side::Long, BTC, 60_000 * USDT, 3_000 * USDT, 100 * USDT as i64), 58_000 * USDT);
+    assert_eq!(long.notional, 58_000 * USDT as i128);
+    assert_eq!(long.unrealized_pnl, -2_000 * USDT as i128);
+    assert_eq!(long.equity, 900 * USDT as i128);
+
+    let short = mark(position(Side::Short, BTC / 2, 60_000 * USDT, 3_000 * USDT, -50 * USDT as i64), 58_000 * USDT);
+    assert_eq!(short.unrealized_pnl, 1_000 * USDT as i128);
+    assert_eq!(short.equity, 4_050 * USDT as i128);
+}
+
+#[test]
+fn cross_margin_counts_free_vault_collateral() {
+    let position = position(Side::Long, BTC, 60_000 * USDT, 3_000 * USDT, 0);
+    let vault = Some(vault(position.owner, 1_000 * USDT));
+
+    let isolated = MarkedPosition::mark(Pubkey::new_unique(), position.clone(), vault.clone(), 58_000 * USDT, false);
+    let cross = MarkedPosition::mark(Pubkey::new_unique(), position, vault, 58_000 * USDT, true);
+
+    assert_eq!(isolated.equity, 1_000 * USDT as i128);
+    assert_eq!(cross.equity, 2_000 * USDT as i128);
+    assert!(isolated.is_liquidatable());
+    assert!(!cross.is_liquidatable());
+}
+
+#[test]
+fn margin_ratio_is_compared_with_the_leverage_tier() {
+    // 1,000 USDT of equity on 58,000 USDT of notional is 172 bps
+    let marked = mark(position(Side::Long, BTC, 60_000 * USDT, 3_000 * USDT, 0), 58_000 * USDT);
+    assert_eq!(marked.margin_ratio_bps, 172);
+    assert_eq!(marked.maintenance_bps, 250);
+    assert!(marked.is_liquidatable());
+
+    let mut high_leverage = position(Side::Long, BTC, 60_000 * USDT, 3_000 * USDT, 0);
+    high_leverage.leverage = 100;
+    let marked = mark(high_leverage, 58_000 * USDT);
+    assert_eq!(marked.maintenance_bps, 50);
+    assert!(!marked.is_liquidatable());
+
+    let healthy = mark(position(Side::Long, BTC, 60_000 * USDT, 3_000 * USDT, 0), 61_000 * USDT);
+    assert!(!healthy.is_liquidatable());
+}
+
+#[test]
+fn full_liquidations_report_the_bad_debt() {
+    // Equity 1,000 USDT cannot pay the 2.5% fee on 58,000 USDT
+    let marked = mark(position(Side::Long, BTC, 60_000 * USDT, 3_000 * USDT, 0), 58_000 * USDT);
+    assert_eq!(marked.recommendation(), Recommendation::Full { bad_debt: 450 * USDT });
+
+    let underwater = mark(position(Side::Long, BTC, 60_000 * USDT, 3_000 * USDT, 0), 56_000 * USDT);
+    assert_eq!(underwater.equity, -1_000 * USDT as i128);
+    assert_eq!(underwater.recommendation(), Recommendation::Full { bad_debt: 1_000 * USDT });
+}
+
+#[test]
+fn extreme_positions_saturate_instead_of_wrapping() {
+    let marked = mark(position(Side::Long, u64::MAX, u64::MAX, 0, 0), 1);
+
+    assert!(marked.equity < -(u64::MAX as i128));
+    assert!(marked.is_liquidatable());
+    assert_eq!(marked.recommendation(), Recommendation::Full { bad_debt: u64::MAX });
+}
+
+#[test]
+fn the_queue_pops_the_lowest_margin_ratio_first() {
+    let entry = 60_000 * USDT;
+    let mild = mark(position(Side::Long, BTC, entry, 3_000 * USDT, 0), 58_400 * USDT);
+    let severe = mark(position(Side::Long, BTC, entry, 3_000 * USDT, 0), 57_500 * USDT);
+    let severe_larger = mark(position(Side::Long, 2 * BTC, entry, 6_000 * USDT, 0), 57_500 * USDT);
+    let healthy = mark(position(Side::Short, BTC, entry, 3_000 * USDT, 0), 58_000 * USDT);
+    assert_eq!(severe.margin_ratio_bps, severe_larger.margin_ratio_bps);
+
+    let mut queue = LiquidationQueue::new();
+    assert!(queue.push(mild.clone()));
+    assert!(queue.push(severe.clone()));
+    assert!(queue.push(severe_larger.clone()));
+    assert!(!queue.push(healthy));
+    assert!(!queue.push(severe.clone()), "already queued");
+    assert_eq!(queue.len(), 3);
+
+    let order: Vec<Pubkey> = queue.sorted().iter().map(|c| c.marked.address).collect();
+    assert_eq!(order, vec![severe_larger.address, severe.address, mild.address]);
+
+    assert_eq!(queue.pop().unwrap().marked.address, severe_larger.address);
+    assert!(queue.push(severe_larger.clone()), "popped positions can be queued again");
+    assert_eq!(queue.len(), 3);
+}
@@ -0,0 +1,122 @@
+//! Load generator order flow: arrivals, order mix and sizes from a fixed seed
+
+use rand::{rngs::StdRng, SeedableRng};
+use solana_examples_scripts::engine::{OrderSide, OrderType, Visibility};
+use solana_examples_scripts::loadgen::{FlowConfig, OrderFlow, SizeDistribution};
+
+const SAMPLES: usize = 20_000;
+
+fn config() -> FlowConfig {
+    FlowConfig {
+        rate: 500.0,
+        markets: vec!["BTC-USDT".to_string(), "ETH-USDT".to_string()],
+        users: (0..10).map(|i| format!("user-{i}")).collect(),
+        maker_ratio: 0.3,
+        dark_ratio: 0.5,
+        size: SizeDistribution::Fixed(0.1),
+        leverage: 2,
+    }
+}
+
+fn share(count: usize) -> f64 {
+    count as f64 / SAMPLES as f64
+}
+
+#[test]
+fn the_same_seed_gives_the_same_flow() {
+    let flow = OrderFlow::new(config()).unwrap();
+    let run = |seed| {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..100)
+            .map(|_| {
+                let gap = flow.next_gap(&mut rng);
+                (gap, serde_json::to_string(&flow.next_order(&mut rng)).unwrap())
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(run(42), run(42));
+    assert_ne!(run(42), run(43));
+}
+
+#[test]
+fn arrivals_average_the_configured_rate() {
+    let flow = OrderFlow::new(config()).unwrap();
+    let mut rng = StdRng::seed_from_u64(7);
+
+    let total: f64 = (0..SAMPLES).map(|_| flow.next_gap(&mut rng).as_secs_f64()).sum();
+
+    let mean = total / SAMPLES as f64;
+    assert!((mean - 1.0 / 500.0).abs() < 0.05 / 500.0, "mean gap {mean}");
+}
+
+#[test]
+fn the_order_mix_follows_the_ratios() {
+    let flow = OrderFlow::new(config()).unwrap();
+    let mut rng = StdRng::seed_from_u64(7);
+    let orders: Vec<_> = (0..SAMPLES).map(|_| flow.next_order(&mut rng)).collect();
+
+    let makers = orders.iter().filter(|o| o.order_type != OrderType::Market).count();
+    let dark = orders.iter().filter(|o| o.visibility == Some(Visibility::Dark)).count();
+    let buys = orders.iter().filter(|o| o.side == OrderSide::Buy).count();
+    let btc = orders.iter().filter(|o| o.symbol == "BTC-USDT").count();
+    assert!((share(makers) - 0.3).abs() < 0.02, "makers {}", share(makers));
+    assert!((share(dark) - 0.5).abs() < 0.02, "dark {}", share(dark));
+    assert!((share(buys) - 0.5).abs() < 0.02, "buys {}", share(buys));
+    assert!((share(btc) - 0.5).abs() < 0.02, "BTC {}", share(btc));
+
+    // Makers peg to their own side of the book; takers are market orders
+    for order in &orders {
+        assert!(
+            matches!(
+                (order.order_type, order.side),
+                (OrderType::Market, _) | (OrderType::PegBid, OrderSide::Buy) | (OrderType::PegAsk, OrderSide::Sell)
+            ),
+            "{:?} {:?}",
+            order.order_type,
+            order.side
+        );
+        assert_eq!((order.quantity, order.leverage, order.price), (0.1, 2, None));
+    }
+}
+
+#[test]
+fn extreme_ratios_give_a_pure_mix() {
+    let flow = OrderFlow::new(FlowConfig {
+        maker_ratio: 0.0,
+        dark_ratio: 1.0,
+        ..config()
+    })
+    .unwrap();
+    let mut rng = StdRng::seed_from_u64(1);
+
+    for _ in 0..1_000 {
+        let order = flow.next_order(&mut rng);
+        assert_eq!(order.order_type, OrderType::Market);
+        assert_eq!(order.visibility, Some(Visibility::Dark));
+    }
+}
+
+#[test]
+fn sizes_follow_their_distribution() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let uniform = SizeDistribution::Uniform { min: 0.5, max: 2.0 };
+    assert!((0..SAMPLES)
+        .map(|_| uniform.sample(&mut rng))
+        .all(|size| (0.5..=2.0).contains(&size)));
+
+    let lognormal = SizeDistribution::LogNormal { mean: 1.0, sigma: 0.5 };
+    let sizes: Vec<f64> = (0..SAMPLES).map(|_| lognormal.sample(&mut rng)).collect();
+    let mean = sizes.iter().sum::<f64>() / SAMPLES as f64;
+    assert!((mean - 1.0).abs() < 0.03, "mean size {mean}");
+    assert!(sizes.iter().all(|size| *size > 0.0));
+}
+
+#[test]
+fn invalid_flows_are_rejected() {
+    assert!(OrderFlow::new(FlowConfig { rate: 0.0, ..config() }).is_err());
+    assert!(OrderFlow::new(FlowConfig { markets: vec![], ..config() }).is_err());
+    assert!(OrderFlow::new(FlowConfig { users: vec![], ..config() }).is_err());
+    assert!(OrderFlow::new(FlowConfig { maker_ratio: 1.5, ..config() }).is_err());
+    assert!(OrderFlow::new(FlowConfig { dark_ratio: -0.1, ..config() }).is_err());
+}
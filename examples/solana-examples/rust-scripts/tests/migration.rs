@@ -0,0 +1,126 @@
+//! Account migrations: layout detection, outdated layouts and batching
+
+use solana_examples_scripts::gdx::discriminator;
+use solana_examples_scripts::migration::{
+    max_per_tx, migrate_instruction, Layout, MigrateAccount, Target, DEFAULT_MIGRATE_ACCOUNTS,
+};
+use solana_sdk::{
+    hash::Hash,
+    packet::Packet,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program,
+    transaction::Transaction,
+};
+use std::str::FromStr;
+
+fn position(version: u32, len: usize) -> Vec<u8> {
+    let mut data = discriminator("account", "Position").to_vec();
+    data.extend_from_slice(&version.to_le_bytes());
+    data.resize(len, 0);
+    data
+}
+
+#[test]
+fn layout_reads_the_discriminator_length_and_version() {
+    let layout = Layout::detect(&position(2, 120), Some(8));
+    assert_eq!(layout.discriminator, discriminator("account", "Position"));
+    assert_eq!((layout.len, layout.version), (120, Some(2)));
+
+    // No offset, or data too short to hold the version
+    assert_eq!(Layout::detect(&position(2, 120), None).version, None);
+    assert_eq!(Layout::detect(&position(2, 12), Some(8)).version, Some(2));
+    assert_eq!(Layout::detect(&position(2, 11), Some(8)).version, None);
+
+    // Shorter than a discriminator: the rest reads as zero
+    let short = Layout::detect(&[1, 2, 3], Some(8));
+    assert_eq!((short.discriminator, short.len, short.version), ([1, 2, 3, 0, 0, 0, 0, 0], 3, None));
+}
+
+#[test]
+fn outdated_means_below_the_version_or_off_the_length() {
+    let v1 = Layout::detect(&position(1, 120), Some(8));
+    let v2 = Layout::detect(&position(2, 120), Some(8));
+    let v3 = Layout::detect(&position(3, 120), Some(8));
+    let unversioned = Layout::detect(&position(2, 120), None);
+
+    let by_version = Target {
+        version: Some(2),
+        len: None,
+    };
+    assert!(by_version.is_outdated(&v1));
+    assert!(!by_version.is_outdated(&v2));
+    assert!(!by_version.is_outdated(&v3));
+    assert!(by_version.is_outdated(&unversioned));
+
+    let by_len = Target {
+        version: None,
+        len: Some(136),
+    };
+    assert!(by_len.is_outdated(&v2));
+    assert!(!by_len.is_outdated(&Layout::detect(&position(1, 136), Some(8))));
+
+    // Either condition is enough
+    let both = Target {
+        version: Some(2),
+        len: Some(120),
+    };
+    assert!(!both.is_outdated(&v2));
+    assert!(both.is_outdated(&v1));
+    assert!(both.is_outdated(&Layout::detect(&position(2, 136), Some(8))));
+    assert!(!Target { version: None, len: None }.is_outdated(&v1));
+}
+
+#[test]
+fn instruction_accounts_follow_the_given_list() {
+    let (program, account, authority) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+    let ix = migrate_instruction(&program, &account, &authority, DEFAULT_MIGRATE_ACCOUNTS);
+    let metas: Vec<_> = ix.accounts.iter().map(|m| (m.pubkey, m.is_signer, m.is_writable)).collect();
+    assert_eq!(
+        metas,
+        vec![(account, false, true), (authority, true, true), (system_program::id(), false, false)]
+    );
+    assert_eq!(ix.data[..8], discriminator("global", "migrate_account"));
+    assert_eq!(ix.data[8..], account.to_bytes());
+
+    let config = Pubkey::new_unique();
+    let accounts: Vec<MigrateAccount> = ["authority", "migrated", &format!("{}:w", config)]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+    let ix = migrate_instruction(&program, &account, &authority, &accounts);
+    let metas: Vec<_> = ix.accounts.iter().map(|m| (m.pubkey, m.is_signer, m.is_writable)).collect();
+    assert_eq!(metas, vec![(authority, true, true), (account, false, true), (config, false, true)]);
+
+    assert!(MigrateAccount::from_str("payer").is_err());
+}
+
+#[test]
+fn max_per_tx_fills_one_signed_transaction() {
+    let (program, authority) = (Pubkey::new_unique(), Keypair::new());
+    let fits = |accounts: &[MigrateAccount], count: usize| {
+        let instructions: Vec<_> = (0..count)
+            .map(|_| migrate_instruction(&program, &Pubkey::new_unique(), &authority.pubkey(), accounts))
+            .collect();
+        let tx = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&authority.pubkey()),
+            &[&authority],
+            Hash::default(),
+        );
+        Packet::from_data(None, &tx).is_ok()
+    };
+
+    let fit = max_per_tx(&program, &authority.pubkey(), DEFAULT_MIGRATE_ACCOUNTS);
+    assert!(fit > 1);
+    assert!(fits(DEFAULT_MIGRATE_ACCOUNTS, fit));
+    assert!(!fits(DEFAULT_MIGRATE_ACCOUNTS, fit + 1));
+
+    // An extra account costs its key once and an index byte per instruction
+    let wider = [DEFAULT_MIGRATE_ACCOUNTS, &[MigrateAccount::Writable(Pubkey::new_unique())]].concat();
+    let fewer = max_per_tx(&program, &authority.pubkey(), &wider);
+    assert!(fewer < fit);
+    assert!(fits(&wider, fewer));
+    assert!(!fits(&wider, fewer + 1));
+}
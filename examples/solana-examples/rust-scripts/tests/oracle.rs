@@ -0,0 +1,215 @@
+//! Pyth and Switchboard account decoding from raw bytes, and the consensus
+//! checks on the decoded prices
+
+use solana_examples_scripts::oracle::mock::MockPrice;
+use solana_examples_scripts::oracle::pyth::{PriceStatus, PythPriceAccount};
+use solana_examples_scripts::oracle::switchboard::{AggregatorAccount, AGGREGATOR_DISCRIMINATOR};
+use solana_examples_scripts::oracle::{validate_consensus, OracleConfig, PriceData, PriceSource, Violation};
+
+fn put(data: &mut [u8], offset: usize, bytes: &[u8]) {
+    data[offset..offset + bytes.len()].copy_from_slice(bytes);
+}
+
+/// A Pyth v2 price account laid out byte by byte
+fn pyth_account(price: i64, conf: u64, expo: i32, status: u32) -> Vec<u8> {
+    let mut data = vec![0u8; 3312];
+    put(&mut data, 0, &0xa1b2_c3d4u32.to_le_bytes());
+    put(&mut data, 4, &2u32.to_le_bytes());
+    put(&mut data, 8, &3u32.to_le_bytes());
+    put(&mut data, 12, &3312u32.to_le_bytes());
+    put(&mut data, 20, &expo.to_le_bytes());
+    put(&mut data, 40, &250_000_000u64.to_le_bytes());
+    put(&mut data, 96, &1_760_000_000i64.to_le_bytes());
+    put(&mut data, 208, &price.to_le_bytes());
+    put(&mut data, 216, &conf.to_le_bytes());
+    put(&mut data, 224, &status.to_le_bytes());
+    put(&mut data, 232, &250_000_001u64.to_le_bytes());
+    data
+}
+
+/// A Switchboard v2 aggregator with its latest confirmed round
+fn switchboard_account(result: (i128, u32), std_deviation: (i128, u32), min_results: u32, successes: u32) -> Vec<u8> {
+    let mut data = vec![0u8; 512];
+    put(&mut data, 0, &AGGREGATOR_DISCRIMINATOR);
+    put(&mut data, 236, &min_results.to_le_bytes());
+    put(&mut data, 341, &successes.to_le_bytes());
+    put(&mut data, 350, &250_000_000u64.to_le_bytes());
+    put(&mut data, 358, &1_760_000_005i64.to_le_bytes());
+    put(&mut data, 366, &result.0.to_le_bytes());
+    put(&mut data, 382, &result.1.to_le_bytes());
+    put(&mut data, 386, &std_deviation.0.to_le_bytes());
+    put(&mut data, 402, &std_deviation.1.to_le_bytes());
+    data
+}
+
+fn price(source: PriceSource, price: i64, confidence: u64, expo: i32, timestamp: i64) -> PriceData {
+    PriceData {
+        price,
+        confidence,
+        expo,
+        timestamp,
+        source,
+    }
+}
+
+#[test]
+fn pyth_price_accounts_decode_from_their_offsets() {
+    let account = PythPriceAccount::decode(&pyth_account(6_500_050_000_000, 1_250_000_000, -8, 1)).unwrap();
+
+    assert_eq!(account.expo, -8);
+    assert_eq!(account.valid_slot, 250_000_000);
+    assert_eq!(account.timestamp, 1_760_000_000);
+    assert_eq!(account.price, 6_500_050_000_000);
+    assert_eq!(account.conf, 1_250_000_000);
+    assert_eq!(account.status, PriceStatus::Trading);
+    assert_eq!(account.pub_slot, 250_000_001);
+
+    let price = account.to_price_data().unwrap();
+    assert_eq!(price.source, PriceSource::Pyth);
+    assert_eq!(price.to_fixed(6), Some(65_000_500_000));
+}
+
+#[test]
+fn pyth_rejects_other_accounts_and_unusable_aggregates() {
+    let mut bad_magic = pyth_account(1, 0, -8, 1);
+    bad_magic[0] ^= 0xff;
+    assert!(PythPriceAccount::decode(&bad_magic).is_err());
+
+    let mut product = pyth_account(1, 0, -8, 1);
+    put(&mut product, 8, &2u32.to_le_bytes());
+    assert!(PythPriceAccount::decode(&product).is_err());
+
+    assert!(PythPriceAccount::decode(&pyth_account(1, 0, -8, 1)[..200]).is_err());
+
+    let halted = PythPriceAccount::decode(&pyth_account(1, 0, -8, 2)).unwrap();
+    assert_eq!(halted.status, PriceStatus::Halted);
+    assert!(halted.to_price_data().is_err());
+}
+
+#[test]
+fn switchboard_rounds_narrow_to_a_64_bit_price() {
+    // 65000.123456789012345678 at scale 18 does not fit in an i64
+    let data = switchboard_account((65_000_123_456_789_012_345_678, 18), (25_000_000_000_000_000_000, 18), 3, 4);
+    let account = AggregatorAccount::decode(&data).unwrap();
+    assert_eq!(account.num_success, 4);
+    assert_eq!(account.round_open_timestamp, 1_760_000_005);
+
+    let price = account.to_price_data().unwrap();
+    assert_eq!(price.source, PriceSource::Switchboard);
+    assert_eq!(price.expo, -14);
+    assert_eq!(price.price, 6_500_012_345_678_901_234);
+    assert_eq!(price.confidence, 2_500_000_000_000_000);
+}
+
+#[test]
+fn switchboard_rejects_short_rounds_and_foreign_accounts() {
+    let short_round = switchboard_account((65_000, 0), (1, 0), 3, 2);
+    assert!(AggregatorAccount::decode(&short_round).unwrap().to_price_data().is_err());
+
+    let mut foreign = switchboard_account((65_000, 0), (1, 0), 1, 1);
+    foreign[0] ^= 0xff;
+    assert!(AggregatorAccount::decode(&foreign).is_err());
+    assert!(AggregatorAccount::decode(&switchboard_account((65_000, 0), (1, 0), 1, 1)[..400]).is_err());
+}
+
+#[test]
+fn consensus_takes_the_median_across_exponents() {
+    let now = 1_760_000_010;
+    let prices = [
+        price(PriceSource::Pyth, 6_500_000_000_000, 1_000_000_000, -8, now - 5),
+        price(PriceSource::Switchboard, 65_010_000_000, 10_000_000, -6, now - 2),
+        price(PriceSource::Internal, 6_499_000, 100, -2, now),
+    ];
+
+    let report = validate_consensus(&prices, now, &OracleConfig::default());
+
+    assert!(report.is_valid(), "{:?}", report.violations);
+    assert_eq!(report.expo, -8);
+    assert_eq!(report.price, Some(6_500_000_000_000));
+    assert_eq!(report.accepted.len(), 3);
+}
+
+#[test]
+fn consensus_reports_each_failed_check() {
+    let now = 1_760_000_100;
+    let prices = [
+        price(PriceSource::Pyth, 6_500_000_000_000, 1_000_000_000, -8, now - 60),
+        price(PriceSource::Switchboard, 65_000_000_000, 1_000_000_000, -6, now),
+        price(PriceSource::Internal, 0, 0, -6, now),
+    ];
+
+    let report = validate_consensus(&prices, now, &OracleConfig::default());
+
+    assert!(!report.is_valid());
+    assert_eq!(
+        report.violations,
+        vec![
+            Violation::Stale {
+                source: PriceSource::Pyth,
+                age: 60
+            },
+            Violation::ConfidenceTooWide {
+                source: PriceSource::Switchboard,
+                bps: 153
+            },
+            Violation::NonPositive {
+                source: PriceSource::Internal
+            },
+        ]
+    );
+    assert_eq!(report.price, None);
+
+    let prices = [
+        price(PriceSource::Pyth, 6_500_000_000_000, 0, -8, now),
+        price(PriceSource::Switchboard, 67_000_000_000, 0, -6, now),
+    ];
+    let report = validate_consensus(&prices, now, &OracleConfig::default());
+    assert!(report
+        .violations
+        .iter()
+        .all(|v| matches!(v, Violation::Deviation { bps: 151, .. })));
+    assert_eq!(report.violations.len(), 2);
+}
+
+#[test]
+fn exponents_too_far_apart_are_violations_not_overflows() {
+    let now = 1_760_000_000;
+    let prices = [
+        price(PriceSource::Pyth, 6_500_000_000_000, 0, -8, now),
+        price(PriceSource::Switchboard, 65_000, 0, 40, now),
+    ];
+
+    let report = validate_consensus(&prices, now, &OracleConfig::default());
+
+    assert!(!report.is_valid());
+    assert_eq!(
+        report.violations,
+        vec![Violation::Unscalable {
+            source: PriceSource::Switchboard,
+            expo: 40
+        }]
+    );
+}
+
+#[test]
+fn mock_prices_round_trip_through_the_pyth_layout() {
+    let mock = MockPrice::from_ui(65_000.5, 12.25, -8, 1_760_000_000, 42).unwrap();
+    let account = PythPriceAccount::decode(&mock.encode_header()).unwrap();
+
+    assert_eq!(account.price, 6_500_050_000_000);
+    assert_eq!(account.conf, 1_225_000_000);
+    assert_eq!(account.pub_slot, 42);
+    assert_eq!(account.status, PriceStatus::Trading);
+}
+
+#[test]
+fn mock_prices_reject_values_the_account_cannot_hold() {
+    assert!(MockPrice::from_ui(65_000.0, -1.0, -8, 0, 0).is_err());
+    assert!(MockPrice::from_ui(65_000.0, f64::NAN, -8, 0, 0).is_err());
+    assert!(MockPrice::from_ui(f64::NAN, 1.0, -8, 0, 0).is_err());
+    assert!(MockPrice::from_ui(f64::INFINITY, 1.0, -8, 0, 0).is_err());
+    // 1e12 at expo -8 is 1e20, past i64::MAX
+    assert!(MockPrice::from_ui(1e12, 1.0, -8, 0, 0).is_err());
+    assert!(MockPrice::from_ui(65_000.0, 1e12, -8, 0, 0).is_err());
+    assert!(MockPrice::from_ui(-5.0, 0.0, -8, 0, 0).is_ok());
+}
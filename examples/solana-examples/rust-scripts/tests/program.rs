@@ -0,0 +1,65 @@
+//! Program uploads: padding, chunk sizing and resume offsets
+
+use solana_examples_scripts::program::{chunk_size, elf_hash, stale_chunks, trim_padding};
+use solana_sdk::{
+    bpf_loader_upgradeable,
+    hash::Hash,
+    packet::Packet,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+#[test]
+fn padding_is_trimmed_from_the_end_only() {
+    assert_eq!(trim_padding(&[1, 0, 2, 0, 0]), &[1, 0, 2]);
+    assert_eq!(trim_padding(&[0, 0, 7]), &[0, 0, 7]);
+    assert_eq!(trim_padding(&[0, 0, 0]), &[] as &[u8]);
+    assert_eq!(trim_padding(&[]), &[] as &[u8]);
+
+    let elf = [0x7f, b'E', b'L', b'F', 0, 3];
+    let mut programdata = elf.to_vec();
+    programdata.resize(64, 0);
+    assert_eq!(elf_hash(&programdata), elf_hash(&elf));
+    assert_ne!(elf_hash(&elf), elf_hash(&elf[..5]));
+}
+
+/// Whether a `Write` of `len` bytes fits in one packet
+fn fits(payer: &Keypair, authority: &Keypair, buffer: &Pubkey, len: usize) -> bool {
+    let write = bpf_loader_upgradeable::write(buffer, &authority.pubkey(), 0, vec![0xff; len]);
+    let tx = Transaction::new_signed_with_payer(&[write], Some(&payer.pubkey()), &[payer, authority], Hash::default());
+    Packet::from_data(None, &tx).is_ok()
+}
+
+#[test]
+fn a_chunk_fills_one_write_transaction() {
+    let (payer, authority, buffer) = (Keypair::new(), Keypair::new(), Pubkey::new_unique());
+
+    let chunk = chunk_size(&payer.pubkey(), &authority.pubkey(), &buffer);
+    assert!(fits(&payer, &authority, &buffer, chunk));
+    assert!(!fits(&payer, &authority, &buffer, chunk + 1));
+
+    // One signature and one account key less when the payer is the authority
+    let alone = chunk_size(&payer.pubkey(), &payer.pubkey(), &buffer);
+    assert!(alone > chunk);
+    assert!(fits(&payer, &payer, &buffer, alone));
+    assert!(!fits(&payer, &payer, &buffer, alone + 1));
+}
+
+#[test]
+fn only_chunks_that_differ_are_stale() {
+    let elf: Vec<u8> = (0..25u8).collect();
+
+    assert_eq!(stale_chunks(&elf, &[0; 25], 10), vec![0, 10, 20]);
+    assert!(stale_chunks(&elf, &elf, 10).is_empty());
+
+    let mut buffer = elf.clone();
+    buffer[14] = 0xff;
+    buffer[24] = 0xff;
+    assert_eq!(stale_chunks(&elf, &buffer, 10), vec![10, 20]);
+
+    // A buffer shorter than the program is missing its tail
+    assert_eq!(stale_chunks(&elf, &elf[..15], 10), vec![10, 20]);
+    assert_eq!(stale_chunks(&elf, &elf, 100), Vec::<usize>::new());
+    assert!(stale_chunks(&[], &[], 10).is_empty());
+}
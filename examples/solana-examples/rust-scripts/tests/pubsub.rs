@@ -0,0 +1,127 @@
+//! PubSub subscriber: reconnects and subscribes again when the socket drops
+
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use solana_examples_scripts::pubsub::{Notification, Subscriber, Subscription};
+use solana_sdk::pubkey::Pubkey;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_tungstenite::{accept_async, tungstenite::Message};
+
+/// Accept `connections` sockets in turn; on each, answer every subscribe
+/// request, push one notification per subscription tagged with the
+/// connection number as its slot, then close. Subscribe methods are
+/// reported in the order they arrive.
+async fn serve(listener: TcpListener, connections: u64, methods: mpsc::UnboundedSender<(u64, String)>) {
+    for connection in 1..=connections {
+        let (tcp, _) = listener.accept().await.unwrap();
+        let mut socket = accept_async(tcp).await.unwrap();
+        let mut subscribed = 0;
+        while let Some(Ok(message)) = socket.next().await {
+            let Message::Text(text) = message else { continue };
+            let request: Value = serde_json::from_str(&text).unwrap();
+            let method = request["method"].as_str().unwrap().to_string();
+            let subscription = connection * 100 + subscribed;
+            let reply = json!({ "jsonrpc": "2.0", "result": subscription, "id": request["id"] });
+            socket.send(Message::Text(reply.to_string())).await.unwrap();
+
+            let notification = match method.as_str() {
+                "slotSubscribe" => json!({
+                    "jsonrpc": "2.0",
+                    "method": "slotNotification",
+                    "params": {
+                        "result": { "parent": connection - 1, "root": 0, "slot": connection },
+                        "subscription": subscription,
+                    },
+                }),
+                "accountSubscribe" => json!({
+                    "jsonrpc": "2.0",
+                    "method": "accountNotification",
+                    "params": {
+                        "result": {
+                            "context": { "slot": connection },
+                            "value": {
+                                "lamports": 42,
+                                "data": ["", "base64"],
+                                "owner": Pubkey::default().to_string(),
+                                "executable": false,
+                                "rentEpoch": 0,
+                                "space": 0,
+                            },
+                        },
+                        "subscription": subscription,
+                    },
+                }),
+                other => panic!("unexpected {other}"),
+            };
+            methods.send((connection, method)).unwrap();
+            socket.send(Message::Text(notification.to_string())).await.unwrap();
+            subscribed += 1;
+            if subscribed == 2 {
+                break;
+            }
+        }
+        socket.close(None).await.ok();
+    }
+}
+
+#[tokio::test]
+async fn subscriptions_are_renewed_after_a_reconnect() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let (methods, mut seen) = mpsc::unbounded_channel();
+    tokio::spawn(serve(listener, 2, methods));
+
+    let address = Pubkey::new_unique();
+    let mut notifications = Subscriber::new(url)
+        .subscribe(Subscription::Slot)
+        .subscribe(Subscription::Account(address))
+        .spawn();
+
+    let mut slots = Vec::new();
+    let mut accounts = Vec::new();
+    let mut connected = Vec::new();
+    let mut disconnects = 0;
+    while connected.len() < 2 || slots.len() < 2 || accounts.len() < 2 {
+        let notification = tokio::time::timeout(Duration::from_secs(10), notifications.recv())
+            .await
+            .expect("no notification within 10s")
+            .expect("subscriber stopped");
+        match notification {
+            Notification::Connected { attempt } => connected.push(attempt),
+            Notification::Disconnected { .. } => disconnects += 1,
+            Notification::Slot(info) => slots.push(info.slot),
+            Notification::Account {
+                address: updated,
+                slot,
+                account,
+            } => {
+                assert_eq!((updated, account.lamports), (address, 42));
+                accounts.push(slot);
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    assert_eq!(connected, vec![0, 1]);
+    assert!(disconnects >= 1);
+    slots.sort();
+    accounts.sort();
+    assert_eq!((slots, accounts), (vec![1, 2], vec![1, 2]));
+
+    let mut subscribes = Vec::new();
+    while let Ok(method) = seen.try_recv() {
+        subscribes.push(method);
+    }
+    subscribes.sort();
+    assert_eq!(
+        subscribes,
+        vec![
+            (1, "accountSubscribe".to_string()),
+            (1, "slotSubscribe".to_string()),
+            (2, "accountSubscribe".to_string()),
+            (2, "slotSubscribe".to_string()),
+        ]
+    );
+}
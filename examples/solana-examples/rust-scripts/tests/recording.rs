@@ -0,0 +1,119 @@
+//! Record flows against the bank, replay them without it
+
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_examples_scripts::bank::Bank;
+use solana_examples_scripts::client::recording::{Recorder, Replayer};
+use solana_examples_scripts::client::{get_accounts, LedgerClient, MAX_MULTIPLE_ACCOUNTS};
+use solana_examples_scripts::examples::{airdrop::airdrop, send_transaction};
+use solana_sdk::{
+    account::Account,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    signer::keypair::keypair_from_seed,
+};
+
+const SOL: u64 = 1_000_000_000;
+
+fn keypair(seed: u8) -> Keypair {
+    keypair_from_seed(&[seed; 32]).unwrap()
+}
+
+#[tokio::test]
+async fn replay_answers_like_the_recorded_ledger() {
+    let from = keypair(1);
+    let to = keypair(2).pubkey();
+
+    let recorder = Recorder::new(Bank::new().await);
+    airdrop(&recorder, &from.pubkey(), SOL).await.unwrap();
+    let signature = send_transaction::transfer(&recorder, &from, &to, SOL / 4).await.unwrap();
+    let failure = send_transaction::transfer(&recorder, &from, &to, 2 * SOL).await.unwrap_err();
+    let balance = recorder.get_balance(&to).await.unwrap();
+
+    let path = std::env::temp_dir().join(format!("ledger-tape-{}.json", std::process::id()));
+    recorder.save(&path).unwrap();
+    let replayer = Replayer::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    airdrop(&replayer, &from.pubkey(), SOL).await.unwrap();
+    assert_eq!(send_transaction::transfer(&replayer, &from, &to, SOL / 4).await.unwrap(), signature);
+    let replayed = send_transaction::transfer(&replayer, &from, &to, 2 * SOL).await.unwrap_err();
+    assert_eq!(format!("{:#}", replayed), format!("{:#}", failure));
+    assert_eq!(replayer.get_balance(&to).await.unwrap(), balance);
+    assert_eq!(replayer.remaining(), 0);
+}
+
+#[tokio::test]
+async fn replay_rejects_calls_that_differ_from_the_tape() {
+    let recorder = Recorder::new(Bank::new().await);
+    recorder.get_balance(&Pubkey::new_unique()).await.unwrap();
+    let replayer = Replayer::new(recorder.calls());
+
+    let error = replayer.get_balance(&Pubkey::new_unique()).await.unwrap_err();
+    assert!(error.to_string().starts_with("Replay: expected get_balance"), "{}", error);
+
+    let error = replayer.get_slot().await.unwrap_err();
+    assert!(error.to_string().contains("the tape has ended"), "{}", error);
+}
+
+#[tokio::test]
+async fn program_accounts_are_filtered_like_rpc() {
+    let bank = Bank::new().await;
+    let program_id = Pubkey::new_unique();
+    let account = |data: &[u8]| Account {
+        lamports: 1_000_000,
+        data: data.to_vec(),
+        owner: program_id,
+        executable: false,
+        rent_epoch: 0,
+    };
+    let (a, b, c) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    bank.set_account(a, account(&[1, 2, 3]));
+    bank.set_account(b, account(&[1, 9]));
+    bank.set_account(c, account(&[7, 2, 3]));
+
+    let all = bank.get_program_accounts(&program_id, &[]).await.unwrap();
+    assert_eq!(all.len(), 3);
+
+    let filters = [
+        RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, vec![1])),
+        RpcFilterType::DataSize(3),
+    ];
+    let matched = bank.get_program_accounts(&program_id, &filters).await.unwrap();
+    assert_eq!(matched.iter().map(|(address, _)| *address).collect::<Vec<_>>(), vec![a]);
+
+    let many = bank.get_multiple_accounts(&[b, Pubkey::new_unique()]).await.unwrap();
+    assert_eq!(many[0].as_ref().map(|a| a.data.clone()), Some(vec![1, 9]));
+    assert!(many[1].is_none());
+}
+
+#[tokio::test]
+async fn get_accounts_batches_large_reads_in_order() {
+    let bank = Bank::new().await;
+    let addresses: Vec<Pubkey> = (0..250).map(|_| Pubkey::new_unique()).collect();
+    for (i, address) in addresses.iter().enumerate().filter(|(i, _)| i % 3 != 0) {
+        bank.set_account(
+            *address,
+            Account {
+                lamports: 1_000_000,
+                data: (i as u32).to_le_bytes().to_vec(),
+                owner: Pubkey::new_unique(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    let recorder = Recorder::new(bank);
+    let accounts = get_accounts(&recorder, &addresses).await.unwrap();
+    assert_eq!(accounts.len(), addresses.len());
+    for (i, account) in accounts.iter().enumerate() {
+        match account {
+            Some(account) => assert_eq!(account.data, (i as u32).to_le_bytes()),
+            None => assert_eq!(i % 3, 0),
+        }
+    }
+
+    let calls = recorder.calls();
+    assert_eq!(calls.len(), addresses.len().div_ceil(MAX_MULTIPLE_ACCOUNTS));
+    assert!(calls.iter().all(|call| call.method == "get_multiple_accounts"));
+}
@@ -0,0 +1,222 @@
+//! Failover, retries and rate limiting against scripted JSON-RPC endpoints
+
+use serde_json::{json, Value};
+use solana_examples_scripts::client::resilient::{classify, ErrorClass, ResilientClient, RetryPolicy};
+use solana_examples_scripts::client::LedgerClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::time::Instant;
+
+/// An HTTP endpoint answering JSON-RPC requests with `respond(request
+/// number, method)`; returns its URL and the request counter. `getVersion`,
+/// which the RPC client sends once before its first call, is answered here
+/// and not counted. A status of 0 drops the connection without answering.
+async fn endpoint(respond: fn(usize, &str) -> (u16, Value)) -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let count = Arc::new(AtomicUsize::new(0));
+    let counter = count.clone();
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let counter = counter.clone();
+            tokio::spawn(async move {
+                let Some(request) = read_request(&mut socket).await else {
+                    return;
+                };
+                let method = request["method"].as_str().unwrap_or_default();
+                let (status, mut body) = if method == "getVersion" {
+                    (200, json!({ "result": { "solana-core": "1.18.26", "feature-set": 0 } }))
+                } else {
+                    respond(counter.fetch_add(1, Ordering::SeqCst), method)
+                };
+                if status == 0 {
+                    return;
+                }
+                body["jsonrpc"] = json!("2.0");
+                body["id"] = request["id"].clone();
+                let body = body.to_string();
+                let response = format!(
+                    "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+    (url, count)
+}
+
+async fn read_request(socket: &mut tokio::net::TcpStream) -> Option<Value> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        let n = socket.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+        let text = String::from_utf8_lossy(&buffer);
+        let Some(end) = text.find("\r\n\r\n") else {
+            continue;
+        };
+        let length: usize = text[..end]
+            .lines()
+            .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().to_string()))
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0);
+        if buffer.len() >= end + 4 + length {
+            return serde_json::from_slice(&buffer[end + 4..end + 4 + length]).ok();
+        }
+    }
+}
+
+fn fast_policy() -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: 4,
+        min_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(50),
+        cooldown: Duration::from_secs(60),
+        max_failures: 3,
+    }
+}
+
+fn client(urls: &[String], rps: Option<u32>) -> ResilientClient {
+    ResilientClient::new(urls, CommitmentConfig::confirmed(), rps, fast_policy()).unwrap()
+}
+
+fn slot(slot: u64) -> (u16, Value) {
+    (200, json!({ "result": slot }))
+}
+
+fn node_behind() -> (u16, Value) {
+    (
+        200,
+        json!({ "error": { "code": -32005, "message": "Node is behind by 42 slots", "data": { "numSlotsBehind": 42 } } }),
+    )
+}
+
+#[tokio::test]
+async fn a_node_that_is_behind_fails_over_and_stays_down() {
+    let (behind, behind_count) = endpoint(|_, _| node_behind()).await;
+    let (healthy, healthy_count) = endpoint(|_, _| slot(7)).await;
+    let client = client(&[behind, healthy], None);
+
+    assert_eq!(client.get_slot().await.unwrap(), 7);
+    assert_eq!(client.get_slot().await.unwrap(), 7);
+    assert_eq!(behind_count.load(Ordering::SeqCst), 1);
+    assert_eq!(healthy_count.load(Ordering::SeqCst), 2);
+
+    let status = client.status();
+    assert!(status[0].down_for.is_some());
+    assert!(status[0].last_error.as_deref().unwrap().contains("behind"));
+    assert!(status[1].down_for.is_none());
+}
+
+#[tokio::test]
+async fn rate_limited_responses_are_retried() {
+    // The RPC client itself retries a 429 five times, half a second apart
+    let (url, count) = endpoint(|_, _| (429, json!({}))).await;
+    let policy = RetryPolicy {
+        max_attempts: 2,
+        cooldown: Duration::ZERO,
+        ..fast_policy()
+    };
+    let client = ResilientClient::new(&[url], CommitmentConfig::confirmed(), None, policy).unwrap();
+
+    let error = client.get_slot().await.unwrap_err();
+    assert_eq!(classify(&error), ErrorClass::Failover);
+    assert!(format!("{:#}", error).contains("Giving up after 2 attempts"), "{:#}", error);
+    assert_eq!(count.load(Ordering::SeqCst), 12);
+}
+
+#[tokio::test]
+async fn invalid_params_are_not_retried() {
+    let (url, count) =
+        endpoint(|_, _| (200, json!({ "error": { "code": -32602, "message": "Invalid params" } }))).await;
+    let client = client(&[url], None);
+
+    let error = client.get_slot().await.unwrap_err();
+    assert_eq!(classify(&error), ErrorClass::Fatal);
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+    assert!(client.status()[0].down_for.is_none());
+}
+
+#[tokio::test]
+async fn dropped_connections_are_retried_on_the_same_endpoint() {
+    let (flaky, count) = endpoint(|n, _| if n < 2 { (0, json!({})) } else { slot(6) }).await;
+    let (backup, backup_count) = endpoint(|_, _| slot(8)).await;
+    let client = client(&[flaky, backup], None);
+
+    assert_eq!(client.get_slot().await.unwrap(), 6);
+    assert_eq!(count.load(Ordering::SeqCst), 3);
+    assert_eq!(backup_count.load(Ordering::SeqCst), 0);
+    assert_eq!(client.status()[0].failures, 0);
+}
+
+#[tokio::test]
+async fn unreachable_endpoints_are_failed_over() {
+    let dead = {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    };
+    let (healthy, _) = endpoint(|_, _| slot(9)).await;
+    let client = client(&[dead, healthy], None);
+
+    assert_eq!(client.get_slot().await.unwrap(), 9);
+    let status = client.status();
+    assert!(status[0].down_for.is_some());
+    assert!(status[1].down_for.is_none());
+}
+
+#[tokio::test]
+async fn health_checks_bring_endpoints_back() {
+    let (flaky, _) = endpoint(|n, method| match (n, method) {
+        (0, _) => node_behind(),
+        (_, "getHealth") => (200, json!({ "result": "ok" })),
+        _ => slot(5),
+    })
+    .await;
+    let (backup, backup_count) = endpoint(|_, method| match method {
+        "getHealth" => (200, json!({ "result": "ok" })),
+        _ => slot(4),
+    })
+    .await;
+    let client = client(&[flaky, backup], None);
+
+    assert_eq!(client.get_slot().await.unwrap(), 4);
+    assert!(client.status()[0].down_for.is_some());
+    client.check_health().await;
+    assert!(client.status().iter().all(|s| s.down_for.is_none()));
+    assert_eq!(client.get_slot().await.unwrap(), 5);
+    assert_eq!(backup_count.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn requests_are_spaced_by_the_rate_limit() {
+    let (url, _) = endpoint(|_, _| slot(1)).await;
+    let client = client(&[url], Some(20));
+
+    let start = Instant::now();
+    for _ in 0..5 {
+        client.get_slot().await.unwrap();
+    }
+    assert!(start.elapsed() >= Duration::from_millis(200), "{:?}", start.elapsed());
+}
+
+#[test]
+fn backoff_is_capped_and_jittered() {
+    let policy = fast_policy();
+    for retry in 0..10 {
+        let cap = (policy.min_backoff * 2u32.pow(retry)).min(policy.max_backoff);
+        assert!(policy.backoff(retry) <= cap);
+    }
+    let samples: Vec<_> = (0..20).map(|_| policy.backoff(5)).collect();
+    assert!(samples.iter().any(|d| *d != samples[0]));
+}
@@ -0,0 +1,209 @@
+//! The send-and-retry pipeline against a bank behind a lossy network
+
+use anyhow::Result;
+use solana_client::rpc_filter::RpcFilterType;
+use solana_examples_scripts::bank::Bank;
+use solana_examples_scripts::client::sender::{send_with_retry, AttemptEnd, SendConfig};
+use solana_examples_scripts::client::LedgerClient;
+use solana_examples_scripts::examples::airdrop::airdrop;
+use solana_sdk::{
+    account::Account,
+    clock::Slot,
+    hash::Hash,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    system_instruction,
+    transaction::{Transaction, TransactionError},
+};
+use std::sync::Mutex;
+use std::time::Duration;
+
+const SOL: u64 = 1_000_000_000;
+
+/// Slots that pass between two status checks
+const SLOTS_PER_CHECK: u64 = 20;
+
+/// A bank behind a network that loses transactions. Every status check
+/// warps the bank forward, so blockhashes expire after enough checks.
+struct Lossy {
+    bank: Bank,
+    /// Sends to drop before one gets through
+    drop_sends: Mutex<usize>,
+    /// Drop every transaction signed with the first blockhash handed out
+    drop_first_blockhash: bool,
+    first_blockhash: Mutex<Option<Hash>>,
+}
+
+impl Lossy {
+    fn new(bank: Bank, drop_sends: usize, drop_first_blockhash: bool) -> Self {
+        Self {
+            bank,
+            drop_sends: Mutex::new(drop_sends),
+            drop_first_blockhash,
+            first_blockhash: Mutex::new(None),
+        }
+    }
+
+    fn dropped(&self, tx: &Transaction) -> bool {
+        if self.drop_first_blockhash && *self.first_blockhash.lock().unwrap() == Some(tx.message.recent_blockhash) {
+            return true;
+        }
+        let mut drop_sends = self.drop_sends.lock().unwrap();
+        if *drop_sends > 0 {
+            *drop_sends -= 1;
+            return true;
+        }
+        false
+    }
+
+    async fn deliver(&self, tx: &Transaction) -> Result<Signature> {
+        if self.dropped(tx) {
+            return Ok(tx.signatures[0]);
+        }
+        self.bank.send_transaction(tx).await
+    }
+}
+
+impl LedgerClient for Lossy {
+    async fn get_balance(&self, address: &Pubkey) -> Result<u64> {
+        self.bank.get_balance(address).await
+    }
+
+    async fn get_account(&self, address: &Pubkey) -> Result<Option<Account>> {
+        self.bank.get_account(address).await
+    }
+
+    async fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        self.bank.get_multiple_accounts(addresses).await
+    }
+
+    async fn get_program_accounts(&self, program_id: &Pubkey, filters: &[RpcFilterType]) -> Result<Vec<(Pubkey, Account)>> {
+        self.bank.get_program_accounts(program_id, filters).await
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64> {
+        self.bank.get_minimum_balance_for_rent_exemption(data_len).await
+    }
+
+    async fn get_latest_blockhash(&self) -> Result<Hash> {
+        Ok(self.get_latest_blockhash_with_expiry().await?.0)
+    }
+
+    async fn get_latest_blockhash_with_expiry(&self) -> Result<(Hash, u64)> {
+        let latest = self.bank.get_latest_blockhash_with_expiry().await?;
+        self.first_blockhash.lock().unwrap().get_or_insert(latest.0);
+        Ok(latest)
+    }
+
+    async fn get_slot(&self) -> Result<Slot> {
+        self.bank.get_slot().await
+    }
+
+    async fn get_block_height(&self) -> Result<u64> {
+        self.bank.get_block_height().await
+    }
+
+    async fn request_airdrop(&self, to: &Pubkey, lamports: u64) -> Result<Signature> {
+        self.bank.request_airdrop(to, lamports).await
+    }
+
+    async fn send_transaction(&self, tx: &Transaction) -> Result<Signature> {
+        self.deliver(tx).await
+    }
+
+    async fn resend_transaction(&self, tx: &Transaction) -> Result<Signature> {
+        self.deliver(tx).await
+    }
+
+    async fn get_signature_status(&self, signature: &Signature) -> Result<Option<Result<(), TransactionError>>> {
+        let slot = self.bank.get_slot().await?;
+        self.bank.warp_to_slot(slot + SLOTS_PER_CHECK).await?;
+        self.bank.get_signature_status(signature).await
+    }
+
+    async fn send_and_confirm_transaction(&self, tx: &Transaction) -> Result<Signature> {
+        self.bank.send_and_confirm_transaction(tx).await
+    }
+}
+
+fn config(max_blockhashes: u32) -> SendConfig {
+    SendConfig {
+        rebroadcast_interval: Duration::from_millis(1),
+        max_blockhashes,
+    }
+}
+
+async fn funded(lossy: &Lossy) -> Keypair {
+    let payer = Keypair::new();
+    airdrop(&lossy.bank, &payer.pubkey(), SOL).await.unwrap();
+    payer
+}
+
+fn transfer(from: &Keypair, to: &Pubkey, lamports: u64) -> Vec<Instruction> {
+    vec![system_instruction::transfer(&from.pubkey(), to, lamports)]
+}
+
+#[tokio::test]
+async fn dropped_sends_are_rebroadcast_until_one_lands() {
+    let lossy = Lossy::new(Bank::new().await, 3, false);
+    let payer = funded(&lossy).await;
+    let to = Pubkey::new_unique();
+
+    let report = send_with_retry(&lossy, &transfer(&payer, &to, SOL / 10), &payer, &[], &config(3)).await;
+
+    assert_eq!(report.attempts.len(), 1);
+    assert_eq!(report.attempts[0].broadcasts, 4);
+    assert_eq!(report.attempts[0].end, AttemptEnd::Confirmed);
+    assert_eq!(report.signature(), Some(report.attempts[0].signature));
+    assert_eq!(lossy.bank.get_balance(&to).await.unwrap(), SOL / 10);
+}
+
+#[tokio::test]
+async fn expired_transactions_are_re_signed_with_a_fresh_blockhash() {
+    let lossy = Lossy::new(Bank::new().await, 0, true);
+    let payer = funded(&lossy).await;
+    let to = Pubkey::new_unique();
+
+    let report = send_with_retry(&lossy, &transfer(&payer, &to, SOL / 10), &payer, &[], &config(3)).await;
+
+    assert_eq!(report.attempts.len(), 2, "{}", report);
+    let (first, second) = (&report.attempts[0], &report.attempts[1]);
+    assert_eq!(first.end, AttemptEnd::Expired);
+    assert!(first.broadcasts > 1);
+    assert_eq!(second.end, AttemptEnd::Confirmed);
+    assert_ne!(first.blockhash, second.blockhash);
+    assert_ne!(first.signature, second.signature);
+    assert_eq!(report.signature(), Some(second.signature));
+    // Paid once
+    assert_eq!(lossy.bank.get_balance(&to).await.unwrap(), SOL / 10);
+}
+
+#[tokio::test]
+async fn gives_up_after_the_last_blockhash_expires() {
+    let lossy = Lossy::new(Bank::new().await, usize::MAX, false);
+    let payer = funded(&lossy).await;
+
+    let report = send_with_retry(&lossy, &transfer(&payer, &Pubkey::new_unique(), 1), &payer, &[], &config(2)).await;
+
+    assert_eq!(report.attempts.len(), 2);
+    assert!(report.attempts.iter().all(|attempt| attempt.end == AttemptEnd::Expired));
+    let error = report.into_result().unwrap_err().to_string();
+    assert!(error.starts_with("Transaction not confirmed (blockhash expired) after 2 attempt(s)"), "{}", error);
+    assert!(error.contains("📨 Attempt 2"), "{}", error);
+}
+
+#[tokio::test]
+async fn failed_transactions_are_not_retried() {
+    let lossy = Lossy::new(Bank::new().await, 0, false);
+    let payer = funded(&lossy).await;
+    let to = Pubkey::new_unique();
+
+    // More than the payer holds: it executes and fails
+    let report = send_with_retry(&lossy, &transfer(&payer, &to, 2 * SOL), &payer, &[], &config(3)).await;
+
+    assert_eq!(report.attempts.len(), 1);
+    assert!(matches!(report.attempts[0].end, AttemptEnd::Failed(_)), "{}", report);
+    assert!(report.signature().is_none());
+    assert_eq!(lossy.bank.get_balance(&to).await.unwrap(), 0);
+}
@@ -0,0 +1,236 @@
+//! Settlement netting, batch packing limits and Merkle proofs
+
+use solana_examples_scripts::settlement::merkle;
+use solana_examples_scripts::settlement::netting::NetPositionCalculator;
+use solana_examples_scripts::settlement::packing::{pack, Limits, SettlementPrograms};
+use solana_examples_scripts::settlement::{Fill, FillSide, TradeRequest};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashSet;
+
+const UNIT: u64 = 1_000_000;
+
+fn fill(trade_id: &str, user: Pubkey, symbol: &str, side: FillSide, quantity: u64, price: u64) -> Fill {
+    Fill {
+        trade_id: trade_id.to_string(),
+        user,
+        symbol: symbol.to_string(),
+        side,
+        quantity,
+        price,
+        timestamp: 1_760_000_000,
+    }
+}
+
+fn programs() -> SettlementPrograms {
+    SettlementPrograms {
+        settlement: Pubkey::new_unique(),
+        vault: Pubkey::new_unique(),
+    }
+}
+
+#[test]
+fn fills_net_per_user_and_market() {
+    let alice = Pubkey::new_unique();
+    let mut calculator = NetPositionCalculator::new();
+    calculator.extend(&[
+        fill("t1", alice, "BTC-USDT", FillSide::Long, 10 * UNIT, 60_000 * UNIT),
+        fill("t2", alice, "BTC-USDT", FillSide::Short, 3 * UNIT, 61_000 * UNIT),
+        fill("t3", alice, "BTC-USDT", FillSide::Long, 5 * UNIT, 59_000 * UNIT),
+        fill("t4", alice, "ETH-USDT", FillSide::Short, 2 * UNIT, 3_000 * UNIT),
+    ]);
+
+    let positions = calculator.net_positions().unwrap();
+
+    assert_eq!(calculator.fill_count(), 4);
+    assert_eq!(positions.len(), 2);
+    let btc = &positions[0];
+    assert_eq!(btc.symbol, "BTC-USDT");
+    assert_eq!(btc.size_delta, 12 * UNIT as i64);
+    // Pays 600,000 + 295,000 and receives 183,000
+    assert_eq!(btc.quote_delta, -712_000 * UNIT as i64);
+    assert_eq!(btc.gross_quantity, 18 * UNIT);
+    assert_eq!(btc.trade_ids, vec!["t1", "t2", "t3"]);
+    assert_eq!(positions[1].quote_delta, 6_000 * UNIT as i64);
+
+    let transfers = calculator.collateral_transfers().unwrap();
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(transfers[0].amount, -706_000 * UNIT as i64);
+}
+
+#[test]
+fn matched_fills_conserve_size_and_collateral() {
+    let users: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+    let mut fills = Vec::new();
+    for (i, price) in [60_000, 60_100, 59_950, 60_020, 60_005].iter().enumerate() {
+        let (buyer, seller) = (users[i % 4], users[(i + 1) % 4]);
+        let quantity = (i as u64 + 1) * UNIT / 4;
+        fills.push(fill(&format!("b{}", i), buyer, "BTC-USDT", FillSide::Long, quantity, price * UNIT));
+        fills.push(fill(&format!("s{}", i), seller, "BTC-USDT", FillSide::Short, quantity, price * UNIT));
+    }
+    let mut calculator = NetPositionCalculator::new();
+    calculator.extend(&fills);
+
+    let positions = calculator.net_positions().unwrap();
+    let transfers = calculator.collateral_transfers().unwrap();
+
+    assert_eq!(positions.iter().map(|p| p.size_delta).sum::<i64>(), 0);
+    assert_eq!(positions.iter().map(|p| p.quote_delta).sum::<i64>(), 0);
+    assert_eq!(transfers.iter().map(|t| t.amount).sum::<i64>(), 0);
+    assert!(calculator.market_imbalance().values().all(|net| *net == 0));
+}
+
+#[test]
+fn offsetting_fills_leave_no_entry() {
+    let user = Pubkey::new_unique();
+    let mut calculator = NetPositionCalculator::new();
+    calculator.extend(&[
+        fill("t1", user, "SOL-USDT", FillSide::Long, UNIT, 150 * UNIT),
+        fill("t2", user, "SOL-USDT", FillSide::Short, UNIT, 150 * UNIT),
+    ]);
+
+    assert!(calculator.net_positions().unwrap().is_empty());
+    assert!(calculator.collateral_transfers().unwrap().is_empty());
+}
+
+#[test]
+fn net_amounts_past_64_bits_are_errors() {
+    let user = Pubkey::new_unique();
+    let mut calculator = NetPositionCalculator::new();
+    calculator.extend(&[
+        fill("t1", user, "BTC-USDT", FillSide::Long, u64::MAX, UNIT),
+        fill("t2", user, "BTC-USDT", FillSide::Long, u64::MAX, UNIT),
+    ]);
+
+    let error = calculator.net_positions().unwrap_err().to_string();
+    assert!(error.contains("overflows 64 bits"), "{}", error);
+    assert!(calculator.collateral_transfers().is_err());
+}
+
+#[test]
+fn engine_fills_with_unusable_amounts_are_rejected() {
+    let request = TradeRequest {
+        user_id: Pubkey::new_unique().to_string(),
+        symbol: "BTC-USDT".to_string(),
+        side: FillSide::Long,
+        quantity: 0.5,
+        price: 64_000.25,
+        timestamp: 1_760_000_000,
+        trade_id: Some("t-1".to_string()),
+    };
+    let fill = Fill::from_request(&request, 0).unwrap();
+    assert_eq!((fill.quantity, fill.price), (500_000, 64_000_250_000));
+
+    for (quantity, price) in [
+        (f64::NAN, 1.0),
+        (-1.0, 1.0),
+        (0.0, 1.0),
+        (1.0, f64::INFINITY),
+        (1.0, -64_000.0),
+        (1e14, 1.0),
+        (1.0, 1e14),
+    ] {
+        let bad = TradeRequest {
+            quantity,
+            price,
+            ..request.clone()
+        };
+        let error = Fill::from_request(&bad, 0).unwrap_err();
+        assert!(format!("{:#}", error).contains("Trade t-1"), "{:#}", error);
+    }
+}
+
+#[test]
+fn batches_stay_within_every_limit() {
+    let programs = programs();
+    let limits = Limits::default();
+    let users: Vec<Pubkey> = (0..40).map(|_| Pubkey::new_unique()).collect();
+    let mut calculator = NetPositionCalculator::new();
+    for (i, user) in users.iter().enumerate() {
+        // Every third user trades two markets
+        let markets: &[&str] = if i % 3 == 0 { &["BTC-USDT", "ETH-USDT"] } else { &["BTC-USDT"] };
+        for symbol in markets {
+            calculator.add(&fill(&format!("{}-{}", i, symbol), *user, symbol, FillSide::Long, UNIT, 100 * UNIT));
+        }
+    }
+    let positions = calculator.net_positions().unwrap();
+    let entries = positions.len();
+
+    let batches = pack(positions, &programs, &limits).unwrap();
+
+    assert!(batches.len() > 1);
+    assert_eq!(batches.iter().map(|b| b.entries.len()).sum::<usize>(), entries);
+    for batch in &batches {
+        assert!(batch.estimated_size <= limits.max_tx_bytes, "{} bytes", batch.estimated_size);
+        assert!(batch.compute_units <= limits.max_compute_units);
+        let instructions = batch.instructions(&programs, &Pubkey::new_unique()).unwrap();
+        let accounts: HashSet<Pubkey> = instructions.iter().flat_map(|ix| ix.accounts.iter().map(|a| a.pubkey)).collect();
+        assert!(accounts.len() + 2 <= limits.max_accounts);
+    }
+    // Every user's entries land in exactly one batch
+    for user in &users {
+        let holding = batches.iter().filter(|b| b.entries.iter().any(|e| e.user == *user)).count();
+        assert_eq!(holding, 1);
+    }
+}
+
+#[test]
+fn compute_limits_split_batches() {
+    let programs = programs();
+    let limits = Limits {
+        max_compute_units: 20_000 + 3 * 30_000,
+        ..Limits::default()
+    };
+    let mut calculator = NetPositionCalculator::new();
+    for i in 0..7 {
+        calculator.add(&fill(&format!("t{}", i), Pubkey::new_unique(), "BTC-USDT", FillSide::Long, UNIT, UNIT));
+    }
+
+    let batches = pack(calculator.net_positions().unwrap(), &programs, &limits).unwrap();
+
+    let sizes: Vec<usize> = batches.iter().map(|b| b.entries.len()).collect();
+    assert_eq!(sizes, vec![3, 3, 1]);
+    assert!(batches.iter().all(|b| b.compute_units <= limits.max_compute_units));
+}
+
+#[test]
+fn a_user_too_large_for_one_transaction_is_rejected() {
+    let programs = programs();
+    let limits = Limits {
+        max_compute_units: 20_000 + 2 * 30_000,
+        ..Limits::default()
+    };
+    let user = Pubkey::new_unique();
+    let mut calculator = NetPositionCalculator::new();
+    for symbol in ["BTC-USDT", "ETH-USDT", "SOL-USDT"] {
+        calculator.add(&fill(symbol, user, symbol, FillSide::Long, UNIT, UNIT));
+    }
+
+    assert!(pack(calculator.net_positions().unwrap(), &programs, &limits).is_err());
+}
+
+#[test]
+fn merkle_proofs_round_trip_for_every_leaf() {
+    for count in 1..=9 {
+        let leaves: Vec<[u8; 32]> = (0..count).map(|i| merkle::leaf(&format!("trade-{}", i))).collect();
+        let root = merkle::root(&leaves);
+        for (index, leaf) in leaves.iter().enumerate() {
+            let path = merkle::proof(&leaves, index);
+            assert!(merkle::verify(&root, leaf, index, &path), "{} of {}", index, count);
+            if count > 1 {
+                assert!(!merkle::verify(&root, &leaves[(index + 1) % count], index, &path));
+            }
+        }
+    }
+}
+
+#[test]
+fn merkle_proofs_reject_other_leaves_positions_and_roots() {
+    let leaves: Vec<[u8; 32]> = ["a", "b", "c", "d"].iter().map(|id| merkle::leaf(id)).collect();
+    let root = merkle::root(&leaves);
+    let path = merkle::proof(&leaves, 2);
+
+    assert!(!merkle::verify(&root, &merkle::leaf("e"), 2, &path));
+    assert!(!merkle::verify(&root, &leaves[2], 3, &path));
+    assert!(!merkle::verify(&merkle::root(&leaves[..3]), &leaves[2], 2, &path));
+    assert_eq!(merkle::root(&[]), [0u8; 32]);
+}
@@ -0,0 +1,68 @@
+//! Off-chain message signing and verification
+
+use solana_examples_scripts::signing::{format, sign_message, signed_bytes, verify_message, Encoding};
+use solana_sdk::{
+    offchain_message::MessageFormat,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+fn keypair(seed: u8) -> Keypair {
+    solana_sdk::signature::keypair_from_seed(&[seed; 32]).unwrap()
+}
+
+#[test]
+fn signatures_verify_for_the_signer_and_message_only() {
+    let signer = keypair(1);
+    let message = b"Link wallet to GoDark account\nAccount: qa@example.com\nTimestamp: 1760000000000";
+
+    let signature = sign_message(&signer, message, Encoding::Offchain).unwrap();
+
+    assert!(verify_message(&signer.pubkey(), message, &signature, Encoding::Offchain).unwrap());
+    assert!(!verify_message(&keypair(2).pubkey(), message, &signature, Encoding::Offchain).unwrap());
+    assert!(!verify_message(&signer.pubkey(), b"Link wallet to someone else", &signature, Encoding::Offchain).unwrap());
+    // An off-chain signature is not a signature over the bare text
+    assert!(!verify_message(&signer.pubkey(), message, &signature, Encoding::Raw).unwrap());
+}
+
+#[test]
+fn messages_carry_the_versioned_header() {
+    let bytes = signed_bytes(b"Test Message", Encoding::Offchain).unwrap();
+
+    assert_eq!(&bytes[..16], b"\xffsolana offchain");
+    // Version 0, restricted ASCII, little-endian length 12
+    assert_eq!(&bytes[16..20], &[0, 0, 12, 0]);
+    assert_eq!(&bytes[20..], b"Test Message");
+    assert_eq!(signed_bytes(b"Test Message", Encoding::Raw).unwrap(), b"Test Message");
+}
+
+#[test]
+fn the_format_follows_the_content() {
+    assert_eq!(format(b"plain ascii").unwrap(), MessageFormat::RestrictedAscii);
+    // Newlines are not printable ASCII
+    assert_eq!(format(b"two\nlines").unwrap(), MessageFormat::LimitedUtf8);
+    assert_eq!(format("héllo".as_bytes()).unwrap(), MessageFormat::LimitedUtf8);
+    assert_eq!(format("x".repeat(2_000).as_bytes()).unwrap(), MessageFormat::ExtendedUtf8);
+
+    assert!(format(b"").unwrap_err().to_string().contains("empty"));
+    assert!(format(&[0xff, 0xfe]).unwrap_err().to_string().contains("UTF-8"));
+    assert!(format("x".repeat(70_000).as_bytes()).unwrap_err().to_string().contains("at most 65515"));
+}
+
+#[test]
+fn raw_signatures_match_browser_wallets() {
+    let signer = keypair(3);
+    let message = b"Sign in to GoDark";
+
+    // What nacl.sign.detached produces for wallet.signMessage(bytes)
+    let expected = signer.sign_message(message).to_string();
+
+    assert_eq!(sign_message(&signer, message, Encoding::Raw).unwrap(), expected);
+    assert!(verify_message(&signer.pubkey(), message, &expected, Encoding::Raw).unwrap());
+}
+
+#[test]
+fn malformed_signatures_are_errors() {
+    let error = verify_message(&Pubkey::new_unique(), b"hi", "not-base58!", Encoding::Offchain).unwrap_err();
+    assert!(error.to_string().starts_with("Invalid signature"), "{}", error);
+}
@@ -0,0 +1,114 @@
+//! Multisig upgrade approvals and the timelock derived from them
+
+use solana_examples_scripts::upgrade::{MultisigConfig, UpgradeProposal, UpgradeStatus};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const PROPOSED_AT: i64 = 1_760_000_000;
+const TIMELOCK: i64 = 48 * 60 * 60;
+
+fn keypair(seed: u8) -> Keypair {
+    solana_sdk::signature::keypair_from_seed(&[seed; 32]).unwrap()
+}
+
+fn setup(threshold: u8) -> (Vec<Keypair>, MultisigConfig, UpgradeProposal) {
+    let members: Vec<Keypair> = (1..=3).map(keypair).collect();
+    let config = MultisigConfig {
+        members: members.iter().map(|m| m.pubkey().to_string()).collect(),
+        threshold,
+        upgrade_authority: Pubkey::new_unique().to_string(),
+        timelock_secs: TIMELOCK,
+    };
+    let mut proposal = UpgradeProposal::new(
+        1,
+        &members[0].pubkey(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        "ab".repeat(32),
+        "Vault v2".to_string(),
+        threshold,
+    );
+    proposal.proposed_at = PROPOSED_AT;
+    (members, config, proposal)
+}
+
+fn approve(proposal: &mut UpgradeProposal, config: &MultisigConfig, member: &Keypair, signed_at: i64) {
+    let signature = proposal.sign(member, signed_at);
+    assert!(proposal
+        .add_approval(config, &member.pubkey(), &signature, signed_at)
+        .unwrap());
+}
+
+#[test]
+fn the_timelock_runs_from_the_approval_that_completes_the_threshold() {
+    let (members, config, mut proposal) = setup(2);
+    approve(&mut proposal, &config, &members[0], PROPOSED_AT + 10);
+    assert_eq!(proposal.status, UpgradeStatus::Proposed);
+    assert!(proposal.ensure_executable(&config, PROPOSED_AT + 10 * TIMELOCK).is_err());
+
+    approve(&mut proposal, &config, &members[1], PROPOSED_AT + 100);
+
+    assert_eq!(proposal.status, UpgradeStatus::TimelockActive);
+    assert_eq!(proposal.timelock_until(&config), Some(PROPOSED_AT + 100 + TIMELOCK));
+    assert!(proposal.ensure_executable(&config, PROPOSED_AT + 99 + TIMELOCK).is_err());
+    assert!(proposal.ensure_executable(&config, PROPOSED_AT + 100 + TIMELOCK).is_ok());
+    assert_eq!(
+        proposal.status_at(&config, PROPOSED_AT + 100 + TIMELOCK),
+        UpgradeStatus::Approved
+    );
+}
+
+#[test]
+fn editing_an_approval_time_invalidates_the_signature() {
+    let (members, config, mut proposal) = setup(2);
+    approve(&mut proposal, &config, &members[0], PROPOSED_AT + 10);
+    approve(&mut proposal, &config, &members[1], PROPOSED_AT + 100);
+
+    // Moving the approvals back to skip the timelock breaks both signatures
+    for approval in &mut proposal.approvals {
+        approval.signed_at = PROPOSED_AT - TIMELOCK;
+    }
+
+    let report = proposal.check_approvals(&config);
+    assert!(report.valid.is_empty());
+    assert_eq!(report.invalid.len(), 2);
+    assert!(proposal.ensure_executable(&config, PROPOSED_AT + 1).is_err());
+}
+
+#[test]
+fn one_backdated_approval_does_not_shorten_the_timelock() {
+    let (members, config, mut proposal) = setup(2);
+    // Signed honestly, but with a time far in the past
+    approve(&mut proposal, &config, &members[0], 0);
+    approve(&mut proposal, &config, &members[1], PROPOSED_AT + 100);
+
+    assert_eq!(proposal.timelock_until(&config), Some(PROPOSED_AT + 100 + TIMELOCK));
+    assert!(proposal.ensure_executable(&config, PROPOSED_AT + 200).is_err());
+}
+
+#[test]
+fn approvals_are_checked_against_the_local_config() {
+    let (members, config, mut proposal) = setup(2);
+    let outsider = keypair(9);
+    let signature = proposal.sign(&outsider, PROPOSED_AT);
+    assert!(proposal
+        .add_approval(&config, &outsider.pubkey(), &signature, PROPOSED_AT)
+        .is_err());
+    // A signature for another time does not verify
+    let signature = proposal.sign(&members[0], PROPOSED_AT);
+    assert!(proposal
+        .add_approval(&config, &members[0].pubkey(), &signature, PROPOSED_AT + 1)
+        .is_err());
+
+    approve(&mut proposal, &config, &members[0], PROPOSED_AT);
+    let again = proposal.sign(&members[0], PROPOSED_AT + 5);
+    assert!(!proposal
+        .add_approval(&config, &members[0].pubkey(), &again, PROPOSED_AT + 5)
+        .unwrap());
+
+    approve(&mut proposal, &config, &members[1], PROPOSED_AT);
+    let stricter = MultisigConfig { threshold: 3, ..config };
+    assert!(proposal.ensure_executable(&stricter, PROPOSED_AT + 10 * TIMELOCK).is_err());
+}
@@ -0,0 +1,115 @@
+//! Seed phrases and SLIP-0010 derivation against published test vectors,
+//! and vanity address grinding
+
+use solana_examples_scripts::wallet::grind::{grind, Pattern, Progress};
+use solana_examples_scripts::wallet::{derive_key, derive_users, generate_phrase, parse_phrase, path, seed, DerivationPath};
+use solana_sdk::derivation_path::DerivationPath as SdkPath;
+use solana_sdk::signature::{keypair_from_seed_and_derivation_path, Signer};
+
+const ABANDON: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[test]
+fn bip39_seed_matches_the_reference_vector() {
+    let seed = seed(&parse_phrase(ABANDON).unwrap(), "TREZOR");
+    assert_eq!(
+        hex(&seed),
+        "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"
+    );
+}
+
+#[test]
+fn imported_phrases_are_normalized_and_checksummed() {
+    let messy = format!("  {}  ", ABANDON.to_uppercase().replace(' ', "\n"));
+    assert_eq!(parse_phrase(&messy).unwrap().phrase(), ABANDON);
+    assert!(parse_phrase(&ABANDON.replace("about", "abandon")).is_err());
+    assert!(parse_phrase("not a seed phrase").is_err());
+}
+
+#[test]
+fn generated_phrases_have_the_requested_length() {
+    for words in [12, 24] {
+        let phrase = generate_phrase(words).unwrap();
+        assert_eq!(phrase.split(' ').count(), words);
+        parse_phrase(&phrase).unwrap();
+    }
+    assert!(generate_phrase(13).is_err());
+}
+
+#[test]
+fn slip10_matches_the_ed25519_test_vector() {
+    let seed: Vec<u8> = (0u8..16).collect();
+    let (key, chain_code) = derive_key(&seed, &"m".parse().unwrap());
+    assert_eq!(hex(&key), "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7");
+    assert_eq!(hex(&chain_code), "90046a93de5380a72b5e45010748567d5ea02bbf6522f979e05c0d8d8ca9fffb");
+
+    let (key, chain_code) = derive_key(&seed, &"m/0'/1'".parse().unwrap());
+    assert_eq!(hex(&key), "b1d0bad404bf35da785a64ca1ac54b2617211d2777696fbffaf208f746ae84f2");
+    assert_eq!(hex(&chain_code), "a320425f77d1b5c2505a6b1b27382b37368ee640e3557c315416801243552f14");
+}
+
+#[test]
+fn users_match_solana_keygen_derivation() {
+    let seed = seed(&parse_phrase(ABANDON).unwrap(), "");
+    let users = derive_users(&seed, 3, 3).unwrap();
+
+    assert_eq!(users.len(), 3);
+    for (i, (path, keypair)) in (3..).zip(&users) {
+        assert_eq!(path.to_string(), format!("m/44'/501'/{}'/0'", i));
+        let expected = keypair_from_seed_and_derivation_path(&seed, Some(SdkPath::new_bip44(Some(i), Some(0)))).unwrap();
+        assert_eq!(keypair.pubkey(), expected.pubkey());
+    }
+    // Same seed, same users
+    assert_eq!(derive_users(&seed, 3, 1).unwrap()[0].1.pubkey(), users[0].1.pubkey());
+}
+
+#[test]
+fn paths_parse_only_hardened_indices() {
+    assert_eq!("m/44'/501'/7'/0'".parse::<DerivationPath>().unwrap(), path(7));
+    assert_eq!("m/44h/501h".parse::<DerivationPath>().unwrap().0, vec![44, 501]);
+    assert!("m/44'/501/0'".parse::<DerivationPath>().is_err());
+    assert!("44'/501'".parse::<DerivationPath>().is_err());
+    assert!("m/2147483648'".parse::<DerivationPath>().is_err());
+}
+
+#[test]
+fn patterns_must_be_base58() {
+    for bad in ["G0X", "GDO", "IX", "lx"] {
+        let error = Pattern::new(bad, "", false).unwrap_err().to_string();
+        assert!(error.contains("base58 has no 0, O, I or l"), "{}: {}", bad, error);
+    }
+    // Ignoring case, O, I and l still have a base58 form
+    assert!(Pattern::new("GDO", "il", true).is_ok());
+    assert!(Pattern::new("G0", "", true).is_err());
+    assert!(Pattern::new("", "", false).is_err());
+    assert!(Pattern::new("GDX", "", false).unwrap().matches("GDX1111"));
+}
+
+#[test]
+fn case_insensitive_patterns_match_either_case_and_are_likelier() {
+    let exact = Pattern::new("gdx", "", false).unwrap();
+    let any_case = Pattern::new("gdx", "", true).unwrap();
+
+    assert!(!exact.matches("GdX9"));
+    assert!(any_case.matches("GdX9"));
+    assert!(Pattern::new("", "ol", true).unwrap().matches("abcoL"));
+    assert_eq!(exact.expected_attempts().round(), 195_112.0);
+    assert_eq!(any_case.expected_attempts().round(), (58f64.powi(3) / 8.0).round());
+    // "o" has one base58 form, "L" one
+    assert_eq!(Pattern::new("ol", "", true).unwrap().expected_attempts().round(), 3_364.0);
+}
+
+#[test]
+fn grinding_finds_the_requested_matches() {
+    let pattern = Pattern::new("", "a", false).unwrap();
+    let progress = Progress::default();
+
+    let found = grind(&pattern, 2, 3, &progress);
+
+    assert_eq!(found.len(), 3);
+    assert!(found.iter().all(|keypair| keypair.pubkey().to_string().ends_with('a')));
+    assert!(progress.attempts.load(std::sync::atomic::Ordering::Relaxed) >= 3);
+}
@@ -203,6 +203,29 @@ app.get("/api/orders/:id", (req: Request, res: Response) => {
   res.json(order);
 });
 
+/**
+ * Cancel a pending order
+ */
+app.delete("/api/orders/:id", (req: Request, res: Response) => {
+  const order = matchingEngine.getOrder(req.params.id);
+
+  if (!order) {
+    return res.status(404).json({ error: "Order not found" });
+  }
+
+  if (order.status !== "pending") {
+    return res.status(409).json({ error: `Order is already ${order.status}` });
+  }
+
+  matchingEngine.cancelOrder(order.id);
+
+  res.json({
+    orderId: order.id,
+    status: order.status,
+    timestamp: Date.now(),
+  });
+});
+
 /**
  * Get user orders
  */
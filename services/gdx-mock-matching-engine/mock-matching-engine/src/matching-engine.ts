@@ -46,6 +46,11 @@ export class MatchingEngine {
    * Match an order against simulated liquidity
    */
   private async matchOrder(order: Order, tradingViewPrice?: number): Promise<void> {
+    // Cancelled while waiting to be matched
+    if (order.status !== "pending") {
+      return;
+    }
+
     // Check success rate (simulate occasional failures)
     if (Math.random() > this.fillSuccessRate) {
       order.status = "failed";
@@ -102,6 +107,20 @@ export class MatchingEngine {
     }, 200);
   }
 
+  /**
+   * Cancel an order that has not been matched yet
+   */
+  cancelOrder(orderId: string): Order | undefined {
+    const order = this.orders.get(orderId);
+    if (!order || order.status !== "pending") {
+      return order;
+    }
+
+    order.status = "cancelled";
+    this.notifyListeners(order);
+    return order;
+  }
+
   /**
    * Subscribe to order updates
    */